@@ -0,0 +1,132 @@
+// 缓冲区操作基准测试：行首/行中/行尾的插入删除、换行拆分、大文件读入和整屏渲染
+//
+// 为换用 rope、差量渲染等改动提供对比数据。渲染画到内存里的假终端，不需要真实终端。
+//
+// 运行：cargo bench --features bench --bench buffer
+
+use std::fs;
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use rsnano::args::Args;
+use rsnano::buffer::{Position, TextBuffer};
+use rsnano::editor::Editor;
+use rsnano::paths::CONFIG_DIR_ENV;
+use rsnano::version::read_app_info;
+
+const LINES: usize = 10_000;
+const LINE_LENGTH: usize = 200;
+
+fn bench<F: FnMut()>(name: &str, iterations: u32, mut f: F) {
+    // 预热
+    for _ in 0..iterations / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let per_iter = start.elapsed().as_nanos() / iterations as u128;
+    println!("{:<36} {:>10} ns/iter", name, per_iter);
+}
+
+/// 每行 len 个字符的测试文本，夹杂一些中文
+fn sample_lines(count: usize, len: usize) -> Vec<String> {
+    let pattern: Vec<char> = "fn main() { let 变量 = 42; } ".chars().collect();
+    (0..count)
+        .map(|i| (0..len).map(|j| pattern[(i + j) % pattern.len()]).collect())
+        .collect()
+}
+
+/// 与用户配置隔离的临时配置目录，并跳过首次运行设置
+fn isolated_config_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rsnano-bench-{}", std::process::id()));
+    let _ = fs::create_dir_all(dir.join("state"));
+    let _ = fs::write(dir.join("state").join("first-run-done"), "");
+    std::env::set_var(CONFIG_DIR_ENV, &dir);
+    dir
+}
+
+fn bench_edits() {
+    let mut buffer = TextBuffer::new();
+    buffer.replace_contents(sample_lines(LINES, LINE_LENGTH));
+    let y = LINES / 2;
+    for (name, x) in [
+        ("insert+delete/line_start", 0),
+        ("insert+delete/line_middle", LINE_LENGTH / 2),
+        ("insert+delete/line_end", LINE_LENGTH),
+    ] {
+        bench(name, 200_000, || {
+            buffer.set_cursor_position(Position::new(x, y));
+            buffer.insert_char('x');
+            buffer.delete_char();
+            black_box(&buffer);
+        });
+    }
+    bench("newline+join/line_middle", 100_000, || {
+        buffer.set_cursor_position(Position::new(LINE_LENGTH / 2, y));
+        buffer.insert_newline();
+        buffer.delete_char();
+        black_box(&buffer);
+    });
+}
+
+fn bench_load(dir: &Path) {
+    let path = dir.join("large.txt");
+    let text = sample_lines(100_000, 80).join("\n");
+    if fs::write(&path, &text).is_err() {
+        println!("无法写入 {}，跳过大文件读入", path.display());
+        return;
+    }
+    let name = format!("load/{}MB", text.len() >> 20);
+    bench(&name, 20, || {
+        black_box(TextBuffer::from_file(&path).ok());
+    });
+    let _ = fs::remove_file(&path);
+}
+
+fn bench_render() {
+    let Ok(args) = Args::parse_from_args(["rsnano".into()]) else {
+        return;
+    };
+    let mut editor = match Editor::with_terminal_size(args, read_app_info(), (80, 24)) {
+        Ok(editor) => editor,
+        Err(e) => {
+            println!("无法创建编辑器（{}），跳过渲染", e);
+            return;
+        }
+    };
+    editor
+        .buffer
+        .replace_contents(sample_lines(LINES, LINE_LENGTH));
+    let mut screen = Vec::new();
+    for (name, size, line_numbers, soft_wrap) in [
+        ("render/80x24", (80, 24), false, false),
+        ("render/200x60", (200, 60), false, false),
+        ("render/200x60_line_numbers", (200, 60), true, false),
+        ("render/200x60_soft_wrap", (200, 60), false, true),
+    ] {
+        editor.terminal_size = size;
+        editor.buffer.settings.line_numbers = line_numbers;
+        editor.buffer.settings.soft_wrap = soft_wrap;
+        editor
+            .buffer
+            .set_cursor_position(Position::new(0, LINES / 2));
+        let page_rows = editor.layout().page_rows();
+        editor.buffer.scroll_to_cursor(page_rows);
+        bench(name, 2_000, || {
+            screen.clear();
+            let _ = editor.render_to(&mut screen);
+            black_box(&screen);
+        });
+    }
+}
+
+fn main() {
+    let dir = isolated_config_dir();
+    bench_edits();
+    bench_load(&dir);
+    bench_render();
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,105 @@
+// 渲染路径基准测试：对比每帧新建字符串与复用缓冲区的开销
+//
+// 运行：cargo bench --features bench --bench render
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write as _;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use rsnano::render::{fit_to_width, pad_to_width, FrameBuffers};
+
+const ITERATIONS: u32 = 200_000;
+const WIDTH: usize = 120;
+
+/// 统计分配次数的全局分配器
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn bench<F: FnMut()>(name: &str, mut f: F) {
+    // 预热，让复用的缓冲区先长到所需容量
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+    let allocs_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let per_iter = start.elapsed().as_nanos() / ITERATIONS as u128;
+    let allocs = ALLOCATIONS.load(Ordering::Relaxed) - allocs_before;
+    println!(
+        "{:<36} {:>8} ns/iter {:>8.2} allocs/iter",
+        name,
+        per_iter,
+        allocs as f64 / ITERATIONS as f64
+    );
+}
+
+fn main() {
+    let filename = "main.rs";
+    let lines = 1234;
+    let modified = 17;
+    let message = "已保存，已修改 17 行";
+
+    // 旧写法：每帧 format! 多个临时字符串并用 repeat 填充
+    bench("status_bar/format_and_repeat", || {
+        let modified_indicator = format!(" [已修改 {} 行]", modified);
+        let mut status = format!(" {} - {} 行{}", filename, lines, modified_indicator);
+        let right_msg = format!("  {}", message);
+        let space = WIDTH.saturating_sub(status.len() + right_msg.len());
+        status.push_str(&" ".repeat(space));
+        status.push_str(&right_msg);
+        black_box(status);
+    });
+
+    // 新写法：写入复用的帧缓冲区，并按显示宽度填充
+    let mut frame = FrameBuffers::new();
+    bench("status_bar/reused_buffer", || {
+        let status = &mut frame.status_bar;
+        status.clear();
+        let _ = write!(status, " {} - {} 行", filename, lines);
+        let _ = write!(status, " [已修改 {} 行]", modified);
+        pad_to_width(status, WIDTH - 22);
+        status.push_str("  ");
+        status.push_str(message);
+        fit_to_width(status, WIDTH);
+        black_box(&*status);
+    });
+
+    bench("help_bar/repeat", || {
+        let help = "^X 退出  ^O 保存  ^G 帮助";
+        let mut line = help.to_string();
+        line.push_str(&" ".repeat(WIDTH.saturating_sub(help.len())));
+        black_box(line);
+    });
+
+    bench("help_bar/reused_buffer", || {
+        let help = &mut frame.help_bar;
+        help.clear();
+        help.push_str("^X 退出  ^O 保存  ^G 帮助");
+        fit_to_width(help, WIDTH);
+        black_box(&*help);
+    });
+}
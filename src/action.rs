@@ -0,0 +1,535 @@
+use crate::direction::Direction;
+
+/// 编辑器可执行的操作
+///
+/// 按键只负责解析成 `Action`，真正的行为统一由 `Editor::execute` 执行，
+/// 宏、命令面板、脚本和测试都复用同一条执行路径。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// 关闭当前缓冲区，只剩一个时退出编辑器（已修改时先询问是否保存）
+    Quit,
+    /// 在新缓冲区中打开文件
+    OpenFile,
+    /// 切换到下一个缓冲区
+    NextBuffer,
+    /// 切换到上一个缓冲区
+    PrevBuffer,
+    /// 打开保存提示
+    Save,
+    /// 打开帮助页面
+    ShowHelp,
+    /// 在主光标处添加光标，那里已有光标时删除它
+    ToggleSecondaryCursor,
+    /// 只保留主光标，删除其余的光标
+    ClearCursors,
+    /// 移动光标（多光标时所有光标一起移动）
+    MoveCursor(Direction),
+    /// 移动最近添加的光标
+    MoveSecondaryCursor(Direction),
+    /// 按住 Shift 移动光标，没有标记时先在原位置设置标记；之后不按 Shift 移动会取消选区
+    SelectMove(Direction),
+    /// 插入新行（多光标时每个光标处都插入）
+    InsertNewline,
+    /// 删除光标前的字符（有选区时删除选区）
+    DeleteBackward,
+    /// 删除光标处的字符（有选区时删除选区）
+    DeleteForward,
+    /// 剪切当前行（或光标到行尾、或选区），连续剪切会累积
+    CutLine,
+    /// 剪切光标到行尾（光标已在行尾时剪切换行符），不受 cut_from_cursor 设置影响
+    CutToLineEnd,
+    /// 在下面复制一份当前行（或选区经过的各行）
+    DuplicateLine,
+    /// 删除当前行（或选区经过的各行），不放进剪切缓冲区
+    DeleteLine,
+    /// 把当前行（或选区经过的各行）上移一行
+    MoveLineUp,
+    /// 把当前行（或选区经过的各行）下移一行
+    MoveLineDown,
+    /// 把选区（没有选区时为当前行）复制到剪切缓冲区
+    Copy,
+    /// 粘贴剪切缓冲区
+    Paste,
+    /// 紧接粘贴之后，把粘贴的内容换成上一条剪切历史
+    YankPop,
+    /// 从剪切历史列表中选择一条粘贴
+    PasteFromHistory,
+    /// 粘贴系统剪贴板的内容
+    PasteSystem,
+    /// 撤销上一步修改，连续输入或删除的字符算作一步
+    Undo,
+    /// 重做上一步撤销的修改
+    Redo,
+    /// 插入撤销断点，之后的修改另起一步
+    UndoBoundary,
+    /// 切换 ^K 是剪切整行还是从光标剪切到行尾
+    ToggleCutFromCursor,
+    /// 交换光标前后的两个字符
+    TransposeChars,
+    /// 交换光标前后的两个单词
+    TransposeWords,
+    /// 跳到光标后第一个右括号或引号之后
+    JumpPastCloser,
+    /// 打开 :set 提示修改当前缓冲区的设置
+    SetOption,
+    /// 重新加载配置文件和主题
+    ReloadConfig,
+    /// 把生效配置和键位表导出到文件
+    DumpConfig,
+    /// 搜索
+    Search,
+    /// 从光标处往文件开头方向搜索
+    SearchBackward,
+    /// 查找下一个
+    SearchNext,
+    /// 查找上一个
+    SearchPrev,
+    /// 打开/关闭搜索结果列表
+    SearchResults,
+    /// 替换，可选当前缓冲区或所有缓冲区
+    Replace,
+    /// 清除搜索高亮
+    ClearHighlight,
+    /// 用二合字母代码输入字符
+    InsertDigraph,
+    /// 完整读入预览中的大文件并允许编辑
+    EnableEditing,
+    /// 跳到最长的行
+    JumpLongestLine,
+    /// 跳到指定的行和列
+    GotoLine,
+    /// 向上翻一页
+    PageUp,
+    /// 向下翻一页
+    PageDown,
+    /// 智能行首：在第一个非空白字符和第 0 列之间切换
+    LineStart,
+    /// 移到行尾
+    LineEnd,
+    /// 移到上一个单词的开头
+    WordLeft,
+    /// 移到下一个单词的开头
+    WordRight,
+    /// 删除光标前的单词
+    DeleteWordBackward,
+    /// 删除光标后的单词
+    DeleteWordForward,
+    /// 保护或取消保护选中的行（没有选区时为当前行）
+    ToggleProtectLines,
+    /// 跳到位置列表的下一项
+    NextLocation,
+    /// 跳到位置列表的上一项
+    PrevLocation,
+    /// 跳到位置列表的第一项
+    FirstLocation,
+    /// 跳到位置列表的最后一项
+    LastLocation,
+    /// 把修改过的行生成位置列表
+    ListChanges,
+    /// 查看当前文件的编辑日志
+    ShowJournal,
+    /// 查看各缓冲区的内存占用
+    MemoryReport,
+    /// 收紧各缓冲区的内存，只保留最近的撤销历史
+    CompactMemory,
+    /// 在状态栏显示光标处字符的码位、UTF-8 字节、名称和宽度
+    InspectChar,
+    /// 跳到下一个可疑的 Unicode 字符（双向控制符、零宽字符、形似 ASCII 的字符）
+    NextSuspiciousChar,
+    /// 在当前行设置或取消书签
+    ToggleBookmark,
+    /// 跳到下一个书签
+    NextBookmark,
+    /// 开始块选择；已在块选择中时在矩形的每一行放一个光标
+    ToggleBlockSelection,
+    /// 开始/停止录制键盘宏
+    RecordMacro,
+    /// 运行最近录制的宏
+    RunMacro,
+    /// 给最近录制的宏起名并保存
+    SaveMacro,
+    /// 从命名的宏中选择一个运行
+    ListMacros,
+    /// 切换到下一个主题（只影响本次运行）
+    CycleTheme,
+    /// 从自动快照恢复当前缓冲区
+    RestoreSnapshot,
+    /// 从本地历史（保存前的版本）恢复当前缓冲区
+    RestoreHistory,
+    /// 设置/取消标记
+    ToggleMark,
+    /// 开关自由光标模式
+    ToggleVirtualSpace,
+    /// 格式化光标所在的 Markdown 表格
+    FormatTable,
+    /// 移到表格下一个单元格
+    TableNextCell,
+    /// 移到表格上一个单元格
+    TablePrevCell,
+    /// 插入制表符（tabstospaces 开启时插入空格），Markdown 表格中移到下一个单元格
+    InsertTab,
+    /// 开关 Markdown 预览面板
+    ToggleMarkdownPreview,
+    /// 开关大纲面板：文档的标题或代码中的函数、类型等符号
+    ToggleOutline,
+    /// 切换画框模式：关闭 → Unicode 框线 → ASCII 框线 → 关闭
+    CycleBoxDrawing,
+    /// 移到下一个句子开头
+    NextSentence,
+    /// 移到本句或上一句开头
+    PrevSentence,
+    /// 选中光标所在的句子
+    SelectSentence,
+    /// 选中光标处的单词
+    SelectWord,
+    /// 选中光标所在的整行
+    SelectLine,
+    /// 逐级扩大选区：单词 → 整行 → 段落 → 全部
+    ExpandSelection,
+    /// 开关散文模式（软换行与排版替换）
+    ToggleProse,
+    /// 开关软换行
+    ToggleSoftWrap,
+    /// 显示/隐藏顶部标题栏
+    ToggleTitleBar,
+    /// 显示/隐藏状态栏（有提示或消息时仍会临时显示）
+    ToggleStatusBar,
+    /// 显示/隐藏底部帮助栏
+    ToggleHelpLine,
+    /// 帮助栏放不下所有快捷键时翻到下一页
+    HelpLineNextPage,
+    /// 暂时回到终端启动 shell，退出 shell 后返回编辑器
+    ShellOut,
+    /// 运行当前文件类型的运行命令（如 cargo build），输出显示在底部面板
+    RunCommand,
+    /// 把焦点交给运行输出面板，已有焦点时关闭
+    RunOutput,
+    /// 显示内置终端并把焦点交给它，终端有焦点时隐藏
+    ToggleTerminal,
+    /// 列出打开的缓冲区中的 TODO、FIXME 等标记
+    TaskList,
+    /// 打印当前缓冲区，或导出为分页文本、PostScript
+    Print,
+    /// 用当前主题的颜色把缓冲区导出为 HTML 页面
+    ExportHtml,
+    /// 查看模式中切换按颜色显示 ANSI 控制序列还是按原样显示
+    ToggleAnsi,
+    /// 把光标处的 `文件:行:列` 或选区的 `文件#L10-L20` 放进剪切缓冲区
+    CopyReference,
+    /// 在光标处插入字符（多光标时每个光标处都插入）
+    InsertChar(char),
+}
+
+/// 默认显示在帮助栏中的操作，按重要程度排列，放不下时分页
+pub const DEFAULT_HELP_LINE: &[Action] = &[
+    Action::Quit,
+    Action::Save,
+    Action::ShowHelp,
+    Action::OpenFile,
+    Action::Search,
+    Action::Replace,
+    Action::CutLine,
+    Action::Paste,
+    Action::ToggleMark,
+    Action::SetOption,
+    Action::ToggleSecondaryCursor,
+    Action::SearchNext,
+    Action::NextBuffer,
+    Action::PrevBuffer,
+    Action::ToggleSoftWrap,
+    Action::ToggleMarkdownPreview,
+];
+
+/// 可以在配置文件中绑定的操作及其名字（插入字符除外）
+pub const ACTION_NAMES: &[(Action, &str)] = &[
+    (Action::Quit, "quit"),
+    (Action::OpenFile, "open_file"),
+    (Action::NextBuffer, "next_buffer"),
+    (Action::PrevBuffer, "prev_buffer"),
+    (Action::Save, "save"),
+    (Action::ShowHelp, "help"),
+    (Action::ToggleSecondaryCursor, "toggle_secondary_cursor"),
+    (Action::ClearCursors, "clear_cursors"),
+    (Action::MoveCursor(Direction::Up), "move_up"),
+    (Action::MoveCursor(Direction::Down), "move_down"),
+    (Action::MoveCursor(Direction::Left), "move_left"),
+    (Action::MoveCursor(Direction::Right), "move_right"),
+    (
+        Action::MoveSecondaryCursor(Direction::Up),
+        "move_secondary_up",
+    ),
+    (
+        Action::MoveSecondaryCursor(Direction::Down),
+        "move_secondary_down",
+    ),
+    (
+        Action::MoveSecondaryCursor(Direction::Left),
+        "move_secondary_left",
+    ),
+    (
+        Action::MoveSecondaryCursor(Direction::Right),
+        "move_secondary_right",
+    ),
+    (Action::SelectMove(Direction::Up), "select_up"),
+    (Action::SelectMove(Direction::Down), "select_down"),
+    (Action::SelectMove(Direction::Left), "select_left"),
+    (Action::SelectMove(Direction::Right), "select_right"),
+    (Action::InsertNewline, "newline"),
+    (Action::DeleteBackward, "backspace"),
+    (Action::DeleteForward, "delete"),
+    (Action::CutLine, "cut_line"),
+    (Action::CutToLineEnd, "cut_to_line_end"),
+    (Action::DuplicateLine, "duplicate_line"),
+    (Action::DeleteLine, "delete_line"),
+    (Action::MoveLineUp, "move_line_up"),
+    (Action::MoveLineDown, "move_line_down"),
+    (Action::Copy, "copy"),
+    (Action::Paste, "paste"),
+    (Action::YankPop, "yank_pop"),
+    (Action::PasteFromHistory, "paste_from_history"),
+    (Action::PasteSystem, "paste_system"),
+    (Action::Undo, "undo"),
+    (Action::Redo, "redo"),
+    (Action::UndoBoundary, "undo_boundary"),
+    (Action::ToggleCutFromCursor, "toggle_cut_from_cursor"),
+    (Action::TransposeChars, "transpose_chars"),
+    (Action::TransposeWords, "transpose_words"),
+    (Action::JumpPastCloser, "jump_past_closer"),
+    (Action::SetOption, "set_option"),
+    (Action::ReloadConfig, "reload_config"),
+    (Action::DumpConfig, "dump_config"),
+    (Action::Search, "search"),
+    (Action::SearchBackward, "search_backward"),
+    (Action::SearchNext, "search_next"),
+    (Action::SearchPrev, "search_prev"),
+    (Action::SearchResults, "search_results"),
+    (Action::Replace, "replace"),
+    (Action::ClearHighlight, "clear_highlight"),
+    (Action::InsertDigraph, "insert_digraph"),
+    (Action::EnableEditing, "enable_editing"),
+    (Action::JumpLongestLine, "jump_longest_line"),
+    (Action::GotoLine, "goto_line"),
+    (Action::PageUp, "page_up"),
+    (Action::PageDown, "page_down"),
+    (Action::LineStart, "line_start"),
+    (Action::LineEnd, "line_end"),
+    (Action::WordLeft, "word_left"),
+    (Action::WordRight, "word_right"),
+    (Action::DeleteWordBackward, "delete_word_backward"),
+    (Action::DeleteWordForward, "delete_word_forward"),
+    (Action::ToggleProtectLines, "toggle_protect_lines"),
+    (Action::NextLocation, "next_location"),
+    (Action::PrevLocation, "prev_location"),
+    (Action::FirstLocation, "first_location"),
+    (Action::LastLocation, "last_location"),
+    (Action::ListChanges, "list_changes"),
+    (Action::ShowJournal, "show_journal"),
+    (Action::MemoryReport, "memory_report"),
+    (Action::CompactMemory, "compact_memory"),
+    (Action::InspectChar, "inspect_char"),
+    (Action::NextSuspiciousChar, "next_suspicious_char"),
+    (Action::ToggleBookmark, "toggle_bookmark"),
+    (Action::NextBookmark, "next_bookmark"),
+    (Action::ToggleBlockSelection, "block_selection"),
+    (Action::RecordMacro, "record_macro"),
+    (Action::RunMacro, "run_macro"),
+    (Action::SaveMacro, "save_macro"),
+    (Action::ListMacros, "list_macros"),
+    (Action::CycleTheme, "cycle_theme"),
+    (Action::RestoreSnapshot, "restore_snapshot"),
+    (Action::RestoreHistory, "restore_history"),
+    (Action::ToggleMark, "toggle_mark"),
+    (Action::ToggleVirtualSpace, "toggle_virtual_space"),
+    (Action::FormatTable, "format_table"),
+    (Action::TableNextCell, "table_next_cell"),
+    (Action::TablePrevCell, "table_prev_cell"),
+    (Action::InsertTab, "insert_tab"),
+    (Action::ToggleMarkdownPreview, "toggle_markdown_preview"),
+    (Action::ToggleOutline, "toggle_outline"),
+    (Action::CycleBoxDrawing, "cycle_box_drawing"),
+    (Action::NextSentence, "next_sentence"),
+    (Action::PrevSentence, "prev_sentence"),
+    (Action::SelectSentence, "select_sentence"),
+    (Action::SelectWord, "select_word"),
+    (Action::SelectLine, "select_line"),
+    (Action::ExpandSelection, "expand_selection"),
+    (Action::ToggleProse, "toggle_prose"),
+    (Action::ToggleSoftWrap, "toggle_soft_wrap"),
+    (Action::ToggleTitleBar, "toggle_title_bar"),
+    (Action::ToggleStatusBar, "toggle_status_bar"),
+    (Action::ToggleHelpLine, "toggle_help_line"),
+    (Action::HelpLineNextPage, "help_line_next_page"),
+    (Action::ShellOut, "shell"),
+    (Action::RunCommand, "run"),
+    (Action::RunOutput, "run_output"),
+    (Action::ToggleTerminal, "terminal"),
+    (Action::TaskList, "task_list"),
+    (Action::Print, "print"),
+    (Action::ExportHtml, "export_html"),
+    (Action::ToggleAnsi, "ansi_colors"),
+    (Action::CopyReference, "copy_reference"),
+];
+
+impl Action {
+    /// 配置文件中使用的名字
+    pub fn name(self) -> Option<&'static str> {
+        ACTION_NAMES
+            .iter()
+            .find(|(action, _)| *action == self)
+            .map(|(_, name)| *name)
+    }
+
+    /// 根据名字查找操作
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTION_NAMES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(action, _)| *action)
+    }
+
+    /// 帮助栏中显示的简短说明
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Quit => "退出",
+            Action::OpenFile => "打开",
+            Action::NextBuffer => "下一缓冲区",
+            Action::PrevBuffer => "上一缓冲区",
+            Action::Save => "保存",
+            Action::ShowHelp => "帮助",
+            Action::ToggleSecondaryCursor => "多光标",
+            Action::ClearCursors => "单光标",
+            Action::MoveCursor(_) => "移动光标",
+            Action::MoveSecondaryCursor(_) => "移动多光标",
+            Action::SelectMove(_) => "选择",
+            Action::InsertNewline => "换行",
+            Action::DeleteBackward => "退格",
+            Action::DeleteForward => "删除",
+            Action::CutLine => "剪切",
+            Action::CutToLineEnd => "剪切到行尾",
+            Action::DuplicateLine => "复制行",
+            Action::DeleteLine => "删除行",
+            Action::MoveLineUp => "上移行",
+            Action::MoveLineDown => "下移行",
+            Action::Copy => "复制",
+            Action::Paste => "粘贴",
+            Action::YankPop => "换粘贴内容",
+            Action::PasteFromHistory => "剪切历史",
+            Action::PasteSystem => "粘贴剪贴板",
+            Action::Undo => "撤销",
+            Action::Redo => "重做",
+            Action::UndoBoundary => "撤销断点",
+            Action::ToggleCutFromCursor => "剪切到行尾",
+            Action::TransposeChars => "交换字符",
+            Action::TransposeWords => "交换单词",
+            Action::JumpPastCloser => "跳出括号",
+            Action::SetOption => "设置",
+            Action::ReloadConfig => "重载配置",
+            Action::DumpConfig => "导出配置",
+            Action::Search => "搜索",
+            Action::SearchBackward => "反向搜索",
+            Action::SearchNext => "下一个",
+            Action::SearchPrev => "上一个",
+            Action::SearchResults => "搜索结果",
+            Action::Replace => "替换",
+            Action::ClearHighlight => "清除高亮",
+            Action::InsertDigraph => "二合字母",
+            Action::EnableEditing => "完整读入",
+            Action::JumpLongestLine => "最长行",
+            Action::GotoLine => "跳到行",
+            Action::PageUp => "上一页",
+            Action::PageDown => "下一页",
+            Action::LineStart => "行首",
+            Action::LineEnd => "行尾",
+            Action::WordLeft => "上一词",
+            Action::WordRight => "下一词",
+            Action::DeleteWordBackward => "删前词",
+            Action::DeleteWordForward => "删后词",
+            Action::ToggleProtectLines => "保护行",
+            Action::NextLocation => "下一位置",
+            Action::PrevLocation => "上一位置",
+            Action::FirstLocation => "第一位置",
+            Action::LastLocation => "最后位置",
+            Action::ListChanges => "修改列表",
+            Action::ShowJournal => "编辑日志",
+            Action::MemoryReport => "内存占用",
+            Action::CompactMemory => "整理内存",
+            Action::InspectChar => "字符信息",
+            Action::NextSuspiciousChar => "可疑字符",
+            Action::ToggleBookmark => "书签",
+            Action::NextBookmark => "下一书签",
+            Action::ToggleBlockSelection => "块选择",
+            Action::RecordMacro => "录制宏",
+            Action::RunMacro => "运行宏",
+            Action::SaveMacro => "保存宏",
+            Action::ListMacros => "宏列表",
+            Action::CycleTheme => "主题",
+            Action::RestoreSnapshot => "快照",
+            Action::RestoreHistory => "历史版本",
+            Action::ToggleMark => "标记",
+            Action::ToggleVirtualSpace => "自由光标",
+            Action::FormatTable => "格式化表格",
+            Action::TableNextCell => "下一单元格",
+            Action::TablePrevCell => "上一单元格",
+            Action::InsertTab => "制表符",
+            Action::ToggleMarkdownPreview => "预览",
+            Action::ToggleOutline => "大纲",
+            Action::CycleBoxDrawing => "画框",
+            Action::NextSentence => "下一句",
+            Action::PrevSentence => "上一句",
+            Action::SelectSentence => "选中句子",
+            Action::SelectWord => "选中单词",
+            Action::SelectLine => "选中行",
+            Action::ExpandSelection => "扩大选区",
+            Action::ToggleProse => "散文模式",
+            Action::ToggleSoftWrap => "软换行",
+            Action::ToggleTitleBar => "标题栏",
+            Action::ToggleStatusBar => "状态栏",
+            Action::ToggleHelpLine => "帮助栏",
+            Action::HelpLineNextPage => "更多",
+            Action::ShellOut => "Shell",
+            Action::RunCommand => "运行",
+            Action::RunOutput => "输出",
+            Action::ToggleTerminal => "终端",
+            Action::TaskList => "任务",
+            Action::Print => "打印",
+            Action::ExportHtml => "HTML",
+            Action::ToggleAnsi => "颜色",
+            Action::CopyReference => "引用",
+            Action::InsertChar(_) => "插入",
+        }
+    }
+
+    /// 是否会修改缓冲区内容，只读缓冲区里这些操作会被拒绝
+    pub fn modifies_buffer(self) -> bool {
+        matches!(
+            self,
+            Action::InsertNewline
+                | Action::DeleteBackward
+                | Action::DeleteForward
+                | Action::DeleteWordBackward
+                | Action::DeleteWordForward
+                | Action::InsertChar(_)
+                | Action::FormatTable
+                | Action::TableNextCell
+                | Action::TablePrevCell
+                | Action::InsertTab
+                | Action::CutLine
+                | Action::CutToLineEnd
+                | Action::DuplicateLine
+                | Action::DeleteLine
+                | Action::MoveLineUp
+                | Action::MoveLineDown
+                | Action::Paste
+                | Action::YankPop
+                | Action::PasteFromHistory
+                | Action::PasteSystem
+                | Action::Undo
+                | Action::Redo
+                | Action::TransposeChars
+                | Action::TransposeWords
+                | Action::RestoreSnapshot
+                | Action::RestoreHistory
+                | Action::InsertDigraph
+        )
+    }
+}
@@ -0,0 +1,175 @@
+// 查看模式中的 ANSI 颜色：从输入中去掉 SGR 等控制序列，记下每段文字的颜色
+//
+// 例如 `cargo build --color=always | rsnano --pager` 的输出。去掉序列后的文本用于显示和
+// 搜索，原始文本另外保存，可以切换回去查看序列本身。颜色、粗体和反色之外的属性忽略。
+
+use crate::theme::Style;
+use crossterm::style::Color;
+
+/// 一段带颜色的文字：行内的字符范围和样式
+pub type ColorSpan = (usize, usize, Style);
+
+/// 缓冲区的颜色信息和另一种显示方式的文本
+#[derive(Debug, Clone, Default)]
+pub struct AnsiText {
+    /// 每行的颜色段
+    pub spans: Vec<Vec<ColorSpan>>,
+    /// 当前没有显示的那份文本：显示颜色时是原始文本，显示序列时是去掉序列的文本
+    pub other_lines: Vec<String>,
+    /// 正在按原样显示控制序列
+    pub literal: bool,
+}
+
+impl AnsiText {
+    /// 第 y 行的颜色段，按原样显示序列时没有颜色
+    pub fn spans_on(&self, y: usize) -> &[ColorSpan] {
+        if self.literal {
+            return &[];
+        }
+        self.spans.get(y).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// SGR 30–37、40–47 对应的颜色，加 8 为 90–97、100–107 的亮色
+const BASIC: [Color; 16] = [
+    Color::Black,
+    Color::DarkRed,
+    Color::DarkGreen,
+    Color::DarkYellow,
+    Color::DarkBlue,
+    Color::DarkMagenta,
+    Color::DarkCyan,
+    Color::Grey,
+    Color::DarkGrey,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+];
+
+/// 38/48 之后的扩展颜色：`5;n` 或 `2;r;g;b`，返回颜色和用掉的参数个数
+fn extended_color(params: &[u16]) -> (Option<Color>, usize) {
+    match params {
+        [5, n, ..] => (Some(Color::AnsiValue(*n as u8)), 2),
+        [2, r, g, b, ..] => (
+            Some(Color::Rgb {
+                r: *r as u8,
+                g: *g as u8,
+                b: *b as u8,
+            }),
+            4,
+        ),
+        _ => (None, params.len()),
+    }
+}
+
+/// 按一条 SGR 序列的参数修改当前样式
+fn apply_sgr(style: &mut Style, params: &[u16]) {
+    if params.is_empty() {
+        *style = Style::default();
+        return;
+    }
+    let mut i = 0;
+    while i < params.len() {
+        let code = params[i];
+        i += 1;
+        match code {
+            0 => *style = Style::default(),
+            1 => style.bold = true,
+            22 => style.bold = false,
+            7 => style.reverse = true,
+            27 => style.reverse = false,
+            30..=37 => style.fg = Some(BASIC[(code - 30) as usize]),
+            90..=97 => style.fg = Some(BASIC[(code - 90 + 8) as usize]),
+            40..=47 => style.bg = Some(BASIC[(code - 40) as usize]),
+            100..=107 => style.bg = Some(BASIC[(code - 100 + 8) as usize]),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            38 | 48 => {
+                let (color, used) = extended_color(&params[i..]);
+                i += used;
+                if code == 38 {
+                    style.fg = color;
+                } else {
+                    style.bg = color;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 去掉一行中的控制序列，style 为行首的样式，处理完后为行尾的样式
+fn strip_line(line: &str, style: &mut Style, spans: &mut Vec<ColorSpan>) -> String {
+    let mut text = String::with_capacity(line.len());
+    let mut count = 0;
+    let mut span_start = 0;
+    let mut span_style = *style;
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            text.push(ch);
+            count += 1;
+            continue;
+        }
+        if chars.peek() != Some(&'[') {
+            // 其他 ESC 序列只去掉 ESC 和后面的一个字符
+            chars.next();
+            continue;
+        }
+        chars.next();
+        let mut params = String::new();
+        let mut action = None;
+        for ch in chars.by_ref() {
+            if ('\x40'..='\x7e').contains(&ch) {
+                action = Some(ch);
+                break;
+            }
+            params.push(ch);
+        }
+        if action != Some('m') {
+            continue;
+        }
+        let codes: Vec<u16> = params
+            .split([';', ':'])
+            .filter(|p| !p.is_empty())
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        apply_sgr(style, &codes);
+        if *style != span_style {
+            if span_style != Style::default() && count > span_start {
+                spans.push((span_start, count, span_style));
+            }
+            span_start = count;
+            span_style = *style;
+        }
+    }
+    if span_style != Style::default() && count > span_start {
+        spans.push((span_start, count, span_style));
+    }
+    text
+}
+
+/// 去掉所有行中的控制序列，返回去掉后的文本和颜色信息；没有 ESC 时返回 None
+pub fn strip(lines: &[String]) -> Option<(Vec<String>, AnsiText)> {
+    if !lines.iter().any(|l| l.contains('\x1b')) {
+        return None;
+    }
+    let mut style = Style::default();
+    let mut stripped = Vec::with_capacity(lines.len());
+    let mut all_spans = Vec::with_capacity(lines.len());
+    for line in lines {
+        let mut spans = Vec::new();
+        stripped.push(strip_line(line, &mut style, &mut spans));
+        all_spans.push(spans);
+    }
+    let ansi = AnsiText {
+        spans: all_spans,
+        other_lines: lines.to_vec(),
+        literal: false,
+    };
+    Some((stripped, ansi))
+}
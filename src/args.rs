@@ -1,24 +1,187 @@
 use clap::Parser;
+use std::ffi::OsString;
 use std::path::PathBuf;
 
-use crate::Result;
+use crate::{EditorError, Result};
 
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[derive(Parser, Debug, Clone)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    after_help = "文件名可以写成 路径:行[:列]，例如 main.rs:10:5，直接粘贴编译器或 grep 的输出即可\n\
+                  在文件名前加 +/模式 打开后光标停在第一处匹配，+?模式 停在最后一处匹配（作用于第一个文件）"
+)]
 pub struct Args {
-    /// 要编辑的文件
-    pub file: Option<PathBuf>,
-    
+    /// 要编辑的文件，可以有多个，每个文件一个缓冲区
+    #[arg(value_name = "FILE[:LINE[:COL]]", value_parser = FileArg::parse)]
+    pub files: Vec<FileArg>,
+
     /// 显示行号
     #[arg(short, long)]
     pub line_numbers: bool,
+
+    /// 制表符宽度（1 到 16）
+    #[arg(short = 'T', long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..=16))]
+    pub tabsize: Option<u8>,
+
+    /// 按 Tab 时插入空格而不是制表符
+    #[arg(short = 'E', long)]
+    pub tabstospaces: bool,
+
+    /// 自由光标模式：光标可移到行尾和文件末尾之后
+    #[arg(long)]
+    pub virtual_space: bool,
+
+    /// ^K 从光标处剪切到行尾，而不是剪切整行
+    #[arg(short = 'k', long)]
+    pub cut_from_cursor: bool,
+
+    /// 退出时保存剪切历史，下次启动时可以继续粘贴
+    #[arg(long)]
+    pub persist_cutbuffer: bool,
+
+    /// 自动补全括号和引号
+    #[arg(long)]
+    pub autoclose: bool,
+
+    /// 把生效配置和完整键位表写到文件后退出，文件名为 - 时输出到终端
+    #[arg(long, value_name = "FILE")]
+    pub dump_config: Option<PathBuf>,
+
+    /// 不显示底部帮助栏
+    #[arg(short = 'x', long)]
+    pub no_help: bool,
+
+    /// 不显示顶部标题栏
+    #[arg(long)]
+    pub no_title: bool,
+
+    /// 散文模式：对 Markdown 和 .txt 文件开启软换行和排版替换（破折号、弯引号）
+    #[arg(long)]
+    pub prose: bool,
+
+    /// 把这次会话的按键和粘贴连同时间间隔记录到文件，用于复现问题
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// 按原来的节奏回放 --record 记录的输入，回放中按任意键中断
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// 只读查看模式（以 rspager 为名启动时也是）：空格/b 翻页、/ 搜索、q 退出、v 开始编辑；
+    /// 没有文件且标准输入不是终端时读入标准输入
+    #[arg(long)]
+    pub pager: bool,
+
+    /// 安全模式：只用内置默认设置，不读配置文件、主题、项目配置和各种历史，
+    /// 也不往状态目录写任何东西，用于排查问题是否来自自定义设置
+    #[arg(long, visible_alias = "ignore-rc")]
+    pub safe: bool,
+
+    /// 打开文件后查找的内容，来自 `+/模式` 或 `+?模式` 参数
+    #[arg(skip)]
+    pub start_search: Option<StartSearch>,
+}
+
+/// 命令行上的一个文件，可以带行号和列号（从 1 开始）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileArg {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl FileArg {
+    /// 解析 `路径`、`路径:行` 或 `路径:行:列`，末尾多出的冒号（grep -n 的输出）忽略
+    ///
+    /// 整个参数本身就是一个存在的文件时按原样作为路径。
+    pub fn parse(arg: &str) -> std::result::Result<Self, String> {
+        let plain = Self {
+            path: PathBuf::from(arg),
+            line: None,
+            column: None,
+        };
+        if arg.is_empty() || plain.path.exists() {
+            return Ok(plain);
+        }
+        let trimmed = arg.strip_suffix(':').unwrap_or(arg);
+        let number = |s: &str| s.parse::<usize>().ok().filter(|&n| n > 0);
+        let mut parts = trimmed.rsplitn(3, ':');
+        let (last, middle, first) = (parts.next(), parts.next(), parts.next());
+        let parsed = match (first, middle.and_then(number), last.and_then(number)) {
+            (Some(path), Some(line), Some(column)) => Some((path, line, Some(column))),
+            _ => trimmed
+                .rsplit_once(':')
+                .and_then(|(path, line)| Some((path, number(line)?, None))),
+        };
+        Ok(match parsed {
+            Some((path, line, column)) if !path.is_empty() => Self {
+                path: PathBuf::from(path),
+                line: Some(line),
+                column,
+            },
+            _ => plain,
+        })
+    }
+}
+
+/// 命令行上的 `+/模式`（从开头查找）或 `+?模式`（从末尾向前查找）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartSearch {
+    pub pattern: String,
+    pub backwards: bool,
+}
+
+impl StartSearch {
+    /// 解析一个命令行参数，不是 `+/` 或 `+?` 开头时返回 None
+    pub fn parse(arg: &str) -> Option<Self> {
+        let rest = arg.strip_prefix('+')?;
+        let backwards = match rest.chars().next()? {
+            '/' => false,
+            '?' => true,
+            _ => return None,
+        };
+        Some(Self {
+            pattern: rest[1..].to_string(),
+            backwards,
+        })
+    }
 }
 
 impl Args {
     /// 从命令行参数解析Args实例
     pub fn from_cli() -> Result<Self> {
-        Self::try_parse_from(std::env::args_os()).map_err(|e| {
-            Box::new(e) as Box<dyn std::error::Error>
-        })
+        let mut args = Self::parse_from_args(std::env::args_os())?;
+        let program = std::env::args_os().next().map(PathBuf::from);
+        args.pager |= program
+            .as_deref()
+            .and_then(|p| p.file_stem())
+            .is_some_and(|stem| stem == "rspager");
+        Ok(args)
+    }
+
+    /// 先取出 clap 不认识的 `+/模式` 参数，其余交给 clap；有多个时以最后一个为准
+    pub fn parse_from_args(args: impl IntoIterator<Item = OsString>) -> Result<Self> {
+        let mut start_search = None;
+        let mut rest = Vec::new();
+        let mut options_ended = false;
+        for arg in args {
+            let parsed = match arg.to_str() {
+                Some(s) if !options_ended => StartSearch::parse(s),
+                _ => None,
+            };
+            match parsed {
+                Some(search) => start_search = Some(search),
+                None => {
+                    options_ended |= arg == "--";
+                    rest.push(arg);
+                }
+            }
+        }
+        let mut args = Self::try_parse_from(rest).map_err(EditorError::Args)?;
+        args.start_search = start_search;
+        Ok(args)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,92 @@
+// 画框模式：用方向键延伸制表符，自动连接拐角
+
+use crate::direction::Direction;
+
+/// 画框使用的字符风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoxStyle {
+    /// ┌─┐│└┘ 等 Unicode 制表符
+    Unicode,
+    /// + - | 组成的 ASCII 框线
+    Ascii,
+}
+
+impl BoxStyle {
+    /// 状态栏显示的名称
+    pub fn label(self) -> &'static str {
+        match self {
+            BoxStyle::Unicode => "─┼",
+            BoxStyle::Ascii => "-+",
+        }
+    }
+}
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+/// 方向对应的连接位
+pub fn direction_bit(direction: Direction) -> u8 {
+    match direction {
+        Direction::Up => UP,
+        Direction::Down => DOWN,
+        Direction::Left => LEFT,
+        Direction::Right => RIGHT,
+    }
+}
+
+/// 相反方向
+pub fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+    }
+}
+
+/// 字符已有的连接方向，不是框线字符时返回 None
+pub fn connections(ch: char) -> Option<u8> {
+    let mask = match ch {
+        '─' | '-' => LEFT | RIGHT,
+        '│' | '|' => UP | DOWN,
+        '┌' => DOWN | RIGHT,
+        '┐' => DOWN | LEFT,
+        '└' => UP | RIGHT,
+        '┘' => UP | LEFT,
+        '├' => UP | DOWN | RIGHT,
+        '┤' => UP | DOWN | LEFT,
+        '┬' => DOWN | LEFT | RIGHT,
+        '┴' => UP | LEFT | RIGHT,
+        '┼' | '+' => UP | DOWN | LEFT | RIGHT,
+        _ => return None,
+    };
+    Some(mask)
+}
+
+/// 按连接方向选出对应的框线字符
+pub fn glyph(mask: u8, style: BoxStyle) -> char {
+    let vertical = mask & (UP | DOWN) != 0;
+    let horizontal = mask & (LEFT | RIGHT) != 0;
+    match style {
+        BoxStyle::Ascii => match (vertical, horizontal) {
+            (true, true) => '+',
+            (true, false) => '|',
+            _ => '-',
+        },
+        BoxStyle::Unicode => match mask {
+            m if m == DOWN | RIGHT => '┌',
+            m if m == DOWN | LEFT => '┐',
+            m if m == UP | RIGHT => '└',
+            m if m == UP | LEFT => '┘',
+            m if m == UP | DOWN | RIGHT => '├',
+            m if m == UP | DOWN | LEFT => '┤',
+            m if m == DOWN | LEFT | RIGHT => '┬',
+            m if m == UP | LEFT | RIGHT => '┴',
+            m if m == UP | DOWN | LEFT | RIGHT => '┼',
+            _ if vertical => '│',
+            _ => '─',
+        },
+    }
+}
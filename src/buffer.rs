@@ -1,62 +1,206 @@
 use std::fs;
+use std::io;
 use std::path::PathBuf;
 use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
 
+mod annotation;
+mod ansi;
+mod autoclose;
+mod block;
+mod cut;
+mod drawing;
+mod highlight;
+mod indent;
+mod invariants;
+mod line_limit;
+mod line_ops;
+mod memory;
+mod motion;
+mod multicursor;
+mod preview;
+mod prose;
+mod protected;
+mod search;
+mod sign;
+mod stats;
+mod selection;
+mod sentence;
+mod tab;
+mod table;
+mod transpose;
+mod undo;
+mod virtual_space;
+
+pub use annotation::{Annotation, AnnotationPlacement};
+pub use highlight::SearchHighlight;
+pub use indent::IndentStyle;
+pub use memory::{MemoryUsage, COMPACT_UNDO_STEPS};
+pub use multicursor::Cursor;
+pub use preview::LargeFilePreview;
+pub use search::{find_in_line, Matcher, SearchMatch};
+pub use sign::Sign;
+pub use stats::BufferStats;
+pub use undo::{EditKind, UndoHistory};
+
+use crate::ansi::AnsiText;
 use crate::direction::Direction;
-use crate::Result;
+use crate::filetype::FileType;
+use crate::rawbytes;
+use crate::settings::{BufferSettings, LineLimit};
+use crate::unicode::{display_column, grapheme_bounds};
+use crate::{EditorError, Result};
+
+/// 下一个新建缓冲区的编号
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
 
 /// 文本缓冲区，存储编辑器的内容和光标状态
 #[derive(Clone)]
 pub struct TextBuffer {
+    /// 进程内唯一的编号，区分未命名缓冲区的快照
+    pub id: u64,
     pub lines: Vec<String>,
     /// 光标所在字符索引（不是字节索引，支持中文）
     pub cursor_x: usize,
     pub cursor_y: usize,
-    pub cursor_x2: Option<usize>,
-    pub cursor_y2: Option<usize>,
+    /// 主光标之外的光标，多光标模式下编辑在每个光标处各执行一次
+    pub cursors: Vec<Cursor>,
+    /// 不换行时水平滚动到的显示列，屏幕上从这一列开始显示
     pub offset_x: usize,
     pub offset_y: usize,
     pub modified: bool,
     pub filename: Option<PathBuf>,
     pub modified_lines_set: HashSet<usize>, // 新增：记录被修改过的行号
+    /// 选区锚点（标记），选区为锚点与主光标之间的文本
+    pub mark: Option<Position>,
+    /// 块选择的锚点，选区为锚点与主光标之间的矩形（按显示列）
+    pub block_mark: Option<Position>,
+    /// 上下移动时希望保持的列（粘性列），水平移动或编辑后清除
+    pub goal_x: Option<usize>,
+    /// 文件类型
+    pub filetype: FileType,
+    /// 本缓冲区的设置（制表符宽度、软换行、只读等）
+    pub settings: BufferSettings,
+    /// 读入时从内容中检测到的缩进方式，判断不出时为 None
+    pub indent_style: Option<IndentStyle>,
+    /// 内容版本号，每次修改文本都会递增，供预览、高亮等缓存判断是否需要刷新
+    pub revision: u64,
+    /// 上次自动快照时的版本号
+    pub snapshot_revision: Option<u64>,
+    /// 搜索高亮，清除或开始新的搜索前一直保留
+    pub highlight: Option<SearchHighlight>,
+    /// 大文件只读预览，完整读入后为 None
+    pub preview: Option<LargeFilePreview>,
+    /// 最长行和总大小，每帧按版本号更新
+    pub stats: Option<BufferStats>,
+    /// 读入时遇到的无效 UTF-8 字节个数，这些字节转义保存（见 rawbytes）
+    pub invalid_bytes: usize,
+    /// 查看模式中输入带的 ANSI 颜色（见 crate::ansi），没有时为 None
+    pub ansi: Option<AnsiText>,
+    /// 撤销和重做历史
+    pub history: UndoHistory,
+    /// 受保护的行，按行号排列、互不重叠；碰到这些行的修改会被撤回（撤销和重做不受限制）
+    pub protected_lines: Vec<Range<usize>>,
+    /// 上一次因为碰到受保护的行而被撤回的修改所在的行，由编辑器取出后提示
+    pub rejected_edit: Option<usize>,
+    /// 附在行上的虚拟文本，只用于显示
+    pub annotations: Vec<Annotation>,
+    /// 符号栏中的符号，只用于显示
+    pub signs: Vec<Sign>,
+}
+
+/// 缓冲区中的位置，先按行再按列比较先后
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub y: usize,
+    /// 字符索引（不是字节索引）
+    pub x: usize,
+}
+
+impl Position {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { y, x }
+    }
+}
+
+/// 把字符索引转换为字节索引，超出行尾时返回行的字节长度
+pub fn byte_index(line: &str, char_idx: usize) -> usize {
+    line.char_indices()
+        .nth(char_idx)
+        .map(|(pos, _)| pos)
+        .unwrap_or(line.len())
+}
+
+impl Default for TextBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TextBuffer {
     pub fn new() -> Self {
         Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             lines: vec![String::new()],
             cursor_x: 0,
             cursor_y: 0,
-            cursor_x2: None,
-            cursor_y2: None,
+            cursors: Vec::new(),
             offset_x: 0,
             offset_y: 0,
             modified: false,
             filename: None,
             modified_lines_set: HashSet::new(),
+            mark: None,
+            block_mark: None,
+            goal_x: None,
+            filetype: FileType::Plain,
+            settings: BufferSettings::default(),
+            indent_style: None,
+            revision: 0,
+            snapshot_revision: None,
+            highlight: None,
+            preview: None,
+            stats: None,
+            invalid_bytes: 0,
+            ansi: None,
+            history: UndoHistory::default(),
+            protected_lines: Vec::new(),
+            rejected_edit: None,
+            annotations: Vec::new(),
+            signs: Vec::new(),
         }
     }
 
     pub fn from_file(path: &PathBuf) -> Result<Self> {
-        let contents = fs::read_to_string(path).unwrap_or_default();
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            // 文件不存在时作为新文件打开
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(EditorError::io(path, e)),
+        };
+        Ok(Self::from_bytes(Some(path), &bytes))
+    }
+
+    /// 由文件内容创建缓冲区，path 为 None 时是没有文件名的缓冲区（例如读自标准输入）
+    pub fn from_bytes(path: Option<&PathBuf>, bytes: &[u8]) -> Self {
+        // 无效的 UTF-8 字节转义保存，写回时原样还原
+        let (contents, invalid_bytes) = rawbytes::decode(bytes);
         let lines = if contents.is_empty() {
             vec![String::new()]
         } else {
             contents.lines().map(|s| s.to_string()).collect()
         };
 
-        Ok(Self {
+        let first_line = lines.first().map(String::as_str).unwrap_or("");
+        Self {
+            filename: path.cloned(),
+            filetype: FileType::detect(path.map(PathBuf::as_path), first_line),
+            indent_style: IndentStyle::detect(&lines),
             lines,
-            cursor_x: 0,
-            cursor_y: 0,
-            cursor_x2: None,
-            cursor_y2: None,
-            offset_x: 0,
-            offset_y: 0,
-            modified: false,
-            filename: Some(path.clone()),
-            modified_lines_set: HashSet::new(),
-        })
+            invalid_bytes,
+            ..Self::new()
+        }
     }
 
     pub fn current_line(&self) -> &String {
@@ -68,148 +212,200 @@ impl TextBuffer {
     }
 
     /// 在当前光标位置插入字符（按字符索引插入，支持中文）
+    ///
+    /// 总是插入，不检查光标处是否已有同一个字符；重复按键的过滤只在输入层
+    /// （`input::is_actionable`），跳过已有的右括号只发生在自动补全模式中。
     pub fn insert_char(&mut self, ch: char) {
-        let cursor_x = self.cursor_x; // 保存光标位置，避免借用冲突
-        let line = self.current_line_mut();
-        let mut byte_pos = 0;
-        let mut char_count = 0;
-        for (pos, _) in line.char_indices() {
-            if char_count == cursor_x {
-                byte_pos = pos;
-                break;
+        let lines = self.lines_around_cursor();
+        self.record_edit(EditKind::Typing, lines, |buffer| {
+            // 有选区时输入的字符替换选中内容
+            buffer.delete_selection();
+            buffer.clear_goal_columns();
+            buffer.fill_virtual_space();
+            let cursor_x = buffer.cursor_x; // 保存光标位置，避免借用冲突
+            let line = buffer.current_line_mut();
+            let mut byte_pos = 0;
+            for (char_count, (pos, _)) in line.char_indices().enumerate() {
+                if char_count == cursor_x {
+                    byte_pos = pos;
+                    break;
+                }
             }
-            char_count += 1;
-        }
-        if cursor_x >= line.chars().count() {
-            byte_pos = line.len();
-        }
-        line.insert(byte_pos, ch);
-        self.cursor_x += 1;
-        self.modified = true;
-        self.modified_lines_set.insert(self.cursor_y);
+            if cursor_x >= line.chars().count() {
+                byte_pos = line.len();
+            }
+            line.insert(byte_pos, ch);
+            buffer.cursor_x += 1;
+            buffer.mark_line_modified(buffer.cursor_y);
+        });
     }
 
     /// 插入新行，光标移到下一行行首
     pub fn insert_newline(&mut self) {
-        let cursor_x = self.cursor_x; // 保存光标位置
-        let line = self.current_line().clone();
-        let mut byte_pos = 0;
-        let mut char_count = 0;
-        for (pos, _) in line.char_indices() {
-            if char_count == cursor_x {
-                byte_pos = pos;
-                break;
+        let lines = self.lines_around_cursor();
+        self.record_edit(EditKind::Other, lines, |buffer| {
+            buffer.delete_selection();
+            buffer.clear_goal_columns();
+            buffer.fill_virtual_space();
+            let cursor_x = buffer.cursor_x; // 保存光标位置
+            let line = buffer.current_line().clone();
+            let mut byte_pos = 0;
+            for (char_count, (pos, _)) in line.char_indices().enumerate() {
+                if char_count == cursor_x {
+                    byte_pos = pos;
+                    break;
+                }
             }
-            char_count += 1;
-        }
-        if cursor_x >= line.chars().count() {
-            byte_pos = line.len();
-        }
-        let (left, right) = line.split_at(byte_pos);
-        self.lines[self.cursor_y] = left.to_string();
-        self.lines.insert(self.cursor_y + 1, right.to_string());
-        self.cursor_y += 1;
-        self.cursor_x = 0;
-        self.modified = true;
-        self.modified_lines_set.insert(self.cursor_y - 1);
-        self.modified_lines_set.insert(self.cursor_y);
+            if cursor_x >= line.chars().count() {
+                byte_pos = line.len();
+            }
+            let (left, right) = line.split_at(byte_pos);
+            buffer.lines[buffer.cursor_y] = left.to_string();
+            buffer.lines.insert(buffer.cursor_y + 1, right.to_string());
+            buffer.cursor_y += 1;
+            buffer.cursor_x = 0;
+            buffer.mark_line_modified(buffer.cursor_y - 1);
+            buffer.modified_lines_set.insert(buffer.cursor_y);
+        });
     }
 
     /// 删除光标前字符（支持中文，按字符索引删除）
     pub fn delete_char(&mut self) {
-        let cursor_x = self.cursor_x; // 保存光标位置
-        if cursor_x > 0 {
-            let line = self.current_line_mut();
-            let char_indices: Vec<usize> = line.char_indices().map(|(i, _)| i).collect();
-            let byte_pos = if cursor_x < char_indices.len() {
-                char_indices[cursor_x]
-            } else {
-                line.len()
-            };
-            let prev_pos = if cursor_x > 0 {
-                char_indices[cursor_x - 1]
-            } else {
-                0
-            };
-            line.drain(prev_pos..byte_pos);
-            self.cursor_x -= 1;
-            self.modified = true;
-            self.modified_lines_set.insert(self.cursor_y);
-        } else if self.cursor_y > 0 {
-            // 与上一行合并
-            let current_line = self.lines.remove(self.cursor_y);
-            self.cursor_y -= 1;
-            self.cursor_x = self.lines[self.cursor_y].chars().count();
-            self.lines[self.cursor_y].push_str(&current_line);
-            self.modified = true;
-            self.modified_lines_set.insert(self.cursor_y);
+        let lines = self.lines_around_cursor();
+        self.record_edit(EditKind::Deleting, lines, |buffer| {
+            // 有选区时删除整个选区
+            if buffer.delete_selection() {
+                return;
+            }
+            buffer.clear_goal_columns();
+            // 空白区域里退格只移动光标，不删除文字
+            if buffer.cursor_y >= buffer.lines.len() {
+                buffer.cursor_y -= 1;
+                return;
+            }
+            if buffer.cursor_x > buffer.current_line().chars().count() {
+                buffer.cursor_x -= 1;
+                return;
+            }
+            let cursor_x = buffer.cursor_x; // 保存光标位置
+            if cursor_x > 0 {
+                let line = buffer.current_line_mut();
+                let char_indices: Vec<usize> = line.char_indices().map(|(i, _)| i).collect();
+                let byte_pos = if cursor_x < char_indices.len() {
+                    char_indices[cursor_x]
+                } else {
+                    line.len()
+                };
+                let prev_pos = if cursor_x > 0 {
+                    char_indices[cursor_x - 1]
+                } else {
+                    0
+                };
+                line.drain(prev_pos..byte_pos);
+                buffer.cursor_x -= 1;
+                buffer.mark_line_modified(buffer.cursor_y);
+            } else if buffer.cursor_y > 0 {
+                // 与上一行合并
+                let current_line = buffer.lines.remove(buffer.cursor_y);
+                buffer.cursor_y -= 1;
+                buffer.cursor_x = buffer.lines[buffer.cursor_y].chars().count();
+                buffer.lines[buffer.cursor_y].push_str(&current_line);
+                buffer.mark_line_modified(buffer.cursor_y);
+            }
+        });
+    }
+
+    /// 记录第 y 行被修改
+    pub fn mark_line_modified(&mut self, y: usize) {
+        self.modified = true;
+        self.modified_lines_set.insert(y);
+        self.revision += 1;
+    }
+
+    /// 用 lines 替换全部内容（恢复快照等），光标移到合法位置
+    pub fn replace_contents(&mut self, lines: Vec<String>) {
+        let range = 0..self.lines.len();
+        self.record_edit(EditKind::Other, range, |buffer| {
+            buffer.lines = if lines.is_empty() { vec![String::new()] } else { lines };
+            buffer.mark = None;
+            buffer.cursors.clear();
+            buffer.clear_goal_columns();
+            let pos = buffer.clamp_position(buffer.cursor_position());
+            buffer.set_cursor_position(pos);
+            buffer.modified = true;
+            buffer.modified_lines_set.extend(0..buffer.lines.len());
+            buffer.revision += 1;
+        });
+    }
+
+    /// 清除所有光标的粘性列，光标被编辑或跳转后调用
+    pub fn clear_goal_columns(&mut self) {
+        self.goal_x = None;
+        for cursor in &mut self.cursors {
+            cursor.goal_x = None;
         }
     }
 
-    /// 光标移动，支持左右行首/行尾跳转
-    pub fn move_cursor(&mut self, direction: Direction, terminal_size: (u16, u16), is_secondary: bool) {
+    /// 删除光标处的字符（连同附着在它上面的组合符号），位于行尾时与下一行合并
+    pub fn delete_char_forward(&mut self) {
+        let lines = self.lines_around_cursor();
+        self.record_edit(EditKind::Deleting, lines, |buffer| {
+            if buffer.delete_selection() {
+                return;
+            }
+            buffer.clear_goal_columns();
+            buffer.fill_virtual_space();
+            let cursor_x = buffer.cursor_x;
+            let chars: Vec<char> = buffer.current_line().chars().collect();
+            if cursor_x < chars.len() {
+                // 整个字素簇一起删除，不留下孤立的组合符号
+                let end = grapheme_bounds(&chars)
+                    .into_iter()
+                    .find(|&b| b > cursor_x)
+                    .unwrap_or(chars.len());
+                let line = buffer.current_line_mut();
+                let from = byte_index(line, cursor_x);
+                let to = byte_index(line, end);
+                line.drain(from..to);
+                buffer.mark_line_modified(buffer.cursor_y);
+            } else if buffer.cursor_y + 1 < buffer.lines.len() {
+                // 与下一行合并
+                let next_line = buffer.lines.remove(buffer.cursor_y + 1);
+                buffer.current_line_mut().push_str(&next_line);
+                buffer.mark_line_modified(buffer.cursor_y);
+            }
+        });
+    }
+
+    /// 光标移动，支持左右行首/行尾跳转；page_height 为编辑区的行数
+    pub fn move_cursor(&mut self, direction: Direction, page_height: usize, is_secondary: bool) {
         let lines_len = self.lines.len();
         if is_secondary {
-            let (x, y) = match (self.cursor_x2, self.cursor_y2) {
-                (Some(x), Some(y)) => (x, y),
-                _ => {
-                    self.cursor_x2 = Some(self.cursor_x);
-                    self.cursor_y2 = Some(self.cursor_y);
-                    (self.cursor_x, self.cursor_y)
-                }
-            };
-            let line_len = self.lines[y].chars().count();
-            match direction {
-                Direction::Up => {
-                    if y > 0 {
-                        self.cursor_y2 = Some(y - 1);
-                        let up_len = self.lines[y - 1].chars().count();
-                        self.cursor_x2 = Some(x.min(up_len));
-                    }
-                }
-                Direction::Down => {
-                    if y < lines_len - 1 {
-                        self.cursor_y2 = Some(y + 1);
-                        let down_len = self.lines[y + 1].chars().count();
-                        self.cursor_x2 = Some(x.min(down_len));
-                    }
-                }
-                Direction::Left => {
-                    if x > 0 {
-                        self.cursor_x2 = Some(x - 1);
-                    } else if y > 0 {
-                        self.cursor_y2 = Some(y - 1);
-                        let prev_len = self.lines[y - 1].chars().count();
-                        self.cursor_x2 = Some(prev_len);
-                    }
-                }
-                Direction::Right => {
-                    if x < line_len {
-                        self.cursor_x2 = Some(x + 1);
-                    } else if y < lines_len - 1 {
-                        self.cursor_y2 = Some(y + 1);
-                        self.cursor_x2 = Some(0);
-                    }
-                }
-            }
+            self.move_last_cursor(direction);
+        } else if self.settings.virtual_space {
+            self.move_cursor_free(direction, page_height);
         } else {
             let line_len = self.current_line().chars().count();
             match direction {
                 Direction::Up => {
                     if self.cursor_y > 0 {
+                        // 经过较短的行时记住原来的列，之后回到较长的行时恢复
+                        let goal = *self.goal_x.get_or_insert(self.cursor_x);
                         self.cursor_y -= 1;
                         let up_len = self.current_line().chars().count();
-                        self.cursor_x = self.cursor_x.min(up_len);
+                        self.cursor_x = goal.min(up_len);
                     }
                 }
                 Direction::Down => {
                     if self.cursor_y < lines_len - 1 {
+                        let goal = *self.goal_x.get_or_insert(self.cursor_x);
                         self.cursor_y += 1;
                         let down_len = self.current_line().chars().count();
-                        self.cursor_x = self.cursor_x.min(down_len);
+                        self.cursor_x = goal.min(down_len);
                     }
                 }
                 Direction::Left => {
+                    self.goal_x = None;
                     if self.cursor_x > 0 {
                         self.cursor_x -= 1;
                     } else if self.cursor_y > 0 {
@@ -218,6 +414,7 @@ impl TextBuffer {
                     }
                 }
                 Direction::Right => {
+                    self.goal_x = None;
                     if self.cursor_x < line_len {
                         self.cursor_x += 1;
                     } else if self.cursor_y < lines_len - 1 {
@@ -227,29 +424,40 @@ impl TextBuffer {
                 }
             }
         }
-        // 滚动逻辑略
-        let (_, height) = terminal_size;
-        let editor_height = height as usize - 2;
+        self.scroll_to_cursor(page_height);
+    }
+
+    /// 调整垂直滚动，让主光标在可见区域内；editor_height 为编辑区的行数
+    pub fn scroll_to_cursor(&mut self, editor_height: usize) {
         if self.cursor_y < self.offset_y {
             self.offset_y = self.cursor_y;
         } else if self.cursor_y >= self.offset_y + editor_height {
             self.offset_y = self.cursor_y - editor_height + 1;
         }
-        if let Some(y2) = self.cursor_y2 {
-            if y2 < self.offset_y {
-                self.offset_y = y2;
-            } else if y2 >= self.offset_y + editor_height {
-                self.offset_y = y2 - editor_height + 1;
-            }
-        }
+    }
+
+    /// 不换行时调整水平滚动，让主光标在可见区域内；width 为编辑区的列数
+    ///
+    /// 与 nano 一样按页滚动：光标接近右边时整屏左移，左右各留出滚动标记和几列上下文。
+    pub fn scroll_to_cursor_column(&mut self, width: usize) {
+        let line = &self.lines[self.cursor_y.min(self.lines.len() - 1)];
+        let col = display_column(line, self.cursor_x, self.settings.tab_width);
+        self.offset_x = if col + 2 < width {
+            0
+        } else if width > 8 {
+            col - 6 - (col - 6) % (width - 8)
+        } else {
+            col.saturating_sub(2)
+        };
     }
 
     /// 保存缓冲区内容到文件，返回被修改过的行数
     pub fn save(&mut self) -> Result<usize> {
         if let Some(filename) = &self.filename {
-            let contents = self.lines.join("\n");
-            fs::write(filename, contents)?;
+            let contents = rawbytes::encode(&self.lines.join("\n"));
+            fs::write(filename, contents).map_err(|e| EditorError::io(filename, e))?;
             self.modified = false;
+            self.history.mark_saved();
             let count = self.modified_lines_set.len();
             self.modified_lines_set.clear();
             Ok(count)
@@ -258,40 +466,63 @@ impl TextBuffer {
         }
     }
     
-    /// 切换第二个光标的显示/隐藏
-    pub fn toggle_secondary_cursor(&mut self) {
-        if self.cursor_x2.is_some() && self.cursor_y2.is_some() {
-            // 隐藏第二个光标
-            self.cursor_x2 = None;
-            self.cursor_y2 = None;
+    /// 重新识别文件类型（文件名变化或新文件写入 shebang 后调用）
+    pub fn detect_filetype(&mut self) {
+        self.filetype = FileType::detect(self.filename.as_deref(), &self.lines[0]);
+    }
+
+    /// 首行是否是 `#!` 开头的 shebang
+    pub fn has_shebang(&self) -> bool {
+        self.lines[0].starts_with("#!")
+    }
+
+    /// 给文件加上可执行权限（只对有读权限的用户加），非 Unix 平台上什么也不做
+    pub fn make_executable(&self) -> Result<()> {
+        #[cfg(unix)]
+        if let Some(filename) = &self.filename {
+            use std::os::unix::fs::PermissionsExt;
+            let mut permissions = fs::metadata(filename)
+                .map_err(|e| EditorError::io(filename, e))?
+                .permissions();
+            let mode = permissions.mode();
+            permissions.set_mode(mode | ((mode & 0o444) >> 2));
+            fs::set_permissions(filename, permissions).map_err(|e| EditorError::io(filename, e))?;
+        }
+        Ok(())
+    }
+
+    /// 在每个光标处插入字符
+    pub fn insert_char_at_all_cursors(&mut self, ch: char) {
+        self.edit_at_all_cursors(|buffer| buffer.insert_char(ch));
+    }
+
+    /// 键入一个字符：按当前设置做括号补全或排版替换，多光标时每个光标处都输入
+    pub fn type_char_at_all_cursors(&mut self, ch: char) {
+        if self.settings.auto_close && !(self.settings.prose && matches!(ch, '"' | '\'')) {
+            self.edit_at_all_cursors(|buffer| buffer.insert_char_autoclose(ch));
+        } else if self.settings.prose {
+            self.edit_at_all_cursors(|buffer| buffer.insert_char_typographic(ch));
         } else {
-            // 显示第二个光标，初始位置与主光标相同
-            self.cursor_x2 = Some(self.cursor_x);
-            self.cursor_y2 = Some(self.cursor_y);
+            self.insert_char_at_all_cursors(ch);
+        }
+        if self.settings.prose && (self.settings.auto_capitalize || self.settings.collapse_spaces) {
+            self.edit_at_all_cursors(|buffer| buffer.prose_autocorrect(ch));
+        }
+        if self.settings.line_limit == LineLimit::Wrap && self.settings.max_line_length > 0 {
+            self.edit_at_all_cursors(|buffer| buffer.hard_wrap_at_cursor());
         }
     }
-    
-    /// 同时在两个光标位置插入字符
-    pub fn insert_char_at_both_cursors(&mut self, ch: char) {
-        // 先在主光标位置插入
-        self.insert_char(ch);
-        
-        // 再在第二个光标位置插入
-        if let (Some(x2), Some(y2)) = (self.cursor_x2, self.cursor_y2) {
-            // 保存当前主光标位置
-            let main_x = self.cursor_x;
-            let main_y = self.cursor_y;
-            
-            // 临时切换到第二个光标位置
-            self.cursor_x = x2;
-            self.cursor_y = y2;
-            
-            // 插入字符
-            self.insert_char(ch);
-            
-            // 恢复主光标位置
-            self.cursor_x = main_x;
-            self.cursor_y = main_y;
+
+    /// 退格：自动补全模式下在空括号中间时删除整对
+    pub fn backspace_at_all_cursors(&mut self) {
+        if self.settings.auto_close {
+            self.edit_at_all_cursors(|buffer| {
+                if !buffer.delete_empty_pair() {
+                    buffer.delete_char();
+                }
+            });
+        } else {
+            self.delete_char_at_all_cursors();
         }
     }
-}
\ No newline at end of file
+}
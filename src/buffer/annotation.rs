@@ -0,0 +1,89 @@
+use super::protected::common_ends;
+use super::TextBuffer;
+
+/// 虚拟文本显示在哪里
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationPlacement {
+    /// 行尾之后，与文本隔开两列
+    EndOfLine,
+    /// 该行上方单独占一个屏幕行
+    Above,
+}
+
+/// 附在某一行上的虚拟文本（检查结果、追溯信息、搜索计数等）
+///
+/// 只用于显示，不属于缓冲区内容：不保存、不参与撤销，修改时跟着所在的行移动，
+/// 所在的行被删除时一起删除。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub line: usize,
+    pub text: String,
+    pub placement: AnnotationPlacement,
+    /// 产生者，例如 `run`，用来整组替换或清除
+    pub source: &'static str,
+}
+
+impl Annotation {
+    pub fn new(
+        line: usize,
+        text: impl Into<String>,
+        placement: AnnotationPlacement,
+        source: &'static str,
+    ) -> Self {
+        Self {
+            line,
+            text: text.into(),
+            placement,
+            source,
+        }
+    }
+}
+
+impl TextBuffer {
+    /// 添加一条虚拟文本
+    pub fn add_annotation(&mut self, annotation: Annotation) {
+        self.annotations.push(annotation);
+    }
+
+    /// 删除某个产生者的全部虚拟文本
+    pub fn clear_annotations(&mut self, source: &str) {
+        self.annotations.retain(|a| a.source != source);
+    }
+
+    /// 第 y 行在某个位置显示的虚拟文本，关闭 annotations 选项时没有
+    pub fn annotations_on(
+        &self,
+        y: usize,
+        placement: AnnotationPlacement,
+    ) -> impl Iterator<Item = &Annotation> {
+        let visible = self.settings.annotations;
+        self.annotations
+            .iter()
+            .filter(move |a| visible && a.line == y && a.placement == placement)
+    }
+
+    /// 第 y 行上方的虚拟文本占几个屏幕行
+    pub fn annotation_rows_above(&self, y: usize) -> usize {
+        self.annotations_on(y, AnnotationPlacement::Above).count()
+    }
+
+    /// 修改生效后，让虚拟文本跟着所在的行移动，被删除的行上的虚拟文本一起删除
+    pub(crate) fn shift_annotations(&mut self, start: usize, old: &[String], new: &[String]) {
+        if self.annotations.is_empty() || old.len() == new.len() {
+            return;
+        }
+        let (prefix, suffix) = common_ends(old, new);
+        let changed_end = start + old.len() - suffix;
+        // 变化部分中保留下来的行数，超出的行算作被删除
+        let kept_end =
+            start + prefix + (new.len() - prefix - suffix).min(old.len() - prefix - suffix);
+        self.annotations.retain_mut(|a| {
+            if a.line >= changed_end {
+                a.line = a.line + new.len() - old.len();
+            } else if a.line >= kept_end {
+                return false;
+            }
+            true
+        });
+    }
+}
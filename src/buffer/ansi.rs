@@ -0,0 +1,35 @@
+use super::{Position, TextBuffer};
+use crate::ansi;
+
+impl TextBuffer {
+    /// 去掉文本中的 ANSI 控制序列，改为按颜色显示；没有控制序列时什么也不做
+    pub fn apply_ansi_colors(&mut self) {
+        if let Some((lines, colors)) = ansi::strip(&self.lines) {
+            self.lines = lines;
+            self.ansi = Some(colors);
+            self.clamp_cursor_after_ansi_swap();
+        }
+    }
+
+    /// 在按颜色显示和按原样显示控制序列之间切换，返回切换后是否按原样显示；
+    /// 没有颜色信息时返回 None
+    pub fn toggle_ansi_literal(&mut self) -> Option<bool> {
+        let colors = self.ansi.as_mut()?;
+        std::mem::swap(&mut self.lines, &mut colors.other_lines);
+        colors.literal = !colors.literal;
+        let literal = colors.literal;
+        self.highlight = None;
+        self.clamp_cursor_after_ansi_swap();
+        Some(literal)
+    }
+
+    /// 两份文本的行数相同，但每行的长度不同
+    fn clamp_cursor_after_ansi_swap(&mut self) {
+        let y = self.cursor_y.min(self.lines.len().saturating_sub(1));
+        let x = self
+            .cursor_x
+            .min(self.lines.get(y).map_or(0, |l| l.chars().count()));
+        self.set_cursor_position(Position::new(x, y));
+        self.mark = None;
+    }
+}
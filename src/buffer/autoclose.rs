@@ -0,0 +1,99 @@
+use super::TextBuffer;
+use crate::unicode::is_word_char;
+
+/// 自动补全的括号与引号
+const PAIRS: [(char, char); 6] = [
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('"', '"'),
+    ('\'', '\''),
+    ('`', '`'),
+];
+
+/// 左括号或引号对应的右侧字符
+fn closer_for(ch: char) -> Option<char> {
+    PAIRS
+        .iter()
+        .find(|(open, _)| *open == ch)
+        .map(|(_, close)| *close)
+}
+
+fn is_closer(ch: char) -> bool {
+    PAIRS.iter().any(|(_, close)| *close == ch)
+}
+
+impl TextBuffer {
+    /// 自动补全模式下输入字符
+    ///
+    /// 输入左括号或引号时补上右侧字符，有选区时用这一对把选区括起来；
+    /// 光标正好在同一个右括号前时直接跳过它，不会出现重复的右括号。
+    /// 紧挨着单词输入引号（如英文撇号）时不补全。
+    pub fn insert_char_autoclose(&mut self, ch: char) {
+        let next = self.char_at(self.cursor_x, self.cursor_y);
+        if let Some((start, end)) = self.selection_range() {
+            if let Some(close) = closer_for(ch) {
+                self.mark = None;
+                self.set_cursor_position(end);
+                self.insert_char(close);
+                self.set_cursor_position(start);
+                self.insert_char(ch);
+                return;
+            }
+        } else if is_closer(ch) && next == Some(ch) {
+            self.clear_goal_columns();
+            self.cursor_x += 1;
+            return;
+        }
+        if let Some(close) = closer_for(ch) {
+            let prev = self.char_before_cursor();
+            let is_quote = ch == close;
+            let touches_word = next.is_some_and(is_word_char)
+                || (is_quote && prev.is_some_and(is_word_char));
+            if !touches_word {
+                self.insert_char(ch);
+                self.insert_char(close);
+                self.cursor_x -= 1;
+                return;
+            }
+        }
+        self.insert_char(ch);
+    }
+
+    /// 光标在一对空括号或引号中间时删除这一对，返回是否删除
+    pub fn delete_empty_pair(&mut self) -> bool {
+        if self.selection_range().is_some() || self.cursor_x == 0 {
+            return false;
+        }
+        let prev = self.char_at(self.cursor_x - 1, self.cursor_y);
+        let next = self.char_at(self.cursor_x, self.cursor_y);
+        match prev.and_then(closer_for) {
+            Some(close) if next == Some(close) => {
+                self.delete_char_forward();
+                self.delete_char();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 把光标移到本行光标之后第一个右括号或引号之后，没有时返回 false
+    pub fn jump_past_closer(&mut self) -> bool {
+        let Some(line) = self.lines.get(self.cursor_y) else {
+            return false;
+        };
+        let found = line
+            .chars()
+            .enumerate()
+            .skip(self.cursor_x)
+            .find(|(_, ch)| is_closer(*ch));
+        match found {
+            Some((x, _)) => {
+                self.clear_goal_columns();
+                self.cursor_x = x + 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
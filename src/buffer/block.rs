@@ -0,0 +1,99 @@
+use super::{byte_index, Cursor, Position, TextBuffer};
+use crate::unicode::{cell_width, display_column};
+use std::ops::Range;
+
+impl TextBuffer {
+    /// 在主光标处放下块选择的锚点，之后移动光标选出矩形
+    pub fn start_block_selection(&mut self) {
+        self.mark = None;
+        self.clear_cursors();
+        self.block_mark = Some(self.cursor_position());
+    }
+
+    /// 块选择覆盖的行和显示列（矩形），没有块选择时返回 None
+    pub fn block_range(&self) -> Option<(Range<usize>, Range<usize>)> {
+        let anchor = self.clamp_position(self.block_mark?);
+        let cursor = self.clamp_position(self.cursor_position());
+        let tab_width = self.settings.tab_width;
+        let anchor_col = display_column(&self.lines[anchor.y], anchor.x, tab_width);
+        let cursor_col = display_column(&self.lines[cursor.y], cursor.x, tab_width);
+        Some((
+            anchor.y.min(cursor.y)..anchor.y.max(cursor.y) + 1,
+            anchor_col.min(cursor_col)..anchor_col.max(cursor_col),
+        ))
+    }
+
+    /// 第 y 行中从显示列 col 开始的字符下标；行没有这么宽时返回 None
+    ///
+    /// 跨过 col 的宽字符或制表符不算在内，下标指向它后面的字符。
+    fn char_at_column(&self, y: usize, col: usize) -> Option<usize> {
+        let tab_width = self.settings.tab_width;
+        let mut used = 0;
+        for (i, ch) in self.lines[y].chars().enumerate() {
+            if used >= col {
+                return Some(i);
+            }
+            used += cell_width(ch, used, tab_width);
+        }
+        (used >= col).then(|| self.line_char_count(y))
+    }
+
+    /// 结束块选择，在矩形左边的每一行上放一个光标，返回光标总数
+    ///
+    /// 矩形宽度为零时光标都在主光标所在的列；没有这么宽的行跳过。
+    pub fn block_to_cursors(&mut self) -> usize {
+        let Some((lines, cols)) = self.block_range() else {
+            return self.cursor_count();
+        };
+        self.block_mark = None;
+        self.place_column_cursors(lines, cols.start);
+        self.cursor_count()
+    }
+
+    /// 在 lines 的每一行的第 col 列放一个光标，主光标留在原来的行
+    fn place_column_cursors(&mut self, lines: Range<usize>, col: usize) {
+        self.cursors.clear();
+        let primary_y = self.cursor_y.clamp(lines.start, lines.end - 1);
+        for y in lines {
+            let Some(x) = self.char_at_column(y, col) else {
+                continue;
+            };
+            if y == primary_y {
+                self.set_cursor_position(Position::new(x, y));
+            } else {
+                self.cursors.push(Cursor::new(x, y));
+            }
+        }
+    }
+
+    /// 开始编辑前结束块选择：删除矩形中的文本（一步撤销），并在每一行矩形左边放光标
+    ///
+    /// 返回是否删除了文本；没有块选择时返回 None。之后的输入在每个光标处各执行一次，
+    /// 所以在块选择中打字就是按列替换，宽度为零时就是按列插入。
+    pub fn begin_block_edit(&mut self) -> Option<bool> {
+        let (lines, cols) = self.block_range()?;
+        self.block_mark = None;
+        if cols.is_empty() {
+            self.place_column_cursors(lines, cols.start);
+            return Some(false);
+        }
+        self.record_compound_edit(lines.clone(), |buffer| {
+            for y in lines.clone() {
+                let Some(from) = buffer.char_at_column(y, cols.start) else {
+                    continue;
+                };
+                let to = buffer
+                    .char_at_column(y, cols.end)
+                    .unwrap_or_else(|| buffer.line_char_count(y));
+                if from < to {
+                    let line = &mut buffer.lines[y];
+                    let range = byte_index(line, from)..byte_index(line, to);
+                    line.drain(range);
+                    buffer.mark_line_modified(y);
+                }
+            }
+            buffer.place_column_cursors(lines, cols.start);
+        });
+        Some(true)
+    }
+}
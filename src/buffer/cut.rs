@@ -0,0 +1,68 @@
+use super::{byte_index, EditKind, Position, TextBuffer};
+
+impl TextBuffer {
+    /// 剪切当前行并返回被剪切的文本，有选区时剪切选区
+    ///
+    /// from_cursor 为 true 时只剪切光标到行尾（光标已在行尾时剪切换行符），
+    /// 否则剪切整行连同换行符。
+    pub fn cut_line(&mut self, from_cursor: bool) -> String {
+        if let Some((start, end)) = self.selection_range() {
+            return self.delete_region(start, end);
+        }
+        self.mark = None;
+        let y = self.cursor_y.min(self.lines.len() - 1);
+        let line_len = self.line_char_count(y);
+        let has_next = y + 1 < self.lines.len();
+        let start = if from_cursor {
+            self.clamp_position(self.cursor_position())
+        } else {
+            Position::new(0, y)
+        };
+        if start.x == line_len && !has_next {
+            return String::new();
+        }
+        let end = if has_next && (!from_cursor || start.x == line_len) {
+            Position::new(0, y + 1)
+        } else {
+            Position::new(line_len, y)
+        };
+        let mut removed = self.delete_region(start, end);
+        if !from_cursor && !has_next {
+            // 最后一行没有换行符，补上一个，粘贴时仍是完整的一行
+            removed.push('\n');
+        }
+        removed
+    }
+
+    /// 在光标处插入文本（可以包含多行），光标移到插入内容之后，返回插入的起点
+    pub fn insert_text(&mut self, text: &str) -> Position {
+        let lines = self.lines_around_cursor();
+        self.record_edit(EditKind::Other, lines, |buffer| {
+            buffer.delete_selection();
+            buffer.clear_goal_columns();
+            buffer.fill_virtual_space();
+            let y = buffer.cursor_y;
+            let line = &buffer.lines[y];
+            let split_at_char = buffer.cursor_x;
+            let split_at = byte_index(line, split_at_char);
+            let tail = line[split_at..].to_string();
+            buffer.lines[y].truncate(split_at);
+
+            let mut parts = text.split('\n');
+            if let Some(first) = parts.next() {
+                buffer.lines[y].push_str(first);
+            }
+            let mut last_y = y;
+            for part in parts {
+                last_y += 1;
+                buffer.lines.insert(last_y, part.to_string());
+                buffer.modified_lines_set.insert(last_y);
+            }
+            buffer.cursor_y = last_y;
+            buffer.cursor_x = buffer.lines[last_y].chars().count();
+            buffer.lines[last_y].push_str(&tail);
+            buffer.mark_line_modified(y);
+            Position::new(split_at_char, y)
+        })
+    }
+}
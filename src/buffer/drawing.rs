@@ -0,0 +1,73 @@
+use super::{byte_index, EditKind, TextBuffer};
+use crate::boxdraw::{connections, direction_bit, glyph, opposite, BoxStyle};
+use crate::direction::Direction;
+
+impl TextBuffer {
+    /// 指定位置的字符，超出文本范围时返回 None
+    pub fn char_at(&self, x: usize, y: usize) -> Option<char> {
+        self.lines.get(y)?.chars().nth(x)
+    }
+
+    /// 用 ch 覆盖光标处的字符，光标在空白区域时先用空格补齐
+    pub fn overwrite_char(&mut self, ch: char) {
+        let lines = self.lines_around_cursor();
+        self.record_edit(EditKind::Typing, lines, |buffer| {
+            buffer.fill_virtual_space();
+            let cursor_x = buffer.cursor_x;
+            let line = buffer.current_line_mut();
+            let from = byte_index(line, cursor_x);
+            let to = byte_index(line, cursor_x + 1);
+            line.replace_range(from..to, ch.encode_utf8(&mut [0; 4]));
+            buffer.mark_line_modified(buffer.cursor_y);
+        });
+    }
+
+    /// 从光标处朝 direction 画一段框线，并把光标移到新格子上
+    ///
+    /// 起点和终点都会与原有的框线字符合并，所以画过的线会自动连成拐角或交叉。
+    pub fn draw_box_segment(&mut self, direction: Direction, style: BoxStyle, page_height: usize) {
+        if (direction == Direction::Up && self.cursor_y == 0)
+            || (direction == Direction::Left && self.cursor_x == 0)
+        {
+            return;
+        }
+        self.mark = None;
+        self.extend_box_char(direction_bit(direction), style);
+        self.move_cursor_free(direction, page_height);
+        self.extend_box_char(direction_bit(opposite(direction)), style);
+    }
+
+    /// 给光标处的框线字符增加一个连接方向
+    fn extend_box_char(&mut self, bit: u8, style: BoxStyle) {
+        let (x, y) = (self.cursor_x, self.cursor_y);
+        let existing = self.char_at(x, y).and_then(connections).unwrap_or(0);
+        // ─ 和 │ 既可能是线段中间也可能是端点，只保留确实有相邻框线的方向
+        let mut linked = 0;
+        for direction in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            let dir_bit = direction_bit(direction);
+            if existing & dir_bit != 0 && self.neighbour_links_back(x, y, direction) {
+                linked |= dir_bit;
+            }
+        }
+        self.overwrite_char(glyph(linked | bit, style));
+    }
+
+    /// (x, y) 在 direction 方向上的相邻字符是否有指回来的连接
+    fn neighbour_links_back(&self, x: usize, y: usize, direction: Direction) -> bool {
+        let (nx, ny) = match direction {
+            Direction::Up if y > 0 => (x, y - 1),
+            Direction::Down => (x, y + 1),
+            Direction::Left if x > 0 => (x - 1, y),
+            Direction::Right => (x + 1, y),
+            _ => return false,
+        };
+        self.char_at(nx, ny)
+            .and_then(connections)
+            .is_some_and(|mask| mask & direction_bit(opposite(direction)) != 0)
+    }
+}
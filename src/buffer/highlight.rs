@@ -0,0 +1,103 @@
+use super::{find_in_line, TextBuffer};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem::size_of;
+
+fn line_hash(line: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    line.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 搜索高亮层：记住每行的匹配位置，直到清除或开始新的搜索
+///
+/// 缓冲区变化后按行内容的哈希对齐新旧两版，开头和结尾没有变化的行沿用原来的结果，
+/// 只对中间变化的部分重新查找，插入或删除行时下面的高亮跟着移动。
+#[derive(Debug, Clone)]
+pub struct SearchHighlight {
+    pub query: String,
+    revision: Option<u64>,
+    /// 每行的内容哈希和匹配范围
+    lines: Vec<(u64, Vec<(usize, usize)>)>,
+}
+
+impl SearchHighlight {
+    pub fn new(query: &str) -> Self {
+        Self {
+            query: query.to_string(),
+            revision: None,
+            lines: Vec::new(),
+        }
+    }
+
+    /// 第 y 行的匹配范围（字符索引）
+    pub fn matches_on(&self, y: usize) -> &[(usize, usize)] {
+        self.lines.get(y).map_or(&[], |(_, ranges)| ranges)
+    }
+
+    /// 各行匹配位置占用的堆内存（字节）
+    pub(crate) fn heap_size(&self) -> usize {
+        self.query.capacity()
+            + self.lines.capacity() * size_of::<(u64, Vec<(usize, usize)>)>()
+            + self
+                .lines
+                .iter()
+                .map(|(_, ranges)| ranges.capacity() * size_of::<(usize, usize)>())
+                .sum::<usize>()
+    }
+
+    /// 丢弃各行的匹配位置，下次绘制时重新查找
+    pub(crate) fn clear_cache(&mut self) {
+        self.lines = Vec::new();
+        self.revision = None;
+    }
+
+    /// 让高亮跟上缓冲区内容，版本号没变时什么也不做
+    fn sync(&mut self, lines: &[String], revision: u64) {
+        if self.revision == Some(revision) {
+            return;
+        }
+        let hashes: Vec<u64> = lines.iter().map(|line| line_hash(line)).collect();
+        let prefix = self
+            .lines
+            .iter()
+            .zip(&hashes)
+            .take_while(|((old, _), new)| old == *new)
+            .count();
+        let suffix = self.lines[prefix..]
+            .iter()
+            .rev()
+            .zip(hashes[prefix..].iter().rev())
+            .take_while(|((old, _), new)| old == *new)
+            .count();
+        let old_end = self.lines.len() - suffix;
+        let new_end = hashes.len() - suffix;
+        let changed = (prefix..new_end).map(|y| (hashes[y], find_in_line(&lines[y], &self.query)));
+        self.lines.splice(prefix..old_end, changed);
+        self.revision = Some(revision);
+    }
+}
+
+impl TextBuffer {
+    /// 高亮 query 的所有匹配，替换原有的高亮
+    pub fn set_highlight(&mut self, query: &str) {
+        if self.highlight.as_ref().is_some_and(|h| h.query == query) {
+            return;
+        }
+        let mut highlight = SearchHighlight::new(query);
+        highlight.sync(&self.lines, self.revision);
+        self.highlight = Some(highlight);
+    }
+
+    /// 清除搜索高亮，原来有高亮时返回 true
+    pub fn clear_highlight(&mut self) -> bool {
+        self.highlight.take().is_some()
+    }
+
+    /// 每帧绘制前调用，让高亮层跟上编辑
+    pub fn sync_highlight(&mut self) {
+        if let Some(highlight) = self.highlight.as_mut() {
+            highlight.sync(&self.lines, self.revision);
+        }
+    }
+}
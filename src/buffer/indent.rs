@@ -0,0 +1,77 @@
+use crate::settings::BufferSettings;
+
+/// 检测缩进时最多看多少行
+const SAMPLE_LINES: usize = 10_000;
+
+/// 文件中使用的缩进方式，读入时从内容中检测
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    Tabs,
+    /// 每级缩进的空格数
+    Spaces(usize),
+}
+
+impl IndentStyle {
+    /// 以制表符缩进的行多于以空格缩进的行时是制表符；否则看相邻两行缩进的增加量，
+    /// 最常见的一个（2 到 8）就是每级的宽度。没有缩进的行时无法判断，返回 None
+    pub fn detect(lines: &[String]) -> Option<Self> {
+        let mut tab_lines = 0;
+        let mut space_lines = 0;
+        // 下标为缩进的增加量
+        let mut steps = [0usize; 9];
+        let mut previous = 0;
+        for line in lines.iter().take(SAMPLE_LINES) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.starts_with('\t') {
+                tab_lines += 1;
+                previous = 0;
+                continue;
+            }
+            let spaces = line.bytes().take_while(|&b| b == b' ').count();
+            // 块注释续行的 " * " 是对齐而不是缩进
+            let rest = &line[spaces..];
+            if spaces > 0 && (rest == "*" || rest.starts_with("* ") || rest.starts_with("*/")) {
+                continue;
+            }
+            if spaces > 0 {
+                space_lines += 1;
+            }
+            if spaces > previous && spaces - previous < steps.len() {
+                steps[spaces - previous] += 1;
+            }
+            previous = spaces;
+        }
+        if tab_lines > space_lines {
+            return Some(IndentStyle::Tabs);
+        }
+        // steps[0] 总是 0；次数相同时取较窄的宽度
+        let mut width = 0;
+        for step in 2..steps.len() {
+            if steps[step] > steps[width] {
+                width = step;
+            }
+        }
+        (width > 0).then_some(IndentStyle::Spaces(width))
+    }
+
+    /// 把缩进方式写入设置：制表符关闭 tabstospaces，空格开启它并把 tabsize 设为每级宽度
+    pub fn apply(self, settings: &mut BufferSettings) {
+        match self {
+            IndentStyle::Tabs => settings.expand_tab = false,
+            IndentStyle::Spaces(width) => {
+                settings.expand_tab = true;
+                settings.tab_width = width;
+            }
+        }
+    }
+
+    /// 状态栏中的说明
+    pub fn label(self) -> String {
+        match self {
+            IndentStyle::Tabs => "制表符".to_string(),
+            IndentStyle::Spaces(width) => format!("{} 空格", width),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use super::{Position, TextBuffer};
+
+impl TextBuffer {
+    /// 检查缓冲区的内部约束，返回违反的条目；调试构建中每次输入处理后调用
+    ///
+    /// 光标和标记都是字符索引，只要不超过行的字符数就一定落在字符边界上。
+    /// 自由光标模式下光标本来就可以在行尾和文件末尾之后，不检查各个光标的范围。
+    pub fn check_invariants(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.lines.is_empty() {
+            problems.push("缓冲区没有任何行".to_string());
+            return problems;
+        }
+        let free = self.settings.virtual_space;
+        let mut check = |what: &str, pos: Position| {
+            if free {
+                return;
+            }
+            if pos.y >= self.lines.len() {
+                problems.push(format!(
+                    "{}在第 {} 行，超出共 {} 行",
+                    what,
+                    pos.y + 1,
+                    self.lines.len()
+                ));
+            } else if pos.x > self.line_char_count(pos.y) {
+                problems.push(format!(
+                    "{}在第 {} 行第 {} 列，超出行长 {}",
+                    what,
+                    pos.y + 1,
+                    pos.x + 1,
+                    self.line_char_count(pos.y)
+                ));
+            }
+        };
+        check("光标", self.cursor_position());
+        for cursor in &self.cursors {
+            check("多光标中的光标", cursor.position());
+        }
+        let mut previous_end = None;
+        for r in &self.protected_lines {
+            if r.is_empty() || previous_end.is_some_and(|end| r.start < end) {
+                problems.push(format!("保护区域 {:?} 为空或与前一个区域重叠", r));
+            }
+            previous_end = Some(r.end);
+        }
+        if let Some(a) = self.annotations.iter().find(|a| a.line >= self.lines.len()) {
+            problems.push(format!(
+                "虚拟文本在第 {} 行，超出共 {} 行",
+                a.line + 1,
+                self.lines.len()
+            ));
+        }
+        if let Some(s) = self.signs.iter().find(|s| s.line >= self.lines.len()) {
+            problems.push(format!(
+                "符号在第 {} 行，超出共 {} 行",
+                s.line + 1,
+                self.lines.len()
+            ));
+        }
+        if let Some(mark) = self.mark {
+            if mark.y >= self.lines.len() {
+                problems.push(format!(
+                    "标记在第 {} 行，超出共 {} 行",
+                    mark.y + 1,
+                    self.lines.len()
+                ));
+            }
+        }
+        if let Some(mark) = self.block_mark {
+            if mark.y >= self.lines.len() {
+                problems.push(format!(
+                    "块选择锚点在第 {} 行，超出共 {} 行",
+                    mark.y + 1,
+                    self.lines.len()
+                ));
+            }
+        }
+        if self.offset_y > self.lines.len() && !free {
+            problems.push(format!(
+                "滚动位置在第 {} 行，超出共 {} 行",
+                self.offset_y + 1,
+                self.lines.len()
+            ));
+        }
+        problems
+    }
+
+    /// 把违反约束的状态修正到合法范围，避免之后在绘制时出错
+    pub fn repair_invariants(&mut self) {
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        if !self.settings.virtual_space {
+            self.set_cursor_position(self.cursor_position());
+            self.offset_y = self.offset_y.min(self.lines.len() - 1);
+        }
+        if !self.settings.virtual_space {
+            self.clamp_cursors();
+        }
+        if let Some(mark) = self.mark {
+            self.mark = Some(self.clamp_position(mark));
+        }
+        if let Some(mark) = self.block_mark {
+            self.block_mark = Some(self.clamp_position(mark));
+        }
+        let count = self.lines.len();
+        self.annotations.retain(|a| a.line < count);
+        self.signs.retain(|s| s.line < count);
+    }
+}
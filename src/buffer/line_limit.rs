@@ -0,0 +1,88 @@
+use super::{EditKind, TextBuffer};
+use crate::settings::LineLimit;
+
+impl TextBuffer {
+    /// block 模式下光标所在的行已经达到行长上限时返回该行号，此时不能再输入
+    ///
+    /// 有选区时输入会先删除选中内容，不做限制。
+    pub fn line_limit_reached(&self) -> Option<usize> {
+        let limit = self.settings.max_line_length;
+        if limit == 0 || self.settings.line_limit != LineLimit::Block || self.mark.is_some() {
+            return None;
+        }
+        let mut rows = std::iter::once(self.cursor_y).chain(self.cursors.iter().map(|c| c.y));
+        rows.find(|&y| {
+            self.lines
+                .get(y)
+                .is_some_and(|l| l.chars().count() >= limit)
+        })
+    }
+
+    /// block 模式下在光标处插入一段文字会让某一行超过行长上限时返回该行号
+    ///
+    /// 用于粘贴：只检查被插入内容加长的行，文字中原本就超长的行照常插入。
+    pub fn insert_exceeds_line_limit(&self, text: &str) -> Option<usize> {
+        let limit = self.settings.max_line_length;
+        if limit == 0 || self.settings.line_limit != LineLimit::Block || self.mark.is_some() {
+            return None;
+        }
+        let y = self.cursor_y;
+        let line_len = self.lines.get(y).map_or(0, |l| l.chars().count());
+        // 自由光标在行尾之后时，插入前会先用空格补到光标处
+        let head = self.cursor_x;
+        let tail = line_len.saturating_sub(self.cursor_x);
+        let parts: Vec<usize> = text.split('\n').map(|p| p.chars().count()).collect();
+        let last = parts.len() - 1;
+        if last == 0 {
+            return (parts[0] > 0 && head + parts[0] + tail > limit).then_some(y);
+        }
+        if parts[0] > 0 && head + parts[0] > limit {
+            return Some(y);
+        }
+        (parts[last] > 0 && parts[last] + tail > limit).then_some(y + last)
+    }
+
+    /// wrap 模式下输入之后，把光标所在行在上限之前的最后一个空白处断开
+    ///
+    /// 新行沿用原行的缩进，断开处的空白去掉；一个单词就超过上限时不断开。
+    pub(crate) fn hard_wrap_at_cursor(&mut self) {
+        let lines = self.lines_around_cursor();
+        self.record_edit(EditKind::Typing, lines, |buffer| {
+            let limit = buffer.settings.max_line_length;
+            if limit == 0 || buffer.settings.line_limit != LineLimit::Wrap {
+                return;
+            }
+            let y = buffer.cursor_y;
+            let chars: Vec<char> = buffer.lines[y].chars().collect();
+            if chars.len() <= limit {
+                return;
+            }
+            let indent = chars.iter().take_while(|c| c.is_whitespace()).count();
+            let Some(space) = (indent + 1..=limit)
+                .rev()
+                .find(|&i| chars[i].is_whitespace())
+            else {
+                return;
+            };
+            let head_end = chars[..space]
+                .iter()
+                .rposition(|c| !c.is_whitespace())
+                .map_or(0, |i| i + 1);
+            let tail_start = (space..chars.len())
+                .find(|&i| !chars[i].is_whitespace())
+                .unwrap_or(chars.len());
+            let head: String = chars[..head_end].iter().collect();
+            let tail: String = chars[..indent].iter().chain(&chars[tail_start..]).collect();
+            buffer.lines[y] = head;
+            buffer.lines.insert(y + 1, tail);
+            if buffer.cursor_x >= tail_start {
+                buffer.cursor_y += 1;
+                buffer.cursor_x = indent + buffer.cursor_x - tail_start;
+            } else {
+                buffer.cursor_x = buffer.cursor_x.min(head_end);
+            }
+            buffer.mark_line_modified(y);
+            buffer.modified_lines_set.insert(y + 1);
+        });
+    }
+}
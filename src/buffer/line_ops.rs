@@ -0,0 +1,92 @@
+use super::{EditKind, Position, TextBuffer};
+use std::ops::Range;
+
+impl TextBuffer {
+    /// 整行操作的对象：选区经过的各行（结束在行首时不含那一行），没有选区时是光标所在的行
+    fn operated_lines(&self) -> Range<usize> {
+        let y = self.cursor_y.min(self.lines.len() - 1);
+        let end = |to: usize| to.min(self.lines.len());
+        match self.selection_range() {
+            Some((from, to)) if to.x == 0 && to.y > from.y => from.y..end(to.y),
+            Some((from, to)) => from.y..end(to.y + 1),
+            None => y..y + 1,
+        }
+    }
+
+    /// 把光标和标记下移 delta 行（delta 为负时上移），移出文件末尾时停在最后一行的行尾
+    fn shift_cursor_lines(&mut self, delta: isize) {
+        let last = self.lines.len() - 1;
+        let shift = |pos: Position| {
+            let y = pos.y.saturating_add_signed(delta);
+            if y > last {
+                Position::new(self.lines[last].chars().count(), last)
+            } else {
+                Position::new(pos.x, y)
+            }
+        };
+        let cursor = shift(self.cursor_position());
+        self.cursor_x = cursor.x;
+        self.cursor_y = cursor.y;
+        self.mark = self.mark.map(shift);
+    }
+
+    /// 在下面复制一份当前行（或选区经过的各行），光标和选区移到副本上
+    pub fn duplicate_lines(&mut self) {
+        let lines = self.operated_lines();
+        self.record_edit(EditKind::Other, lines.clone(), |buffer| {
+            let copy = buffer.lines[lines.clone()].to_vec();
+            buffer.lines.splice(lines.end..lines.end, copy);
+            buffer.clear_goal_columns();
+            buffer.shift_cursor_lines(lines.len() as isize);
+            for y in lines.end..lines.end + lines.len() {
+                buffer.mark_line_modified(y);
+            }
+        });
+    }
+
+    /// 删除当前行（或选区经过的各行），不放进剪切缓冲区，返回删除的行数
+    pub fn delete_lines(&mut self) -> usize {
+        let lines = self.operated_lines();
+        let count = lines.len();
+        self.record_edit(EditKind::Other, lines.clone(), |buffer| {
+            buffer.lines.drain(lines.clone());
+            if buffer.lines.is_empty() {
+                buffer.lines.push(String::new());
+            }
+            buffer.mark = None;
+            buffer.clear_goal_columns();
+            let y = lines.start.min(buffer.lines.len() - 1);
+            buffer.set_cursor_position(Position::new(buffer.cursor_x, y));
+            buffer.clamp_cursors();
+            buffer.mark_line_modified(y);
+        });
+        count
+    }
+
+    /// 把当前行（或选区经过的各行）与上面或下面的一行交换，已在开头或末尾时返回 false
+    pub fn move_lines(&mut self, up: bool) -> bool {
+        let lines = self.operated_lines();
+        if (up && lines.start == 0) || (!up && lines.end >= self.lines.len()) {
+            return false;
+        }
+        let range = if up {
+            lines.start - 1..lines.end
+        } else {
+            lines.start..lines.end + 1
+        };
+        self.record_edit(EditKind::Other, range.clone(), |buffer| {
+            if up {
+                buffer.lines[range.clone()].rotate_left(1);
+                buffer.shift_cursor_lines(-1);
+            } else {
+                buffer.lines[range.clone()].rotate_right(1);
+                buffer.shift_cursor_lines(1);
+            }
+            buffer.clear_goal_columns();
+            for y in range {
+                buffer.mark_line_modified(y);
+            }
+        });
+        true
+    }
+}
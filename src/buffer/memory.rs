@@ -0,0 +1,87 @@
+use super::TextBuffer;
+use crate::ansi::ColorSpan;
+use std::mem::size_of;
+use std::ops::AddAssign;
+
+/// 整理内存时每个缓冲区保留的撤销步数
+pub const COMPACT_UNDO_STEPS: usize = 100;
+
+/// 缓冲区各部分大约占用的堆内存（字节），按已分配的容量计算
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// 文本各行
+    pub text: usize,
+    /// 撤销和重做历史
+    pub undo: usize,
+    /// 搜索高亮、ANSI 颜色和修改行记录等可以重新生成的缓存
+    pub caches: usize,
+}
+
+impl MemoryUsage {
+    pub fn total(&self) -> usize {
+        self.text + self.undo + self.caches
+    }
+}
+
+impl AddAssign for MemoryUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.text += other.text;
+        self.undo += other.undo;
+        self.caches += other.caches;
+    }
+}
+
+/// 一组行占用的堆内存，包括 Vec 预留而没有用到的空间
+pub(crate) fn lines_heap_size(lines: &Vec<String>) -> usize {
+    lines.capacity() * size_of::<String>() + lines.iter().map(String::capacity).sum::<usize>()
+}
+
+/// 收紧一组行的分配
+fn shrink_lines(lines: &mut Vec<String>) {
+    lines.shrink_to_fit();
+    for line in lines {
+        line.shrink_to_fit();
+    }
+}
+
+impl TextBuffer {
+    /// 统计本缓冲区占用的内存
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut caches = self.modified_lines_set.capacity() * size_of::<usize>();
+        if let Some(highlight) = &self.highlight {
+            caches += highlight.heap_size();
+        }
+        if let Some(ansi) = &self.ansi {
+            caches += lines_heap_size(&ansi.other_lines)
+                + ansi.spans.capacity() * size_of::<Vec<ColorSpan>>()
+                + ansi
+                    .spans
+                    .iter()
+                    .map(|spans| spans.capacity() * size_of::<ColorSpan>())
+                    .sum::<usize>();
+        }
+        MemoryUsage {
+            text: lines_heap_size(&self.lines),
+            undo: self.history.heap_size(),
+            caches,
+        }
+    }
+
+    /// 收紧各部分的分配，丢弃搜索高亮的缓存，撤销历史只保留最近 keep_undo 步；
+    /// 返回丢弃的撤销步数
+    pub fn compact(&mut self, keep_undo: usize) -> usize {
+        shrink_lines(&mut self.lines);
+        self.modified_lines_set.shrink_to_fit();
+        if let Some(highlight) = self.highlight.as_mut() {
+            highlight.clear_cache();
+        }
+        if let Some(ansi) = self.ansi.as_mut() {
+            shrink_lines(&mut ansi.other_lines);
+            ansi.spans.shrink_to_fit();
+            for spans in &mut ansi.spans {
+                spans.shrink_to_fit();
+            }
+        }
+        self.history.trim(keep_undo)
+    }
+}
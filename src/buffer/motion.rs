@@ -0,0 +1,154 @@
+use super::{Position, TextBuffer};
+use crate::unicode::{char_class, grapheme_bounds, CharClass};
+
+impl TextBuffer {
+    /// 翻一页：光标和视图一起移动 page_rows 行，光标在屏幕上的位置不变；
+    /// 视图已经到头时只移动光标，光标已在第一行或最后一行时返回 false
+    pub fn move_page(&mut self, down: bool, page_rows: usize) -> bool {
+        let step = page_rows.max(1);
+        let last = self.lines.len() - 1;
+        let y = if down {
+            (self.cursor_y + step).min(last)
+        } else {
+            self.cursor_y.saturating_sub(step)
+        };
+        if y == self.cursor_y {
+            return false;
+        }
+        self.offset_y = if down {
+            (self.offset_y + step).min(self.lines.len().saturating_sub(page_rows))
+        } else {
+            self.offset_y.saturating_sub(step)
+        };
+        // 和上下移动一样保持粘性列
+        let goal = *self.goal_x.get_or_insert(self.cursor_x);
+        self.cursor_y = y;
+        self.cursor_x = if self.settings.virtual_space {
+            goal
+        } else {
+            goal.min(self.line_char_count(y))
+        };
+        self.scroll_to_cursor(page_rows);
+        true
+    }
+
+    /// 光标所在行第一个非空白字符的位置，整行都是空白时为行尾
+    pub fn indent_end(&self) -> usize {
+        self.lines.get(self.cursor_y).map_or(0, |line| {
+            line.chars().take_while(|c| c.is_whitespace()).count()
+        })
+    }
+
+    /// 智能行首：光标不在缩进末尾时移到第一个非空白字符，已经在那里时移到第 0 列
+    pub fn move_smart_home(&mut self) {
+        let indent = self.indent_end();
+        self.cursor_x = if self.cursor_x == indent { 0 } else { indent };
+        self.goal_x = None;
+    }
+
+    /// 移到行尾
+    pub fn move_line_end(&mut self) {
+        self.cursor_x = self.line_char_count(self.cursor_y);
+        self.goal_x = None;
+    }
+}
+
+/// 一行中每个字素簇的字符范围和类别，组合符号随前面的字符
+fn clusters(line: &str) -> Vec<(usize, usize, CharClass)> {
+    let chars: Vec<char> = line.chars().collect();
+    grapheme_bounds(&chars)
+        .windows(2)
+        .map(|pair| (pair[0], pair[1], char_class(chars[pair[0]])))
+        .collect()
+}
+
+impl TextBuffer {
+    /// pos 之后下一个单词的开头：跳过当前的单词或一串标点，再跳过空白；
+    /// 在行尾时移到下一行行首
+    pub(crate) fn next_word_start(&self, pos: Position) -> Position {
+        let pos = self.clamp_position(pos);
+        let clusters = clusters(&self.lines[pos.y]);
+        let Some(mut i) = clusters.iter().position(|c| c.1 > pos.x) else {
+            if pos.y + 1 < self.lines.len() {
+                return Position::new(0, pos.y + 1);
+            }
+            return pos;
+        };
+        let class = clusters[i].2;
+        if class != CharClass::Space {
+            while i < clusters.len() && clusters[i].2 == class {
+                i += 1;
+            }
+        }
+        while i < clusters.len() && clusters[i].2 == CharClass::Space {
+            i += 1;
+        }
+        let x = clusters
+            .get(i)
+            .map_or(clusters[clusters.len() - 1].1, |c| c.0);
+        Position::new(x, pos.y)
+    }
+
+    /// pos 之前上一个单词的开头：跳过空白，再跳过前面的单词或一串标点；
+    /// 在行首时移到上一行行尾
+    pub(crate) fn prev_word_start(&self, pos: Position) -> Position {
+        let pos = self.clamp_position(pos);
+        if pos.x == 0 {
+            if pos.y > 0 {
+                return Position::new(self.line_char_count(pos.y - 1), pos.y - 1);
+            }
+            return pos;
+        }
+        let clusters = clusters(&self.lines[pos.y]);
+        let mut i = clusters.iter().take_while(|c| c.1 <= pos.x).count();
+        while i > 0 && clusters[i - 1].2 == CharClass::Space {
+            i -= 1;
+        }
+        if i > 0 {
+            let class = clusters[i - 1].2;
+            while i > 0 && clusters[i - 1].2 == class {
+                i -= 1;
+            }
+        }
+        let x = if i == 0 { 0 } else { clusters[i - 1].1 };
+        Position::new(x, pos.y)
+    }
+
+    /// 光标移到下一个单词的开头，已在缓冲区末尾时返回 false
+    pub fn move_word_right(&mut self) -> bool {
+        let cursor = self.cursor_position();
+        let target = self.next_word_start(cursor);
+        self.set_cursor_position(target);
+        target != cursor
+    }
+
+    /// 光标移到上一个单词的开头，已在缓冲区开头时返回 false
+    pub fn move_word_left(&mut self) -> bool {
+        let cursor = self.cursor_position();
+        let target = self.prev_word_start(cursor);
+        self.set_cursor_position(target);
+        target != cursor
+    }
+
+    /// 删除光标之前到上一个单词开头的文本，在行首时与上一行合并；没有可删除的内容时返回 false
+    pub fn delete_word_backward(&mut self) -> bool {
+        let cursor = self.clamp_position(self.cursor_position());
+        let start = self.prev_word_start(cursor);
+        if start == cursor {
+            return false;
+        }
+        self.delete_region(start, cursor);
+        true
+    }
+
+    /// 删除光标之后到下一个单词开头的文本，在行尾时与下一行合并；没有可删除的内容时返回 false
+    pub fn delete_word_forward(&mut self) -> bool {
+        let cursor = self.clamp_position(self.cursor_position());
+        let end = self.next_word_start(cursor);
+        if end == cursor {
+            return false;
+        }
+        self.delete_region(cursor, end);
+        true
+    }
+}
@@ -0,0 +1,231 @@
+use super::{Position, TextBuffer};
+use crate::direction::Direction;
+use std::cell::Cell;
+
+/// 主光标之外的一个光标，多光标模式下编辑在每个光标处各执行一次
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub x: usize,
+    pub y: usize,
+    /// 上下移动时希望保持的列（粘性列）
+    pub goal_x: Option<usize>,
+}
+
+impl Cursor {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y, goal_x: None }
+    }
+
+    pub fn position(&self) -> Position {
+        Position::new(self.x, self.y)
+    }
+}
+
+impl TextBuffer {
+    /// 除主光标之外是否还有别的光标
+    pub fn has_multiple_cursors(&self) -> bool {
+        !self.cursors.is_empty()
+    }
+
+    /// 光标总数，包括主光标
+    pub fn cursor_count(&self) -> usize {
+        self.cursors.len() + 1
+    }
+
+    /// 第 y 行第 x 个字符处是否有主光标之外的光标
+    pub fn has_cursor_at(&self, x: usize, y: usize) -> bool {
+        self.cursors.iter().any(|c| c.x == x && c.y == y)
+    }
+
+    /// 在主光标处添加一个光标，那里已经有光标时删除它；返回是否添加了光标
+    ///
+    /// 新光标用 Alt+方向键移到别处，之后输入在每个光标处各执行一次。
+    pub fn toggle_cursor(&mut self) -> bool {
+        let pos = self.cursor_position();
+        match self.cursors.iter().position(|c| c.position() == pos) {
+            Some(i) => {
+                self.cursors.remove(i);
+                false
+            }
+            None => {
+                self.cursors.push(Cursor::new(pos.x, pos.y));
+                true
+            }
+        }
+    }
+
+    /// 删除主光标之外的全部光标
+    pub fn clear_cursors(&mut self) {
+        self.cursors.clear();
+    }
+
+    /// 把各个光标限制回有效范围，删除行之后调用
+    pub(crate) fn clamp_cursors(&mut self) {
+        for i in 0..self.cursors.len() {
+            let pos = self.clamp_position(self.cursors[i].position());
+            self.cursors[i].x = pos.x;
+            self.cursors[i].y = pos.y;
+        }
+    }
+
+    /// 删除与主光标或前面的光标重合的光标
+    fn merge_cursors(&mut self) {
+        let mut seen = vec![self.cursor_position()];
+        self.cursors.retain(|c| {
+            let pos = c.position();
+            if seen.contains(&pos) {
+                return false;
+            }
+            seen.push(pos);
+            true
+        });
+    }
+
+    /// 移动最近添加的光标，还没有时先在主光标处添加一个
+    pub(crate) fn move_last_cursor(&mut self, direction: Direction) {
+        if self.cursors.is_empty() {
+            self.cursors.push(Cursor::new(self.cursor_x, self.cursor_y));
+        }
+        let last = self.cursors.len() - 1;
+        self.move_extra_cursor(last, direction);
+    }
+
+    /// 主光标之外的光标都朝 direction 移动一步，移到一起的光标合并
+    pub(crate) fn move_extra_cursors(&mut self, direction: Direction) {
+        for i in 0..self.cursors.len() {
+            self.move_extra_cursor(i, direction);
+        }
+        self.merge_cursors();
+    }
+
+    /// 主光标之外的光标都移到 target 给出的位置（按单词移动等），移到一起的光标合并
+    pub(crate) fn move_extra_cursors_to(&mut self, target: impl Fn(&Self, Position) -> Position) {
+        for i in 0..self.cursors.len() {
+            let pos = target(self, self.cursors[i].position());
+            self.cursors[i] = Cursor::new(pos.x, pos.y);
+        }
+        self.merge_cursors();
+    }
+
+    fn move_extra_cursor(&mut self, i: usize, direction: Direction) {
+        let lines_len = self.lines.len();
+        let Cursor { x, y, goal_x } = self.cursors[i];
+        let y = y.min(lines_len - 1);
+        let line_len = self.lines[y].chars().count();
+        let (x, y, goal_x) = match direction {
+            Direction::Up if y > 0 => {
+                let goal = goal_x.unwrap_or(x);
+                (
+                    goal.min(self.lines[y - 1].chars().count()),
+                    y - 1,
+                    Some(goal),
+                )
+            }
+            Direction::Down if y < lines_len - 1 => {
+                let goal = goal_x.unwrap_or(x);
+                (
+                    goal.min(self.lines[y + 1].chars().count()),
+                    y + 1,
+                    Some(goal),
+                )
+            }
+            Direction::Up | Direction::Down => (x, y, goal_x),
+            Direction::Left if x > 0 => (x - 1, y, None),
+            Direction::Left if y > 0 => (self.lines[y - 1].chars().count(), y - 1, None),
+            Direction::Right if x < line_len => (x + 1, y, None),
+            Direction::Right if y < lines_len - 1 => (0, y + 1, None),
+            Direction::Left | Direction::Right => (x, y, None),
+        };
+        self.cursors[i] = Cursor { x, y, goal_x };
+    }
+
+    /// 在每个光标处各执行一次编辑操作，并修正所有光标的位置
+    ///
+    /// 从最靠后的光标开始编辑，这样靠前的位置不受影响；编辑完一个光标后记下它到缓冲区
+    /// 末尾的行数和到行尾的字符数，之后靠前位置的编辑不改变它后面的文本，最后据此还原位置。
+    pub(crate) fn edit_at_all_cursors(&mut self, edit: impl Fn(&mut Self)) {
+        if self.cursors.is_empty() {
+            edit(self);
+            return;
+        }
+        let ys = self.cursors.iter().map(|c| c.y).chain([self.cursor_y]);
+        let (first, last) = ys.fold((usize::MAX, 0), |(lo, hi), y| (lo.min(y), hi.max(y)));
+        // 各处的修改合起来算一步撤销
+        let lines = first.saturating_sub(1)..last + 2;
+        self.record_compound_edit(lines, |buffer| buffer.edit_at_cursors(edit));
+    }
+
+    fn edit_at_cursors(&mut self, edit: impl Fn(&mut Self)) {
+        // 多光标编辑不与选区混用
+        self.mark = None;
+        self.fill_virtual_space();
+        self.clamp_cursors();
+        self.merge_cursors();
+        // 下标 0 是主光标，其余依次是 cursors 中的光标
+        let mut order: Vec<(usize, Position)> = std::iter::once(self.cursor_position())
+            .chain(self.cursors.iter().map(Cursor::position))
+            .enumerate()
+            .collect();
+        order.sort_by_key(|&(_, pos)| std::cmp::Reverse(pos));
+
+        let mut from_end = vec![(0, 0); order.len()];
+        for &(index, pos) in &order {
+            self.set_cursor_position(pos);
+            edit(self);
+            let after = self.cursor_position();
+            let lines_from_end = self.lines.len() - 1 - after.y;
+            let chars_from_line_end = self.line_char_count(after.y).saturating_sub(after.x);
+            from_end[index] = (lines_from_end, chars_from_line_end);
+        }
+
+        let positions: Vec<Position> = from_end
+            .into_iter()
+            .map(|(lines_from_end, chars)| {
+                let y = (self.lines.len() - 1).saturating_sub(lines_from_end);
+                Position::new(self.line_char_count(y).saturating_sub(chars), y)
+            })
+            .collect();
+        self.set_cursor_position(positions[0]);
+        for (cursor, &pos) in self.cursors.iter_mut().zip(&positions[1..]) {
+            *cursor = Cursor::new(pos.x, pos.y);
+        }
+        self.merge_cursors();
+    }
+
+    /// 在每个光标处插入新行
+    pub fn insert_newline_at_all_cursors(&mut self) {
+        self.edit_at_all_cursors(|buffer| buffer.insert_newline());
+    }
+
+    /// 删除每个光标前的字符
+    pub fn delete_char_at_all_cursors(&mut self) {
+        self.edit_at_all_cursors(|buffer| buffer.delete_char());
+    }
+
+    /// 删除每个光标处的字符
+    pub fn delete_char_forward_at_all_cursors(&mut self) {
+        self.edit_at_all_cursors(|buffer| buffer.delete_char_forward());
+    }
+
+    /// 删除每个光标前的单词，哪里都没有可删除的内容时返回 false
+    pub fn delete_word_backward_at_all_cursors(&mut self) -> bool {
+        let deleted = Cell::new(false);
+        self.edit_at_all_cursors(|buffer| {
+            if buffer.delete_word_backward() {
+                deleted.set(true);
+            }
+        });
+        deleted.get()
+    }
+
+    /// 删除每个光标后的单词，哪里都没有可删除的内容时返回 false
+    pub fn delete_word_forward_at_all_cursors(&mut self) -> bool {
+        let deleted = Cell::new(false);
+        self.edit_at_all_cursors(|buffer| {
+            if buffer.delete_word_forward() {
+                deleted.set(true);
+            }
+        });
+        deleted.get()
+    }
+}
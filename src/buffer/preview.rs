@@ -0,0 +1,128 @@
+use super::{IndentStyle, TextBuffer};
+use crate::filetype::FileType;
+use crate::rawbytes;
+use crate::{EditorError, Result};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+/// 预览时每次读入的字节数
+const PREVIEW_CHUNK: u64 = 1 << 20;
+
+/// 超过大小阈值的文件先以只读预览打开：只读入开头一部分，滚动到末尾附近时继续读入
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargeFilePreview {
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 已经读入的字节数
+    pub loaded: u64,
+    /// 上一块在行中间结束，下一块的开头接在最后一行后面
+    mid_line: bool,
+}
+
+impl LargeFilePreview {
+    /// 已读入的百分比
+    pub fn percent(&self) -> u64 {
+        (self.loaded * 100).checked_div(self.size).unwrap_or(100)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.loaded >= self.size
+    }
+}
+
+impl TextBuffer {
+    /// 打开文件；大小超过 threshold 字节时以只读预览打开，threshold 为 0 时总是完整读入
+    pub fn open(path: &PathBuf, threshold: u64) -> Result<Self> {
+        let size = fs::metadata(path).map_or(0, |m| m.len());
+        if threshold == 0 || size <= threshold {
+            return Self::from_file(path);
+        }
+        let mut buffer = Self {
+            filename: Some(path.clone()),
+            lines: Vec::new(),
+            preview: Some(LargeFilePreview {
+                size,
+                loaded: 0,
+                mid_line: false,
+            }),
+            ..Self::new()
+        };
+        buffer.load_more_preview()?;
+        let first_line = buffer.lines.first().map(String::as_str).unwrap_or("");
+        buffer.filetype = FileType::detect(Some(path), first_line);
+        buffer.indent_style = IndentStyle::detect(&buffer.lines);
+        Ok(buffer)
+    }
+
+    /// 预览再读入一块，返回是否读到了新内容
+    pub fn load_more_preview(&mut self) -> Result<bool> {
+        let (Some(preview), Some(path)) = (self.preview.as_mut(), self.filename.as_ref()) else {
+            return Ok(false);
+        };
+        if preview.is_complete() {
+            return Ok(false);
+        }
+        let mut chunk = Vec::new();
+        File::open(path)
+            .and_then(|mut file| {
+                file.seek(SeekFrom::Start(preview.loaded))?;
+                file.take(PREVIEW_CHUNK).read_to_end(&mut chunk)
+            })
+            .map_err(|e| EditorError::io(path, e))?;
+        if chunk.is_empty() {
+            // 文件在打开后变短了
+            preview.size = preview.loaded;
+            return Ok(false);
+        }
+        let at_end = preview.loaded + chunk.len() as u64 >= preview.size;
+        // 不是最后一块时切在最后一个换行之后；整块都没有换行时切在完整的字符之后
+        if !at_end {
+            match chunk.iter().rposition(|&b| b == b'\n') {
+                Some(newline) => chunk.truncate(newline + 1),
+                None => chunk.truncate(chunk.len() - rawbytes::incomplete_tail(&chunk)),
+            }
+        }
+        let (text, invalid) = rawbytes::decode(&chunk);
+        preview.loaded += chunk.len() as u64;
+        self.invalid_bytes += invalid;
+        let continues_line = preview.mid_line;
+        preview.mid_line = !text.ends_with('\n');
+        let mut pieces = text.strip_suffix('\n').unwrap_or(&text).split('\n');
+        if continues_line {
+            if let (Some(last), Some(first)) = (self.lines.last_mut(), pieces.next()) {
+                last.push_str(first);
+            }
+        }
+        self.lines
+            .extend(pieces.map(|line| line.strip_suffix('\r').unwrap_or(line).to_string()));
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.revision += 1;
+        Ok(true)
+    }
+
+    /// 结束预览：完整读入文件后允许编辑，光标和滚动位置保持不变
+    pub fn finish_preview(&mut self) -> Result<()> {
+        let Some(path) = self.filename.clone() else {
+            return Ok(());
+        };
+        if self.preview.is_none() {
+            return Ok(());
+        }
+        let full = Self::from_file(&path)?;
+        self.lines = full.lines;
+        self.invalid_bytes = full.invalid_bytes;
+        self.preview = None;
+        self.revision += 1;
+        let pos = self.clamp_position(self.cursor_position());
+        self.set_cursor_position(pos);
+        Ok(())
+    }
+
+    /// 只读设置打开或者还在预览时不能修改
+    pub fn is_read_only(&self) -> bool {
+        self.settings.read_only || self.preview.is_some()
+    }
+}
@@ -0,0 +1,72 @@
+use super::{byte_index, EditKind, TextBuffer};
+use crate::prose::{starts_sentence, typographic_replacement, Typography};
+
+impl TextBuffer {
+    /// 开启或关闭散文模式，软换行随之开关
+    pub fn set_prose(&mut self, on: bool) {
+        self.settings.prose = on;
+        self.settings.soft_wrap = on;
+    }
+
+    /// 光标前的字符；位于自由光标的空白区域时视为空格
+    pub(crate) fn char_before_cursor(&self) -> Option<char> {
+        if self.cursor_x == 0 || self.cursor_y >= self.lines.len() {
+            return None;
+        }
+        self.current_line()
+            .chars()
+            .nth(self.cursor_x - 1)
+            .or(Some(' '))
+    }
+
+    /// 散文模式下输入字符：`--` 变为破折号，直引号变为弯引号
+    pub fn insert_char_typographic(&mut self, ch: char) {
+        self.delete_selection();
+        match typographic_replacement(self.char_before_cursor(), ch) {
+            Typography::Insert(ch) => self.insert_char(ch),
+            Typography::ReplacePrevious(ch) => {
+                self.delete_char();
+                self.insert_char(ch);
+            }
+        }
+    }
+
+    /// 散文模式下刚输入 ch 之后的自动更正：句首字母大写、合并连续的空格
+    ///
+    /// 更正单独记作一步撤销，紧接着撤销只还原更正，保留原样输入的字符。
+    pub(crate) fn prose_autocorrect(&mut self, ch: char) {
+        let (x, y) = (self.cursor_x, self.cursor_y);
+        if x == 0 || y >= self.lines.len() {
+            return;
+        }
+        let chars: Vec<char> = self.lines[y].chars().take(x).collect();
+        if chars.len() < x || chars[x - 1] != ch {
+            return;
+        }
+        if ch == ' ' && self.settings.collapse_spaces {
+            // 只合并词与词之间的空格，行首的缩进不动
+            let between_words = x >= 3 && chars[x - 2] == ' ' && !chars[x - 3].is_whitespace();
+            if between_words {
+                self.record_edit(EditKind::Other, y..y + 1, |buffer| buffer.delete_char());
+            }
+        } else if ch.is_lowercase() && self.settings.auto_capitalize {
+            let mut before: String = chars[..x - 1].iter().collect();
+            // 行首的字母接着上一行的末尾判断
+            if before.trim().is_empty() && y > 0 {
+                before = format!("{} {}", self.lines[y - 1], before);
+            }
+            let mut upper = ch.to_uppercase();
+            let (Some(upper), None) = (upper.next(), upper.next()) else {
+                return;
+            };
+            if upper != ch && starts_sentence(&before) {
+                self.record_edit(EditKind::Other, y..y + 1, |buffer| {
+                    let line = &mut buffer.lines[y];
+                    let range = byte_index(line, x - 1)..byte_index(line, x);
+                    line.replace_range(range, upper.encode_utf8(&mut [0; 4]));
+                    buffer.mark_line_modified(y);
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use super::TextBuffer;
+use std::ops::Range;
+
+/// 修改前后两组行中真正变化的部分：(开头相同的行数, 结尾相同的行数)
+pub(super) fn common_ends(old: &[String], new: &[String]) -> (usize, usize) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (prefix, suffix)
+}
+
+impl TextBuffer {
+    /// 第 y 行是否受保护
+    pub fn is_line_protected(&self, y: usize) -> bool {
+        self.protected_lines.iter().any(|r| r.contains(&y))
+    }
+
+    /// 保护 lines 范围内的行，之后对这些行的修改都会被拒绝；与已有的区域重叠或相连时合并
+    pub fn protect_lines(&mut self, lines: Range<usize>) {
+        if lines.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = (lines.start, lines.end);
+        self.protected_lines.retain(|r| {
+            if r.end < start || r.start > end {
+                return true;
+            }
+            start = start.min(r.start);
+            end = end.max(r.end);
+            false
+        });
+        let i = self.protected_lines.partition_point(|r| r.start < start);
+        self.protected_lines.insert(i, start..end);
+    }
+
+    /// 取消 lines 范围内各行的保护，范围两侧的部分仍受保护
+    pub fn unprotect_lines(&mut self, lines: Range<usize>) {
+        let mut kept = Vec::with_capacity(self.protected_lines.len() + 1);
+        for r in self.protected_lines.drain(..) {
+            let before = r.start..r.end.min(lines.start);
+            let after = r.start.max(lines.end)..r.end;
+            kept.extend([before, after].into_iter().filter(|r| !r.is_empty()));
+        }
+        self.protected_lines = kept;
+    }
+
+    /// 把从 start 开始的 old 几行换成 new 几行时碰到的第一个受保护的行；不碰到时返回 None
+    ///
+    /// 只看真正变化的行：记录范围两端没有变化的行即使受保护也不算。
+    /// 纯插入时插入点在某个区域中间也算碰到，区域开头之前和结尾之后可以插入。
+    pub(crate) fn protected_line_hit(
+        &self,
+        start: usize,
+        old: &[String],
+        new: &[String],
+    ) -> Option<usize> {
+        let (prefix, suffix) = common_ends(old, new);
+        let changed = start + prefix..start + old.len() - suffix;
+        self.protected_lines.iter().find_map(|r| {
+            if changed.is_empty() {
+                (r.start < changed.start && changed.start < r.end).then_some(changed.start)
+            } else {
+                (r.start < changed.end && changed.start < r.end).then(|| r.start.max(changed.start))
+            }
+        })
+    }
+
+    /// 修改生效后，让变化部分之后的保护区域跟着行号移动
+    pub(crate) fn shift_protected(&mut self, start: usize, old: &[String], new: &[String]) {
+        if self.protected_lines.is_empty() || old.len() == new.len() {
+            return;
+        }
+        let (_, suffix) = common_ends(old, new);
+        let changed_end = start + old.len() - suffix;
+        for r in &mut self.protected_lines {
+            if r.start >= changed_end {
+                *r = r.start + new.len() - old.len()..r.end + new.len() - old.len();
+            } else if r.end > changed_end {
+                // 撤销、重做不受保护限制，变化落在区域中间时只调整结尾
+                r.end = (r.end + new.len())
+                    .saturating_sub(old.len())
+                    .max(r.start + 1);
+            }
+        }
+    }
+
+    /// 取出上一次因为碰到受保护的行而被撤回的修改所在的行
+    pub fn take_rejected_edit(&mut self) -> Option<usize> {
+        self.rejected_edit.take()
+    }
+}
@@ -0,0 +1,179 @@
+use super::{byte_index, EditKind, Position, TextBuffer};
+use crate::regex::Regex;
+
+/// 一处匹配，只在一行之内，start..end 为字符索引
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub y: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl SearchMatch {
+    pub fn start_position(&self) -> Position {
+        Position::new(self.start, self.y)
+    }
+}
+
+/// 查询中没有大写字母时不区分大小写（smart case）
+fn ignores_case(query: &str) -> bool {
+    !query.chars().any(char::is_uppercase)
+}
+
+fn chars_equal(a: char, b: char, ignore_case: bool) -> bool {
+    a == b || (ignore_case && a.to_lowercase().eq(b.to_lowercase()))
+}
+
+/// 一行中所有不重叠的匹配，返回字符索引范围
+pub fn find_in_line(line: &str, query: &str) -> Vec<(usize, usize)> {
+    let needle: Vec<char> = query.chars().collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let ignore_case = ignores_case(query);
+    let hay: Vec<char> = line.chars().collect();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i + needle.len() <= hay.len() {
+        let matched = hay[i..i + needle.len()]
+            .iter()
+            .zip(&needle)
+            .all(|(&a, &b)| chars_equal(a, b, ignore_case));
+        if matched {
+            found.push((i, i + needle.len()));
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+/// 替换时查找的内容：普通文字或正则表达式
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    Text(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    /// regex 为真时把 query 编译成正则表达式，模式有误时返回错误说明
+    pub fn new(query: &str, regex: bool) -> Result<Self, String> {
+        if regex {
+            Regex::new(query).map(Matcher::Regex)
+        } else {
+            Ok(Matcher::Text(query.to_string()))
+        }
+    }
+
+    /// 一行中所有不重叠的匹配
+    pub fn find_in_line(&self, line: &str) -> Vec<(usize, usize)> {
+        match self {
+            Matcher::Text(query) => find_in_line(line, query),
+            Matcher::Regex(regex) => regex.find_in_line(line),
+        }
+    }
+
+    /// line 中从 start 开始的匹配要换成的内容：正则表达式的模板中 `$1` 等换成分组
+    pub fn replacement_for(&self, line: &str, start: usize, template: &str) -> String {
+        match self {
+            Matcher::Text(_) => template.to_string(),
+            Matcher::Regex(regex) => {
+                let hay: Vec<char> = line.chars().collect();
+                match regex.captures_at(&hay, start) {
+                    Some(caps) => regex.expand(template, &hay, &caps),
+                    None => template.to_string(),
+                }
+            }
+        }
+    }
+}
+
+impl TextBuffer {
+    /// 缓冲区中所有匹配，按位置排序
+    pub fn find_all(&self, query: &str) -> Vec<SearchMatch> {
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                find_in_line(line, query)
+                    .into_iter()
+                    .map(move |(start, end)| SearchMatch { y, start, end })
+            })
+            .collect()
+    }
+
+    /// 从 from 开始向后（或向前）查找下一处匹配，到达末尾后从另一端继续
+    ///
+    /// 向后查找时不包括正好从 from 开始的匹配，这样重复查找会前进到下一处。
+    pub fn find_next(&self, query: &str, from: Position, backwards: bool) -> Option<SearchMatch> {
+        let matches = self.find_all(query);
+        if backwards {
+            matches
+                .iter()
+                .rev()
+                .find(|m| m.start_position() < from)
+                .or(matches.last())
+                .copied()
+        } else {
+            matches
+                .iter()
+                .find(|m| m.start_position() > from)
+                .or(matches.first())
+                .copied()
+        }
+    }
+
+    /// 把所有匹配替换为 replacement，返回替换的处数
+    ///
+    /// 整个替换只递增一次版本号，算作一次修改。
+    pub fn replace_all(&mut self, matcher: &Matcher, replacement: &str) -> usize {
+        let lines = 0..self.lines.len();
+        self.record_edit(EditKind::Other, lines, |buffer| {
+            let mut count = 0;
+            for y in 0..buffer.lines.len() {
+                let line = &buffer.lines[y];
+                let ranges = matcher.find_in_line(line);
+                if ranges.is_empty() {
+                    continue;
+                }
+                count += ranges.len();
+                let chars: Vec<char> = line.chars().collect();
+                let mut replaced = String::with_capacity(line.len());
+                let mut last = 0;
+                for (start, end) in ranges {
+                    replaced.extend(&chars[last..start]);
+                    replaced.push_str(&matcher.replacement_for(line, start, replacement));
+                    last = end;
+                }
+                replaced.extend(&chars[last..]);
+                buffer.lines[y] = replaced;
+                buffer.modified_lines_set.insert(y);
+            }
+            if count > 0 {
+                buffer.modified = true;
+                buffer.revision += 1;
+                buffer.mark = None;
+                let pos = buffer.clamp_position(buffer.cursor_position());
+                buffer.set_cursor_position(pos);
+                buffer.clamp_cursors();
+            }
+            count
+        })
+    }
+
+    /// 把一处匹配换成 text，光标移到替换后的文字末尾并返回该位置
+    pub fn replace_match(&mut self, found: SearchMatch, text: &str) -> Position {
+        self.record_edit(EditKind::Other, found.y..found.y + 1, |buffer| {
+            let line = &mut buffer.lines[found.y];
+            let from = byte_index(line, found.start);
+            let to = byte_index(line, found.end);
+            line.replace_range(from..to, text);
+            let end = Position::new(found.start + text.chars().count(), found.y);
+            buffer.mark = None;
+            buffer.set_cursor_position(end);
+            buffer.mark_line_modified(found.y);
+            end
+        })
+    }
+}
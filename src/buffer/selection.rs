@@ -0,0 +1,220 @@
+use super::{byte_index, EditKind, Position, TextBuffer};
+use crate::unicode::is_word_char;
+
+impl TextBuffer {
+    /// 主光标当前位置
+    pub fn cursor_position(&self) -> Position {
+        Position::new(self.cursor_x, self.cursor_y)
+    }
+
+    /// 移动主光标到指定位置
+    pub fn set_cursor_position(&mut self, pos: Position) {
+        let pos = self.clamp_position(pos);
+        self.cursor_x = pos.x;
+        self.cursor_y = pos.y;
+        self.goal_x = None;
+    }
+
+    /// 把位置限制在缓冲区范围内
+    pub fn clamp_position(&self, pos: Position) -> Position {
+        let y = pos.y.min(self.lines.len() - 1);
+        let x = pos.x.min(self.lines[y].chars().count());
+        Position::new(x, y)
+    }
+
+    /// 在主光标处设置标记，已设置时取消，返回标记现在是否存在
+    pub fn toggle_mark(&mut self) -> bool {
+        if self.mark.is_some() {
+            self.mark = None;
+        } else {
+            self.mark = Some(self.cursor_position());
+        }
+        self.mark.is_some()
+    }
+
+    /// 当前选区（起点, 终点），起点总在终点之前；没有标记或选区为空时返回 None
+    pub fn selection_range(&self) -> Option<(Position, Position)> {
+        // 自由光标模式下光标可能在行尾或文件末尾之后，选区只到实际的文本为止
+        let mark = self.clamp_position(self.mark?);
+        let cursor = self.clamp_position(self.cursor_position());
+        match mark.cmp(&cursor) {
+            std::cmp::Ordering::Less => Some((mark, cursor)),
+            std::cmp::Ordering::Greater => Some((cursor, mark)),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// 删除 start 到 end 之间的文本（可跨行），返回被删除的内容，光标移到 start
+    ///
+    /// 选区删除、剪切等操作都通过这里修改缓冲区。
+    pub fn delete_region(&mut self, start: Position, end: Position) -> String {
+        let lines = start.y.min(end.y)..start.y.max(end.y) + 1;
+        self.record_edit(EditKind::Other, lines, |buffer| {
+            let (start, end) = if start <= end {
+                (start, end)
+            } else {
+                (end, start)
+            };
+            let start = buffer.clamp_position(start);
+            let end = buffer.clamp_position(end);
+
+            let removed = if start.y == end.y {
+                let line = &mut buffer.lines[start.y];
+                let from = byte_index(line, start.x);
+                let to = byte_index(line, end.x);
+                line.drain(from..to).collect()
+            } else {
+                let from = byte_index(&buffer.lines[start.y], start.x);
+                let to = byte_index(&buffer.lines[end.y], end.x);
+                let mut removed = buffer.lines[start.y][from..].to_string();
+                for line in &buffer.lines[start.y + 1..end.y] {
+                    removed.push('\n');
+                    removed.push_str(line);
+                }
+                removed.push('\n');
+                removed.push_str(&buffer.lines[end.y][..to]);
+
+                let tail = buffer.lines[end.y][to..].to_string();
+                let first = &mut buffer.lines[start.y];
+                first.truncate(from);
+                first.push_str(&tail);
+                buffer.lines.drain(start.y + 1..=end.y);
+                removed
+            };
+
+            buffer.cursor_x = start.x;
+            buffer.cursor_y = start.y;
+            buffer.mark = None;
+            buffer.clear_goal_columns();
+            // 其余光标可能落在被删除的行上，限制回有效范围
+            buffer.clamp_cursors();
+            if !removed.is_empty() {
+                buffer.mark_line_modified(start.y);
+            }
+            removed
+        })
+    }
+
+    /// 删除选中的文本，没有选区时返回 false
+    pub fn delete_selection(&mut self) -> bool {
+        match self.selection_range() {
+            Some((start, end)) => {
+                self.delete_region(start, end);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// start 到 end 之间的文本（可跨行），行与行之间用换行符连接
+    pub fn region_text(&self, start: Position, end: Position) -> String {
+        let start = self.clamp_position(start);
+        let end = self.clamp_position(end);
+        let mut text = String::new();
+        for y in start.y..=end.y {
+            let line = &self.lines[y];
+            let from = if y == start.y {
+                byte_index(line, start.x)
+            } else {
+                0
+            };
+            let to = if y == end.y {
+                byte_index(line, end.x)
+            } else {
+                line.len()
+            };
+            if y > start.y {
+                text.push('\n');
+            }
+            text.push_str(&line[from..to]);
+        }
+        text
+    }
+
+    /// 选中 start 到 end 之间的文本：标记放在 start，光标放在 end
+    pub(crate) fn select_range(&mut self, start: Position, end: Position) {
+        self.mark = Some(start);
+        self.set_cursor_position(end);
+    }
+
+    /// pos 处（或紧挨在 pos 之前）的单词范围
+    fn word_range_at(&self, pos: Position) -> Option<(Position, Position)> {
+        let chars: Vec<char> = self.lines.get(pos.y)?.chars().collect();
+        let is_word = |i: usize| chars.get(i).is_some_and(|&c| is_word_char(c));
+        let x = if is_word(pos.x) {
+            pos.x
+        } else if pos.x > 0 && is_word(pos.x - 1) {
+            pos.x - 1
+        } else {
+            return None;
+        };
+        let mut start = x;
+        while start > 0 && is_word(start - 1) {
+            start -= 1;
+        }
+        let mut end = x + 1;
+        while is_word(end) {
+            end += 1;
+        }
+        Some((Position::new(start, pos.y), Position::new(end, pos.y)))
+    }
+
+    /// 第 first 到 last 行的整行范围，包括最后一行的换行符
+    fn line_range(&self, first: usize, last: usize) -> (Position, Position) {
+        let end = if last + 1 < self.lines.len() {
+            Position::new(0, last + 1)
+        } else {
+            Position::new(self.lines[last].chars().count(), last)
+        };
+        (Position::new(0, first), end)
+    }
+
+    /// 选中光标处的单词，光标不在单词上时返回 false
+    pub fn select_word(&mut self) -> bool {
+        match self.word_range_at(self.cursor_position()) {
+            Some((start, end)) => {
+                self.select_range(start, end);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 选中光标所在的整行
+    pub fn select_line(&mut self) {
+        let y = self.cursor_y.min(self.lines.len() - 1);
+        let (start, end) = self.line_range(y, y);
+        self.select_range(start, end);
+    }
+
+    /// 逐级扩大选区：单词 → 整行 → 段落 → 整个缓冲区，已全选时返回 false
+    pub fn expand_selection(&mut self) -> bool {
+        let cursor = self.clamp_position(self.cursor_position());
+        let (from, to) = self.selection_range().unwrap_or((cursor, cursor));
+        // 选区结束在某行行首时，那一行不算在选区内
+        let last = if to.x == 0 && to.y > from.y {
+            to.y - 1
+        } else {
+            to.y
+        };
+        let buffer_end = self.lines.len() - 1;
+        let candidates = [
+            self.word_range_at(cursor),
+            Some(self.line_range(from.y, last)),
+            Some(self.line_range(self.paragraph_start(from.y), self.paragraph_end(last))),
+            Some(self.line_range(0, buffer_end)),
+        ];
+        // 取第一个严格包含当前选区的范围
+        let next = candidates
+            .into_iter()
+            .flatten()
+            .find(|&(start, end)| start <= from && to <= end && (start, end) != (from, to));
+        match next {
+            Some((start, end)) => {
+                self.select_range(start, end);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use super::{Position, TextBuffer};
+use crate::prose::{is_closing_punctuation, is_sentence_terminal, is_wide_sentence_terminal};
+
+impl TextBuffer {
+    /// 第 y 行所在段落的第一行（段落以空行分隔）
+    pub(crate) fn paragraph_start(&self, mut y: usize) -> usize {
+        while y > 0 && !self.lines[y - 1].trim().is_empty() {
+            y -= 1;
+        }
+        y
+    }
+
+    /// 第 y 行所在段落的最后一行
+    pub(crate) fn paragraph_end(&self, mut y: usize) -> usize {
+        while y + 1 < self.lines.len() && !self.lines[y + 1].trim().is_empty() {
+            y += 1;
+        }
+        y
+    }
+
+    /// 从第 start_y 行开始依次找出每个句子的开头，f 返回 false 时停止
+    ///
+    /// 句子从段落的第一个非空白字符开始，或者在句末标点（及其后的右引号、右括号）
+    /// 加空白之后开始；全角句号之后不需要空白。小写字母开头的不算新句子，
+    /// 以免把 "e.g. this" 之类的缩写当成句子结尾。
+    fn scan_sentence_starts(&self, start_y: usize, mut f: impl FnMut(Position) -> bool) {
+        let mut at_paragraph_start = true;
+        let mut after_terminal = false;
+        let mut wide_terminal = false;
+        let mut gap = false;
+        for (y, line) in self.lines.iter().enumerate().skip(start_y) {
+            if line.trim().is_empty() {
+                at_paragraph_start = true;
+                after_terminal = false;
+                continue;
+            }
+            for (x, ch) in line.chars().enumerate() {
+                if ch.is_whitespace() {
+                    gap |= after_terminal;
+                    continue;
+                }
+                let starts_here = at_paragraph_start
+                    || (after_terminal
+                        && !ch.is_lowercase()
+                        && (gap || (wide_terminal && !is_closing_punctuation(ch))));
+                if starts_here {
+                    if !f(Position::new(x, y)) {
+                        return;
+                    }
+                    at_paragraph_start = false;
+                    after_terminal = false;
+                }
+                if is_sentence_terminal(ch) {
+                    after_terminal = true;
+                    wide_terminal = is_wide_sentence_terminal(ch);
+                    gap = false;
+                } else if !(after_terminal && !gap && is_closing_punctuation(ch)) {
+                    after_terminal = false;
+                }
+            }
+            // 换行也算句末标点之后的空白
+            gap |= after_terminal;
+        }
+    }
+
+    /// pos 之后的第一个句子开头
+    pub fn next_sentence_start(&self, pos: Position) -> Option<Position> {
+        let mut found = None;
+        // 自由光标模式下 pos 可能在文件末尾之后
+        let y = pos.y.min(self.lines.len() - 1);
+        self.scan_sentence_starts(self.paragraph_start(y), |start| {
+            if start > pos {
+                found = Some(start);
+                false
+            } else {
+                true
+            }
+        });
+        found
+    }
+
+    /// pos 之前（inclusive 时包括 pos 本身）的最后一个句子开头
+    pub fn prev_sentence_start(&self, pos: Position, inclusive: bool) -> Option<Position> {
+        let mut y = self.paragraph_start(pos.y.min(self.lines.len() - 1));
+        loop {
+            let mut found = None;
+            self.scan_sentence_starts(y, |start| {
+                if start < pos || (inclusive && start == pos) {
+                    found = Some(start);
+                    true
+                } else {
+                    false
+                }
+            });
+            if found.is_some() || y == 0 {
+                return found;
+            }
+            // 本段没有更早的句子，跳过空行到上一段
+            let mut above = y - 1;
+            while above > 0 && self.lines[above].trim().is_empty() {
+                above -= 1;
+            }
+            y = self.paragraph_start(above);
+        }
+    }
+
+    /// 句子结尾：下一句开头（没有时为文件末尾）再往回跳过空白
+    fn sentence_end(&self, pos: Position) -> Position {
+        let last = self.lines.len() - 1;
+        let mut end = self
+            .next_sentence_start(pos)
+            .unwrap_or(Position::new(self.lines[last].chars().count(), last));
+        loop {
+            if end.x == 0 {
+                if end.y == 0 || end <= pos {
+                    return end;
+                }
+                end = Position::new(self.lines[end.y - 1].chars().count(), end.y - 1);
+            } else if self.lines[end.y]
+                .chars()
+                .nth(end.x - 1)
+                .is_some_and(char::is_whitespace)
+            {
+                end.x -= 1;
+            } else {
+                return end;
+            }
+        }
+    }
+
+    /// 把主光标移到下一个句子开头，没有下一句时移到文件末尾
+    pub fn move_to_next_sentence(&mut self) {
+        let pos = self.cursor_position();
+        let last = self.lines.len() - 1;
+        let target = self
+            .next_sentence_start(pos)
+            .unwrap_or(Position::new(self.lines[last].chars().count(), last));
+        self.set_cursor_position(target);
+    }
+
+    /// 把主光标移到本句开头，已在句首时移到上一句开头
+    pub fn move_to_prev_sentence(&mut self) {
+        let pos = self.cursor_position();
+        let target = self
+            .prev_sentence_start(pos, false)
+            .unwrap_or(Position::new(0, 0));
+        self.set_cursor_position(target);
+    }
+
+    /// 选中光标所在的句子（标记放在句首，光标放在句末），不在句子中时返回 false
+    pub fn select_sentence(&mut self) -> bool {
+        let pos = self.clamp_position(self.cursor_position());
+        let Some(start) = self.prev_sentence_start(pos, true) else {
+            return false;
+        };
+        let end = self.sentence_end(start);
+        if end <= start {
+            return false;
+        }
+        self.select_range(start, end);
+        true
+    }
+}
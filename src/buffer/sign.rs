@@ -0,0 +1,140 @@
+use super::protected::common_ends;
+use super::TextBuffer;
+use crossterm::style::Color;
+
+/// 修改过的行在符号栏中的标记，由 `modified_lines_set` 生成，不单独保存
+const CHANGE_SIGN: Sign = Sign {
+    line: 0,
+    symbol: '▎',
+    color: Color::DarkYellow,
+    priority: 0,
+    source: "change",
+};
+
+/// 符号栏占的列数：一个符号加一个空格
+const SIGN_COLUMN_WIDTH: usize = 2;
+
+/// 显示行号时行号部分占的列数
+const LINE_NUMBER_WIDTH: usize = 4;
+
+/// 行号左侧符号栏中的一个符号（书签、运行结果中的错误等）
+///
+/// 和虚拟文本一样只用于显示：不保存、不参与撤销，修改时跟着所在的行移动，
+/// 所在的行被删除时一起删除。同一行有多个符号时显示优先级最高的一个。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sign {
+    pub line: usize,
+    /// 显示的符号，应占一列
+    pub symbol: char,
+    pub color: Color,
+    /// 优先级，数值大的优先显示
+    pub priority: u8,
+    /// 产生者，例如 `bookmark`，用来整组替换或清除
+    pub source: &'static str,
+}
+
+impl Sign {
+    pub fn new(
+        line: usize,
+        symbol: char,
+        color: Color,
+        priority: u8,
+        source: &'static str,
+    ) -> Self {
+        Self {
+            line,
+            symbol,
+            color,
+            priority,
+            source,
+        }
+    }
+}
+
+impl TextBuffer {
+    /// 放置一个符号，同一产生者在同一行上原有的符号被替换
+    pub fn place_sign(&mut self, sign: Sign) {
+        self.remove_sign(sign.line, sign.source);
+        self.signs.push(sign);
+    }
+
+    /// 删除某个产生者在第 y 行上的符号，原来没有时返回 false
+    pub fn remove_sign(&mut self, y: usize, source: &str) -> bool {
+        let count = self.signs.len();
+        self.signs.retain(|s| s.line != y || s.source != source);
+        self.signs.len() != count
+    }
+
+    /// 删除某个产生者的全部符号
+    pub fn clear_signs(&mut self, source: &str) {
+        self.signs.retain(|s| s.source != source);
+    }
+
+    /// 某个产生者的符号所在的行，按行号排列
+    pub fn sign_lines(&self, source: &str) -> Vec<usize> {
+        let mut lines: Vec<usize> = self
+            .signs
+            .iter()
+            .filter(|s| s.source == source)
+            .map(|s| s.line)
+            .collect();
+        lines.sort_unstable();
+        lines
+    }
+
+    /// 第 y 行在符号栏中显示的符号：优先级最高的符号，没有时是修改标记
+    pub fn sign_at(&self, y: usize) -> Option<Sign> {
+        let placed = self
+            .signs
+            .iter()
+            .filter(|s| s.line == y)
+            .max_by_key(|s| s.priority)
+            .copied();
+        placed.or_else(|| {
+            self.modified_lines_set.contains(&y).then_some(Sign {
+                line: y,
+                ..CHANGE_SIGN
+            })
+        })
+    }
+
+    /// 符号栏占的列数：关闭 signcolumn 选项或没有任何符号时不显示
+    pub fn sign_column_width(&self) -> usize {
+        let any = !self.signs.is_empty() || !self.modified_lines_set.is_empty();
+        if self.settings.sign_column && any {
+            SIGN_COLUMN_WIDTH
+        } else {
+            0
+        }
+    }
+
+    /// 文本左侧的总宽度（符号栏加行号），绘制和定位都按这个宽度计算
+    pub fn gutter_width(&self) -> usize {
+        let numbers = if self.settings.line_numbers {
+            LINE_NUMBER_WIDTH
+        } else {
+            0
+        };
+        self.sign_column_width() + numbers
+    }
+
+    /// 修改生效后，让符号跟着所在的行移动，被删除的行上的符号一起删除
+    pub(crate) fn shift_signs(&mut self, start: usize, old: &[String], new: &[String]) {
+        if self.signs.is_empty() || old.len() == new.len() {
+            return;
+        }
+        let (prefix, suffix) = common_ends(old, new);
+        let changed_end = start + old.len() - suffix;
+        // 变化部分中保留下来的行数，超出的行算作被删除
+        let kept_end =
+            start + prefix + (new.len() - prefix - suffix).min(old.len() - prefix - suffix);
+        self.signs.retain_mut(|s| {
+            if s.line >= changed_end {
+                s.line = s.line + new.len() - old.len();
+            } else if s.line >= kept_end {
+                return false;
+            }
+            true
+        });
+    }
+}
@@ -0,0 +1,51 @@
+use super::TextBuffer;
+use crate::outline;
+
+/// 最长行和总大小，用于状态栏的长行、大文件提醒；文档还统计字数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferStats {
+    /// 统计时的版本号
+    pub revision: u64,
+    /// 最长行的行号
+    pub longest_line: usize,
+    /// 最长行的字符数
+    pub longest_len: usize,
+    /// 保存后的字节数（含换行符）
+    pub bytes: u64,
+    /// 字数，只统计有标题结构的文档（Markdown 等）
+    pub words: Option<usize>,
+}
+
+impl TextBuffer {
+    /// 内容变化后重新统计，版本号没变时什么也不做
+    pub fn update_stats(&mut self) {
+        let count_words = outline::is_document(self.filetype);
+        // 另存为可能改变文件类型而不改变版本号
+        if self
+            .stats
+            .is_some_and(|s| s.revision == self.revision && s.words.is_some() == count_words)
+        {
+            return;
+        }
+        let mut stats = BufferStats {
+            revision: self.revision,
+            longest_line: 0,
+            longest_len: 0,
+            bytes: self.lines.len().saturating_sub(1) as u64,
+            words: count_words.then(|| outline::word_count(&self.lines)),
+        };
+        for (y, line) in self.lines.iter().enumerate() {
+            stats.bytes += line.len() as u64;
+            // 字符数不会超过字节数，字节数不够长的行不必再数字符
+            if line.len() <= stats.longest_len {
+                continue;
+            }
+            let len = line.chars().count();
+            if len > stats.longest_len {
+                stats.longest_line = y;
+                stats.longest_len = len;
+            }
+        }
+        self.stats = Some(stats);
+    }
+}
@@ -0,0 +1,27 @@
+use super::TextBuffer;
+use crate::unicode::display_column;
+
+impl TextBuffer {
+    /// 按 Tab：插入制表符，开启 tabstospaces 时改为插入空格补到下一个制表位；多光标时每个光标处都插入
+    pub fn insert_tab_at_all_cursors(&mut self) {
+        if self.settings.expand_tab {
+            self.edit_at_all_cursors(|buffer| buffer.insert_spaces_to_tab_stop());
+        } else {
+            self.insert_char_at_all_cursors('\t');
+        }
+    }
+
+    /// 插入空格直到下一个制表位，有选区时先删除选中内容
+    fn insert_spaces_to_tab_stop(&mut self) {
+        let lines = self.lines_around_cursor();
+        self.record_compound_edit(lines, |buffer| {
+            buffer.delete_selection();
+            let tab_width = buffer.settings.tab_width.max(1);
+            let line = buffer.lines.get(buffer.cursor_y).map_or("", String::as_str);
+            let col = display_column(line, buffer.cursor_x, tab_width);
+            for _ in 0..tab_width - col % tab_width {
+                buffer.insert_char(' ');
+            }
+        });
+    }
+}
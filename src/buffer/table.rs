@@ -0,0 +1,87 @@
+use super::{EditKind, TextBuffer};
+use crate::markdown::{format_table, is_separator_row, is_table_row, pipe_positions};
+
+impl TextBuffer {
+    /// 光标所在表格的起止行（包含两端），光标不在表格中时返回 None
+    pub fn table_range(&self) -> Option<(usize, usize)> {
+        if !is_table_row(self.lines.get(self.cursor_y)?) {
+            return None;
+        }
+        let mut start = self.cursor_y;
+        while start > 0 && is_table_row(&self.lines[start - 1]) {
+            start -= 1;
+        }
+        let mut end = self.cursor_y;
+        while end + 1 < self.lines.len() && is_table_row(&self.lines[end + 1]) {
+            end += 1;
+        }
+        Some((start, end))
+    }
+
+    /// 光标所在的单元格序号
+    fn table_cell_index(&self) -> usize {
+        let pipes = pipe_positions(self.current_line());
+        let before = pipes.iter().filter(|&&p| p < self.cursor_x).count();
+        before.saturating_sub(1).min(pipes.len().saturating_sub(2))
+    }
+
+    /// 把光标移到第 y 行第 cell 个单元格内容的开头
+    fn move_to_table_cell_start(&mut self, y: usize, cell: usize) {
+        let pipes = pipe_positions(&self.lines[y]);
+        let cell = cell.min(pipes.len().saturating_sub(2));
+        self.cursor_y = y;
+        self.cursor_x = match (pipes.get(cell), pipes.get(cell + 1)) {
+            (Some(&p), Some(&next)) => (p + 2).min(next),
+            (Some(&p), None) => p + 1,
+            _ => 0,
+        };
+        self.goal_x = None;
+    }
+
+    /// 格式化光标所在的表格，光标保持在原来的单元格中
+    pub fn format_table_at_cursor(&mut self) -> bool {
+        let Some((start, end)) = self.table_range() else {
+            return false;
+        };
+        let cell = self.table_cell_index();
+        let formatted = format_table(&self.lines[start..=end]);
+        self.record_edit(EditKind::Other, start..end + 1, |buffer| {
+            for (i, row) in formatted.into_iter().enumerate() {
+                if buffer.lines[start + i] != row {
+                    buffer.lines[start + i] = row;
+                    buffer.mark_line_modified(start + i);
+                }
+            }
+        });
+        self.move_to_table_cell_start(self.cursor_y, cell);
+        true
+    }
+
+    /// 格式化表格并移到下一个（forward）或上一个单元格，跨行时跳过分隔行
+    pub fn move_table_cell(&mut self, forward: bool) -> bool {
+        if !self.format_table_at_cursor() {
+            return false;
+        }
+        let Some((start, end)) = self.table_range() else {
+            return false;
+        };
+        let cell = self.table_cell_index();
+        let cells = pipe_positions(self.current_line()).len().saturating_sub(1);
+        let y = self.cursor_y;
+        if forward {
+            if cell + 1 < cells {
+                self.move_to_table_cell_start(y, cell + 1);
+            } else if let Some(next) = (y + 1..=end).find(|&r| !is_separator_row(&self.lines[r])) {
+                self.move_to_table_cell_start(next, 0);
+            }
+        } else if cell > 0 {
+            self.move_to_table_cell_start(y, cell - 1);
+        } else if let Some(prev) = (start..y)
+            .rev()
+            .find(|&r| !is_separator_row(&self.lines[r]))
+        {
+            self.move_to_table_cell_start(prev, usize::MAX);
+        }
+        true
+    }
+}
@@ -0,0 +1,87 @@
+use super::{EditKind, TextBuffer};
+use crate::unicode::{grapheme_bounds, is_word_char};
+use std::ops::Range;
+
+impl TextBuffer {
+    /// 交换光标前后的两个字符（按字素簇），光标移到两者之后；
+    /// 在行尾时交换行尾的两个字符。没有可交换的字符时返回 false
+    pub fn transpose_chars(&mut self) -> bool {
+        if self.cursor_y >= self.lines.len() {
+            return false;
+        }
+        let chars: Vec<char> = self.current_line().chars().collect();
+        let bounds = grapheme_bounds(&chars);
+        // bounds 包括行首和行尾，少于两个字素簇时没有可交换的
+        if bounds.len() < 3 {
+            return false;
+        }
+        let x = self.cursor_x.min(chars.len());
+        // 光标所在的簇，光标在行尾时取最后一个簇
+        let Some(mut i) = bounds.iter().rposition(|&b| b <= x) else {
+            return false;
+        };
+        if i + 1 == bounds.len() {
+            i -= 1;
+        }
+        if i == 0 {
+            return false;
+        }
+        let first = bounds[i - 1]..bounds[i];
+        let second = bounds[i]..bounds[i + 1];
+        self.swap_ranges(&chars, first, second);
+        true
+    }
+
+    /// 交换光标前后的两个单词，光标移到后一个单词之后；
+    /// 光标之后没有单词时交换本行最后两个单词。没有两个单词时返回 false
+    pub fn transpose_words(&mut self) -> bool {
+        if self.cursor_y >= self.lines.len() {
+            return false;
+        }
+        let chars: Vec<char> = self.current_line().chars().collect();
+        let words = word_ranges(&chars);
+        let x = self.cursor_x.min(chars.len());
+        let second = words
+            .iter()
+            .position(|w| w.end > x)
+            .unwrap_or(words.len().saturating_sub(1));
+        if second == 0 || second >= words.len() {
+            return false;
+        }
+        self.swap_ranges(&chars, words[second - 1].clone(), words[second].clone());
+        true
+    }
+
+    /// 交换当前行中两段相邻或不相邻的文本，中间部分不变，光标移到后一段之后
+    fn swap_ranges(&mut self, chars: &[char], first: Range<usize>, second: Range<usize>) {
+        let lines = self.lines_around_cursor();
+        self.record_edit(EditKind::Other, lines, |buffer| {
+            let mut line: String = chars[..first.start].iter().collect();
+            line.extend(&chars[second.clone()]);
+            line.extend(&chars[first.end..second.start]);
+            line.extend(&chars[first.clone()]);
+            line.extend(&chars[second.end..]);
+            buffer.mark = None;
+            buffer.clear_goal_columns();
+            *buffer.current_line_mut() = line;
+            buffer.cursor_x = second.end;
+            buffer.mark_line_modified(buffer.cursor_y);
+        });
+    }
+}
+
+/// 一行中每个单词的字符范围，附着在单词字符上的组合符号算作单词的一部分
+fn word_ranges(chars: &[char]) -> Vec<Range<usize>> {
+    let bounds = grapheme_bounds(chars);
+    let mut words: Vec<Range<usize>> = Vec::new();
+    for pair in bounds.windows(2) {
+        if !is_word_char(chars[pair[0]]) {
+            continue;
+        }
+        match words.last_mut() {
+            Some(last) if last.end == pair[0] => last.end = pair[1],
+            _ => words.push(pair[0]..pair[1]),
+        }
+    }
+    words
+}
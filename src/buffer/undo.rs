@@ -0,0 +1,337 @@
+use super::memory::lines_heap_size;
+use super::{Position, TextBuffer};
+use std::mem::size_of;
+use std::ops::Range;
+
+/// 修改的种类，同类的连续修改合并成一步撤销
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    /// 输入字符
+    Typing,
+    /// 退格或删除字符
+    Deleting,
+    /// 其他修改，每次单独成一步
+    Other,
+}
+
+/// 修改前后的光标、选区和滚动位置，撤销或重做时一并恢复
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct View {
+    cursor: Position,
+    mark: Option<Position>,
+    offset_x: usize,
+    offset_y: usize,
+}
+
+/// 一次可以撤销的修改：把从 line 开始的 old 几行换成了 new 几行
+#[derive(Debug, Clone)]
+struct Edit {
+    kind: EditKind,
+    line: usize,
+    old: Vec<String>,
+    new: Vec<String>,
+    before: View,
+    after: View,
+}
+
+impl Edit {
+    /// 把紧接着的 next 合并进来，两次修改涉及的行不相连时返回 false
+    fn merge(&mut self, next: Edit) -> bool {
+        let (a0, a1) = (self.line, self.line + self.new.len());
+        let (b0, b1) = (next.line, next.line + next.old.len());
+        if b0 > a1 || a0 > b1 {
+            return false;
+        }
+        let (u0, u1) = (a0.min(b0), a1.max(b1));
+        // 两次修改之间的文本中，合并后范围内的各行
+        let middle: Vec<String> = (u0..u1)
+            .map(|y| {
+                if (a0..a1).contains(&y) {
+                    self.new[y - a0].clone()
+                } else {
+                    next.old[y - b0].clone()
+                }
+            })
+            .collect();
+        let mut old = middle.clone();
+        old.splice(a0 - u0..a1 - u0, std::mem::take(&mut self.old));
+        let mut new = middle;
+        new.splice(b0 - u0..b1 - u0, next.new);
+        self.line = u0;
+        self.old = old;
+        self.new = new;
+        self.after = next.after;
+        true
+    }
+
+    fn heap_size(&self) -> usize {
+        lines_heap_size(&self.old) + lines_heap_size(&self.new)
+    }
+}
+
+/// 缓冲区的撤销和重做历史
+#[derive(Debug, Clone, Default)]
+pub struct UndoHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    /// 为 false 时下一次修改另起一步，不与上一步合并
+    open_group: bool,
+    /// 保存文件时撤销历史的长度，撤销或重做回到这里时缓冲区算作未修改
+    saved_at: Option<usize>,
+    /// 正在记录的修改层数，外层已经在记录时内层不再单独记录
+    depth: usize,
+    /// 外层不指定种类时，取内层第一次修改的种类
+    inner_kind: Option<EditKind>,
+}
+
+impl UndoHistory {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// 结束当前的一组修改，之后的修改另起一步
+    pub fn break_group(&mut self) {
+        self.open_group = false;
+    }
+
+    /// 记下保存时的位置
+    pub fn mark_saved(&mut self) {
+        self.saved_at = Some(self.undo.len());
+        self.open_group = false;
+    }
+
+    /// 可以撤销的步数
+    pub fn undo_steps(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// 撤销和重做历史占用的堆内存（字节）
+    pub fn heap_size(&self) -> usize {
+        (self.undo.capacity() + self.redo.capacity()) * size_of::<Edit>()
+            + self
+                .undo
+                .iter()
+                .chain(&self.redo)
+                .map(Edit::heap_size)
+                .sum::<usize>()
+    }
+
+    /// 丢弃最早的 count 步修改
+    fn drop_oldest(&mut self, count: usize) {
+        self.undo.drain(..count);
+        // 保存时的状态被丢弃后再也回不去了
+        self.saved_at = self.saved_at.and_then(|saved| saved.checked_sub(count));
+    }
+
+    /// 撤销历史超过 max_steps 步或占用超过 max_bytes 字节时丢弃最早的几步，
+    /// 至少保留最近一步；上限为 0 表示不限制，返回丢弃的步数
+    pub fn enforce_limits(&mut self, max_steps: usize, max_bytes: usize) -> usize {
+        let mut dropped = 0;
+        if max_steps > 0 {
+            dropped = self.undo.len().saturating_sub(max_steps);
+        }
+        if max_bytes > 0 {
+            let mut size = self.heap_size();
+            for edit in &self.undo[..dropped] {
+                size -= edit.heap_size();
+            }
+            while size > max_bytes && dropped + 1 < self.undo.len() {
+                size -= self.undo[dropped].heap_size();
+                dropped += 1;
+            }
+        }
+        if dropped > 0 {
+            self.drop_oldest(dropped);
+        }
+        dropped
+    }
+
+    /// 丢弃较早的修改，只保留最近 keep 步，并收紧分配；返回丢弃的步数
+    pub fn trim(&mut self, keep: usize) -> usize {
+        let dropped = self.undo.len().saturating_sub(keep);
+        self.drop_oldest(dropped);
+        self.open_group = false;
+        self.undo.shrink_to_fit();
+        self.redo.shrink_to_fit();
+        for edit in self.undo.iter_mut().chain(&mut self.redo) {
+            edit.old.shrink_to_fit();
+            edit.new.shrink_to_fit();
+        }
+        dropped
+    }
+
+    fn push(&mut self, edit: Edit) {
+        self.redo.clear();
+        if self.saved_at.is_some_and(|saved| saved > self.undo.len()) {
+            // 保存时的状态在被丢弃的重做历史里，再也回不去了
+            self.saved_at = None;
+        }
+        if let Some(last) = self.undo.last_mut() {
+            let joinable = self.open_group
+                && edit.kind != EditKind::Other
+                && last.kind == edit.kind
+                && last.after.cursor == edit.before.cursor;
+            if joinable && last.merge(edit.clone()) {
+                return;
+            }
+        }
+        self.undo.push(edit);
+        self.open_group = true;
+    }
+
+    fn is_saved(&self) -> bool {
+        self.saved_at == Some(self.undo.len())
+    }
+}
+
+impl TextBuffer {
+    /// 执行一次修改并记入撤销历史；lines 为修改前会被改动的行，修改只能增删这个范围内的行
+    pub(crate) fn record_edit<R>(
+        &mut self,
+        kind: EditKind,
+        lines: Range<usize>,
+        edit: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.record(Some(kind), lines, edit)
+    }
+
+    /// 同 record_edit，由几次内层修改组成，种类取第一次内层修改的种类
+    pub(crate) fn record_compound_edit<R>(
+        &mut self,
+        lines: Range<usize>,
+        edit: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.record(None, lines, edit)
+    }
+
+    fn record<R>(
+        &mut self,
+        kind: Option<EditKind>,
+        lines: Range<usize>,
+        edit: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        if self.history.depth > 0 {
+            if let Some(kind) = kind {
+                self.history.inner_kind.get_or_insert(kind);
+            }
+            return edit(self);
+        }
+        let start = lines.start.min(self.lines.len());
+        let end = lines.end.clamp(start, self.lines.len());
+        let old = self.lines[start..end].to_vec();
+        let len_before = self.lines.len();
+        let before = self.view();
+        // 有保护区域时记下修改标记，修改被撤回时还原
+        let saved = (!self.protected_lines.is_empty())
+            .then(|| (self.modified, self.modified_lines_set.clone()));
+
+        self.history.depth += 1;
+        self.history.inner_kind = None;
+        let result = edit(self);
+        self.history.depth -= 1;
+
+        let new_end = (end + self.lines.len()).saturating_sub(len_before);
+        let new = self.lines[start..new_end.min(self.lines.len())].to_vec();
+        if old != new {
+            if let Some(y) = self.protected_line_hit(start, &old, &new) {
+                self.lines.splice(start..start + new.len(), old);
+                self.restore_view(before);
+                if let Some((modified, lines)) = saved {
+                    self.modified = modified;
+                    self.modified_lines_set = lines;
+                }
+                self.clamp_cursors();
+                self.rejected_edit = Some(y);
+                return result;
+            }
+            self.shift_protected(start, &old, &new);
+            self.shift_annotations(start, &old, &new);
+            self.shift_signs(start, &old, &new);
+            let kind = kind.or(self.history.inner_kind).unwrap_or(EditKind::Other);
+            self.history.push(Edit {
+                kind,
+                line: start,
+                old,
+                new,
+                before,
+                after: self.view(),
+            });
+        }
+        result
+    }
+
+    fn view(&self) -> View {
+        View {
+            cursor: self.cursor_position(),
+            mark: self.mark,
+            offset_x: self.offset_x,
+            offset_y: self.offset_y,
+        }
+    }
+
+    /// 恢复光标、选区和滚动位置
+    fn restore_view(&mut self, view: View) {
+        self.mark = view.mark.map(|mark| self.clamp_position(mark));
+        self.clear_goal_columns();
+        let pos = self.clamp_position(view.cursor);
+        self.set_cursor_position(pos);
+        self.offset_x = view.offset_x;
+        self.offset_y = view.offset_y.min(self.lines.len() - 1);
+    }
+
+    /// 光标所在行及上下各一行，有选区时包括选区的各行，用作一般修改的记录范围
+    pub(crate) fn lines_around_cursor(&self) -> Range<usize> {
+        let (mut start, mut end) = (self.cursor_y, self.cursor_y);
+        if let Some((from, to)) = self.selection_range() {
+            start = start.min(from.y);
+            end = end.max(to.y);
+        }
+        start.saturating_sub(1)..end + 2
+    }
+
+    /// 撤销上一步修改，没有可撤销的修改时返回 false
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.history.undo.pop() else {
+            return false;
+        };
+        self.apply_edit(edit.line, edit.new.len(), &edit.old, edit.before);
+        self.history.redo.push(edit);
+        self.history.open_group = false;
+        self.modified = !self.history.is_saved();
+        true
+    }
+
+    /// 重做上一步撤销的修改，没有可重做的修改时返回 false
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.history.redo.pop() else {
+            return false;
+        };
+        self.apply_edit(edit.line, edit.old.len(), &edit.new, edit.after);
+        self.history.undo.push(edit);
+        self.history.open_group = false;
+        self.modified = !self.history.is_saved();
+        true
+    }
+
+    /// 把从 line 开始的 count 行换成 lines，光标、选区和滚动位置恢复为 view
+    fn apply_edit(&mut self, line: usize, count: usize, lines: &[String], view: View) {
+        let old: Vec<String> = self
+            .lines
+            .splice(line..line + count, lines.iter().cloned())
+            .collect();
+        self.shift_protected(line, &old, lines);
+        self.shift_annotations(line, &old, lines);
+        self.shift_signs(line, &old, lines);
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.restore_view(view);
+        self.clamp_cursors();
+        self.modified_lines_set.extend(line..line + lines.len());
+        self.revision += 1;
+    }
+}
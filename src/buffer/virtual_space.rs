@@ -0,0 +1,67 @@
+use super::TextBuffer;
+use crate::direction::Direction;
+
+impl TextBuffer {
+    /// 指定行的字符数，超出文件末尾的行视为空行
+    pub fn line_char_count(&self, y: usize) -> usize {
+        self.lines.get(y).map_or(0, |line| line.chars().count())
+    }
+
+    /// 开关自由光标模式，关闭时把光标收回到文本范围内
+    pub fn toggle_virtual_space(&mut self) -> bool {
+        self.settings.virtual_space = !self.settings.virtual_space;
+        if !self.settings.virtual_space {
+            self.set_cursor_position(self.cursor_position());
+        }
+        self.settings.virtual_space
+    }
+
+    /// 光标是否位于行尾之后或文件末尾之后的空白区域
+    pub fn cursor_in_virtual_space(&self) -> bool {
+        self.cursor_y >= self.lines.len() || self.cursor_x > self.line_char_count(self.cursor_y)
+    }
+
+    /// 自由光标模式下的移动：左右不换行，上下不受行长限制，可以移到文件末尾之后
+    pub(crate) fn move_cursor_free(&mut self, direction: Direction, page_height: usize) {
+        self.goal_x = None;
+        match direction {
+            Direction::Up => {
+                self.cursor_y = self.cursor_y.saturating_sub(1);
+            }
+            Direction::Down => {
+                // 最多移到文件末尾之后一屏的位置
+                if self.cursor_y + 1 < self.lines.len() + page_height {
+                    self.cursor_y += 1;
+                }
+            }
+            Direction::Left => {
+                if self.cursor_x > 0 {
+                    self.cursor_x -= 1;
+                } else if self.cursor_y > 0 {
+                    self.cursor_y -= 1;
+                    self.cursor_x = self.line_char_count(self.cursor_y);
+                }
+            }
+            Direction::Right => {
+                self.cursor_x += 1;
+            }
+        }
+    }
+
+    /// 光标位于空白区域时，用空行和空格补齐到光标位置，真正输入文字前调用
+    pub fn fill_virtual_space(&mut self) {
+        if !self.cursor_in_virtual_space() {
+            return;
+        }
+        while self.lines.len() <= self.cursor_y {
+            self.lines.push(String::new());
+            self.modified_lines_set.insert(self.lines.len() - 1);
+        }
+        let line_len = self.line_char_count(self.cursor_y);
+        if self.cursor_x > line_len {
+            let line = &mut self.lines[self.cursor_y];
+            line.extend(std::iter::repeat_n(' ', self.cursor_x - line_len));
+        }
+        self.mark_line_modified(self.cursor_y);
+    }
+}
@@ -0,0 +1,345 @@
+// 字符信息：查看光标处字符（Alt+Shift+I）时显示的名称
+//
+// 没有内置完整的 Unicode 名称表：ASCII、各种空白、零宽字符和双向控制符等容易看不见或认错的字符
+// 给出标准名称，其余字符只给出所在的区块。
+
+/// ASCII 控制字符（U+0000..U+001F）的名称
+const CONTROL_NAMES: [&str; 32] = [
+    "NULL",
+    "START OF HEADING",
+    "START OF TEXT",
+    "END OF TEXT",
+    "END OF TRANSMISSION",
+    "ENQUIRY",
+    "ACKNOWLEDGE",
+    "BELL",
+    "BACKSPACE",
+    "CHARACTER TABULATION",
+    "LINE FEED",
+    "LINE TABULATION",
+    "FORM FEED",
+    "CARRIAGE RETURN",
+    "SHIFT OUT",
+    "SHIFT IN",
+    "DATA LINK ESCAPE",
+    "DEVICE CONTROL ONE",
+    "DEVICE CONTROL TWO",
+    "DEVICE CONTROL THREE",
+    "DEVICE CONTROL FOUR",
+    "NEGATIVE ACKNOWLEDGE",
+    "SYNCHRONOUS IDLE",
+    "END OF TRANSMISSION BLOCK",
+    "CANCEL",
+    "END OF MEDIUM",
+    "SUBSTITUTE",
+    "ESCAPE",
+    "INFORMATION SEPARATOR FOUR",
+    "INFORMATION SEPARATOR THREE",
+    "INFORMATION SEPARATOR TWO",
+    "INFORMATION SEPARATOR ONE",
+];
+
+/// 数字的英文名
+const DIGIT_NAMES: [&str; 10] = [
+    "ZERO", "ONE", "TWO", "THREE", "FOUR", "FIVE", "SIX", "SEVEN", "EIGHT", "NINE",
+];
+
+/// 有标准名称的其他字符：ASCII 标点，以及容易看不见或与常见字符混淆的字符
+const NAMES: &[(char, &str)] = &[
+    (' ', "SPACE"),
+    ('!', "EXCLAMATION MARK"),
+    ('"', "QUOTATION MARK"),
+    ('#', "NUMBER SIGN"),
+    ('$', "DOLLAR SIGN"),
+    ('%', "PERCENT SIGN"),
+    ('&', "AMPERSAND"),
+    ('\'', "APOSTROPHE"),
+    ('(', "LEFT PARENTHESIS"),
+    (')', "RIGHT PARENTHESIS"),
+    ('*', "ASTERISK"),
+    ('+', "PLUS SIGN"),
+    (',', "COMMA"),
+    ('-', "HYPHEN-MINUS"),
+    ('.', "FULL STOP"),
+    ('/', "SOLIDUS"),
+    (':', "COLON"),
+    (';', "SEMICOLON"),
+    ('<', "LESS-THAN SIGN"),
+    ('=', "EQUALS SIGN"),
+    ('>', "GREATER-THAN SIGN"),
+    ('?', "QUESTION MARK"),
+    ('@', "COMMERCIAL AT"),
+    ('[', "LEFT SQUARE BRACKET"),
+    ('\\', "REVERSE SOLIDUS"),
+    (']', "RIGHT SQUARE BRACKET"),
+    ('^', "CIRCUMFLEX ACCENT"),
+    ('_', "LOW LINE"),
+    ('`', "GRAVE ACCENT"),
+    ('{', "LEFT CURLY BRACKET"),
+    ('|', "VERTICAL LINE"),
+    ('}', "RIGHT CURLY BRACKET"),
+    ('~', "TILDE"),
+    ('\u{7F}', "DELETE"),
+    ('\u{85}', "NEXT LINE"),
+    ('\u{A0}', "NO-BREAK SPACE"),
+    ('\u{AD}', "SOFT HYPHEN"),
+    ('\u{B7}', "MIDDLE DOT"),
+    ('\u{34F}', "COMBINING GRAPHEME JOINER"),
+    ('\u{61C}', "ARABIC LETTER MARK"),
+    ('\u{180E}', "MONGOLIAN VOWEL SEPARATOR"),
+    ('\u{2000}', "EN QUAD"),
+    ('\u{2001}', "EM QUAD"),
+    ('\u{2002}', "EN SPACE"),
+    ('\u{2003}', "EM SPACE"),
+    ('\u{2004}', "THREE-PER-EM SPACE"),
+    ('\u{2005}', "FOUR-PER-EM SPACE"),
+    ('\u{2006}', "SIX-PER-EM SPACE"),
+    ('\u{2007}', "FIGURE SPACE"),
+    ('\u{2008}', "PUNCTUATION SPACE"),
+    ('\u{2009}', "THIN SPACE"),
+    ('\u{200A}', "HAIR SPACE"),
+    ('\u{200B}', "ZERO WIDTH SPACE"),
+    ('\u{200C}', "ZERO WIDTH NON-JOINER"),
+    ('\u{200D}', "ZERO WIDTH JOINER"),
+    ('\u{200E}', "LEFT-TO-RIGHT MARK"),
+    ('\u{200F}', "RIGHT-TO-LEFT MARK"),
+    ('\u{2010}', "HYPHEN"),
+    ('\u{2011}', "NON-BREAKING HYPHEN"),
+    ('\u{2012}', "FIGURE DASH"),
+    ('\u{2013}', "EN DASH"),
+    ('\u{2014}', "EM DASH"),
+    ('\u{2018}', "LEFT SINGLE QUOTATION MARK"),
+    ('\u{2019}', "RIGHT SINGLE QUOTATION MARK"),
+    ('\u{201C}', "LEFT DOUBLE QUOTATION MARK"),
+    ('\u{201D}', "RIGHT DOUBLE QUOTATION MARK"),
+    ('\u{2026}', "HORIZONTAL ELLIPSIS"),
+    ('\u{2028}', "LINE SEPARATOR"),
+    ('\u{2029}', "PARAGRAPH SEPARATOR"),
+    ('\u{202A}', "LEFT-TO-RIGHT EMBEDDING"),
+    ('\u{202B}', "RIGHT-TO-LEFT EMBEDDING"),
+    ('\u{202C}', "POP DIRECTIONAL FORMATTING"),
+    ('\u{202D}', "LEFT-TO-RIGHT OVERRIDE"),
+    ('\u{202E}', "RIGHT-TO-LEFT OVERRIDE"),
+    ('\u{202F}', "NARROW NO-BREAK SPACE"),
+    ('\u{205F}', "MEDIUM MATHEMATICAL SPACE"),
+    ('\u{2060}', "WORD JOINER"),
+    ('\u{2066}', "LEFT-TO-RIGHT ISOLATE"),
+    ('\u{2067}', "RIGHT-TO-LEFT ISOLATE"),
+    ('\u{2068}', "FIRST STRONG ISOLATE"),
+    ('\u{2069}', "POP DIRECTIONAL ISOLATE"),
+    ('\u{2212}', "MINUS SIGN"),
+    ('\u{3000}', "IDEOGRAPHIC SPACE"),
+    ('\u{3001}', "IDEOGRAPHIC COMMA"),
+    ('\u{3002}', "IDEOGRAPHIC FULL STOP"),
+    ('\u{FE0E}', "VARIATION SELECTOR-15"),
+    ('\u{FE0F}', "VARIATION SELECTOR-16"),
+    ('\u{FEFF}', "ZERO WIDTH NO-BREAK SPACE"),
+    ('\u{FF0C}', "FULLWIDTH COMMA"),
+    ('\u{FF1A}', "FULLWIDTH COLON"),
+    ('\u{FF1B}', "FULLWIDTH SEMICOLON"),
+    ('\u{FFFD}', "REPLACEMENT CHARACTER"),
+];
+
+/// 没有名称的字符所在的区块，按起点排列：(起点, 终点, 名称)
+const BLOCKS: &[(u32, u32, &str)] = &[
+    (0x0080, 0x009F, "C1 控制字符"),
+    (0x00A0, 0x00FF, "拉丁文补充"),
+    (0x0100, 0x024F, "拉丁文扩展"),
+    (0x0250, 0x02AF, "国际音标"),
+    (0x02B0, 0x02FF, "修饰字母"),
+    (0x0300, 0x036F, "组合附加符号"),
+    (0x0370, 0x03FF, "希腊字母"),
+    (0x0400, 0x052F, "西里尔字母"),
+    (0x0530, 0x058F, "亚美尼亚字母"),
+    (0x0590, 0x05FF, "希伯来字母"),
+    (0x0600, 0x06FF, "阿拉伯字母"),
+    (0x0900, 0x097F, "天城文"),
+    (0x0E00, 0x0E7F, "泰文"),
+    (0x1100, 0x11FF, "韩文字母"),
+    (0x1E00, 0x1EFF, "拉丁文扩展附加"),
+    (0x1F00, 0x1FFF, "希腊字母扩展"),
+    (0x2000, 0x206F, "一般标点"),
+    (0x2070, 0x209F, "上标和下标"),
+    (0x20A0, 0x20CF, "货币符号"),
+    (0x20D0, 0x20FF, "符号用组合附加符号"),
+    (0x2100, 0x214F, "类字母符号"),
+    (0x2150, 0x218F, "数字形式"),
+    (0x2190, 0x21FF, "箭头"),
+    (0x2200, 0x22FF, "数学运算符"),
+    (0x2300, 0x23FF, "杂项技术符号"),
+    (0x2460, 0x24FF, "带圈字母数字"),
+    (0x2500, 0x257F, "制表符号"),
+    (0x2580, 0x259F, "方块元素"),
+    (0x25A0, 0x25FF, "几何图形"),
+    (0x2600, 0x26FF, "杂项符号"),
+    (0x2700, 0x27BF, "装饰符号"),
+    (0x2E80, 0x2FDF, "中日韩部首"),
+    (0x3000, 0x303F, "中日韩符号和标点"),
+    (0x3040, 0x309F, "平假名"),
+    (0x30A0, 0x30FF, "片假名"),
+    (0x3100, 0x312F, "注音符号"),
+    (0x3130, 0x318F, "韩文兼容字母"),
+    (0x3400, 0x4DBF, "中日韩统一表意文字扩展 A"),
+    (0x4E00, 0x9FFF, "中日韩统一表意文字"),
+    (0xAC00, 0xD7AF, "韩文音节"),
+    (0xE000, 0xF8FF, "私用区"),
+    (0xF900, 0xFAFF, "中日韩兼容表意文字"),
+    (0xFE00, 0xFE0F, "变体选择符"),
+    (0xFE30, 0xFE4F, "中日韩兼容形式"),
+    (0xFF00, 0xFFEF, "半角及全角字符"),
+    (0x1D400, 0x1D7FF, "数学字母数字符号"),
+    (0x1F000, 0x1F2FF, "游戏符号和带圈字母数字"),
+    (0x1F300, 0x1FAFF, "表情符号和图形"),
+    (0x20000, 0x3FFFF, "中日韩统一表意文字扩展"),
+    (0xE0000, 0xE007F, "标签字符"),
+    (0xE0100, 0xE01EF, "变体选择符补充"),
+    (0xF0000, 0x10FFFF, "补充私用区"),
+];
+
+/// 字符的标准名称，只覆盖 ASCII 和容易看不见或认错的字符
+pub fn char_name(ch: char) -> Option<String> {
+    let c = ch as u32;
+    if c < 0x20 {
+        return Some(CONTROL_NAMES[c as usize].to_string());
+    }
+    if ch.is_ascii_digit() {
+        return Some(format!("DIGIT {}", DIGIT_NAMES[(c - '0' as u32) as usize]));
+    }
+    if ch.is_ascii_uppercase() {
+        return Some(format!("LATIN CAPITAL LETTER {}", ch));
+    }
+    if ch.is_ascii_lowercase() {
+        return Some(format!("LATIN SMALL LETTER {}", ch.to_ascii_uppercase()));
+    }
+    NAMES
+        .iter()
+        .find(|&&(c, _)| c == ch)
+        .map(|&(_, name)| name.to_string())
+}
+
+/// 字符所在的区块名称
+pub fn block_name(ch: char) -> Option<&'static str> {
+    let c = ch as u32;
+    BLOCKS
+        .iter()
+        .find(|&&(start, end, _)| start <= c && c <= end)
+        .map(|&(_, _, name)| name)
+}
+
+/// 字符的说明：有名称时用名称，否则用区块名称，都没有时为空
+pub fn describe(ch: char) -> String {
+    char_name(ch)
+        .or_else(|| block_name(ch).map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// 源代码中可能用来隐藏改动的字符（见 trojan source 攻击）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suspicious {
+    /// 双向控制符，可以让文本显示的顺序与实际顺序不同
+    Bidi,
+    /// 不占宽度或看起来像普通空格的字符
+    Invisible,
+    /// 形似某个 ASCII 字符的非 ASCII 字符
+    Confusable(char),
+}
+
+impl Suspicious {
+    pub fn describe(self) -> String {
+        match self {
+            Suspicious::Bidi => "双向控制符".to_string(),
+            Suspicious::Invisible => "不可见字符".to_string(),
+            Suspicious::Confusable(ascii) => format!("形似 ASCII 字符 “{}”", ascii),
+        }
+    }
+}
+
+/// 形似 ASCII 的字符及其看起来像的字符：西里尔字母、希腊字母和减号等
+const CONFUSABLES: &[(char, char)] = &[
+    ('\u{0391}', 'A'),
+    ('\u{0392}', 'B'),
+    ('\u{0395}', 'E'),
+    ('\u{0396}', 'Z'),
+    ('\u{0397}', 'H'),
+    ('\u{0399}', 'I'),
+    ('\u{039A}', 'K'),
+    ('\u{039C}', 'M'),
+    ('\u{039D}', 'N'),
+    ('\u{039F}', 'O'),
+    ('\u{03A1}', 'P'),
+    ('\u{03A4}', 'T'),
+    ('\u{03A5}', 'Y'),
+    ('\u{03A7}', 'X'),
+    ('\u{03BD}', 'v'),
+    ('\u{03BF}', 'o'),
+    ('\u{0405}', 'S'),
+    ('\u{0406}', 'I'),
+    ('\u{0408}', 'J'),
+    ('\u{0410}', 'A'),
+    ('\u{0412}', 'B'),
+    ('\u{0415}', 'E'),
+    ('\u{041A}', 'K'),
+    ('\u{041C}', 'M'),
+    ('\u{041D}', 'H'),
+    ('\u{041E}', 'O'),
+    ('\u{0420}', 'P'),
+    ('\u{0421}', 'C'),
+    ('\u{0422}', 'T'),
+    ('\u{0425}', 'X'),
+    ('\u{0430}', 'a'),
+    ('\u{0435}', 'e'),
+    ('\u{043E}', 'o'),
+    ('\u{0440}', 'p'),
+    ('\u{0441}', 'c'),
+    ('\u{0443}', 'y'),
+    ('\u{0445}', 'x'),
+    ('\u{0455}', 's'),
+    ('\u{0456}', 'i'),
+    ('\u{0458}', 'j'),
+    ('\u{0501}', 'd'),
+    ('\u{01C0}', '|'),
+    ('\u{02BC}', '\''),
+    ('\u{2010}', '-'),
+    ('\u{2011}', '-'),
+    ('\u{2212}', '-'),
+    ('\u{2215}', '/'),
+    ('\u{2044}', '/'),
+    ('\u{FE63}', '-'),
+];
+
+/// 字符是否可疑；中文标点、弯引号和破折号在注释和文档中很常见，不算在内
+pub fn suspicious(ch: char) -> Option<Suspicious> {
+    let c = ch as u32;
+    if ch.is_ascii() {
+        return None;
+    }
+    match c {
+        0x061C | 0x200E | 0x200F | 0x202A..=0x202E | 0x2066..=0x2069 => {
+            return Some(Suspicious::Bidi)
+        }
+        0x00A0
+        | 0x00AD
+        | 0x034F
+        | 0x115F
+        | 0x1160
+        | 0x1680
+        | 0x180E
+        | 0x2000..=0x200C
+        | 0x2028
+        | 0x2029
+        | 0x202F
+        | 0x205F..=0x2064
+        | 0x3164
+        | 0xFEFF
+        | 0xFFA0 => return Some(Suspicious::Invisible),
+        // 全角字母和数字
+        0xFF10..=0xFF19 | 0xFF21..=0xFF3A | 0xFF41..=0xFF5A => {
+            return char::from_u32(c - 0xFEE0).map(Suspicious::Confusable)
+        }
+        _ => {}
+    }
+    CONFUSABLES
+        .iter()
+        .find(|&&(c, _)| c == ch)
+        .map(|&(_, ascii)| Suspicious::Confusable(ascii))
+}
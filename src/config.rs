@@ -0,0 +1,726 @@
+// 配置文件：~/.config/rsnano/config.toml（位置见 paths 模块）
+//
+// ```toml
+// theme = "default"
+// cut_from_cursor = false
+// persist_cutbuffer = false
+// watch_config = false
+// detect_indent = true   # 打开文件时按文件中的缩进方式设置 tabsize 和 tabstospaces
+// bell = "visual"   # audible、visual 或 none
+// snapshot_idle = 30   # 空闲多少秒后自动快照，0 表示关闭
+// journal = false   # 保存等操作记入编辑日志
+// local_history = 0   # 每个文件保留多少个保存前的版本，0 表示关闭
+// trash = true   # 另存为、导出覆盖已有的文件时先把原文件移进系统回收站（Windows 上默认 false）
+// clipboard = "auto"   # 剪切和复制同步到系统剪贴板：auto、osc52 或 none
+// large_file_mb = 50   # 超过这个大小（MB）的文件以只读预览打开，0 表示不限制
+// warn_line_length = 500   # 有超过这个长度的行时在状态栏提醒，0 表示不提醒
+// warn_size_kb = 1024   # 缓冲区超过这个大小（KB）时在状态栏提醒，0 表示不提醒
+// max_fps = 20   # 没有按键时（终端输出、运行结果等后台更新）每秒最多重绘几次
+// undo_limit = 0   # 每个缓冲区最多保留多少步撤销，0 表示不限制
+// undo_memory_kb = 0   # 每个缓冲区的撤销历史最多占用多少内存（KB），0 表示不限制
+// undo_group_ms = 1000   # 输入停顿超过这么多毫秒后另起一步撤销，0 表示只要连续就合并
+// title_bar = true   # 显示顶部标题栏
+// status_bar = true   # 显示状态栏，关闭后有提示或消息时临时显示
+// help_line = true   # 显示底部帮助栏
+// help_line_actions = ["quit", "save", "help"]   # 帮助栏中的操作，放不下时 Alt+X 翻页
+// todo_markers = ["TODO", "FIXME", "XXX"]   # 任务列表（F9）查找的标记词
+// print_header = true   # 打印时每页印文件名和页码
+// print_line_numbers = false   # 打印时每行前面加行号
+//
+// [options]
+// tabsize = 4
+// tabstospaces = true   # 按 Tab 插入空格
+// linenumbers = true
+//
+// [filetype.markdown]   # 只对某种文件类型生效的设置，选项与 [options] 相同
+// maxlinelength = 72
+// linelimit = "wrap"   # warn 标出超出部分、wrap 输入时自动换行、block 拒绝输入
+//
+// [filetype.python]
+// runcommand = "python3 %f"   # F5 运行的命令，%f 换成文件名
+//
+// [confirm]   # 是/否提示直接按回车时的回答：yes、no，或 ask 表示必须按 Y/N
+// save_on_exit = "ask"
+//
+// [macros]   # 键盘宏：按键写法同 [keys]，其余作为文字输入
+// quote = ["Home", "> ", "Down"]   # 在行首加引用符号并移到下一行
+//
+// [keys]
+// "^K" = "cut_line"
+// "M-k" = "none"   # 取消绑定
+// "F3" = "macro:quote"   # 运行宏（[macros] 中定义或 F4 保存的）
+// ```
+//
+// 项目目录中的 .rsnano.toml 可以再用 [options] 和 [filetype.<名字>] 覆盖这些设置（见 project）。
+
+use crate::action::{Action, DEFAULT_HELP_LINE};
+use crate::args::Args;
+use crate::filetype::FileType;
+use crate::keymap::{KeyBinding, Keymap};
+use crate::layout::Bars;
+use crate::macros::{self, Macro};
+use crate::print::PrintOptions;
+use crate::settings::{BufferSettings, FiletypeOptions, OPTION_NAMES};
+use crate::system_clipboard::ClipboardMode;
+use crate::toml::{self, write_quoted, Value};
+use crate::{paths, EditorError, Result};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::{fs, io};
+
+/// 可以在 [confirm] 中设置默认回答的是/否提示：退出前是否保存、新脚本是否设为可执行
+pub const CONFIRM_NAMES: [&str; 2] = ["save_on_exit", "make_executable"];
+
+/// 后台更新默认的最高重绘频率，与原来每 50 毫秒检查一次事件相同
+pub const DEFAULT_MAX_FPS: u32 = 20;
+
+/// max_fps 允许的最大值
+const MAX_FPS_LIMIT: u32 = 120;
+
+/// 任务列表默认查找的标记词
+pub const DEFAULT_TODO_MARKERS: [&str; 3] = ["TODO", "FIXME", "XXX"];
+
+/// 各个是/否提示直接按回车时的回答，None 表示必须按 Y 或 N
+pub type ConfirmDefaults = [Option<bool>; CONFIRM_NAMES.len()];
+
+/// [confirm] 中的取值
+fn confirm_value_name(value: Option<bool>) -> &'static str {
+    match value {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "ask",
+    }
+}
+
+/// 解析后的配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// 新缓冲区的默认设置
+    pub options: BufferSettings,
+    /// 按文件类型覆盖的设置
+    pub filetypes: FiletypeOptions,
+    /// ^K 从光标剪切到行尾
+    pub cut_from_cursor: bool,
+    /// 退出时保存剪切历史
+    pub persist_cutbuffer: bool,
+    /// 配置或主题文件变化时自动重新加载
+    pub watch_config: bool,
+    /// 打开文件时按检测到的缩进方式覆盖默认的 tabsize 和 tabstospaces
+    pub detect_indent: bool,
+    /// 主题名
+    pub theme: String,
+    /// 操作失败时的提醒方式
+    pub bell: BellStyle,
+    /// 空闲多少秒后给修改过的缓冲区拍快照，0 表示关闭
+    pub snapshot_idle: u64,
+    /// 保存等操作记入状态目录下的编辑日志
+    pub journal: bool,
+    /// 每个文件保留多少个保存前的版本，0 表示关闭
+    pub local_history: usize,
+    /// 覆盖已有的文件前先把原文件移进系统回收站，只在类 Unix 系统上默认开启
+    pub trash: bool,
+    /// 剪切和复制如何同步到系统剪贴板
+    pub clipboard: ClipboardMode,
+    /// 超过这个大小（MB）的文件以只读预览打开，0 表示不限制
+    pub large_file_mb: u64,
+    /// 有超过这个字符数的行时在状态栏提醒，0 表示不提醒
+    pub warn_line_length: usize,
+    /// 缓冲区超过这个大小（KB）时在状态栏提醒，0 表示不提醒
+    pub warn_size_kb: u64,
+    /// 没有按键时每秒最多重绘几次，按键总是立即重绘
+    pub max_fps: u32,
+    /// 每个缓冲区最多保留多少步撤销，0 表示不限制
+    pub undo_limit: usize,
+    /// 每个缓冲区的撤销历史最多占用多少内存（KB），0 表示不限制
+    pub undo_memory_kb: usize,
+    /// 输入停顿超过这么多毫秒后另起一步撤销，0 表示只要连续就合并
+    pub undo_group_ms: u64,
+    /// 显示哪些栏
+    pub bars: Bars,
+    /// 帮助栏中显示的操作，按键从实际键位中查找
+    pub help_line_actions: Vec<Action>,
+    /// 任务列表查找的标记词
+    pub todo_markers: Vec<String>,
+    /// 打印和导出打印格式时的选项
+    pub print: PrintOptions,
+    /// 各种是/否提示的默认回答，顺序同 `CONFIRM_NAMES`
+    pub confirm_defaults: ConfirmDefaults,
+    /// 对默认键位的修改，操作为 None 表示取消绑定
+    pub keys: Vec<(KeyBinding, Option<Action>)>,
+    /// [macros] 中定义的键盘宏
+    pub macros: Vec<Macro>,
+    /// 绑定到宏的按键和宏名
+    pub macro_keys: Vec<(KeyBinding, String)>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            options: BufferSettings::default(),
+            filetypes: Vec::new(),
+            cut_from_cursor: false,
+            persist_cutbuffer: false,
+            watch_config: false,
+            detect_indent: true,
+            theme: "default".to_string(),
+            bell: BellStyle::None,
+            snapshot_idle: 30,
+            journal: false,
+            local_history: 0,
+            // 不支持回收站的系统上默认关闭，否则覆盖文件时总是失败
+            trash: cfg!(unix),
+            clipboard: ClipboardMode::Auto,
+            large_file_mb: 50,
+            warn_line_length: 500,
+            warn_size_kb: 1024,
+            max_fps: DEFAULT_MAX_FPS,
+            undo_limit: 0,
+            undo_memory_kb: 0,
+            undo_group_ms: 1000,
+            bars: Bars::default(),
+            help_line_actions: DEFAULT_HELP_LINE.to_vec(),
+            todo_markers: DEFAULT_TODO_MARKERS.map(String::from).to_vec(),
+            print: PrintOptions::default(),
+            confirm_defaults: [None; CONFIRM_NAMES.len()],
+            keys: Vec::new(),
+            macros: Vec::new(),
+            macro_keys: Vec::new(),
+        }
+    }
+}
+
+/// 操作失败（找不到、到达边缘、只读等）时的提醒方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BellStyle {
+    /// 终端响铃
+    Audible,
+    /// 屏幕闪一下
+    Visual,
+    /// 只在状态栏显示消息
+    None,
+}
+
+impl BellStyle {
+    pub fn name(self) -> &'static str {
+        match self {
+            BellStyle::Audible => "audible",
+            BellStyle::Visual => "visual",
+            BellStyle::None => "none",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [BellStyle::Audible, BellStyle::Visual, BellStyle::None]
+            .into_iter()
+            .find(|style| style.name() == name)
+    }
+}
+
+/// 首次运行时可选的键位风格，写入生成的配置文件的 [keys] 表
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFlavor {
+    /// nano 的默认键位
+    Nano,
+    /// 在 nano 键位之外用 Alt+h/j/k/l 移动光标
+    Vi,
+}
+
+impl KeyFlavor {
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyFlavor::Nano => "nano",
+            KeyFlavor::Vi => "vi",
+        }
+    }
+
+    /// 相对默认键位的修改
+    fn keys(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            KeyFlavor::Nano => &[],
+            KeyFlavor::Vi => &[
+                ("M-h", "move_left"),
+                ("M-j", "move_down"),
+                ("M-k", "move_up"),
+                ("M-l", "move_right"),
+                // 被 hjkl 占用的两个操作改用 Alt+Shift
+                ("M-S-K", "toggle_cut_from_cursor"),
+                ("M-S-L", "select_line"),
+            ],
+        }
+    }
+}
+
+/// 生成带注释的默认配置文件，首次运行时写入配置目录
+pub fn default_config_text(theme: &str, flavor: KeyFlavor) -> String {
+    let defaults = BufferSettings::default();
+    let mut out = String::new();
+    out.push_str("# rsnano 配置文件，修改后按 Alt+R 重新加载\n");
+    out.push_str("# 按 Alt+E 可以导出包含全部选项和键位的生效配置\n\n");
+    out.push_str("# 主题：内置的 default、dark、light、high-contrast、deuteranopia、mono，\n# 或主题目录（themes/）中的 <名字>.toml\ntheme = ");
+    let _ = write_quoted(&mut out, theme);
+    out.push_str("\n# ^K 从光标剪切到行尾，而不是剪切整行\ncut_from_cursor = false\n");
+    out.push_str("# 退出时保存剪切历史，下次启动时读回\npersist_cutbuffer = false\n");
+    out.push_str("# 配置或主题文件变化时自动重新加载\nwatch_config = false\n");
+    out.push_str("# 打开文件时检测文件使用制表符还是几个空格缩进，并据此设置 tabsize 和 tabstospaces\ndetect_indent = true\n");
+    out.push_str(
+        "# 操作失败时的提醒：audible 响铃、visual 闪屏、none 只显示消息\nbell = \"none\"\n",
+    );
+    out.push_str("# 空闲多少秒后把修改过的缓冲区存为快照（Alt+Shift+R 恢复），0 表示关闭\nsnapshot_idle = 30\n");
+    out.push_str(
+        "# 每次保存和恢复快照、全部替换等操作记入编辑日志（Alt+Shift+J 查看）\njournal = false\n",
+    );
+    out.push_str("# 每个文件保留多少个保存前的版本（Alt+Shift+H 查看、恢复），0 表示关闭\nlocal_history = 0\n");
+    out.push_str(&format!(
+        "# 另存为、导出等覆盖已有的文件时，先把原文件移进系统回收站，可以从回收站还原\ntrash = {}\n",
+        cfg!(unix)
+    ));
+    out.push_str("# 剪切和复制同步到系统剪贴板：auto 用系统命令（SSH 中用 OSC 52）、\n# osc52 只用终端的 OSC 52、none 不同步\nclipboard = \"auto\"\n");
+    out.push_str("# 超过这个大小（MB）的文件以只读预览打开（Alt+Shift+E 完整读入），0 表示不限制\nlarge_file_mb = 50\n");
+    out.push_str("# 有超过这个字符数的行时在状态栏提醒（Alt+Shift+G 跳到最长的行），0 表示不提醒\nwarn_line_length = 500\n");
+    out.push_str("# 缓冲区超过这个大小（KB）时在状态栏提醒，0 表示不提醒\nwarn_size_kb = 1024\n");
+    out.push_str("# 没有按键时（内置终端输出、运行结果、大文件读入等后台更新）每秒最多重绘几次，\n# 调小可以省电；按键总是立即重绘\nmax_fps = 20\n");
+    out.push_str("# 每个缓冲区最多保留多少步撤销，0 表示不限制\nundo_limit = 0\n");
+    out.push_str("# 每个缓冲区的撤销历史最多占用多少内存（KB），超出时丢弃最早的几步，0 表示不限制\nundo_memory_kb = 0\n");
+    out.push_str("# 连续输入或删除时，停顿超过这么多毫秒后另起一步撤销，0 表示只要连续就合并成一步；\n# Alt+Shift+U 随时插入撤销断点\nundo_group_ms = 1000\n");
+    out.push_str("# 显示顶部标题栏、状态栏和底部帮助栏（Alt+Shift+T/S/X 临时开关），\n");
+    out.push_str("# 状态栏关闭后有提示或消息时临时显示\ntitle_bar = true\nstatus_bar = true\nhelp_line = true\n");
+    out.push_str(
+        "# 帮助栏中显示的操作（操作名同 [keys]），按键随键位自动更新，放不下时按 Alt+X 翻页\n",
+    );
+    write_action_list(&mut out, "help_line_actions", DEFAULT_HELP_LINE);
+    out.push_str("# 任务列表（F9）在打开的缓冲区中查找的标记词，区分大小写，只匹配完整的词\n");
+    write_string_list(&mut out, "todo_markers", &DEFAULT_TODO_MARKERS);
+    out.push_str("# 打印（Alt+Shift+P）时每页顶部印文件名和页码、每行前面加行号\n");
+    out.push_str("print_header = true\nprint_line_numbers = false\n");
+    out.push_str("\n# 新缓冲区的默认设置，名字与 Alt+O（:set）相同\n[options]\n");
+    for name in OPTION_NAMES {
+        if let Some(value) = defaults.get(name) {
+            out.push_str("# ");
+            write_option(&mut out, name, &value);
+        }
+    }
+    out.push_str("\n# 只对某种文件类型生效的设置，例如限制 Markdown 的行长：\n");
+    out.push_str("# maxlinelength 为行长上限（0 表示不限制），linelimit 为超出时的处理：\n");
+    out.push_str("# warn 标出超出的部分、wrap 输入时自动换行、block 拒绝输入\n");
+    out.push_str("# [filetype.markdown]\n# maxlinelength = 72\n# linelimit = \"wrap\"\n");
+    out.push_str(
+        "# runcommand 为 F5 运行的命令，%f 换成文件名，留空时 Rust 用 cargo build、C 用 make：\n",
+    );
+    out.push_str("# [filetype.python]\n# runcommand = \"python3 %f\"\n");
+    out.push_str("\n# 是/否提示直接按回车时的回答：yes、no，或 ask 表示必须按 Y/N\n");
+    out.push_str(
+        "# save_on_exit 为退出前是否保存，make_executable 为新脚本是否设为可执行\n[confirm]\n",
+    );
+    for name in CONFIRM_NAMES {
+        let _ = writeln!(out, "{} = \"ask\"", name);
+    }
+    let _ = writeln!(
+        out,
+        "\n# 键位（{} 风格）：\"按键\" = \"操作名\"，操作名为 none 时取消绑定\n[keys]",
+        flavor.name()
+    );
+    for (key, action) in flavor.keys() {
+        let _ = writeln!(out, "\"{}\" = \"{}\"", key, action);
+    }
+    out
+}
+
+/// 带行号的配置错误
+fn error(line: usize, message: String) -> EditorError {
+    EditorError::Config(format!("第 {} 行: {}", line, message))
+}
+
+fn expect_bool(value: &Value, key: &str, line: usize) -> Result<bool> {
+    value
+        .as_bool()
+        .ok_or_else(|| error(line, format!("{} 必须是 true 或 false", key)))
+}
+
+fn expect_count(value: &Value, key: &str, line: usize) -> Result<u64> {
+    value
+        .as_integer()
+        .and_then(|n| u64::try_from(n).ok())
+        .ok_or_else(|| error(line, format!("{} 必须是非负整数", key)))
+}
+
+/// 操作名组成的数组
+fn expect_action_list(value: &Value, key: &str, line: usize) -> Result<Vec<Action>> {
+    let Value::Array(items) = value else {
+        return Err(error(line, format!("{} 必须是操作名的数组", key)));
+    };
+    items
+        .iter()
+        .map(|item| {
+            let name = item
+                .as_str()
+                .ok_or_else(|| error(line, format!("{} 必须是操作名的数组", key)))?;
+            Action::from_name(name).ok_or_else(|| error(line, format!("未知操作 {}", name)))
+        })
+        .collect()
+}
+
+fn expect_string_list(value: &Value, key: &str, line: usize) -> Result<Vec<String>> {
+    let Value::Array(items) = value else {
+        return Err(error(line, format!("{} 必须是字符串数组", key)));
+    };
+    items
+        .iter()
+        .map(|item| match item.as_str() {
+            Some(s) if !s.is_empty() => Ok(s.to_string()),
+            _ => Err(error(line, format!("{} 必须是非空字符串的数组", key))),
+        })
+        .collect()
+}
+
+/// 输出一行字符串数组
+fn write_string_list<S: AsRef<str>>(out: &mut String, key: &str, items: &[S]) {
+    let _ = write!(out, "{} = [", key);
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        let _ = write_quoted(out, item.as_ref());
+    }
+    out.push_str("]\n");
+}
+
+/// 输出一行操作名数组
+fn write_action_list(out: &mut String, key: &str, actions: &[Action]) {
+    let _ = write!(out, "{} = [", key);
+    for (i, name) in actions.iter().filter_map(|a| a.name()).enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        let _ = write_quoted(out, name);
+    }
+    out.push_str("]\n");
+}
+
+/// 把配置中的一个选项应用到设置上，返回对应的 `:set` 表达式
+pub(crate) fn apply_option(
+    settings: &mut BufferSettings,
+    key: &str,
+    value: &Value,
+    line: usize,
+) -> Result<String> {
+    let value = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let expr = format!("{}={}", key, value);
+    // 与 :set 共用同一套选项名和取值检查
+    settings.apply(&expr).map_err(|e| match e {
+        EditorError::Config(message) => error(line, message),
+        other => other,
+    })?;
+    Ok(expr)
+}
+
+/// 输出一行选项，不是数字或布尔值的值加引号
+fn write_option(out: &mut String, name: &str, value: &str) {
+    let _ = write!(out, "{} = ", name);
+    if value.parse::<i64>().is_ok() || value.parse::<bool>().is_ok() {
+        out.push_str(value);
+    } else {
+        let _ = write_quoted(out, value);
+    }
+    out.push('\n');
+}
+
+impl Config {
+    /// 解析配置文本
+    pub fn parse(text: &str) -> Result<Self> {
+        let document = toml::parse(text)?;
+        let mut config = Config::default();
+        for (key, value, line) in &document.root().entries {
+            let line = *line;
+            match key.as_str() {
+                "theme" => {
+                    config.theme = value
+                        .as_str()
+                        .ok_or_else(|| error(line, "theme 必须是字符串".to_string()))?
+                        .to_string();
+                }
+                "cut_from_cursor" => config.cut_from_cursor = expect_bool(value, key, line)?,
+                "persist_cutbuffer" => config.persist_cutbuffer = expect_bool(value, key, line)?,
+                "watch_config" => config.watch_config = expect_bool(value, key, line)?,
+                "detect_indent" => config.detect_indent = expect_bool(value, key, line)?,
+                "journal" => config.journal = expect_bool(value, key, line)?,
+                "snapshot_idle" => {
+                    config.snapshot_idle = value
+                        .as_integer()
+                        .and_then(|n| u64::try_from(n).ok())
+                        .ok_or_else(|| {
+                        error(line, "snapshot_idle 必须是非负整数（秒）".to_string())
+                    })?;
+                }
+                "trash" => config.trash = expect_bool(value, key, line)?,
+                "local_history" => {
+                    config.local_history = expect_count(value, key, line)? as usize;
+                }
+                "large_file_mb" => config.large_file_mb = expect_count(value, key, line)?,
+                "warn_line_length" => {
+                    config.warn_line_length = expect_count(value, key, line)? as usize;
+                }
+                "warn_size_kb" => config.warn_size_kb = expect_count(value, key, line)?,
+                "max_fps" => {
+                    config.max_fps = value
+                        .as_integer()
+                        .and_then(|n| u32::try_from(n).ok())
+                        .filter(|n| (1..=MAX_FPS_LIMIT).contains(n))
+                        .ok_or_else(|| {
+                            error(
+                                line,
+                                format!("max_fps 必须是 1 到 {} 的整数", MAX_FPS_LIMIT),
+                            )
+                        })?;
+                }
+                "undo_limit" => config.undo_limit = expect_count(value, key, line)? as usize,
+                "undo_memory_kb" => {
+                    config.undo_memory_kb = expect_count(value, key, line)? as usize;
+                }
+                "undo_group_ms" => config.undo_group_ms = expect_count(value, key, line)?,
+                "title_bar" => config.bars.title = expect_bool(value, key, line)?,
+                "status_bar" => config.bars.status = expect_bool(value, key, line)?,
+                "help_line" => config.bars.help = expect_bool(value, key, line)?,
+                "help_line_actions" => {
+                    config.help_line_actions = expect_action_list(value, key, line)?;
+                }
+                "todo_markers" => config.todo_markers = expect_string_list(value, key, line)?,
+                "print_header" => config.print.header = expect_bool(value, key, line)?,
+                "print_line_numbers" => {
+                    config.print.line_numbers = expect_bool(value, key, line)?;
+                }
+                "clipboard" => {
+                    config.clipboard = value
+                        .as_str()
+                        .and_then(ClipboardMode::from_name)
+                        .ok_or_else(|| {
+                            error(
+                                line,
+                                "clipboard 必须是 \"auto\"、\"osc52\" 或 \"none\"".to_string(),
+                            )
+                        })?;
+                }
+                "bell" => {
+                    config.bell =
+                        value
+                            .as_str()
+                            .and_then(BellStyle::from_name)
+                            .ok_or_else(|| {
+                                error(
+                                    line,
+                                    "bell 必须是 \"audible\"、\"visual\" 或 \"none\"".to_string(),
+                                )
+                            })?;
+                }
+                _ => return Err(error(line, format!("未知配置项 {}", key))),
+            }
+        }
+        for table in &document.tables[1..] {
+            match table.name.as_str() {
+                "options" => {
+                    for (key, value, line) in &table.entries {
+                        apply_option(&mut config.options, key, value, *line)?;
+                    }
+                }
+                name if name.starts_with("filetype.") => {
+                    let filetype = FileType::from_key(&name["filetype.".len()..])
+                        .ok_or_else(|| EditorError::Config(format!("未知的文件类型 [{}]", name)))?;
+                    // 先在一份默认设置上检查，生效时再按顺序叠加到该类型的缓冲区
+                    let mut check = BufferSettings::default();
+                    let mut exprs = Vec::new();
+                    for (key, value, line) in &table.entries {
+                        exprs.push(apply_option(&mut check, key, value, *line)?);
+                    }
+                    config.filetypes.push((filetype, exprs));
+                }
+                "confirm" => {
+                    for (key, value, line) in &table.entries {
+                        let index = CONFIRM_NAMES
+                            .iter()
+                            .position(|n| n == key)
+                            .ok_or_else(|| error(*line, format!("未知的确认提示 {}", key)))?;
+                        config.confirm_defaults[index] = match value.as_str() {
+                            Some("yes") => Some(true),
+                            Some("no") => Some(false),
+                            Some("ask") => None,
+                            _ => {
+                                return Err(error(
+                                    *line,
+                                    format!("{} 必须是 \"yes\"、\"no\" 或 \"ask\"", key),
+                                ))
+                            }
+                        };
+                    }
+                }
+                "keys" => {
+                    for (key, value, line) in &table.entries {
+                        let binding = KeyBinding::parse(key)
+                            .ok_or_else(|| error(*line, format!("无法识别的按键 {}", key)))?;
+                        let name = value
+                            .as_str()
+                            .ok_or_else(|| error(*line, "操作名必须是字符串".to_string()))?;
+                        if let Some(macro_name) = name.strip_prefix("macro:") {
+                            if macro_name.is_empty() {
+                                return Err(error(*line, "macro: 之后缺少宏名".to_string()));
+                            }
+                            config.macro_keys.push((binding, macro_name.to_string()));
+                            continue;
+                        }
+                        let action = match name {
+                            "none" => None,
+                            _ => Some(
+                                Action::from_name(name)
+                                    .ok_or_else(|| error(*line, format!("未知操作 {}", name)))?,
+                            ),
+                        };
+                        config.keys.push((binding, action));
+                    }
+                }
+                "macros" => config.macros = macros::parse_entries(&table.entries)?,
+                other => return Err(EditorError::Config(format!("未知的配置表 [{}]", other))),
+            }
+        }
+        Ok(config)
+    }
+
+    /// 读取配置文件，文件不存在时返回默认配置
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| match e {
+                EditorError::Config(message) => {
+                    EditorError::Config(format!("{}: {}", path.display(), message))
+                }
+                other => other,
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(EditorError::io(path, e)),
+        }
+    }
+
+    /// 默认键位加上配置中的修改
+    pub fn keymap(&self) -> Keymap {
+        let mut keymap = Keymap::default();
+        for (binding, action) in &self.keys {
+            match action {
+                Some(action) => keymap.bind(binding.code, binding.modifiers, *action),
+                None => keymap.unbind(binding),
+            }
+        }
+        for (binding, name) in &self.macro_keys {
+            keymap.bind_macro(*binding, name);
+        }
+        keymap
+    }
+
+    /// 叠加命令行参数：命令行打开的开关优先于配置文件
+    pub fn with_args(mut self, args: &Args) -> Self {
+        let options = &mut self.options;
+        options.line_numbers |= args.line_numbers;
+        if let Some(width) = args.tabsize {
+            options.tab_width = width.into();
+        }
+        options.expand_tab |= args.tabstospaces;
+        options.virtual_space |= args.virtual_space;
+        options.auto_close |= args.autoclose;
+        options.prose |= args.prose;
+        options.read_only |= args.pager;
+        self.cut_from_cursor |= args.cut_from_cursor;
+        self.persist_cutbuffer |= args.persist_cutbuffer;
+        self.bars.help &= !args.no_help;
+        self.bars.title &= !args.no_title;
+        self
+    }
+
+    /// 生成完整的配置文本，keymap 为实际生效的键位表
+    ///
+    /// 输出包含所有选项和所有按键绑定，可以直接作为配置文件使用。
+    pub fn to_toml(&self, keymap: &Keymap) -> String {
+        let mut out = String::new();
+        out.push_str("# rsnano 生效配置：默认值 + 用户配置 + 命令行参数\n");
+        out.push_str("theme = ");
+        let _ = write_quoted(&mut out, &self.theme);
+        let _ = writeln!(out, "\ncut_from_cursor = {}", self.cut_from_cursor);
+        let _ = writeln!(out, "persist_cutbuffer = {}", self.persist_cutbuffer);
+        let _ = writeln!(out, "watch_config = {}", self.watch_config);
+        let _ = writeln!(out, "detect_indent = {}", self.detect_indent);
+        let _ = writeln!(out, "bell = \"{}\"", self.bell.name());
+        let _ = writeln!(out, "snapshot_idle = {}", self.snapshot_idle);
+        let _ = writeln!(out, "journal = {}", self.journal);
+        let _ = writeln!(out, "local_history = {}", self.local_history);
+        let _ = writeln!(out, "trash = {}", self.trash);
+        let _ = writeln!(out, "clipboard = \"{}\"", self.clipboard.name());
+        let _ = writeln!(out, "large_file_mb = {}", self.large_file_mb);
+        let _ = writeln!(out, "warn_line_length = {}", self.warn_line_length);
+        let _ = writeln!(out, "warn_size_kb = {}", self.warn_size_kb);
+        let _ = writeln!(out, "max_fps = {}", self.max_fps);
+        let _ = writeln!(out, "undo_limit = {}", self.undo_limit);
+        let _ = writeln!(out, "undo_memory_kb = {}", self.undo_memory_kb);
+        let _ = writeln!(out, "undo_group_ms = {}", self.undo_group_ms);
+        let _ = writeln!(out, "title_bar = {}", self.bars.title);
+        let _ = writeln!(out, "status_bar = {}", self.bars.status);
+        let _ = writeln!(out, "help_line = {}", self.bars.help);
+        write_action_list(&mut out, "help_line_actions", &self.help_line_actions);
+        write_string_list(&mut out, "todo_markers", &self.todo_markers);
+        let _ = writeln!(out, "print_header = {}", self.print.header);
+        let _ = writeln!(out, "print_line_numbers = {}", self.print.line_numbers);
+        out.push_str("\n[options]\n");
+        for name in OPTION_NAMES {
+            if let Some(value) = self.options.get(name) {
+                write_option(&mut out, name, &value);
+            }
+        }
+        for (filetype, exprs) in &self.filetypes {
+            let _ = writeln!(out, "\n[filetype.{}]", filetype.key());
+            for (name, value) in exprs.iter().filter_map(|e| e.split_once('=')) {
+                write_option(&mut out, name, value);
+            }
+        }
+        out.push_str("\n[confirm]\n");
+        for (name, value) in CONFIRM_NAMES.iter().zip(self.confirm_defaults) {
+            let _ = writeln!(out, "{} = \"{}\"", name, confirm_value_name(value));
+        }
+        if !self.macros.is_empty() {
+            out.push_str("\n[macros]\n");
+            for item in &self.macros {
+                macros::write_entry(&mut out, item);
+            }
+        }
+        out.push_str("\n[keys]\n");
+        for (binding, action) in keymap.sorted_bindings() {
+            if let Some(name) = action.name() {
+                let _ = write_quoted(&mut out, &binding.describe());
+                let _ = writeln!(out, " = \"{}\"", name);
+            }
+        }
+        for (binding, name) in keymap.sorted_macro_bindings() {
+            let _ = write_quoted(&mut out, &binding.describe());
+            out.push_str(" = ");
+            let _ = write_quoted(&mut out, &format!("macro:{}", name));
+            out.push('\n');
+        }
+        // 取消了的默认绑定也写出来，读回时才不会恢复
+        for binding in keymap.removed_defaults() {
+            let _ = write_quoted(&mut out, &binding.describe());
+            out.push_str(" = \"none\"\n");
+        }
+        out
+    }
+}
+
+/// 把生效配置写到文件，路径为 `-` 时写到标准输出（`--dump-config`）
+pub fn dump_effective_config(args: &Args, path: &Path) -> Result<()> {
+    let config = match paths::config_file() {
+        Some(config_path) => Config::load(&config_path)?,
+        None => Config::default(),
+    }
+    .with_args(args);
+    let text = config.to_toml(&config.keymap());
+    if path == Path::new("-") {
+        print!("{}", text);
+        Ok(())
+    } else {
+        fs::write(path, text).map_err(|e| EditorError::io(path, e))
+    }
+}
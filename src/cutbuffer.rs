@@ -0,0 +1,119 @@
+// 剪切缓冲区
+
+use crate::{paths, EditorError, Result};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::{fs, io};
+
+/// 默认保留的剪切历史条数
+pub const DEFAULT_HISTORY_LEN: usize = 16;
+
+/// ^K 剪切、^U 粘贴使用的缓冲区，所有打开的缓冲区共用一份
+///
+/// 保留最近若干次剪切的内容，最新的在最前面，误剪切一次不会丢掉之前的内容。
+#[derive(Debug, Clone)]
+pub struct CutBuffer {
+    entries: VecDeque<String>,
+    capacity: usize,
+}
+
+impl Default for CutBuffer {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_HISTORY_LEN)
+    }
+}
+
+impl CutBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// 新剪切的内容作为最新一条，超出容量时丢弃最旧的
+    pub fn set(&mut self, text: String) {
+        self.entries.push_front(text);
+        self.entries.truncate(self.capacity);
+    }
+
+    /// 连续剪切时把内容追加到最新一条，一次粘贴就能全部贴回
+    pub fn append(&mut self, text: &str) {
+        match self.entries.front_mut() {
+            Some(entry) => entry.push_str(text),
+            None => self.set(text.to_string()),
+        }
+    }
+
+    /// 最新一条
+    pub fn text(&self) -> &str {
+        self.get(0).unwrap_or("")
+    }
+
+    /// 第 index 条，0 是最新的
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(String::is_empty)
+    }
+
+    /// 各条内容占用的堆内存（字节）
+    pub fn heap_size(&self) -> usize {
+        self.entries.capacity() * std::mem::size_of::<String>()
+            + self.entries.iter().map(String::capacity).sum::<usize>()
+    }
+
+    /// 从新到旧遍历历史
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(String::as_str)
+    }
+}
+
+impl CutBuffer {
+    /// 从文件读取剪切历史，文件不存在时返回空历史
+    ///
+    /// 每条记录是一行字节长度，后面跟内容和一个换行符。
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = match fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(EditorError::io(path, e)),
+        };
+        let corrupt = || EditorError::Encoding {
+            path: Some(path.to_path_buf()),
+            message: "剪切历史文件已损坏".to_string(),
+        };
+        let mut cutbuffer = Self::new();
+        let mut rest = data.as_str();
+        while !rest.is_empty() {
+            let (len, tail) = rest.split_once('\n').ok_or_else(corrupt)?;
+            let len: usize = len.parse().map_err(|_| corrupt())?;
+            let entry = tail.get(..len).ok_or_else(corrupt)?;
+            cutbuffer.entries.push_back(entry.to_string());
+            rest = tail[len..].strip_prefix('\n').ok_or_else(corrupt)?;
+        }
+        cutbuffer.entries.truncate(cutbuffer.capacity);
+        Ok(cutbuffer)
+    }
+
+    /// 把剪切历史写入文件，目录不存在时自动创建
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut data = String::new();
+        for entry in &self.entries {
+            let _ = writeln!(data, "{}\n{}", entry.len(), entry);
+        }
+        paths::ensure_parent(path).map_err(|e| EditorError::io(path, e))?;
+        fs::write(path, data).map_err(|e| EditorError::io(path, e))
+    }
+}
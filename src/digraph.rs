@@ -0,0 +1,107 @@
+// 二合字母：用两个字符的代码输入重音字母和符号（RFC 1345 风格，例如 a: → ä）
+//
+// 带重音的拉丁字母由字母加表示重音的符号组成，其余符号单独列表。
+// 两个字符的顺序可以互换，例如 :a 与 a: 相同。
+
+/// 重音符号，以及可以加该重音的字母和对应的结果
+const ACCENTS: [(char, &str, &str); 9] = [
+    ('!', "AEIOUaeiou", "ÀÈÌÒÙàèìòù"),
+    ('\'', "AEIOUYCNSZaeiouycnsz", "ÁÉÍÓÚÝĆŃŚŹáéíóúýćńśź"),
+    ('>', "AEIOUaeiou", "ÂÊÎÔÛâêîôû"),
+    ('?', "ANOano", "ÃÑÕãñõ"),
+    (':', "AEIOUaeiouy", "ÄËÏÖÜäëïöüÿ"),
+    ('0', "AUau", "ÅŮåů"),
+    (',', "CSTcst", "ÇŞŢçşţ"),
+    ('<', "CDENRSTZcdenrstz", "ČĎĚŇŘŠŤŽčďěňřšťž"),
+    ('/', "OLol", "ØŁøł"),
+];
+
+/// 其余符号
+const SYMBOLS: &[(&str, char)] = &[
+    ("ss", 'ß'),
+    ("ae", 'æ'),
+    ("AE", 'Æ'),
+    ("oe", 'œ'),
+    ("OE", 'Œ'),
+    ("aa", 'å'),
+    ("AA", 'Å'),
+    ("Eu", '€'),
+    ("Pd", '£'),
+    ("Ye", '¥'),
+    ("Ct", '¢'),
+    ("Co", '©'),
+    ("Rg", '®'),
+    ("TM", '™'),
+    ("SE", '§'),
+    ("PI", '¶'),
+    ("DG", '°'),
+    ("My", 'µ'),
+    ("+-", '±'),
+    ("*X", '×'),
+    ("-:", '÷'),
+    ("!=", '≠'),
+    ("=<", '≤'),
+    (">=", '≥'),
+    ("?2", '≈'),
+    ("00", '∞'),
+    ("RT", '√'),
+    ("NO", '¬'),
+    ("12", '½'),
+    ("14", '¼'),
+    ("34", '¾'),
+    ("1S", '¹'),
+    ("2S", '²'),
+    ("3S", '³'),
+    ("<<", '«'),
+    (">>", '»'),
+    ("!I", '¡'),
+    ("?I", '¿'),
+    (".M", '·'),
+    (",.", '…'),
+    ("-N", '–'),
+    ("-M", '—'),
+    ("'6", '‘'),
+    ("'9", '’'),
+    ("\"6", '“'),
+    ("\"9", '”'),
+    ("->", '→'),
+    ("<-", '←'),
+    ("-!", '↑'),
+    ("-v", '↓'),
+    ("=>", '⇒'),
+    ("OK", '✓'),
+    ("XX", '✗'),
+    ("a*", 'α'),
+    ("b*", 'β'),
+    ("g*", 'γ'),
+    ("d*", 'δ'),
+    ("e*", 'ε'),
+    ("l*", 'λ'),
+    ("m*", 'μ'),
+    ("p*", 'π'),
+    ("s*", 'σ'),
+    ("w*", 'ω'),
+    ("D*", 'Δ'),
+    ("S*", 'Σ'),
+    ("W*", 'Ω'),
+];
+
+fn lookup_ordered(first: char, second: char) -> Option<char> {
+    let code: String = [first, second].iter().collect();
+    if let Some(&(_, ch)) = SYMBOLS.iter().find(|(c, _)| *c == code) {
+        return Some(ch);
+    }
+    let (_, bases, results) = ACCENTS.iter().find(|(mark, _, _)| *mark == second)?;
+    let index = bases.chars().position(|base| base == first)?;
+    results.chars().nth(index)
+}
+
+/// 查找两个字符的代码对应的字符，顺序可以互换
+pub fn lookup(code: &str) -> Option<char> {
+    let mut chars = code.chars();
+    let (first, second) = (chars.next()?, chars.next()?);
+    if chars.next().is_some() {
+        return None;
+    }
+    lookup_ordered(first, second).or_else(|| lookup_ordered(second, first))
+}
@@ -1,5 +1,5 @@
 /// 定义光标移动的方向
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     Up,
     Down,
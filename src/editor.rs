@@ -1,150 +1,540 @@
+mod bookmarks;
+mod buffers;
+mod clipboard;
+mod commands;
+mod configure;
+mod export;
+mod help;
+mod help_line;
 mod input;
+mod inspect;
+mod journal;
+mod keylog;
+mod large_file;
+mod locations;
+mod macros;
+mod memory;
+mod modal;
+mod outline;
+mod pager;
+mod panel;
+mod print;
+mod project;
 mod prompt;
+mod recovery;
+mod runner;
+mod search;
+mod setup;
+mod shell;
+mod snapshots;
 mod status;
+mod tasks;
+mod terminal;
+mod trash;
 mod ui;
+mod undo;
 
+pub use clipboard::LastPaste;
+pub use configure::ConfigStamp;
+pub use panel::{Panel, PanelKind};
+pub use prompt::{Prompt, PromptOption, PromptOutcome, PromptPurpose};
+pub use search::{IncrementalSearch, ReplaceSession};
+pub use setup::{FirstRunSetup, SetupStep};
+pub use snapshots::VersionSource;
+pub use terminal::EmbeddedTerminal;
+
+use crate::action::{Action, DEFAULT_HELP_LINE};
 use crate::args::Args;
+use crate::boxdraw::BoxStyle;
 use crate::buffer::TextBuffer;
+use crate::config::{
+    BellStyle, ConfirmDefaults, CONFIRM_NAMES, DEFAULT_MAX_FPS, DEFAULT_TODO_MARKERS,
+};
+use crate::cutbuffer::CutBuffer;
+use crate::keylog::{Recorder, Replay};
+use crate::keymap::Keymap;
+use crate::layout::{Bars, Layout};
+use crate::location::{Location, LocationList};
+use crate::macros::{Macro, MacroStep};
+use crate::print::PrintOptions;
+use crate::project::ProjectConfig;
+use crate::paths;
+use crate::render::FrameBuffers;
+use crate::settings::{BufferSettings, FiletypeOptions};
+use crate::system_clipboard::ClipboardMode;
+use crate::theme::Theme;
 // use crate::direction::Direction; // 未使用，可去掉
 use crate::version::AppInfo;
-use crate::Result;
+use crate::{EditorError, Result};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// 两次重绘之间最多连续处理的事件数，防止持续输入时界面完全不刷新
+const MAX_COALESCED_EVENTS: usize = 1024;
 
 pub struct Editor {
+    /// 当前编辑的缓冲区
     pub buffer: TextBuffer,
+    /// 新打开的缓冲区使用的默认设置，:set 只修改当前缓冲区，不影响这里
+    pub defaults: BufferSettings,
+    /// 按文件类型覆盖的默认设置
+    pub filetype_options: FiletypeOptions,
+    /// 打开文件时按检测到的缩进方式覆盖 tabsize 和 tabstospaces
+    pub detect_indent: bool,
+    /// 其他打开的缓冲区，按当前缓冲区之后的顺序排列
+    pub other_buffers: Vec<TextBuffer>,
+    /// 当前缓冲区是第几个（从 0 开始），只用于显示
+    pub buffer_index: usize,
     pub terminal_size: (u16, u16),
-    pub show_line_numbers: bool,
+    /// 配置中要显示的标题栏、状态栏和帮助栏
+    pub bars: Bars,
     pub should_quit: bool,
     pub status_message: String,
-    pub file_save_prompt: Option<String>,
-    pub file_save_input: String,
-    pub exit_confirm_prompt: bool,
+    /// 当前正在显示的提示（保存、退出确认等）
+    pub prompt: Option<Prompt>,
     pub app_info: AppInfo,
+    /// 是否显示帮助框
     pub show_help_page: bool,
-    pub help_page_drawn: bool,
+    /// 帮助框滚动到的行
+    pub help_scroll: usize,
+    /// 帮助栏中显示的操作
+    pub help_line_actions: Vec<Action>,
+    /// 帮助栏当前显示第几页
+    pub help_line_page: usize,
+    /// 任务列表查找的标记词
+    pub todo_markers: Vec<String>,
+    /// 打印选项
+    pub print_options: PrintOptions,
+    /// 是/否提示直接按回车时的回答
+    pub confirm_defaults: ConfirmDefaults,
+    /// 每帧复用的渲染缓冲区
+    pub frame: FrameBuffers,
+    /// 按键到操作的映射
+    pub keymap: Keymap,
+    /// 画框模式，开启时方向键画框线
+    pub box_drawing: Option<BoxStyle>,
+    /// 分屏面板（预览等）
+    pub panel: Option<Panel>,
+    /// 内置终端，面板隐藏后 shell 仍在运行
+    pub terminal: Option<EmbeddedTerminal>,
+    /// 只读查看模式，空格翻页、q 退出等按键像 less 一样
+    pub pager: bool,
+    /// 剪切缓冲区
+    pub cutbuffer: CutBuffer,
+    /// ^K 从光标剪切到行尾而不是剪切整行（nano 的 --cutfromcursor）
+    pub cut_from_cursor: bool,
+    /// 上一次执行的操作，用于判断是否连续剪切
+    pub last_action: Option<Action>,
+    /// 最近一次粘贴，供 Alt+Y 切换剪切历史
+    pub last_paste: Option<LastPaste>,
+    /// 当前选区是否由 Shift+方向键选出，不按 Shift 移动光标时取消
+    pub shift_selecting: bool,
+    /// 退出时把剪切历史保存到这个文件，下次启动时读回
+    pub cutbuffer_path: Option<PathBuf>,
+    /// 命令行参数，重新加载配置时仍然优先于配置文件
+    pub args: Args,
+    /// 配置文件路径
+    pub config_path: Option<PathBuf>,
+    /// 界面配色
+    pub theme: Theme,
+    /// 配置或主题文件变化时自动重新加载
+    pub watch_config: bool,
+    /// 上次加载时配置与主题文件的修改时间
+    pub config_stamp: ConfigStamp,
+    /// 上次检查配置文件是否变化的时间
+    pub last_config_check: Instant,
+    /// 进行中的首次运行设置
+    pub setup: Option<FirstRunSetup>,
+    /// 操作失败时的提醒方式
+    pub bell: BellStyle,
+    /// 下一次重绘时需要响铃或闪屏
+    pub bell_pending: bool,
+    /// 闪屏结束的时间
+    pub flash_until: Option<Instant>,
+    /// 空闲多久后自动快照，为零时关闭
+    pub snapshot_idle: Duration,
+    /// 保存等操作记入编辑日志
+    pub journal: bool,
+    /// 每个文件保留多少个保存前的版本，0 表示不保留
+    pub local_history: usize,
+    /// 覆盖已有的文件前先把原文件移进回收站
+    pub trash: bool,
+    /// 剪切和复制如何同步到系统剪贴板
+    pub clipboard: ClipboardMode,
+    /// 已经生效的项目配置（.rsnano.toml）
+    pub projects: Vec<ProjectConfig>,
+    /// 这次运行中选择不使用的项目配置
+    pub ignored_projects: Vec<PathBuf>,
+    /// 正在询问是否信任的项目配置
+    pub pending_project: Option<ProjectConfig>,
+    /// 超过这个大小（字节）的文件以只读预览打开，0 表示不限制
+    pub large_file_threshold: u64,
+    /// 有超过这个字符数的行时在状态栏提醒，0 表示不提醒
+    pub warn_line_length: usize,
+    /// 缓冲区超过这个大小（字节）时在状态栏提醒，0 表示不提醒
+    pub warn_size: u64,
+    /// 没有按键时每秒最多重绘几次
+    pub max_fps: u32,
+    /// 每个缓冲区最多保留的撤销步数，0 表示不限制
+    pub undo_limit: usize,
+    /// 每个缓冲区撤销历史最多占用的内存（字节），0 表示不限制
+    pub undo_memory: usize,
+    /// 两次输入间隔超过这么久时另起一步撤销，为零时只要连续就合并
+    pub undo_group: Duration,
+    /// 最近一次修改缓冲区的时间
+    pub last_edit: Instant,
+    /// 上一帧绘制完的时间
+    pub last_frame: Instant,
+    /// 最近一次按键的时间
+    pub last_input: Instant,
+    /// 本段空闲已经拍过快照
+    pub idle_snapshot_done: bool,
+    /// 本进程加了锁的文件，退出时释放
+    pub locked_files: Vec<PathBuf>,
+    /// `--record` 的按键记录
+    pub recorder: Option<Recorder>,
+    /// `--replay` 正在回放的记录
+    pub replay: Option<Replay>,
+    /// 上次搜索的内容
+    pub last_search: Option<String>,
+    /// 搜索提示打开期间的增量搜索状态
+    pub incremental_search: Option<IncrementalSearch>,
+    /// 替换时把查找内容当作正则表达式
+    pub replace_regex: bool,
+    /// 逐处确认的替换进行中的状态
+    pub replace_session: Option<ReplaceSession>,
+    /// 上次替换成的内容
+    pub last_replacement: Option<String>,
+    /// 当前的位置列表，F8 / Shift+F8 在其中跳转
+    pub locations: Option<LocationList>,
+    /// 上一次运行（F5）输出中的位置，在对应文件的行尾显示说明
+    pub run_locations: Vec<Location>,
+    /// 正在录制的键盘宏
+    pub recording: Option<Vec<MacroStep>>,
+    /// 最近录制的宏，Alt+; 运行
+    pub last_macro: Vec<MacroStep>,
+    /// 配置 [macros] 中定义的宏
+    pub macros: Vec<Macro>,
+    /// 命名保存在状态目录中的宏
+    pub saved_macros: Vec<Macro>,
+    /// 正在回放宏，回放期间不再运行别的宏
+    pub playing_macro: bool,
 }
 
 impl Editor {
     pub fn new(args: Args, app_info: AppInfo) -> Result<Self> {
-        let buffer = if let Some(file) = &args.file {
-            TextBuffer::from_file(file)?
-        } else {
-            TextBuffer::new()
-        };
-        let terminal_size = crossterm::terminal::size()?;
-        Ok(Self {
-            buffer,
+        Self::with_terminal_size(args, app_info, ui::terminal_size()?)
+    }
+
+    /// 按给定的终端尺寸创建编辑器，不查询终端；用于没有终端的基准测试，配合 `render_to`
+    pub fn with_terminal_size(
+        args: Args,
+        app_info: AppInfo,
+        terminal_size: (u16, u16),
+    ) -> Result<Self> {
+        let mut editor = Self {
+            buffer: TextBuffer::new(),
+            defaults: BufferSettings::default(),
+            filetype_options: Vec::new(),
+            detect_indent: true,
+            other_buffers: Vec::new(),
+            buffer_index: 0,
             terminal_size,
-            show_line_numbers: args.line_numbers,
+            bars: Bars::default(),
             should_quit: false,
             status_message: String::new(),
-            file_save_prompt: None,
-            file_save_input: String::new(),
-            exit_confirm_prompt: false,
+            prompt: None,
             app_info,
             show_help_page: false,
-            help_page_drawn: false,
-        })
+            help_scroll: 0,
+            help_line_actions: DEFAULT_HELP_LINE.to_vec(),
+            help_line_page: 0,
+            todo_markers: DEFAULT_TODO_MARKERS.map(String::from).to_vec(),
+            print_options: PrintOptions::default(),
+            confirm_defaults: [None; CONFIRM_NAMES.len()],
+            frame: FrameBuffers::new(),
+            keymap: Keymap::default(),
+            box_drawing: None,
+            panel: None,
+            terminal: None,
+            pager: args.pager,
+            cutbuffer: CutBuffer::new(),
+            cut_from_cursor: false,
+            last_action: None,
+            last_paste: None,
+            shift_selecting: false,
+            cutbuffer_path: None,
+            args,
+            config_path: paths::config_file(),
+            theme: Theme::default(),
+            watch_config: false,
+            config_stamp: (None, None),
+            last_config_check: Instant::now(),
+            setup: None,
+            bell: BellStyle::None,
+            bell_pending: false,
+            flash_until: None,
+            snapshot_idle: Duration::ZERO,
+            journal: false,
+            local_history: 0,
+            trash: true,
+            clipboard: ClipboardMode::Auto,
+            projects: Vec::new(),
+            ignored_projects: Vec::new(),
+            pending_project: None,
+            large_file_threshold: 0,
+            warn_line_length: 0,
+            warn_size: 0,
+            max_fps: DEFAULT_MAX_FPS,
+            undo_limit: 0,
+            undo_memory: 0,
+            undo_group: Duration::ZERO,
+            last_edit: Instant::now(),
+            last_frame: Instant::now(),
+            last_input: Instant::now(),
+            idle_snapshot_done: false,
+            locked_files: Vec::new(),
+            recorder: None,
+            replay: None,
+            last_search: None,
+            incremental_search: None,
+            replace_regex: false,
+            replace_session: None,
+            last_replacement: None,
+            locations: None,
+            run_locations: Vec::new(),
+            recording: None,
+            last_macro: Vec::new(),
+            macros: Vec::new(),
+            saved_macros: Vec::new(),
+            playing_macro: false,
+        };
+        // 配置和命令行参数决定默认设置、键位、主题，以及是否读回剪切历史
+        configure::load_initial(&mut editor);
+        // 大文件阈值来自配置，所以文件在读完配置之后才打开
+        buffers::open_command_line_files(&mut editor)?;
+        pager::start(&mut editor)?;
+        setup::start_if_needed(&mut editor);
+        recovery::check_current_buffer(&mut editor);
+        project::check(&mut editor);
+        keylog::start(&mut editor)?;
+        macros::load_saved(&mut editor);
+        if let Some(path) = &editor.cutbuffer_path {
+            // 剪切历史读不出来时不影响启动，只在状态栏提示
+            match CutBuffer::load(path) {
+                Ok(cutbuffer) => editor.cutbuffer = cutbuffer,
+                Err(e) => editor.notify_error(e.to_string()),
+            }
+        }
+        if paths::safe_mode() && editor.status_message.is_empty() {
+            editor.status_message = "安全模式：未读取用户配置、主题和历史".to_string();
+        }
+        Ok(editor)
+    }
+
+    /// 操作失败时显示错误消息，并按配置响铃或闪屏
+    pub fn notify_error(&mut self, message: impl Into<String>) {
+        self.status_message = message.into();
+        self.bell_pending = true;
+    }
+
+    /// 在光标处输入一个字符；行长上限为 block 且该行已满时拒绝输入并返回 false
+    pub fn type_char(&mut self, ch: char) -> bool {
+        if let Some(y) = self.buffer.line_limit_reached() {
+            let limit = self.buffer.settings.max_line_length;
+            self.notify_error(format!("第 {} 行已达到 {} 个字符的上限", y + 1, limit));
+            return false;
+        }
+        self.buffer.type_char_at_all_cursors(ch);
+        true
+    }
+
+    /// 执行一个操作，按键、宏和命令面板共用这一入口
+    pub fn execute(&mut self, action: Action) -> Result<()> {
+        let edits = action.modifies_buffer();
+        if edits {
+            undo::before_edit(self);
+        }
+        let result = commands::execute(self, action);
+        if edits {
+            undo::after_edit(self);
+        }
+        self.last_action = Some(action);
+        result
     }
 
     pub fn run(&mut self) -> Result<()> {
         ui::setup_terminal()?;
         let result = self.main_loop();
+        recovery::release_all(self);
         ui::restore_terminal()?;
-        result
+        result?;
+        if let Some(path) = &self.cutbuffer_path {
+            self.cutbuffer.save(path)?;
+        }
+        Ok(())
+    }
+
+    /// 本帧的屏幕布局；状态栏关闭时，有提示或消息要显示时临时显示，
+    /// 消息或提示太长时状态区扩展到多行
+    pub fn layout(&self) -> Layout {
+        let mut bars = self.bars;
+        bars.status |= self.prompt.is_some() || !self.status_message.is_empty();
+        let status_rows = status::status_rows(self, self.terminal_size.0 as usize);
+        let layout = Layout::new(self.terminal_size, bars, status_rows);
+        if self.panel.as_ref().is_some_and(|p| p.bottom) {
+            layout.with_bottom_panel()
+        } else {
+            layout
+        }
     }
 
     fn refresh_screen(&mut self) -> Result<()> {
-        ui::refresh_screen(self)
+        self.prepare_frame();
+        ui::refresh_screen(self).map_err(EditorError::terminal)
     }
 
-    fn draw_help_page(&self) -> Result<()> {
-        use crossterm::{
-            cursor, execute, style,
-            terminal::{self, ClearType},
-        };
-        use std::io::stdout;
-        let (_width, height) = self.terminal_size;
-        execute!(
-            stdout(),
-            cursor::MoveTo(0, 0),
-            terminal::Clear(ClearType::All)
-        )?;
-
-        let help_lines = [
-            "RSNano 帮助页面",
-            "",
-            "^X 退出编辑器",
-            "^O 保存文件",
-            "^C 多光标模式开/关",
-            "Alt+方向键 移动多光标",
-            "^G 打开帮助页面",
-            "",
-            "按任意键返回编辑器",
-        ];
-
-        for (i, line) in help_lines.iter().enumerate() {
-            if i < height as usize {
-                execute!(stdout(), cursor::MoveTo(0, i as u16), style::Print(line))?;
+    /// 每帧绘制前读入内置终端的输出，更新预览、面板、搜索高亮和统计
+    fn prepare_frame(&mut self) {
+        large_file::stream_more(self);
+        terminal::poll(self);
+        panel::update_panel(self);
+        self.buffer.sync_highlight();
+        self.buffer.update_stats();
+    }
+
+    /// 把一帧画到任意输出而不是终端，用于基准测试
+    pub fn render_to<W: std::io::Write>(&mut self, out: &mut W) -> Result<()> {
+        self.prepare_frame();
+        ui::draw_frame(out, self).map_err(EditorError::terminal)
+    }
+
+    /// 处理单个终端事件，不做重绘
+    fn handle_input(&mut self, input: input::Input) -> Result<()> {
+        use crossterm::event::Event;
+        match input {
+            input::Input::Event(Event::Key(key_event)) => {
+                self.last_input = Instant::now();
+                self.idle_snapshot_done = false;
+                // 状态栏关闭时消息只临时显示到下一次按键
+                if !self.bars.status && input::is_actionable(&key_event) {
+                    self.status_message.clear();
+                }
+                if input::is_actionable(&key_event) {
+                    macros::record_key(self, &key_event);
+                    input::process_key(self, key_event)?;
+                }
             }
+            input::Input::Repeat(key_event, count) => {
+                self.last_input = Instant::now();
+                self.idle_snapshot_done = false;
+                if !self.bars.status {
+                    self.status_message.clear();
+                }
+                for _ in 0..count {
+                    macros::record_key(self, &key_event);
+                }
+                input::process_repeat(self, key_event, count)?;
+            }
+            input::Input::Text(text) => {
+                self.last_input = Instant::now();
+                self.idle_snapshot_done = false;
+                macros::record_text(self, &text);
+                input::process_text(self, &text)?;
+            }
+            input::Input::Paste(text) => {
+                self.last_input = Instant::now();
+                self.idle_snapshot_done = false;
+                macros::record_text(self, &text);
+                input::process_paste(self, &text)?;
+            }
+            input::Input::Event(_) => {}
+        }
+        if let Some(y) = self.buffer.take_rejected_edit() {
+            self.notify_error(format!(
+                "第 {} 行受保护，不能修改（Alt+Shift+F 取消保护）",
+                y + 1
+            ));
+        }
+        if cfg!(debug_assertions) {
+            self.check_invariants();
         }
         Ok(())
     }
 
+    /// 调试构建中检查当前缓冲区的内部约束：有问题时在状态栏警告并修正，
+    /// 而不是等到之后绘制时才出错
+    fn check_invariants(&mut self) {
+        let problems = self.buffer.check_invariants();
+        if problems.is_empty() {
+            return;
+        }
+        self.buffer.repair_invariants();
+        self.notify_error(format!("内部状态异常（已修正）：{}", problems.join("；")));
+    }
+
+    /// 两次后台重绘之间的最短间隔
+    fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.max_fps.max(1)
+    }
+
     fn main_loop(&mut self) -> Result<()> {
-        use crossterm::event::{self, KeyCode};
+        use crossterm::event;
+        // 有输入时立即重绘；没有输入时画面只会因后台更新（内置终端、运行结果、
+        // 大文件读入等）而变化，这类重绘按 max_fps 限制频率
+        let mut input_arrived = true;
         loop {
-            // 如果正在显示帮助页面
+            // 帮助框打开时按键只用来滚动或关闭它
             if self.show_help_page {
-                // 只在第一次显示或尺寸变化时绘制帮助页面
-                if !self.help_page_drawn {
-                    self.draw_help_page()?;
-                    self.help_page_drawn = true;
-                }
-                
-                // 按任意键关闭帮助页面
-                if event::poll(std::time::Duration::from_millis(50))? {
-                    if let event::Event::Key(key_event) = event::read()? {
-                        if key_event.kind == event::KeyEventKind::Press {
-                            match key_event.code {
-                                KeyCode::Esc
-                                | KeyCode::Char(_)
-                                | KeyCode::Enter
-                                | KeyCode::Backspace => {
-                                    self.show_help_page = false;
-                                    self.help_page_drawn = false; // 重置绘制状态
-                                    self.status_message.clear();
-                                    // 清除屏幕，准备返回编辑器模式
-                                    use crossterm::{execute, terminal};
-                                    use std::io::stdout;
-                                    execute!(stdout(), terminal::Clear(terminal::ClearType::All))?;
-                                }
-                                _ => {}
-                            }
-                        }
+                self.refresh_screen()?;
+                if let Some(event::Event::Key(key_event)) =
+                    keylog::next_event(self, self.frame_interval())?
+                {
+                    if key_event.kind == event::KeyEventKind::Press {
+                        help::handle_key(self, key_event);
                     }
                 }
-                continue; // 跳过后续刷新和输入处理
+                self.terminal_size = ui::terminal_size()?;
+                continue;
             }
 
-            self.refresh_screen()?;
+            configure::check_config_changed(self);
+            snapshots::autosnapshot(self);
+            if input_arrived || self.last_frame.elapsed() >= self.frame_interval() {
+                self.refresh_screen()?;
+                self.last_frame = Instant::now();
+            }
             if self.should_quit {
                 break;
             }
-            if event::poll(std::time::Duration::from_millis(50))? {
-                if let event::Event::Key(key_event) = event::read()? {
-                    if key_event.kind == event::KeyEventKind::Press {
-                        input::process_key(self, key_event)?;
+            let wait = self
+                .frame_interval()
+                .saturating_sub(self.last_frame.elapsed());
+            input_arrived = false;
+            if let Some(first) = keylog::next_event(self, wait)? {
+                input_arrived = true;
+                // 快速输入或粘贴时事件会堆积，先全部读出再一起处理、重绘；
+                // 同一批到达的输入法提交合并成一次插入
+                let mut events = vec![first];
+                while events.len() < MAX_COALESCED_EVENTS {
+                    match keylog::next_event(self, Duration::ZERO)? {
+                        Some(event) => events.push(event),
+                        None => break,
                     }
                 }
-            }
-            let new_size = crossterm::terminal::size()?;
-            if new_size != self.terminal_size {
-                self.terminal_size = new_size;
-                // 如果正在显示帮助页且终端大小改变，需要重新绘制
-                if self.show_help_page {
-                    self.help_page_drawn = false;
+                for input in input::group_events(events) {
+                    if self.should_quit {
+                        break;
+                    }
+                    // 中途打开了帮助框时，剩下的按键交给帮助框，不丢弃
+                    if self.show_help_page {
+                        help::handle_input(self, input);
+                    } else {
+                        self.handle_input(input)?;
+                    }
                 }
             }
+            self.terminal_size = ui::terminal_size()?;
         }
         Ok(())
     }
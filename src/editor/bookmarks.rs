@@ -0,0 +1,47 @@
+// 书签：在符号栏中标出的行，可以在它们之间跳转
+
+use crate::buffer::{Position, Sign};
+use crate::editor::Editor;
+use crossterm::style::Color;
+
+/// 书签在符号栏中的产生者名
+const BOOKMARK_SOURCE: &str = "bookmark";
+
+/// 书签的优先级高于运行结果，手动标出的行总能看到
+const BOOKMARK_PRIORITY: u8 = 20;
+
+/// 在光标所在的行上设置书签，已有书签时取消
+pub fn toggle(editor: &mut Editor) {
+    let y = editor.buffer.cursor_y.min(editor.buffer.lines.len() - 1);
+    if editor.buffer.remove_sign(y, BOOKMARK_SOURCE) {
+        editor.status_message = format!("已取消第 {} 行的书签", y + 1);
+    } else {
+        editor.buffer.place_sign(Sign::new(
+            y,
+            '◆',
+            Color::Cyan,
+            BOOKMARK_PRIORITY,
+            BOOKMARK_SOURCE,
+        ));
+        editor.status_message = format!("已在第 {} 行设置书签", y + 1);
+    }
+}
+
+/// 跳到光标之后的下一个书签，到末尾后从头开始
+pub fn next(editor: &mut Editor) {
+    let lines = editor.buffer.sign_lines(BOOKMARK_SOURCE);
+    if lines.is_empty() {
+        editor.notify_error("没有书签（Alt+Shift+B 设置）");
+        return;
+    }
+    let index = lines
+        .iter()
+        .position(|&y| y > editor.buffer.cursor_y)
+        .unwrap_or(0);
+    editor.buffer.mark = None;
+    editor
+        .buffer
+        .set_cursor_position(Position::new(0, lines[index]));
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+    editor.status_message = format!("书签 {}/{}", index + 1, lines.len());
+}
@@ -0,0 +1,161 @@
+use crate::args::FileArg;
+use crate::buffer::{Position, TextBuffer};
+use crate::editor::Editor;
+use crate::Result;
+use std::mem;
+use std::path::Path;
+
+/// 打开文件到新的缓冲区并切换过去，已打开的文件直接切换
+pub fn open_file(editor: &mut Editor, path: &Path) {
+    if editor.buffer.filename.as_deref() == Some(path) {
+        editor.status_message = "文件已在当前缓冲区打开".to_string();
+        return;
+    }
+    if let Some(i) = editor
+        .other_buffers
+        .iter()
+        .position(|b| b.filename.as_deref() == Some(path))
+    {
+        // 旋转到该缓冲区，保持其余缓冲区的顺序
+        for _ in 0..=i {
+            switch_buffer(editor, true);
+        }
+        return;
+    }
+    let mut buffer = match TextBuffer::open(&path.to_path_buf(), editor.large_file_threshold) {
+        Ok(buffer) => buffer,
+        Err(e) => {
+            editor.notify_error(e.to_string());
+            return;
+        }
+    };
+    buffer.settings = super::project::settings_for(editor, &buffer);
+    super::runner::annotate(&editor.run_locations, &mut buffer);
+    // 新缓冲区排在当前缓冲区之后
+    let previous = mem::replace(&mut editor.buffer, buffer);
+    editor.other_buffers.push(previous);
+    editor.buffer_index += 1;
+    after_switch(editor);
+    super::large_file::open_notice(editor);
+    super::recovery::check_current_buffer(editor);
+    super::project::check(editor);
+}
+
+/// 打开命令行上的文件：第一个文件作为当前缓冲区，其余依次排在后面，各自移到指定的行列
+///
+/// 第一个文件打不开时启动失败；其余文件打不开时只提示错误。
+pub fn open_command_line_files(editor: &mut Editor) -> Result<()> {
+    let files = editor.args.files.clone();
+    let Some((first, rest)) = files.split_first() else {
+        return Ok(());
+    };
+    editor.buffer = open_at(editor, first)?;
+    let mut failed = None;
+    for file in rest {
+        match open_at(editor, file) {
+            Ok(buffer) => {
+                if let Some(path) = &buffer.filename {
+                    super::recovery::lock_background(editor, path);
+                }
+                editor.other_buffers.push(buffer);
+            }
+            Err(e) => failed = Some(e.to_string()),
+        }
+    }
+    if !editor.other_buffers.is_empty() {
+        after_switch(editor);
+    }
+    super::large_file::open_notice(editor);
+    if let Some(start) = editor.args.start_search.clone() {
+        super::search::search_from_start(editor, &start.pattern, start.backwards);
+    }
+    if let Some(message) = failed {
+        editor.notify_error(message);
+    }
+    Ok(())
+}
+
+/// 读入一个命令行上的文件，并把光标移到指定的行列
+fn open_at(editor: &Editor, file: &FileArg) -> Result<TextBuffer> {
+    let mut buffer = TextBuffer::open(&file.path, editor.large_file_threshold)?;
+    buffer.settings = super::project::settings_for(editor, &buffer);
+    if let Some(line) = file.line {
+        // 预览中的大文件先读到指定的行
+        while buffer.lines.len() < line && buffer.load_more_preview()? {}
+        let column = file.column.unwrap_or(1);
+        let pos = buffer.clamp_position(Position::new(column - 1, line - 1));
+        buffer.set_cursor_position(pos);
+        buffer.scroll_to_cursor(editor.layout().page_rows());
+    }
+    Ok(buffer)
+}
+
+/// 切换到下一个（forward）或上一个缓冲区
+pub fn switch_buffer(editor: &mut Editor, forward: bool) {
+    if editor.other_buffers.is_empty() {
+        editor.notify_error("只有一个缓冲区");
+        return;
+    }
+    let count = buffer_count(editor);
+    // other_buffers 按 “当前之后的第一个” 到 “当前之前的一个” 的顺序排列
+    if forward {
+        let next = editor.other_buffers.remove(0);
+        let previous = mem::replace(&mut editor.buffer, next);
+        editor.other_buffers.push(previous);
+        editor.buffer_index = (editor.buffer_index + 1) % count;
+    } else if let Some(prev) = editor.other_buffers.pop() {
+        let current = mem::replace(&mut editor.buffer, prev);
+        editor.other_buffers.insert(0, current);
+        editor.buffer_index = (editor.buffer_index + count - 1) % count;
+    }
+    after_switch(editor);
+    super::project::check(editor);
+}
+
+/// 关闭当前缓冲区，切换到上一个，关闭的是第一个时切换到新的第一个；
+/// 关闭最后一个缓冲区时退出
+pub fn close_buffer(editor: &mut Editor) {
+    // other_buffers 的第一个是当前之后的缓冲区，最后一个是当前之前的
+    let next = if editor.buffer_index == 0 {
+        (!editor.other_buffers.is_empty()).then(|| editor.other_buffers.remove(0))
+    } else {
+        editor.other_buffers.pop()
+    };
+    match next {
+        Some(next) => {
+            let closed = mem::replace(&mut editor.buffer, next);
+            editor.buffer_index = editor.buffer_index.saturating_sub(1);
+            after_switch(editor);
+            match &closed.filename {
+                Some(path) => super::recovery::release(editor, path),
+                None => super::recovery::release_unnamed(closed.id),
+            }
+            super::project::check(editor);
+        }
+        None => editor.should_quit = true,
+    }
+}
+
+/// 打开的缓冲区总数
+pub fn buffer_count(editor: &Editor) -> usize {
+    editor.other_buffers.len() + 1
+}
+
+fn after_switch(editor: &mut Editor) {
+    editor.last_paste = None;
+    if let Some(panel) = editor.panel.as_mut() {
+        panel.revision = None;
+    }
+    let name = editor
+        .buffer
+        .filename
+        .as_ref()
+        .and_then(|p| p.to_str())
+        .unwrap_or("新缓冲区");
+    editor.status_message = format!(
+        "缓冲区 {}/{}：{}",
+        editor.buffer_index + 1,
+        buffer_count(editor),
+        name
+    );
+}
@@ -0,0 +1,167 @@
+use crate::action::Action;
+use crate::buffer::Position;
+use crate::editor::{Editor, Prompt, PromptOption, PromptPurpose};
+use crate::location;
+use crate::system_clipboard;
+use std::env;
+
+/// 剪切历史列表里每条预览的最大字符数
+const PREVIEW_CHARS: usize = 24;
+
+/// 最近一次粘贴的范围和所用的历史条目，Alt+Y 据此换成更早的内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastPaste {
+    pub start: Position,
+    pub end: Position,
+    pub index: usize,
+}
+
+/// 剪切当前行（from_cursor 时从光标剪切到行尾），紧接着上一次剪切时追加到同一条历史
+pub fn cut_line(editor: &mut Editor, from_cursor: bool) {
+    let text = editor.buffer.cut_line(from_cursor);
+    if matches!(
+        editor.last_action,
+        Some(Action::CutLine | Action::CutToLineEnd)
+    ) {
+        editor.cutbuffer.append(&text);
+    } else if !text.is_empty() {
+        editor.cutbuffer.set(text);
+    }
+    sync_system_clipboard(editor);
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+}
+
+/// 复制选区，没有选区时复制当前行（连同换行符），复制后取消标记
+pub fn copy(editor: &mut Editor) {
+    let buffer = &mut editor.buffer;
+    let text = match buffer.selection_range() {
+        Some((start, end)) => buffer.region_text(start, end),
+        None => {
+            let y = buffer.cursor_y.min(buffer.lines.len() - 1);
+            format!("{}\n", buffer.lines[y])
+        }
+    };
+    buffer.mark = None;
+    editor.shift_selecting = false;
+    editor.status_message = format!("已复制 {} 个字符", text.chars().count());
+    editor.cutbuffer.set(text);
+    sync_system_clipboard(editor);
+}
+
+/// 把最新一条剪切内容同步到系统剪贴板，失败时只在状态栏提示
+fn sync_system_clipboard(editor: &mut Editor) {
+    let text = editor.cutbuffer.text();
+    if editor.clipboard == system_clipboard::ClipboardMode::None || text.is_empty() {
+        return;
+    }
+    if let Err(e) = system_clipboard::copy(editor.clipboard, text) {
+        editor.notify_error(format!("无法写入系统剪贴板：{}", e));
+    }
+}
+
+/// 在光标处粘贴系统剪贴板的内容
+pub fn paste_system(editor: &mut Editor) {
+    match system_clipboard::paste(editor.clipboard) {
+        Ok(text) if text.is_empty() => editor.notify_error("系统剪贴板为空"),
+        Ok(text) => {
+            editor.buffer.insert_text(&text);
+            editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+        }
+        Err(e) => editor.notify_error(format!("无法读取系统剪贴板：{}（可以用终端的粘贴键）", e)),
+    }
+}
+
+/// 粘贴第 index 条剪切历史
+pub fn paste(editor: &mut Editor, index: usize) {
+    let Some(text) = editor.cutbuffer.get(index) else {
+        editor.notify_error("剪切缓冲区为空");
+        return;
+    };
+    if text.is_empty() {
+        editor.notify_error("剪切缓冲区为空");
+        return;
+    }
+    let start = editor.buffer.insert_text(text);
+    editor.last_paste = Some(LastPaste {
+        start,
+        end: editor.buffer.cursor_position(),
+        index,
+    });
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+}
+
+/// 紧接在粘贴之后使用：把刚粘贴的内容换成更早的一条剪切历史
+pub fn yank_pop(editor: &mut Editor) {
+    let just_pasted = matches!(
+        editor.last_action,
+        Some(Action::Paste | Action::YankPop | Action::PasteFromHistory)
+    );
+    let Some(last) = editor.last_paste.filter(|_| just_pasted) else {
+        editor.notify_error("只能在粘贴之后切换剪切历史");
+        return;
+    };
+    let count = editor.cutbuffer.len();
+    editor.buffer.delete_region(last.start, last.end);
+    let index = (last.index + 1) % count;
+    paste(editor, index);
+    editor.status_message = format!("剪切历史 {}/{}", index + 1, count);
+}
+
+/// 打开剪切历史列表，选中的一条粘贴到光标处
+pub fn open_paste_history(editor: &mut Editor) {
+    if editor.cutbuffer.is_empty() {
+        editor.notify_error("剪切缓冲区为空");
+        return;
+    }
+    let keys = ('1'..='9').chain('a'..='z');
+    let options = editor
+        .cutbuffer
+        .iter()
+        .zip(keys)
+        .map(|(text, key)| PromptOption {
+            key,
+            label: preview(text),
+        })
+        .collect();
+    editor.prompt = Some(Prompt::choice(
+        PromptPurpose::PasteHistory,
+        "粘贴哪一条？",
+        options,
+    ));
+}
+
+/// 把光标处或选区的 `文件:行` 引用放进剪切缓冲区；路径在当前目录下时用相对路径
+pub fn copy_reference(editor: &mut Editor) {
+    let Some(filename) = &editor.buffer.filename else {
+        editor.notify_error("缓冲区还没有文件名");
+        return;
+    };
+    let path = env::current_dir()
+        .ok()
+        .and_then(|dir| filename.strip_prefix(dir).ok().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| filename.clone());
+    let reference = match editor.buffer.selection_range() {
+        Some((from, to)) => location::format_reference(&path.to_string_lossy(), from, Some(to)),
+        None => location::format_reference(
+            &path.to_string_lossy(),
+            editor.buffer.cursor_position(),
+            None,
+        ),
+    };
+    editor.status_message = format!("已复制 {}", reference);
+    editor.cutbuffer.set(reference);
+    sync_system_clipboard(editor);
+}
+
+/// 单行预览：换行显示为 ⏎，过长时截断
+fn preview(text: &str) -> String {
+    let mut label: String = text
+        .chars()
+        .take(PREVIEW_CHARS)
+        .map(|c| if c == '\n' { '⏎' } else { c })
+        .collect();
+    if text.chars().nth(PREVIEW_CHARS).is_some() {
+        label.push('…');
+    }
+    label
+}
@@ -0,0 +1,515 @@
+use crate::action::Action;
+use crate::boxdraw::BoxStyle;
+use crate::buffer::Position;
+use crate::direction::Direction;
+use crate::editor::{Editor, Prompt, PromptPurpose, VersionSource};
+use crate::filetype::FileType;
+use crate::location::Step;
+use crate::Result;
+
+/// 执行一个操作，所有输入来源最终都走到这里
+pub fn execute(editor: &mut Editor, action: Action) -> Result<()> {
+    let draws_box = editor.box_drawing.is_some() && matches!(action, Action::MoveCursor(_));
+    if editor.buffer.is_read_only() && (action.modifies_buffer() || draws_box) {
+        editor.notify_error("缓冲区只读");
+        return Ok(());
+    }
+    // 块选择中的输入和删除作用于矩形的每一行：先删掉矩形中的文本，再在各行各执行一次
+    if matches!(
+        action,
+        Action::InsertChar(_)
+            | Action::InsertTab
+            | Action::InsertNewline
+            | Action::DeleteBackward
+            | Action::DeleteForward
+    ) && editor.buffer.begin_block_edit() == Some(true)
+        && matches!(action, Action::DeleteBackward | Action::DeleteForward)
+    {
+        return Ok(());
+    }
+    match action {
+        Action::Quit => {
+            if editor.buffer.modified {
+                super::prompt::ask(editor, PromptPurpose::ExitConfirm, "文件已修改，是否保存？");
+            } else {
+                super::buffers::close_buffer(editor);
+            }
+        }
+        Action::OpenFile => {
+            editor.prompt = Some(Prompt::text(
+                PromptPurpose::OpenFile,
+                "要打开的文件（按 ESC 取消）:",
+                "",
+            ));
+        }
+        Action::NextBuffer => super::buffers::switch_buffer(editor, true),
+        Action::PrevBuffer => super::buffers::switch_buffer(editor, false),
+        Action::Save => {
+            super::prompt::open_save_prompt(editor, PromptPurpose::SaveAs);
+        }
+        Action::ShowHelp => {
+            super::help::open(editor);
+        }
+        Action::ToggleSecondaryCursor => {
+            let added = editor.buffer.toggle_cursor();
+            let count = editor.buffer.cursor_count();
+            editor.status_message = match (added, count) {
+                (true, _) => format!("已添加光标，共 {} 个（Alt+方向键移动新光标）", count),
+                (false, 1) => "多光标已关闭".to_string(),
+                (false, _) => format!("已删除光标，还有 {} 个", count),
+            };
+        }
+        Action::ClearCursors => {
+            if editor.buffer.block_mark.take().is_some() {
+                editor.status_message = "已取消块选择".to_string();
+            } else if editor.buffer.has_multiple_cursors() {
+                editor.buffer.clear_cursors();
+                editor.status_message = "多光标已关闭".to_string();
+            } else {
+                editor.notify_error("没有其他光标（Alt+C 添加）");
+            }
+        }
+        Action::MoveCursor(direction) if editor.box_drawing.is_some() => {
+            // 画框模式下方向键画线而不是移动光标
+            if let Some(style) = editor.box_drawing {
+                let page_height = editor.layout().page_rows();
+                editor
+                    .buffer
+                    .draw_box_segment(direction, style, page_height);
+                editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+            }
+        }
+        Action::MoveCursor(direction) => move_cursor(editor, direction, 1),
+        Action::PageUp | Action::PageDown => {
+            clear_shift_selection(editor);
+            let page_rows = editor.layout().page_rows();
+            let down = action == Action::PageDown;
+            if !editor.buffer.move_page(down, page_rows) {
+                editor.notify_error(if down {
+                    "已在最后一行"
+                } else {
+                    "已在第一行"
+                });
+            }
+        }
+        Action::LineStart => {
+            clear_shift_selection(editor);
+            editor.buffer.move_smart_home();
+        }
+        Action::LineEnd => {
+            clear_shift_selection(editor);
+            editor.buffer.move_line_end();
+        }
+        Action::WordLeft | Action::WordRight => {
+            clear_shift_selection(editor);
+            let moved = if action == Action::WordLeft {
+                editor
+                    .buffer
+                    .move_extra_cursors_to(|buffer, pos| buffer.prev_word_start(pos));
+                editor.buffer.move_word_left()
+            } else {
+                editor
+                    .buffer
+                    .move_extra_cursors_to(|buffer, pos| buffer.next_word_start(pos));
+                editor.buffer.move_word_right()
+            };
+            if !moved {
+                editor.notify_error(if action == Action::WordLeft {
+                    "已在缓冲区开头"
+                } else {
+                    "已在缓冲区末尾"
+                });
+            }
+            editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+        }
+        Action::DeleteWordBackward => {
+            if !editor.buffer.delete_word_backward_at_all_cursors() {
+                editor.notify_error("已在缓冲区开头");
+            }
+            editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+        }
+        Action::ToggleProtectLines => {
+            let buffer = &mut editor.buffer;
+            let (first, last) = match buffer.selection_range() {
+                // 选区结束在某行行首时，那一行不算在内
+                Some((from, to)) if to.x == 0 && to.y > from.y => (from.y, to.y - 1),
+                Some((from, to)) => (from.y, to.y),
+                None => (buffer.cursor_y, buffer.cursor_y),
+            };
+            let lines = first..last + 1;
+            let all_protected = lines.clone().all(|y| buffer.is_line_protected(y));
+            if all_protected {
+                buffer.unprotect_lines(lines);
+            } else {
+                buffer.protect_lines(lines);
+            }
+            buffer.mark = None;
+            let range = if first == last {
+                format!("第 {} 行", first + 1)
+            } else {
+                format!("第 {}-{} 行", first + 1, last + 1)
+            };
+            editor.status_message = if all_protected {
+                format!("已取消保护{}", range)
+            } else {
+                format!("已保护{}，修改这些行会被拒绝", range)
+            };
+        }
+        Action::DeleteWordForward => {
+            if !editor.buffer.delete_word_forward_at_all_cursors() {
+                editor.notify_error("已在缓冲区末尾");
+            }
+        }
+        Action::SelectMove(direction) => {
+            if editor.buffer.mark.is_none() && editor.buffer.block_mark.is_none() {
+                editor.buffer.mark = Some(editor.buffer.cursor_position());
+            }
+            editor
+                .buffer
+                .move_cursor(direction, editor.layout().page_rows(), false);
+            editor.shift_selecting = true;
+        }
+        Action::MoveSecondaryCursor(direction) => {
+            editor
+                .buffer
+                .move_cursor(direction, editor.layout().page_rows(), true);
+        }
+        Action::InsertNewline => editor.buffer.insert_newline_at_all_cursors(),
+        Action::DeleteBackward => editor.buffer.backspace_at_all_cursors(),
+        Action::DeleteForward => {
+            let buffer = &editor.buffer;
+            let at_end = buffer.cursor_y + 1 >= buffer.lines.len()
+                && buffer.cursor_x >= buffer.line_char_count(buffer.cursor_y);
+            if at_end && buffer.selection_range().is_none() && !buffer.has_multiple_cursors() {
+                editor.notify_error("已在缓冲区末尾");
+            } else {
+                editor.buffer.delete_char_forward_at_all_cursors();
+            }
+        }
+        Action::InsertChar(ch) => {
+            editor.type_char(ch);
+        }
+        Action::ToggleVirtualSpace => {
+            editor.status_message = if editor.buffer.toggle_virtual_space() {
+                "自由光标模式已开启".to_string()
+            } else {
+                "自由光标模式已关闭".to_string()
+            };
+        }
+        Action::InsertTab => {
+            // Markdown 表格中 Tab 移到下一个单元格，其他地方插入制表符
+            let in_table = editor.buffer.filetype == FileType::Markdown
+                && !editor.buffer.has_multiple_cursors()
+                && editor.buffer.move_table_cell(true);
+            if !in_table {
+                editor.buffer.insert_tab_at_all_cursors();
+            }
+        }
+        Action::FormatTable | Action::TableNextCell | Action::TablePrevCell => {
+            if editor.buffer.filetype != FileType::Markdown {
+                editor.notify_error("表格功能仅适用于 Markdown 文件");
+                return Ok(());
+            }
+            let done = match action {
+                Action::TableNextCell => editor.buffer.move_table_cell(true),
+                Action::TablePrevCell => editor.buffer.move_table_cell(false),
+                _ => editor.buffer.format_table_at_cursor(),
+            };
+            if !done {
+                editor.notify_error("光标不在表格中");
+            }
+        }
+        Action::ToggleMarkdownPreview => {
+            if editor.buffer.filetype == FileType::Markdown || editor.panel.is_some() {
+                super::panel::toggle_markdown_preview(editor);
+            } else {
+                editor.notify_error("预览仅适用于 Markdown 文件");
+            }
+        }
+        Action::ToggleOutline => super::outline::toggle(editor),
+        Action::CycleBoxDrawing => {
+            editor.box_drawing = match editor.box_drawing {
+                None => Some(BoxStyle::Unicode),
+                Some(BoxStyle::Unicode) => Some(BoxStyle::Ascii),
+                Some(BoxStyle::Ascii) => None,
+            };
+            editor.status_message = match editor.box_drawing {
+                Some(style) => format!("画框模式（{}）：方向键画线", style.label()),
+                None => "画框模式已关闭".to_string(),
+            };
+        }
+        Action::NextSentence | Action::PrevSentence => {
+            if action == Action::NextSentence {
+                editor.buffer.move_to_next_sentence();
+            } else {
+                editor.buffer.move_to_prev_sentence();
+            }
+            editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+        }
+        Action::SelectSentence => {
+            if editor.buffer.select_sentence() {
+                editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+            } else {
+                editor.notify_error("光标不在句子中");
+            }
+        }
+        Action::SelectWord => {
+            if !editor.buffer.select_word() {
+                editor.notify_error("光标不在单词上");
+            }
+        }
+        Action::SelectLine => editor.buffer.select_line(),
+        Action::ExpandSelection => {
+            if editor.buffer.expand_selection() {
+                editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+            } else {
+                editor.status_message = "已选中全部内容".to_string();
+            }
+        }
+        Action::ToggleProse => {
+            if editor.buffer.settings.prose {
+                editor.buffer.set_prose(false);
+                editor.status_message = "散文模式已关闭".to_string();
+            } else if editor.buffer.filetype.is_prose() {
+                editor.buffer.set_prose(true);
+                editor.status_message = "散文模式已开启：软换行，自动替换破折号和引号".to_string();
+            } else {
+                editor.notify_error("散文模式仅适用于 Markdown 和 .txt 文件");
+            }
+        }
+        Action::ToggleTitleBar => {
+            editor.bars.title = !editor.bars.title;
+            editor.status_message = if editor.bars.title {
+                "已显示标题栏".to_string()
+            } else {
+                "已隐藏标题栏".to_string()
+            };
+        }
+        Action::ToggleStatusBar => {
+            editor.bars.status = !editor.bars.status;
+            editor.status_message = if editor.bars.status {
+                "已显示状态栏".to_string()
+            } else {
+                "已隐藏状态栏，有提示或消息时临时显示".to_string()
+            };
+        }
+        Action::ToggleHelpLine => {
+            editor.bars.help = !editor.bars.help;
+            editor.status_message = if editor.bars.help {
+                "已显示帮助栏".to_string()
+            } else {
+                "已隐藏帮助栏".to_string()
+            };
+        }
+        Action::ShellOut => super::shell::shell_out(editor)?,
+        Action::RunCommand => super::runner::run(editor)?,
+        Action::RunOutput => super::runner::toggle_output_panel(editor),
+        Action::ToggleTerminal => super::terminal::toggle(editor),
+        Action::TaskList => super::tasks::toggle_task_list(editor),
+        Action::Print => super::print::open_print_prompt(editor),
+        Action::ExportHtml => super::export::open_html_prompt(editor),
+        Action::ToggleAnsi => super::pager::toggle_ansi(editor),
+        Action::CopyReference => super::clipboard::copy_reference(editor),
+        Action::HelpLineNextPage => {
+            editor.help_line_page =
+                (editor.help_line_page + 1) % super::help_line::page_count(editor);
+        }
+        Action::ToggleSoftWrap => {
+            editor.buffer.settings.soft_wrap = !editor.buffer.settings.soft_wrap;
+            editor.status_message = if editor.buffer.settings.soft_wrap {
+                "软换行已开启".to_string()
+            } else {
+                "软换行已关闭".to_string()
+            };
+        }
+        Action::CutLine => super::clipboard::cut_line(editor, editor.cut_from_cursor),
+        Action::CutToLineEnd => super::clipboard::cut_line(editor, true),
+        Action::DuplicateLine => {
+            editor.buffer.duplicate_lines();
+            editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+        }
+        Action::DeleteLine => {
+            let count = editor.buffer.delete_lines();
+            editor.status_message = format!("已删除 {} 行", count);
+        }
+        Action::MoveLineUp | Action::MoveLineDown => {
+            let up = action == Action::MoveLineUp;
+            if editor.buffer.move_lines(up) {
+                editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+            } else {
+                editor.notify_error(if up {
+                    "已在第一行"
+                } else {
+                    "已在最后一行"
+                });
+            }
+        }
+        Action::Copy => super::clipboard::copy(editor),
+        Action::Paste => super::clipboard::paste(editor, 0),
+        Action::YankPop => super::clipboard::yank_pop(editor),
+        Action::PasteFromHistory => super::clipboard::open_paste_history(editor),
+        Action::PasteSystem => super::clipboard::paste_system(editor),
+        Action::Undo => {
+            if editor.buffer.undo() {
+                editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+            } else {
+                editor.notify_error("没有可以撤销的修改");
+            }
+        }
+        Action::Redo => {
+            if editor.buffer.redo() {
+                editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+            } else {
+                editor.notify_error("没有可以重做的修改");
+            }
+        }
+        Action::UndoBoundary => super::undo::insert_boundary(editor),
+        Action::ToggleCutFromCursor => {
+            editor.cut_from_cursor = !editor.cut_from_cursor;
+            editor.status_message = if editor.cut_from_cursor {
+                "^K 从光标剪切到行尾".to_string()
+            } else {
+                "^K 剪切整行".to_string()
+            };
+        }
+        Action::TransposeChars => {
+            if !editor.buffer.transpose_chars() {
+                editor.notify_error("没有可交换的字符");
+            }
+        }
+        Action::TransposeWords => {
+            if !editor.buffer.transpose_words() {
+                editor.notify_error("没有可交换的单词");
+            }
+        }
+        Action::JumpPastCloser => {
+            if !editor.buffer.jump_past_closer() {
+                editor.notify_error("光标后没有右括号或引号");
+            }
+        }
+        Action::SetOption => {
+            editor.prompt = Some(Prompt::text(
+                PromptPurpose::SetOption,
+                ":set（留空查看当前设置）",
+                "",
+            ));
+        }
+        Action::ReloadConfig => super::configure::reload_config(editor),
+        Action::DumpConfig => super::configure::open_dump_prompt(editor),
+        Action::CycleTheme => super::configure::cycle_theme(editor),
+        Action::Search => super::search::open_search_prompt(editor, false),
+        Action::SearchBackward => super::search::open_search_prompt(editor, true),
+        Action::SearchNext => super::search::search_again(editor, false),
+        Action::SearchPrev => super::search::search_again(editor, true),
+        Action::SearchResults => super::search::toggle_results_panel(editor),
+        Action::Replace => super::search::open_replace_prompt(editor),
+        Action::NextLocation => super::locations::navigate(editor, Step::Next),
+        Action::PrevLocation => super::locations::navigate(editor, Step::Prev),
+        Action::FirstLocation => super::locations::navigate(editor, Step::First),
+        Action::LastLocation => super::locations::navigate(editor, Step::Last),
+        Action::ListChanges => super::locations::list_changes(editor),
+        Action::ShowJournal => super::journal::toggle_viewer(editor),
+        Action::MemoryReport => super::memory::toggle_report(editor),
+        Action::CompactMemory => super::memory::compact(editor),
+        Action::InspectChar => super::inspect::inspect_char(editor),
+        Action::NextSuspiciousChar => super::inspect::next_suspicious(editor),
+        Action::ToggleBookmark => super::bookmarks::toggle(editor),
+        Action::NextBookmark => super::bookmarks::next(editor),
+        Action::ToggleBlockSelection => {
+            if editor.buffer.block_mark.is_some() {
+                let count = editor.buffer.block_to_cursors();
+                editor.status_message = format!("已在 {} 行上各放一个光标", count);
+            } else {
+                editor.buffer.start_block_selection();
+                editor.status_message =
+                    "块选择：移动光标选出矩形，输入或删除作用于每一行，再按 Alt+Shift+A 转为多光标"
+                        .to_string();
+            }
+        }
+        Action::RecordMacro => super::macros::toggle_recording(editor),
+        Action::RunMacro => super::macros::run_last(editor)?,
+        Action::SaveMacro => super::macros::open_save_prompt(editor),
+        Action::ListMacros => super::macros::open_list(editor),
+        Action::EnableEditing => super::large_file::enable_editing(editor),
+        Action::JumpLongestLine => {
+            editor.buffer.update_stats();
+            if let Some(stats) = editor.buffer.stats {
+                editor.buffer.mark = None;
+                editor
+                    .buffer
+                    .set_cursor_position(Position::new(0, stats.longest_line));
+                editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+                editor.status_message = format!(
+                    "最长的行：第 {} 行，{} 个字符",
+                    stats.longest_line + 1,
+                    stats.longest_len
+                );
+            }
+        }
+        Action::GotoLine => {
+            editor.prompt = Some(Prompt::text(
+                PromptPurpose::GotoLine,
+                "跳到 行[,列]（+N/-N 相对移动，按 ESC 取消）:",
+                "",
+            ));
+        }
+        Action::InsertDigraph => {
+            editor.prompt = Some(Prompt::text(
+                PromptPurpose::Digraph,
+                "二合字母（如 a: → ä，e' → é，Eu → €，按 ESC 取消）:",
+                "",
+            ));
+        }
+        Action::ClearHighlight => {
+            if !editor.buffer.clear_highlight() {
+                editor.notify_error("没有搜索高亮");
+            }
+        }
+        Action::RestoreSnapshot => {
+            super::snapshots::open_restore_picker(editor, VersionSource::Snapshots)
+        }
+        Action::RestoreHistory => {
+            if editor.local_history == 0 && editor.buffer.filename.is_some() {
+                editor.status_message =
+                    "本地历史未开启，在配置文件中设置 local_history".to_string();
+            }
+            super::snapshots::open_restore_picker(editor, VersionSource::History)
+        }
+        Action::ToggleMark => {
+            editor.shift_selecting = false;
+            editor.status_message = if editor.buffer.toggle_mark() {
+                "已设置标记".to_string()
+            } else {
+                "已取消标记".to_string()
+            };
+        }
+    }
+    Ok(())
+}
+
+/// 把光标朝 direction 移动 count 步；一步也没动时提示已到边界
+pub fn move_cursor(editor: &mut Editor, direction: Direction, count: usize) {
+    clear_shift_selection(editor);
+    let page_rows = editor.layout().page_rows();
+    let before = (editor.buffer.cursor_x, editor.buffer.cursor_y);
+    for _ in 0..count {
+        editor.buffer.move_cursor(direction, page_rows, false);
+        // 多光标模式下所有光标一起移动
+        editor.buffer.move_extra_cursors(direction);
+    }
+    if (editor.buffer.cursor_x, editor.buffer.cursor_y) == before {
+        editor.notify_error(match direction {
+            Direction::Up => "已在第一行",
+            Direction::Down => "已在最后一行",
+            Direction::Left => "已在缓冲区开头",
+            Direction::Right => "已在缓冲区末尾",
+        });
+    }
+}
+
+/// 不按 Shift 移动时取消按 Shift 选出的选区
+fn clear_shift_selection(editor: &mut Editor) {
+    if std::mem::take(&mut editor.shift_selecting) {
+        editor.buffer.mark = None;
+    }
+}
@@ -0,0 +1,207 @@
+use crate::config::Config;
+use crate::editor::{Editor, Prompt, PromptPurpose};
+use crate::paths;
+use crate::theme::Theme;
+use crate::{EditorError, Result};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+/// 自动重新加载时检查文件修改时间的间隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 配置与当前主题文件的修改时间，任何一个变化都需要重新加载
+pub type ConfigStamp = (Option<SystemTime>, Option<SystemTime>);
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn config_stamp(editor: &Editor) -> ConfigStamp {
+    let config = editor.config_path.as_deref().and_then(modified_time);
+    let theme = paths::themes_dir()
+        .map(|dir| dir.join(format!("{}.toml", editor.theme.name)))
+        .and_then(|path| modified_time(&path));
+    (config, theme)
+}
+
+/// 读取配置文件并应用；出错时保持原配置，只返回错误
+fn load_and_apply(editor: &mut Editor) -> Result<()> {
+    let config = match &editor.config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let theme = Theme::load(&config.theme, paths::themes_dir().as_deref())?;
+    apply_config(editor, config, theme);
+    Ok(())
+}
+
+/// 应用配置：命令行参数优先于配置文件
+///
+/// 用 :set 改过设置的缓冲区保持不变，其余缓冲区换成新的默认设置。
+fn apply_config(editor: &mut Editor, config: Config, theme: Theme) {
+    let config = config.with_args(&editor.args);
+    let old_defaults = std::mem::replace(&mut editor.defaults, config.options.clone());
+    let old_filetypes = std::mem::replace(&mut editor.filetype_options, config.filetypes.clone());
+    let old_detect_indent = std::mem::replace(&mut editor.detect_indent, config.detect_indent);
+    let buffers = std::iter::once(&mut editor.buffer).chain(editor.other_buffers.iter_mut());
+    for buffer in buffers {
+        let project = super::project::project_for(&editor.projects, buffer);
+        let mut old = old_defaults.for_filetype(buffer.filetype, &old_filetypes);
+        let mut new = editor
+            .defaults
+            .for_filetype(buffer.filetype, &editor.filetype_options);
+        if let Some(project) = project {
+            project.apply(&mut old, buffer.filetype);
+            project.apply(&mut new, buffer.filetype);
+        }
+        if let Some(style) = buffer.indent_style {
+            if old_detect_indent {
+                style.apply(&mut old);
+            }
+            if editor.detect_indent {
+                style.apply(&mut new);
+            }
+        }
+        if buffer.settings == old {
+            buffer.settings = new;
+        }
+    }
+    editor.cut_from_cursor = config.cut_from_cursor;
+    editor.cutbuffer_path = if config.persist_cutbuffer {
+        paths::cutbuffer_file()
+    } else {
+        None
+    };
+    editor.keymap = config.keymap();
+    editor.macros = config.macros;
+    editor.watch_config = config.watch_config;
+    editor.bell = config.bell;
+    editor.snapshot_idle = Duration::from_secs(config.snapshot_idle);
+    editor.journal = config.journal;
+    editor.local_history = config.local_history;
+    editor.trash = config.trash;
+    editor.clipboard = config.clipboard;
+    editor.large_file_threshold = config.large_file_mb << 20;
+    editor.warn_line_length = config.warn_line_length;
+    editor.warn_size = config.warn_size_kb << 10;
+    editor.max_fps = config.max_fps;
+    editor.undo_limit = config.undo_limit;
+    editor.undo_memory = config.undo_memory_kb << 10;
+    editor.undo_group = Duration::from_millis(config.undo_group_ms);
+    editor.bars = config.bars;
+    editor.help_line_actions = config.help_line_actions;
+    editor.todo_markers = config.todo_markers;
+    editor.print_options = config.print;
+    editor.confirm_defaults = config.confirm_defaults;
+    editor.theme = theme;
+    editor.config_stamp = config_stamp(editor);
+}
+
+/// 启动时加载配置，出错时使用默认配置并在状态栏提示；命令行参数照常生效
+pub fn load_initial(editor: &mut Editor) {
+    if let Err(e) = load_and_apply(editor) {
+        apply_config(editor, Config::default(), Theme::default());
+        editor.notify_error(e.to_string());
+    }
+}
+
+/// 重新读取配置和主题文件，解析错误显示在状态栏，原配置保持不变
+pub fn reload_config(editor: &mut Editor) {
+    match load_and_apply(editor) {
+        Ok(()) => editor.status_message = "已重新加载配置".to_string(),
+        Err(e) => editor.notify_error(e.to_string()),
+    }
+    // 出错时也记下修改时间，避免每秒重复报告同一个错误
+    editor.config_stamp = config_stamp(editor);
+}
+
+/// 开启 watch_config 时，配置或主题文件变化后自动重新加载
+pub fn check_config_changed(editor: &mut Editor) {
+    if !editor.watch_config || editor.last_config_check.elapsed() < WATCH_INTERVAL {
+        return;
+    }
+    editor.last_config_check = Instant::now();
+    if config_stamp(editor) != editor.config_stamp {
+        reload_config(editor);
+    }
+}
+
+/// 换成下一个可用主题，只影响本次运行，配置文件中的 theme 不变
+pub fn cycle_theme(editor: &mut Editor) {
+    let themes_dir = paths::themes_dir();
+    let names = Theme::available(themes_dir.as_deref());
+    let next = names
+        .iter()
+        .position(|name| *name == editor.theme.name)
+        .map_or(0, |i| (i + 1) % names.len());
+    match Theme::load(&names[next], themes_dir.as_deref()) {
+        Ok(theme) => {
+            editor.theme = theme;
+            editor.status_message = format!("主题: {}", editor.theme.name);
+        }
+        Err(e) => editor.notify_error(e.to_string()),
+    }
+    // 监视的是当前主题文件，换主题后重新记下修改时间
+    editor.config_stamp = config_stamp(editor);
+}
+
+/// 当前生效的配置（包括 :set 之外的全部设置和完整键位表）
+fn effective_config(editor: &Editor) -> Config {
+    Config {
+        options: editor.defaults.clone(),
+        filetypes: editor.filetype_options.clone(),
+        cut_from_cursor: editor.cut_from_cursor,
+        persist_cutbuffer: editor.cutbuffer_path.is_some(),
+        watch_config: editor.watch_config,
+        detect_indent: editor.detect_indent,
+        theme: editor.theme.name.clone(),
+        bell: editor.bell,
+        snapshot_idle: editor.snapshot_idle.as_secs(),
+        journal: editor.journal,
+        local_history: editor.local_history,
+        trash: editor.trash,
+        clipboard: editor.clipboard,
+        large_file_mb: editor.large_file_threshold >> 20,
+        warn_line_length: editor.warn_line_length,
+        warn_size_kb: editor.warn_size >> 10,
+        max_fps: editor.max_fps,
+        undo_limit: editor.undo_limit,
+        undo_memory_kb: editor.undo_memory >> 10,
+        undo_group_ms: editor.undo_group.as_millis() as u64,
+        bars: editor.bars,
+        help_line_actions: editor.help_line_actions.clone(),
+        todo_markers: editor.todo_markers.clone(),
+        print: editor.print_options,
+        confirm_defaults: editor.confirm_defaults,
+        keys: Vec::new(),
+        macros: editor.macros.clone(),
+        macro_keys: Vec::new(),
+    }
+}
+
+/// 打开导出配置的提示，默认写到配置目录下的 config.dump.toml
+pub fn open_dump_prompt(editor: &mut Editor) {
+    let default_path = paths::config_dir()
+        .map(|dir| dir.join("config.dump.toml"))
+        .and_then(|p| p.to_str().map(str::to_string))
+        .unwrap_or_default();
+    editor.prompt = Some(Prompt::text(
+        PromptPurpose::DumpConfig,
+        "导出生效配置到文件（按 ESC 取消）:",
+        &default_path,
+    ));
+}
+
+/// 把生效配置和键位表写到文件
+pub fn dump_config(editor: &mut Editor, path: &Path) {
+    let text = effective_config(editor).to_toml(&editor.keymap);
+    let Some(note) = super::trash::before_overwrite(editor, path) else {
+        return;
+    };
+    let result = paths::ensure_parent(path).and_then(|()| fs::write(path, text));
+    match result {
+        Ok(()) => editor.status_message = format!("已导出配置到 {}{}", path.display(), note),
+        Err(e) => editor.notify_error(EditorError::io(path, e).to_string()),
+    }
+}
@@ -0,0 +1,35 @@
+use crate::editor::{Editor, Prompt, PromptPurpose};
+use crate::html::buffer_to_html;
+use crate::EditorError;
+use std::fs;
+use std::path::Path;
+
+/// 打开导出 HTML 的提示，默认在文件名后加 .html
+pub fn open_html_prompt(editor: &mut Editor) {
+    let default_path = editor.buffer.filename.as_ref().map_or_else(
+        || "export.html".to_string(),
+        |p| format!("{}.html", p.display()),
+    );
+    editor.prompt = Some(Prompt::text(
+        PromptPurpose::ExportHtml,
+        "导出为 HTML（按 ESC 取消）:",
+        &default_path,
+    ));
+}
+
+/// 用当前主题的颜色把缓冲区写成 HTML 页面
+pub fn export_html(editor: &mut Editor, path: &Path) {
+    let title = editor
+        .buffer
+        .filename
+        .as_ref()
+        .map_or_else(|| "rsnano".to_string(), |p| p.display().to_string());
+    let html = buffer_to_html(&editor.buffer, &editor.theme, &title);
+    let Some(note) = super::trash::before_overwrite(editor, path) else {
+        return;
+    };
+    match fs::write(path, html) {
+        Ok(()) => editor.status_message = format!("已导出 HTML 到 {}{}", path.display(), note),
+        Err(e) => editor.notify_error(EditorError::io(path, e).to_string()),
+    }
+}
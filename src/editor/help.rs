@@ -0,0 +1,131 @@
+use super::input::Input;
+use super::modal::Modal;
+use crate::editor::Editor;
+use crate::layout::Layout;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use std::io::{self, Write};
+
+/// 帮助框中的按键说明
+const HELP_LINES: [&str; 64] = [
+        "^X 关闭当前缓冲区，只剩一个时退出编辑器",
+        "^O 保存文件",
+        "^R 在新缓冲区中打开文件  Alt+< / Alt+> 切换缓冲区",
+        "Alt+C 在主光标处添加光标（已有光标时删除）  ^C 只保留主光标",
+        "Alt+方向键 移动最近添加的光标，之后输入、删除、方向键和按单词移动/删除作用于每个光标",
+        "Alt+Shift+A 块选择：移动光标选出矩形，输入按列替换/插入，删除按列删除；再按一次转为每行一个光标  ^C 取消",
+        "^W 搜索  ^Q 反向搜索，输入时光标跟着跳到匹配，提示中 Alt+B 切换方向、ESC 回到原处",
+        "Alt+W / Alt+Q 查找下一个/上一个  Alt+Shift+W 搜索结果列表",
+        "^\\ 替换：在当前缓冲区逐处确认（Y 替换 N 跳过 A 全部），或替换所有打开的缓冲区",
+        "替换提示中 Alt+R 切换正则表达式，替换内容里 $1 等引用分组  Alt+Shift+Q 清除搜索高亮",
+        "Alt+Shift+D 用二合字母输入重音字母和符号（如 a: → ä，Eu → €）",
+        "F8 / Shift+F8 位置列表的下一项/上一项  Alt+F8 / Alt+Shift+F8 第一项/最后一项  F7 列出修改过的行",
+        "Alt+Shift+J 查看当前文件的编辑日志（配置 journal = true 开启）",
+        "Alt+Shift+M 查看各缓冲区的内存占用  Alt+Shift+Z 整理内存（只保留最近 100 步撤销）",
+        "Alt+Shift+H 查看、恢复保存前的历史版本（配置 local_history 开启）",
+        "Alt+Shift+I 查看光标处字符的码位、UTF-8 字节、名称和宽度（排查看不见的字符）",
+        "Alt+Shift+N 跳到下一个双向控制符、零宽字符或形似 ASCII 的字符（:set unicodecheck 时标上颜色）",
+        "Alt+Shift+B 设置/取消书签  ^B 跳到下一个书签（书签、运行错误和修改过的行显示在行号左侧的符号栏）",
+        "Alt+: 开始/停止录制键盘宏  Alt+; 运行刚录制的宏  F4 给宏起名保存  Shift+F4 选择命名的宏运行",
+        "配置 [macros] 中定义宏（如 quote = [\"Home\", \"> \", \"Down\"]），[keys] 中 \"F3\" = \"macro:quote\" 绑定到按键",
+        "Alt+Shift+E 完整读入以只读预览打开的大文件并允许编辑",
+        "Alt+Shift+G 跳到最长的行（状态栏出现 [长行] 提醒时）",
+        "^_ / Alt+G 跳到 行[,列]，+N/-N 相对当前行移动",
+        "PageUp / PageDown 翻页  Home / ^A 在行首缩进和第 0 列之间切换  End / ^E 行尾",
+        "^← / ^→ 按单词移动  ^Backspace / Alt+Backspace 删除前一个单词  ^Delete 删除后一个单词",
+        "^K 剪切当前行（连续剪切会累积）  ^U 粘贴",
+        "^D 复制当前行  Alt+Delete 删除当前行  Alt+Shift+↑/↓ 上移/下移当前行（有选区时作用于选中的各行）",
+        "Alt+Shift+F 保护选中的行（或当前行）不被修改，再按一次取消保护",
+        "^Z / Alt+U 撤销（连续输入或删除算作一步，停顿后另起一步）  ^Y 重做  Alt+Shift+U 插入撤销断点",
+        "Alt+Y 粘贴后换成更早的剪切内容  Alt+Shift+Y 从剪切历史中选择粘贴",
+        "剪切和复制同时放进系统剪贴板  Alt+Shift+V 粘贴系统剪贴板的内容",
+        "Alt+K 切换 ^K 剪切整行/从光标剪切到行尾",
+        "^T 交换前后两个字符  Alt+T 交换前后两个单词",
+        "Alt+] 跳到下一个右括号或引号之后",
+        "Alt+O 修改当前缓冲区的设置（:set tabsize=4、nowrap、ro! 等）",
+        "Alt+A 设置/取消标记（选区）",
+        "Shift+方向键 选择文本  Alt+6 复制选区（没有选区时复制当前行）",
+        "Delete 删除光标处字符",
+        "Alt+V 自由光标模式开/关",
+        "Alt+B 画框模式（Unicode/ASCII/关闭）",
+        "Alt+F 格式化 Markdown 表格",
+        "Tab 插入制表符（:set tabstospaces 时插入空格，tabsize 设置宽度）",
+        "Markdown 表格中 Tab/Shift+Tab 在单元格间移动",
+        "Alt+P Markdown 预览开/关",
+        "F2 大纲：文档的标题或代码中的函数、类型，输入文字模糊过滤，Enter 跳转，←/→ 收起/展开；文档的状态栏显示字数和阅读时间",
+        "Alt+M 散文模式开/关（Markdown 和 .txt），:set autocapitalize 句首自动大写，:set collapsespaces 合并连续空格",
+        "Alt+S 软换行开/关",
+    "Alt+Shift+T / Alt+Shift+S / Alt+Shift+X 显示/隐藏标题栏、状态栏、帮助栏",
+        "Alt+( / Alt+) 移到上一句/下一句",
+        "Alt+. 选中当前句子",
+        "Alt+D 选中单词  Alt+L 选中整行  Alt+= 扩大选区",
+        "Alt+R 重新加载配置文件和主题  Alt+E 导出生效配置和键位表",
+        "Alt+Shift+R 从自动快照恢复当前缓冲区（右侧显示差异）",
+        "Alt+Z 切换主题（default、dark、light、high-contrast、deuteranopia、mono）",
+        "Alt+! 暂时回到终端启动 shell，退出 shell 后返回编辑器",
+        "F5 运行命令（runcommand 选项，如 cargo build），输出中的 文件:行:列 可跳转  Shift+F5 输出面板",
+        "F6 在底部打开内置终端（焦点在终端时按键都发给 shell），再按 F6 隐藏，shell 继续运行",
+        "F9 列出打开的缓冲区中的 TODO、FIXME、XXX（配置 todo_markers 修改）",
+        "Alt+Shift+P 打印（lpr/lp），或导出为分页文本、PostScript",
+        "Alt+Shift+L 用当前主题的颜色导出为 HTML（含搜索高亮、选区和行号）",
+        "Alt+Shift+K 复制 文件:行:列 引用（有选区时为 文件#L10-L20），^U 粘贴",
+        "--pager 查看带颜色的输出时按 c 或 Alt+Shift+C 切换显示颜色还是原样显示控制序列",
+    "^G 打开帮助页面",
+    "Alt+X 帮助栏放不下所有快捷键时翻到下一页",
+];
+
+/// 打开帮助框，编辑区仍然显示在后面
+pub fn open(editor: &mut Editor) {
+    editor.show_help_page = true;
+    editor.help_scroll = 0;
+}
+
+/// 帮助框打开时的按键：上下方向键和翻页键滚动，其他键关闭
+pub fn handle_key(editor: &mut Editor, key_event: KeyEvent) {
+    let layout = editor.layout();
+    let page = Modal::<&str>::visible_rows(&layout).max(1);
+    let max_scroll = Modal::<&str>::max_scroll(HELP_LINES.len(), &layout);
+    let scroll = &mut editor.help_scroll;
+    match key_event.code {
+        KeyCode::Up => *scroll = scroll.saturating_sub(1),
+        KeyCode::Down => *scroll = (*scroll + 1).min(max_scroll),
+        KeyCode::PageUp => *scroll = scroll.saturating_sub(page),
+        KeyCode::PageDown => *scroll = (*scroll + page).min(max_scroll),
+        KeyCode::Home => *scroll = 0,
+        KeyCode::End => *scroll = max_scroll,
+        KeyCode::Modifier(_) => {}
+        _ => editor.show_help_page = false,
+    }
+}
+
+/// 帮助框在一批输入的中途打开时，同一批中剩下的输入：按键照常滚动或关闭帮助框，其他输入忽略
+pub fn handle_input(editor: &mut Editor, input: Input) {
+    match input {
+        Input::Event(Event::Key(key_event)) if key_event.kind == KeyEventKind::Press => {
+            handle_key(editor, key_event)
+        }
+        Input::Repeat(key_event, count) => {
+            for _ in 0..count {
+                if !editor.show_help_page {
+                    break;
+                }
+                handle_key(editor, key_event);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn draw<W: Write>(out: &mut W, editor: &mut Editor, layout: &Layout) -> io::Result<()> {
+    let scrollable = HELP_LINES.len() > Modal::<&str>::visible_rows(layout);
+    let modal = Modal {
+        title: "RSNano 帮助",
+        lines: &HELP_LINES,
+        footer: if scrollable {
+            "↑↓ 翻页键 滚动  其他键返回编辑器"
+        } else {
+            "按任意键返回编辑器"
+        },
+        scroll: editor.help_scroll,
+    };
+    modal.draw(out, &editor.theme.help_bar, layout, &mut editor.frame.line)
+}
@@ -0,0 +1,89 @@
+use crate::action::Action;
+use crate::editor::Editor;
+use crate::unicode::str_width;
+use std::ops::Range;
+
+/// 各项之间的间隔
+const SEPARATOR: &str = "  ";
+
+/// 帮助栏的各项，例如 “^X 退出”；没有绑定按键的操作不显示
+fn items(editor: &Editor) -> Vec<String> {
+    editor
+        .help_line_actions
+        .iter()
+        .filter_map(|&action| {
+            let key = editor.keymap.key_for(action)?;
+            Some(format!("{} {}", key.describe(), action.label()))
+        })
+        .collect()
+}
+
+/// 翻页键一项，翻页操作没有绑定按键时为 None（只显示第一页）
+fn more_item(editor: &Editor) -> Option<String> {
+    let key = editor.keymap.key_for(Action::HelpLineNextPage)?;
+    Some(format!(
+        "{} {}",
+        key.describe(),
+        Action::HelpLineNextPage.label()
+    ))
+}
+
+/// 按宽度把各项分页，放不下一页时每页末尾留出翻页键的位置
+fn paginate(items: &[String], width: usize, more: Option<&str>) -> Vec<Range<usize>> {
+    // 全部放在一行时的宽度多算了一个间隔
+    let total: usize = items.iter().map(|i| str_width(i) + SEPARATOR.len()).sum();
+    let reserve = match more {
+        Some(more) if total > width + SEPARATOR.len() => SEPARATOR.len() + str_width(more),
+        _ => 0,
+    };
+    let mut pages = Vec::new();
+    let mut start = 0;
+    while start < items.len() {
+        let mut used = reserve;
+        let mut end = start;
+        while end < items.len() {
+            let gap = if end > start { SEPARATOR.len() } else { 0 };
+            let needed = gap + str_width(&items[end]);
+            // 每页至少放一项，太长的项由调用方截断
+            if end > start && used + needed > width {
+                break;
+            }
+            used += needed;
+            end += 1;
+        }
+        pages.push(start..end);
+        start = end;
+    }
+    if pages.is_empty() {
+        pages.push(0..0);
+    }
+    pages
+}
+
+/// 当前宽度下帮助栏共有几页
+pub fn page_count(editor: &Editor) -> usize {
+    let more = more_item(editor);
+    paginate(&items(editor), editor.layout().width, more.as_deref()).len()
+}
+
+/// 把当前页的内容写入 buf
+pub fn render_into(editor: &Editor, width: usize, buf: &mut String) {
+    let items = items(editor);
+    let more = more_item(editor);
+    let pages = paginate(&items, width, more.as_deref());
+    let page = match &more {
+        Some(_) => editor.help_line_page % pages.len(),
+        None => 0,
+    };
+    buf.clear();
+    for item in &items[pages[page].clone()] {
+        if !buf.is_empty() {
+            buf.push_str(SEPARATOR);
+        }
+        buf.push_str(item);
+    }
+    if let (Some(more), true) = (&more, pages.len() > 1) {
+        buf.push_str(SEPARATOR);
+        buf.push_str(more);
+    }
+}
@@ -1,169 +1,294 @@
-use crate::direction::Direction;
+use crate::action::Action;
 use crate::editor::Editor;
 use crate::Result;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
-pub fn process_key(editor: &mut Editor, key_event: KeyEvent) -> Result<()> {
-    // 退出确认模式
-    if editor.exit_confirm_prompt {
-        return super::prompt::handle_exit_confirm(editor, key_event);
-    }
-    // 文件名输入模式
-    if editor.file_save_prompt.is_some() {
-        return super::prompt::handle_file_save(editor, key_event);
+/// 判断按键事件是否需要处理
+///
+/// Windows 终端会为每次按键同时发送 Press 和 Release 事件，如果都处理就会出现
+/// 字符重复输入。这里只丢弃 Release；按住按键时的自动重复（Repeat）照常处理，
+/// 所以连续输入 "ll"、"==" 之类的重复字符不受影响。
+pub fn is_actionable(key_event: &KeyEvent) -> bool {
+    matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat)
+}
+
+/// 一批终端事件整理后的输入
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Event(Event),
+    /// 一次插入的文字：输入法提交或死键组合
+    Text(String),
+    /// 终端括号粘贴送来的整段内容
+    Paste(String),
+    /// 同一批到达的相同移动键（按住方向键时的自动重复），合并成一次多步移动
+    Repeat(KeyEvent, usize),
+}
+
+/// 方向键和翻页键，按住时同一批里的相同按键可以合并
+fn movement_key(event: &Event) -> Option<KeyEvent> {
+    match event {
+        Event::Key(key_event)
+            if is_actionable(key_event)
+                && matches!(
+                    key_event.code,
+                    KeyCode::Up
+                        | KeyCode::Down
+                        | KeyCode::Left
+                        | KeyCode::Right
+                        | KeyCode::PageUp
+                        | KeyCode::PageDown
+                ) =>
+        {
+            Some(*key_event)
+        }
+        _ => None,
     }
+}
 
-    match key_event {
-        KeyEvent {
-            code: KeyCode::Char('x'),
-            modifiers: KeyModifiers::CONTROL,
-            ..
-        } => {
-            if editor.buffer.modified {
-                editor.exit_confirm_prompt = true;
-                editor.status_message = "文件已修改，是否保存？Y=保存 N=不保存 ^C=取消".to_string();
-            } else {
-                editor.should_quit = true;
+/// 两个按键是否是同一个键（不管是按下还是自动重复）
+fn same_key(a: &KeyEvent, b: &KeyEvent) -> bool {
+    a.code == b.code && a.modifiers == b.modifiers
+}
+
+/// 不带修饰键（或只带 Shift）的普通字符
+fn plain_char(event: &Event) -> Option<char> {
+    match event {
+        Event::Key(key_event)
+            if is_actionable(key_event)
+                && matches!(
+                    key_event.modifiers,
+                    KeyModifiers::NONE | KeyModifiers::SHIFT
+                ) =>
+        {
+            match key_event.code {
+                KeyCode::Char(ch) => Some(ch),
+                _ => None,
             }
         }
-        KeyEvent {
-            code: KeyCode::Char('o'),
-            modifiers: KeyModifiers::CONTROL,
-            ..
-        } => {
-            let init_filename = editor
-                .buffer
-                .filename
-                .as_ref()
-                .and_then(|p| p.to_str())
-                .unwrap_or("");
-            editor.file_save_prompt = Some("请输入要保存的文件名（按 ESC 取消）:".to_string());
-            editor.file_save_input = init_filename.to_string();
-        }
-        KeyEvent {
-            code: KeyCode::Char('c'),
-            modifiers: KeyModifiers::ALT,
-            ..
-        } => {
-            editor.buffer.toggle_secondary_cursor();
-            editor.status_message = if editor.buffer.cursor_x2.is_some() {
-                "多光标已启用".to_string()
-            } else {
-                "多光标已关闭".to_string()
-            };
-        }
-        // Ctrl+G 打开帮助页面
-        KeyEvent {
-            code: KeyCode::Char('g'),
-            modifiers: KeyModifiers::CONTROL,
-            ..
-        } => {
-            editor.show_help_page = true;
-            editor.help_page_drawn = false; // 确保下次会重新绘制帮助页面
-            editor.status_message = "按任意键返回编辑器".to_string();
-            return Ok(());
-        }
-        KeyEvent {
-            code: KeyCode::Up,
-            modifiers: KeyModifiers::ALT,
-            ..
-        } => {
-            editor
-                .buffer
-                .move_cursor(Direction::Up, editor.terminal_size, true);
-        }
-        KeyEvent {
-            code: KeyCode::Down,
-            modifiers: KeyModifiers::ALT,
-            ..
-        } => {
-            editor
-                .buffer
-                .move_cursor(Direction::Down, editor.terminal_size, true);
-        }
-        KeyEvent {
-            code: KeyCode::Left,
-            modifiers: KeyModifiers::ALT,
-            ..
-        } => {
-            editor
-                .buffer
-                .move_cursor(Direction::Left, editor.terminal_size, true);
-        }
-        KeyEvent {
-            code: KeyCode::Right,
-            modifiers: KeyModifiers::ALT,
-            ..
-        } => {
-            editor
-                .buffer
-                .move_cursor(Direction::Right, editor.terminal_size, true);
+        _ => None,
+    }
+}
+
+/// 把同一批到达的连续普通字符合并成一次插入
+///
+/// 输入法提交的词组和死键组合出的字符会作为一串字符事件同时到达；其中含非 ASCII
+/// 字符的连续片段合并为一段文字，纯 ASCII 的片段（通常是快速打字）仍逐键处理。
+/// 粘贴事件单独成为一个 `Input::Paste`；连续的相同移动键合并成一个 `Input::Repeat`。
+pub fn group_events(events: Vec<Event>) -> Vec<Input> {
+    let mut inputs = Vec::with_capacity(events.len());
+    let mut run: Vec<Event> = Vec::new();
+    let flush = |run: &mut Vec<Event>, inputs: &mut Vec<Input>| {
+        let text: String = run.iter().filter_map(plain_char).collect();
+        if run.len() > 1 && !text.is_ascii() {
+            inputs.push(Input::Text(text));
+            run.clear();
+        } else {
+            inputs.extend(run.drain(..).map(Input::Event));
         }
-        KeyEvent {
-            code: KeyCode::Up,
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            editor
-                .buffer
-                .move_cursor(Direction::Up, editor.terminal_size, false);
+    };
+    for event in events {
+        if plain_char(&event).is_some() {
+            run.push(event);
+            continue;
         }
-        KeyEvent {
-            code: KeyCode::Down,
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            editor
-                .buffer
-                .move_cursor(Direction::Down, editor.terminal_size, false);
+        flush(&mut run, &mut inputs);
+        if let Some(key) = movement_key(&event) {
+            let merged = match inputs.last() {
+                Some(Input::Repeat(last, count)) if same_key(last, &key) => {
+                    Some(Input::Repeat(*last, count + 1))
+                }
+                Some(Input::Event(last))
+                    if movement_key(last).is_some_and(|last| same_key(&last, &key)) =>
+                {
+                    Some(Input::Repeat(key, 2))
+                }
+                _ => None,
+            };
+            if let (Some(merged), Some(last)) = (merged, inputs.last_mut()) {
+                *last = merged;
+                continue;
+            }
         }
-        KeyEvent {
-            code: KeyCode::Left,
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            editor
-                .buffer
-                .move_cursor(Direction::Left, editor.terminal_size, false);
+        match event {
+            Event::Paste(text) => inputs.push(Input::Paste(text)),
+            event => inputs.push(Input::Event(event)),
         }
-        KeyEvent {
-            code: KeyCode::Right,
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            editor
-                .buffer
-                .move_cursor(Direction::Right, editor.terminal_size, false);
+    }
+    flush(&mut run, &mut inputs);
+    inputs
+}
+
+/// 插入一段文字：提示中逐字输入，编辑区中作为一次修改插入
+pub fn process_text(editor: &mut Editor, text: &str) -> Result<()> {
+    if editor.prompt.is_some() {
+        for ch in text.chars().filter(|ch| !ch.is_control()) {
+            let key_event = KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE);
+            super::prompt::handle_prompt_key(editor, key_event)?;
         }
-        KeyEvent {
-            code: KeyCode::Enter,
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            editor.buffer.insert_newline();
+        return Ok(());
+    }
+    if super::terminal::is_focused(editor) {
+        super::terminal::send_text(editor, text);
+        return Ok(());
+    }
+    if editor.panel.as_ref().is_some_and(|p| p.focused) {
+        return Ok(());
+    }
+    if editor.buffer.is_read_only() {
+        editor.notify_error("缓冲区只读");
+        return Ok(());
+    }
+    // 与 Action::InsertChar 一样参与撤销分组和历史上限，并记作一次输入
+    super::undo::before_edit(editor);
+    let mut count = 0;
+    let mut blocked = false;
+    for ch in text.chars() {
+        editor.last_action = Some(Action::InsertChar(ch));
+        match ch {
+            '\n' => editor.buffer.insert_newline(),
+            '\t' => editor.buffer.insert_char_at_all_cursors('\t'),
+            '\r' => {}
+            ch if ch.is_control() => {}
+            ch => {
+                if !editor.type_char(ch) {
+                    blocked = true;
+                    break;
+                }
+            }
         }
-        KeyEvent {
-            code: KeyCode::Backspace,
-            modifiers: KeyModifiers::NONE,
-            ..
-        } => {
-            editor.buffer.delete_char();
+        count += 1;
+    }
+    super::undo::after_edit(editor);
+    if blocked {
+        // 保留行长上限的错误提示
+        editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+        return Ok(());
+    }
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+    // 状态栏提示刚刚组合输入的内容，便于确认输入法提交了什么
+    let mut preview: String = text.chars().take(COMPOSE_HINT_CHARS).collect();
+    if count > COMPOSE_HINT_CHARS {
+        preview.push('…');
+    }
+    editor.status_message = format!("组合输入 “{}”（{} 个字符）", preview, count);
+    Ok(())
+}
+
+/// 状态栏组合输入提示最多显示的字符数
+const COMPOSE_HINT_CHARS: usize = 16;
+
+/// 插入括号粘贴的内容：编辑区中整段作为一次修改插入，一次撤销即可还原
+///
+/// 不经过逐键处理，所以粘贴的代码不会被自动缩进、自动补全括号等再改一遍。
+/// 提示、终端和面板中与组合输入的处理相同。
+pub fn process_paste(editor: &mut Editor, text: &str) -> Result<()> {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let editing = editor.prompt.is_none()
+        && !super::terminal::is_focused(editor)
+        && !editor.panel.as_ref().is_some_and(|p| p.focused);
+    if !editing {
+        return process_text(editor, &text);
+    }
+    if editor.buffer.is_read_only() {
+        editor.notify_error("缓冲区只读");
+        return Ok(());
+    }
+    let text: String = text
+        .chars()
+        .filter(|&ch| matches!(ch, '\n' | '\t') || !ch.is_control())
+        .collect();
+    if text.is_empty() {
+        return Ok(());
+    }
+    if let Some(y) = editor.buffer.insert_exceeds_line_limit(&text) {
+        let limit = editor.buffer.settings.max_line_length;
+        editor.notify_error(format!(
+            "粘贴后第 {} 行会超过 {} 个字符的上限",
+            y + 1,
+            limit
+        ));
+        return Ok(());
+    }
+    // 与按键的修改一样参与撤销分组和历史上限，并记作一次输入，
+    // 之后的 Alt+Y 和 ^K 不会把它当成紧接着的粘贴或剪切
+    super::undo::before_edit(editor);
+    editor.buffer.insert_text(&text);
+    super::undo::after_edit(editor);
+    editor.last_action = text.chars().last().map(Action::InsertChar);
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+    editor.status_message = format!(
+        "已粘贴 {} 行（{} 个字符）",
+        text.split('\n').count(),
+        text.chars().count()
+    );
+    Ok(())
+}
+
+pub fn process_key(editor: &mut Editor, key_event: KeyEvent) -> Result<()> {
+    // 提示模式下按键全部交给提示处理
+    if editor.prompt.is_some() {
+        return super::prompt::handle_prompt_key(editor, key_event);
+    }
+    // 终端有焦点时除了切换终端的键，按键都发给 shell
+    if super::terminal::is_focused(editor) {
+        if editor.keymap.lookup(&key_event) == Some(Action::ToggleTerminal) {
+            super::terminal::toggle(editor);
+        } else {
+            super::terminal::send_key(editor, key_event);
         }
-        KeyEvent {
-            code: KeyCode::Char(ch),
-            modifiers: KeyModifiers::CONTROL,
-            ..
-        } if editor.buffer.cursor_x2.is_some() && editor.buffer.cursor_y2.is_some() => {
-            editor.buffer.insert_char_at_both_cursors(ch);
+        return Ok(());
+    }
+    // 面板有焦点时按键用于在面板中选择
+    if editor.panel.as_ref().is_some_and(|p| p.focused) {
+        super::panel::handle_panel_key(editor, key_event);
+        return Ok(());
+    }
+    if editor.pager && super::pager::handle_key(editor, key_event)? {
+        return Ok(());
+    }
+    if let Some(name) = editor.keymap.macro_for(&key_event) {
+        let name = name.to_string();
+        return super::macros::run_named(editor, &name);
+    }
+    if let Some(action) = resolve_key(editor, &key_event) {
+        editor.execute(action)?;
+    }
+    Ok(())
+}
+
+/// 处理合并起来的 count 次相同按键：编辑区中的光标移动一次走完再滚动，
+/// 其他情况（提示、面板、绑定成别的操作等）逐次处理
+pub fn process_repeat(editor: &mut Editor, key_event: KeyEvent, count: usize) -> Result<()> {
+    let editing = editor.prompt.is_none()
+        && !super::terminal::is_focused(editor)
+        && !editor.panel.as_ref().is_some_and(|p| p.focused)
+        && !editor.pager;
+    match resolve_key(editor, &key_event) {
+        Some(action @ Action::MoveCursor(direction)) if editing => {
+            super::commands::move_cursor(editor, direction, count);
+            editor.last_action = Some(action);
         }
-        KeyEvent {
-            code: KeyCode::Char(ch),
-            modifiers,
-            ..
-        } if modifiers == KeyModifiers::NONE || modifiers == KeyModifiers::SHIFT => {
-            editor.buffer.insert_char(ch);
+        _ => {
+            for _ in 0..count {
+                process_key(editor, key_event)?;
+            }
         }
-        _ => {}
     }
     Ok(())
 }
+
+/// 把按键解析成操作：先查键位表，再把普通字符解析为插入
+pub fn resolve_key(editor: &Editor, key_event: &KeyEvent) -> Option<Action> {
+    if let Some(action) = editor.keymap.lookup(key_event) {
+        return Some(action);
+    }
+    match key_event.code {
+        KeyCode::Char(ch)
+            if key_event.modifiers == KeyModifiers::NONE
+                || key_event.modifiers == KeyModifiers::SHIFT =>
+        {
+            Some(Action::InsertChar(ch))
+        }
+        _ => None,
+    }
+}
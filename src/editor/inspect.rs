@@ -0,0 +1,126 @@
+use crate::buffer::Position;
+use crate::charinfo;
+use crate::editor::Editor;
+use crate::rawbytes;
+use crate::unicode::{cell_width, display_column, grapheme_bounds, str_width};
+use std::fmt::Write as _;
+
+/// 在状态栏显示光标处字素簇的码位、UTF-8 字节、名称、显示宽度和在文件中的字节偏移
+pub fn inspect_char(editor: &mut Editor) {
+    let buffer = &editor.buffer;
+    let Some(line) = buffer.lines.get(buffer.cursor_y) else {
+        editor.status_message = "光标在文件末尾之后".to_string();
+        return;
+    };
+    let chars: Vec<char> = line.chars().collect();
+    if buffer.cursor_x >= chars.len() {
+        let offset = byte_offset(&buffer.lines, buffer.cursor_y, chars.len());
+        editor.status_message = if buffer.cursor_y + 1 < buffer.lines.len() {
+            format!("行尾：换行符 U+000A LINE FEED，字节偏移 {}", offset)
+        } else {
+            format!("文件末尾，字节偏移 {}", offset)
+        };
+        return;
+    }
+    let bounds = grapheme_bounds(&chars);
+    let start = bounds
+        .iter()
+        .rposition(|&b| b <= buffer.cursor_x)
+        .unwrap_or(0);
+    let cluster = &chars[bounds[start]..bounds[start + 1]];
+    let offset = byte_offset(&buffer.lines, buffer.cursor_y, bounds[start]);
+
+    let mut message = String::new();
+    // 看得见的字符先显示字符本身，控制字符和零宽字符只显示码位
+    let text: String = cluster
+        .iter()
+        .copied()
+        .map(rawbytes::display_char)
+        .collect();
+    if str_width(&text) > 0 && !cluster.iter().any(|c| c.is_control()) {
+        let _ = write!(message, "“{}” ", text);
+    }
+    let mut bytes = Vec::new();
+    for (i, &ch) in cluster.iter().enumerate() {
+        if i > 0 {
+            message.push_str(" + ");
+        }
+        match rawbytes::byte_of(ch) {
+            Some(byte) => {
+                let _ = write!(message, "无效字节 0x{:02X}", byte);
+                bytes.push(byte);
+            }
+            None => {
+                let _ = write!(message, "U+{:04X}", ch as u32);
+                let description = charinfo::describe(ch);
+                if !description.is_empty() {
+                    let _ = write!(message, " {}", description);
+                }
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    message.push_str("，UTF-8");
+    for byte in bytes {
+        let _ = write!(message, " {:02X}", byte);
+    }
+    let tab_width = buffer.settings.tab_width;
+    let column = display_column(line, bounds[start], tab_width);
+    let width: usize = cluster
+        .iter()
+        .map(|&ch| cell_width(ch, column, tab_width))
+        .sum();
+    let _ = write!(message, "，宽 {} 列，字节偏移 {}", width, offset);
+    editor.status_message = message;
+}
+
+/// 跳到光标之后的下一个可疑字符（双向控制符、零宽字符、形似 ASCII 的字符），到末尾后从头继续
+pub fn next_suspicious(editor: &mut Editor) {
+    let buffer = &editor.buffer;
+    let mut found = Vec::new();
+    for (y, line) in buffer.lines.iter().enumerate() {
+        for (x, ch) in line.chars().enumerate() {
+            if let Some(kind) = charinfo::suspicious(ch) {
+                found.push((Position::new(x, y), kind));
+            }
+        }
+    }
+    if found.is_empty() {
+        editor.status_message = "没有可疑的 Unicode 字符".to_string();
+        return;
+    }
+    let cursor = buffer.cursor_position();
+    let index = found.iter().position(|&(pos, _)| pos > cursor).unwrap_or(0);
+    let (pos, kind) = found[index];
+    editor.buffer.mark = None;
+    editor.buffer.set_cursor_position(pos);
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+    inspect_char(editor);
+    editor.status_message = format!(
+        "{}/{} {}：{}",
+        index + 1,
+        found.len(),
+        kind.describe(),
+        editor.status_message
+    );
+}
+
+/// 第 y 行第 x 个字符在保存后的文件中的字节偏移，行之间按一个换行符计算
+fn byte_offset(lines: &[String], y: usize, x: usize) -> usize {
+    let before: usize = lines[..y]
+        .iter()
+        .map(|line| encoded_len(line.chars()) + 1)
+        .sum();
+    before + encoded_len(lines[y].chars().take(x))
+}
+
+/// 一串字符保存后的字节数，无效字节的转义字符只占一个字节
+fn encoded_len(chars: impl Iterator<Item = char>) -> usize {
+    chars
+        .map(|ch| match rawbytes::byte_of(ch) {
+            Some(_) => 1,
+            None => ch.len_utf8(),
+        })
+        .sum()
+}
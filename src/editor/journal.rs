@@ -0,0 +1,111 @@
+use crate::editor::{Editor, Panel, PanelKind};
+use crate::journal::{Journal, JournalEvent};
+use crate::paths;
+use crate::render::{SpanStyle, StyledLine};
+use std::path::Path;
+
+fn journal() -> Option<Journal> {
+    paths::journal_dir().map(Journal::new)
+}
+
+/// 开启 journal 时给当前缓冲区的文件记一条日志，未命名的缓冲区不记录
+pub fn record(editor: &mut Editor, event: JournalEvent, detail: &str) {
+    let Some(path) = editor.buffer.filename.clone() else {
+        return;
+    };
+    record_for(editor, &path, event, detail);
+}
+
+/// 给指定文件记一条日志
+pub fn record_for(editor: &mut Editor, path: &Path, event: JournalEvent, detail: &str) {
+    if !editor.journal {
+        return;
+    }
+    let Some(journal) = journal() else {
+        return;
+    };
+    if let Err(e) = journal.append(path, event, detail) {
+        editor.notify_error(e.to_string());
+        return;
+    }
+    if let Some(panel) = editor
+        .panel
+        .as_mut()
+        .filter(|p| p.kind == PanelKind::Journal)
+    {
+        panel.revision = None;
+    }
+}
+
+/// 保存记录的说明，例如 `+12 字节，修改 3 行`；old_size 为 None 表示新文件
+pub fn save_detail(old_size: Option<u64>, new_size: u64, modified_lines: usize) -> String {
+    match old_size {
+        Some(old) => format!(
+            "{:+} 字节，修改 {} 行",
+            new_size as i64 - old as i64,
+            modified_lines
+        ),
+        None => format!("新文件，{} 字节", new_size),
+    }
+}
+
+/// 开关日志查看面板，打开时焦点在面板上
+pub fn toggle_viewer(editor: &mut Editor) {
+    if editor
+        .panel
+        .as_ref()
+        .is_some_and(|p| p.kind == PanelKind::Journal)
+    {
+        editor.panel = None;
+        return;
+    }
+    if editor.buffer.filename.is_none() {
+        editor.notify_error("未命名的缓冲区没有编辑日志");
+        return;
+    }
+    let mut panel = Panel::new(PanelKind::Journal, "编辑日志");
+    panel.focused = true;
+    editor.panel = Some(panel);
+    update_viewer(editor);
+    if !editor.journal {
+        editor.status_message = "编辑日志未开启，在配置文件中设置 journal = true".to_string();
+    }
+}
+
+/// 需要时重新读取日志，最新的记录在最上面
+pub fn update_viewer(editor: &mut Editor) {
+    let Some(panel) = editor.panel.as_mut() else {
+        return;
+    };
+    if panel.revision.is_some() {
+        return;
+    }
+    panel.revision = Some(editor.buffer.revision);
+    panel.lines.clear();
+    panel.selected = None;
+    let (Some(path), Some(journal)) = (&editor.buffer.filename, journal()) else {
+        return;
+    };
+    let mut entries = match journal.read(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            panel.title = e.to_string();
+            return;
+        }
+    };
+    entries.reverse();
+    for entry in &entries {
+        let mut line = StyledLine::new(None);
+        line.push(&entry.label(), SpanStyle::Normal);
+        panel.lines.push(line);
+    }
+    if panel.lines.is_empty() {
+        let mut line = StyledLine::new(None);
+        line.push("（没有记录）", SpanStyle::Dim);
+        panel.lines.push(line);
+    } else {
+        panel.selected = Some(0);
+    }
+    panel.scroll = 0;
+    panel.title = format!("编辑日志：{} 条（Esc 关闭）", entries.len());
+}
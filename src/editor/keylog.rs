@@ -0,0 +1,54 @@
+use crate::editor::{ui, Editor};
+use crate::keylog::{Recorder, Replay};
+use crate::Result;
+use crossterm::event::Event;
+use std::time::Duration;
+
+/// 按命令行参数开始记录或回放
+pub fn start(editor: &mut Editor) -> Result<()> {
+    if let Some(path) = editor.args.record.clone() {
+        editor.recorder = Some(Recorder::create(&path)?);
+    }
+    if let Some(path) = editor.args.replay.clone() {
+        let replay = Replay::load(&path)?;
+        editor.status_message = format!("回放 {}，按任意键中断", path.display());
+        editor.replay = Some(replay);
+    }
+    Ok(())
+}
+
+/// 等待下一个输入事件，最多等 timeout；回放中先交出记录里到时间的事件
+///
+/// 回放时用户按下任意键会中断回放，这个按键照常处理。开启记录时交出的事件都写入记录。
+pub fn next_event(editor: &mut Editor, timeout: Duration) -> Result<Option<Event>> {
+    let event = match editor.replay.as_mut() {
+        Some(replay) => {
+            if let Some(event) = replay.next_due() {
+                Some(event)
+            } else {
+                let wait = replay.wait_time().unwrap_or_default().min(timeout);
+                if ui::poll_event(wait)? {
+                    let played = replay.played;
+                    editor.replay = None;
+                    editor.status_message = format!("回放已中断（已回放 {} 个事件）", played);
+                    Some(ui::read_event()?)
+                } else {
+                    replay.next_due()
+                }
+            }
+        }
+        None if ui::poll_event(timeout)? => Some(ui::read_event()?),
+        None => None,
+    };
+    if editor.replay.as_ref().is_some_and(Replay::is_done) {
+        let played = editor.replay.take().map_or(0, |r| r.played);
+        editor.status_message = format!("回放结束，共 {} 个事件", played);
+    }
+    if let (Some(recorder), Some(event)) = (editor.recorder.as_mut(), &event) {
+        if !recorder.record(event) {
+            editor.recorder = None;
+            editor.notify_error("写入按键记录失败，已停止记录");
+        }
+    }
+    Ok(event)
+}
@@ -0,0 +1,47 @@
+use crate::editor::Editor;
+
+/// 预览中光标接近已读入部分的末尾（两屏以内）时继续读入
+pub fn stream_more(editor: &mut Editor) {
+    let margin = editor.terminal_size.1 as usize * 2;
+    while editor.buffer.preview.is_some()
+        && editor.buffer.cursor_y + margin >= editor.buffer.lines.len()
+    {
+        match editor.buffer.load_more_preview() {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => {
+                editor.notify_error(e.to_string());
+                break;
+            }
+        }
+    }
+}
+
+/// 完整读入预览中的大文件并允许编辑
+pub fn enable_editing(editor: &mut Editor) {
+    if editor.buffer.preview.is_none() {
+        editor.notify_error("当前缓冲区不是大文件预览");
+        return;
+    }
+    match editor.buffer.finish_preview() {
+        Ok(()) => {
+            editor.status_message = format!("已完整读入 {} 行，可以编辑", editor.buffer.lines.len())
+        }
+        Err(e) => editor.notify_error(e.to_string()),
+    }
+}
+
+/// 打开文件后的提示：以预览打开的大文件，或者含有无效 UTF-8 字节的文件
+pub fn open_notice(editor: &mut Editor) {
+    if let Some(preview) = &editor.buffer.preview {
+        editor.status_message = format!(
+            "文件有 {} MB，以只读预览打开；按 Alt+Shift+E 完整读入并编辑",
+            preview.size / (1 << 20)
+        );
+    } else if editor.buffer.invalid_bytes > 0 {
+        editor.status_message = format!(
+            "文件含有 {} 个无效的 UTF-8 字节，显示为 �，保存时原样写回",
+            editor.buffer.invalid_bytes
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use crate::buffer::Position;
+use crate::editor::Editor;
+use crate::location::{Location, LocationList, Step};
+
+/// 换上新的位置列表，替换之前的列表
+pub fn set_list(editor: &mut Editor, mut list: LocationList) {
+    // 相对当前缓冲区的项在这里固定下来，之后切换缓冲区也能跳回来
+    for item in &mut list.items {
+        if item.path.is_none() {
+            item.path.clone_from(&editor.buffer.filename);
+        }
+    }
+    editor.locations = Some(list);
+}
+
+/// 在位置列表中前进或后退一项并跳过去
+pub fn navigate(editor: &mut Editor, step: Step) {
+    let Some(list) = editor.locations.as_mut() else {
+        editor.notify_error("没有位置列表");
+        return;
+    };
+    let Some(item) = list.step(step).cloned() else {
+        let message = format!("{}：没有任何项", list.title);
+        editor.notify_error(message);
+        return;
+    };
+    let description = list.describe();
+    if !jump(editor, &item) {
+        return;
+    }
+    editor.status_message = description;
+}
+
+/// 跳到列表中的第 index 项，用于从面板中选择
+pub fn jump_to(editor: &mut Editor, index: usize) {
+    let Some(list) = editor.locations.as_mut() else {
+        return;
+    };
+    let Some(item) = list.items.get(index).cloned() else {
+        return;
+    };
+    list.current = Some(index);
+    let description = list.describe();
+    if jump(editor, &item) {
+        editor.status_message = description;
+    }
+}
+
+/// 跳到一项所在的文件和位置，文件打不开时返回 false
+fn jump(editor: &mut Editor, item: &Location) -> bool {
+    if let Some(path) = &item.path {
+        if editor.buffer.filename.as_ref() != Some(path) {
+            super::buffers::open_file(editor, path);
+            if editor.buffer.filename.as_ref() != Some(path) {
+                return false;
+            }
+        }
+    }
+    editor.buffer.mark = None;
+    editor.buffer.set_cursor_position(item.position);
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+    true
+}
+
+/// 把当前缓冲区中修改过的行按连续的段落生成位置列表
+pub fn list_changes(editor: &mut Editor) {
+    let line_count = editor.buffer.lines.len();
+    let mut lines: Vec<usize> = editor
+        .buffer
+        .modified_lines_set
+        .iter()
+        .copied()
+        .filter(|&y| y < line_count)
+        .collect();
+    lines.sort_unstable();
+    let mut items = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let start = lines[i];
+        let mut end = start;
+        while lines.get(i + 1) == Some(&(end + 1)) {
+            end += 1;
+            i += 1;
+        }
+        let message = if start == end {
+            format!("第 {} 行", start + 1)
+        } else {
+            format!("第 {}–{} 行", start + 1, end + 1)
+        };
+        items.push(Location::new(None, Position::new(0, start), message));
+        i += 1;
+    }
+    if items.is_empty() {
+        editor.notify_error("没有修改过的行");
+        return;
+    }
+    set_list(editor, LocationList::new("修改", items));
+    navigate(editor, Step::First);
+}
@@ -0,0 +1,219 @@
+// 键盘宏：录制、回放、命名保存，以及运行绑定到按键的宏
+
+use crate::action::Action;
+use crate::editor::{input, Editor, Prompt, PromptOption, PromptPurpose};
+use crate::keymap::KeyBinding;
+use crate::macros::{self, Macro, MacroStep};
+use crate::paths;
+use crate::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// 启动时读回保存的宏，读不出来时只在状态栏提示
+pub fn load_saved(editor: &mut Editor) {
+    let Some(path) = paths::macros_file() else {
+        return;
+    };
+    match macros::load(&path) {
+        Ok(saved) => editor.saved_macros = saved,
+        Err(e) => editor.notify_error(e.to_string()),
+    }
+}
+
+/// 录制中记下一个按键；回放宏时按的键不记录
+pub fn record_key(editor: &mut Editor, key_event: &KeyEvent) {
+    if let Some(steps) = &mut editor.recording {
+        macros::push_key(steps, KeyBinding::new(key_event.code, key_event.modifiers));
+    }
+}
+
+/// 录制中记下一段输入法提交或粘贴的文字
+pub fn record_text(editor: &mut Editor, text: &str) {
+    if let Some(steps) = &mut editor.recording {
+        macros::push_text(steps, text);
+    }
+}
+
+/// 开始录制；正在录制时停止，录下的按键成为最近的宏
+pub fn toggle_recording(editor: &mut Editor) {
+    if editor.playing_macro {
+        return;
+    }
+    let Some(mut steps) = editor.recording.take() else {
+        editor.recording = Some(Vec::new());
+        editor.status_message = "开始录制宏，再按 Alt+: 停止".to_string();
+        return;
+    };
+    // 停止录制的这个键也被记了下来
+    if let Some(MacroStep::Key(binding)) = steps.last() {
+        let key_event = KeyEvent::new(binding.code, binding.modifiers);
+        if editor.keymap.lookup(&key_event) == Some(Action::RecordMacro) {
+            steps.pop();
+        }
+    }
+    if steps.is_empty() {
+        editor.status_message = "已停止录制，宏为空".to_string();
+        return;
+    }
+    editor.status_message = format!("已录制 {} 步，Alt+; 运行，F4 起名保存", steps.len());
+    editor.last_macro = steps;
+}
+
+/// 运行最近录制的宏
+pub fn run_last(editor: &mut Editor) -> Result<()> {
+    if editor.recording.is_some() {
+        editor.notify_error("正在录制宏，先按 Alt+: 停止");
+        return Ok(());
+    }
+    if editor.last_macro.is_empty() {
+        editor.notify_error("还没有录制宏（Alt+: 开始录制）");
+        return Ok(());
+    }
+    let steps = editor.last_macro.clone();
+    play(editor, &steps)
+}
+
+/// 按名字查找宏：配置中定义的优先于保存的
+fn find<'a>(editor: &'a Editor, name: &str) -> Option<&'a Macro> {
+    editor
+        .macros
+        .iter()
+        .chain(&editor.saved_macros)
+        .find(|m| m.name == name)
+}
+
+/// 运行命名的宏，绑定到宏的按键走这里
+pub fn run_named(editor: &mut Editor, name: &str) -> Result<()> {
+    let Some(item) = find(editor, name) else {
+        editor.notify_error(format!("没有名为 {} 的宏", name));
+        return Ok(());
+    };
+    let steps = item.steps.clone();
+    editor.status_message.clear();
+    play(editor, &steps)?;
+    if editor.status_message.is_empty() {
+        editor.status_message = format!("已运行宏 {}", name);
+    }
+    Ok(())
+}
+
+/// 逐步回放：按键按当前键位执行，文字逐字输入，与手动操作的效果相同
+fn play(editor: &mut Editor, steps: &[MacroStep]) -> Result<()> {
+    // 宏里再运行宏会无限展开，回放期间不再进入
+    if editor.playing_macro {
+        editor.notify_error("宏中不能再运行宏");
+        return Ok(());
+    }
+    editor.playing_macro = true;
+    // 宏的修改不与之前的输入合并成一步撤销
+    editor.buffer.history.break_group();
+    let result = play_steps(editor, steps);
+    editor.playing_macro = false;
+    result
+}
+
+fn play_steps(editor: &mut Editor, steps: &[MacroStep]) -> Result<()> {
+    for step in steps {
+        match step {
+            MacroStep::Key(binding) => {
+                input::process_key(editor, KeyEvent::new(binding.code, binding.modifiers))?;
+            }
+            MacroStep::Text(text) => {
+                for ch in text.chars() {
+                    let code = match ch {
+                        '\n' => KeyCode::Enter,
+                        '\t' => KeyCode::Tab,
+                        ch => KeyCode::Char(ch),
+                    };
+                    input::process_key(editor, KeyEvent::new(code, KeyModifiers::NONE))?;
+                }
+            }
+        }
+        if editor.should_quit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 询问最近录制的宏的名字
+pub fn open_save_prompt(editor: &mut Editor) {
+    if editor.last_macro.is_empty() {
+        editor.notify_error("还没有录制宏（Alt+: 开始录制）");
+        return;
+    }
+    editor.prompt = Some(Prompt::text(PromptPurpose::SaveMacro, "宏名称: ", ""));
+}
+
+/// 以 name 保存最近录制的宏，同名的已保存宏被替换
+pub fn save(editor: &mut Editor, name: &str) {
+    let name = name.trim();
+    if name.is_empty() {
+        editor.notify_error("宏名称不能为空");
+        return;
+    }
+    if editor.macros.iter().any(|m| m.name == name) {
+        editor.notify_error(format!("配置的 [macros] 中已有宏 {}", name));
+        return;
+    }
+    let Some(path) = paths::macros_file() else {
+        editor.notify_error("没有可用的状态目录，无法保存宏");
+        return;
+    };
+    let item = Macro {
+        name: name.to_string(),
+        steps: editor.last_macro.clone(),
+    };
+    let mut saved = editor.saved_macros.clone();
+    match saved.iter_mut().find(|m| m.name == name) {
+        Some(old) => *old = item,
+        None => saved.push(item),
+    }
+    match macros::save(&path, &saved) {
+        Ok(()) => {
+            editor.saved_macros = saved;
+            editor.status_message = format!(
+                "已保存宏 {}，可在配置 [keys] 中绑定，如 \"F3\" = \"macro:{}\"",
+                name, name
+            );
+        }
+        Err(e) => editor.notify_error(e.to_string()),
+    }
+}
+
+/// 列出配置中和保存的宏，选中的一个立即运行
+pub fn open_list(editor: &mut Editor) {
+    let keys = ('1'..='9').chain('a'..='z');
+    let options: Vec<PromptOption> = editor
+        .macros
+        .iter()
+        .chain(&editor.saved_macros)
+        .zip(keys)
+        .map(|(item, key)| PromptOption {
+            key,
+            label: item.name.clone(),
+        })
+        .collect();
+    if options.is_empty() {
+        editor.notify_error("没有命名的宏（F4 保存录制的宏，或在配置 [macros] 中定义）");
+        return;
+    }
+    editor.prompt = Some(Prompt::choice(
+        PromptPurpose::RunMacro,
+        "运行哪个宏？",
+        options,
+    ));
+}
+
+/// 运行列表中选中的宏
+pub fn finish_list(editor: &mut Editor, index: usize) -> Result<()> {
+    let name = editor
+        .macros
+        .iter()
+        .chain(&editor.saved_macros)
+        .nth(index)
+        .map(|m| m.name.clone());
+    match name {
+        Some(name) => run_named(editor, &name),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,161 @@
+use crate::buffer::{MemoryUsage, TextBuffer, COMPACT_UNDO_STEPS};
+use crate::editor::{Editor, Panel, PanelKind};
+use crate::render::{SpanStyle, StyledLine};
+use crate::unicode::str_width;
+
+/// 可读的大小，例如 `512 B`、`12.3 KB`、`4.5 MB`
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// 按显示宽度右对齐
+fn pad(text: &str, width: usize) -> String {
+    format!(
+        "{}{}",
+        " ".repeat(width.saturating_sub(str_width(text))),
+        text
+    )
+}
+
+fn row(columns: [&str; 4], name: &str) -> String {
+    let mut row: String = columns.iter().map(|column| pad(column, 10)).collect();
+    row.push_str("  ");
+    row.push_str(name);
+    row
+}
+
+fn usage_row(usage: MemoryUsage, name: &str) -> String {
+    row(
+        [
+            &format_size(usage.text),
+            &format_size(usage.undo),
+            &format_size(usage.caches),
+            &format_size(usage.total()),
+        ],
+        name,
+    )
+}
+
+fn buffer_name(buffer: &TextBuffer) -> String {
+    let name = buffer
+        .filename
+        .as_ref()
+        .map_or_else(|| "新缓冲区".to_string(), |p| p.display().to_string());
+    format!("{}（可撤销 {} 步）", name, buffer.history.undo_steps())
+}
+
+/// 所有缓冲区（当前缓冲区在前）
+fn all_buffers(editor: &Editor) -> impl Iterator<Item = &TextBuffer> {
+    std::iter::once(&editor.buffer).chain(&editor.other_buffers)
+}
+
+/// 打开的缓冲区和剪切缓冲区合计占用的内存
+fn total_usage(editor: &Editor) -> usize {
+    all_buffers(editor)
+        .map(|buffer| buffer.memory_usage().total())
+        .sum::<usize>()
+        + editor.cutbuffer.heap_size()
+}
+
+/// 开关内存占用面板：逐个缓冲区列出文本、撤销历史和缓存大约占用的内存
+pub fn toggle_report(editor: &mut Editor) {
+    if editor
+        .panel
+        .as_ref()
+        .is_some_and(|p| p.kind == PanelKind::Memory)
+    {
+        editor.panel = None;
+        return;
+    }
+    let mut panel = Panel::new(PanelKind::Memory, "内存占用");
+    panel.bottom = true;
+    editor.panel = Some(panel);
+    update_report(editor);
+}
+
+/// 当前缓冲区变化或整理内存后重新统计
+pub fn update_report(editor: &mut Editor) {
+    let revision = editor.buffer.revision;
+    if editor
+        .panel
+        .as_ref()
+        .is_none_or(|p| p.revision == Some(revision))
+    {
+        return;
+    }
+    let mut lines = Vec::new();
+    let mut push = |text: &str, style: SpanStyle| {
+        let mut line = StyledLine::new(None);
+        line.push(text, style);
+        lines.push(line);
+    };
+    push(
+        &row(["文本", "撤销", "缓存", "合计"], "缓冲区"),
+        SpanStyle::Dim,
+    );
+    let mut total = MemoryUsage::default();
+    for buffer in all_buffers(editor) {
+        let usage = buffer.memory_usage();
+        total += usage;
+        push(&usage_row(usage, &buffer_name(buffer)), SpanStyle::Normal);
+    }
+    let cut = editor.cutbuffer.heap_size();
+    let cut_usage = MemoryUsage {
+        text: cut,
+        ..MemoryUsage::default()
+    };
+    total += cut_usage;
+    push(
+        &usage_row(
+            cut_usage,
+            &format!("剪切历史（{} 条）", editor.cutbuffer.len()),
+        ),
+        SpanStyle::Normal,
+    );
+    push(&usage_row(total, "合计"), SpanStyle::Normal);
+    let buffers = editor.other_buffers.len() + 1;
+    let panel = editor.panel.as_mut().expect("面板已打开");
+    panel.lines = lines;
+    panel.revision = Some(revision);
+    panel.title = format!(
+        "内存占用：{} 个缓冲区，共约 {}（Alt+Shift+Z 整理，Esc 关闭）",
+        buffers,
+        format_size(total.total())
+    );
+}
+
+/// 整理所有缓冲区的内存：收紧分配、丢弃可以重新生成的缓存，
+/// 撤销历史只保留最近 COMPACT_UNDO_STEPS 步
+pub fn compact(editor: &mut Editor) {
+    let before = total_usage(editor);
+    let mut dropped = editor.buffer.compact(COMPACT_UNDO_STEPS);
+    for buffer in &mut editor.other_buffers {
+        dropped += buffer.compact(COMPACT_UNDO_STEPS);
+    }
+    let after = total_usage(editor);
+    editor.status_message = if dropped > 0 {
+        format!(
+            "整理完成，释放约 {}，丢弃了 {} 步较早的撤销",
+            format_size(before.saturating_sub(after)),
+            dropped
+        )
+    } else {
+        format!(
+            "整理完成，释放约 {}",
+            format_size(before.saturating_sub(after))
+        )
+    };
+    if let Some(panel) = editor
+        .panel
+        .as_mut()
+        .filter(|p| p.kind == PanelKind::Memory)
+    {
+        panel.revision = None;
+    }
+}
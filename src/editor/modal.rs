@@ -0,0 +1,97 @@
+use super::ui::print_styled;
+use crate::editor::{Editor, Prompt};
+use crate::layout::Layout;
+use crate::render::{fit_to_width, truncate_to_width};
+use crate::theme::Style as ThemeStyle;
+use crate::unicode::str_width;
+use crossterm::{cursor, queue};
+use std::io::{self, Write};
+
+/// 居中显示在屏幕上的带边框对话框，打开时背景的编辑区变暗但仍然可见
+pub struct Modal<'a, S: AsRef<str>> {
+    /// 显示在上边框中的标题
+    pub title: &'a str,
+    pub lines: &'a [S],
+    /// 显示在下边框中的按键说明
+    pub footer: &'a str,
+    /// 内容放不下时从第几行开始显示
+    pub scroll: usize,
+}
+
+impl<S: AsRef<str>> Modal<'_, S> {
+    /// 屏幕上最多能显示的内容行数
+    pub fn visible_rows(layout: &Layout) -> usize {
+        layout.height.saturating_sub(2)
+    }
+
+    /// 共 line_count 行内容时最多能滚动到第几行
+    pub fn max_scroll(line_count: usize, layout: &Layout) -> usize {
+        line_count.saturating_sub(Self::visible_rows(layout))
+    }
+
+    pub fn draw<W: Write>(
+        &self,
+        out: &mut W,
+        style: &ThemeStyle,
+        layout: &Layout,
+        line: &mut String,
+    ) -> io::Result<()> {
+        // 边框和两侧各一格空白占 4 列，标题和按键说明嵌在边框线中，两侧各留一格
+        let content_width = self
+            .lines
+            .iter()
+            .map(|l| str_width(l.as_ref()))
+            .chain([str_width(self.title) + 2, str_width(self.footer) + 2])
+            .max()
+            .unwrap_or(0);
+        let box_width = (content_width + 4).min(layout.width);
+        let rows = self.lines.len().min(Self::visible_rows(layout));
+        if box_width < 5 || rows == 0 {
+            return Ok(());
+        }
+        let inner = box_width - 4;
+        let box_height = rows + 2;
+        let left = ((layout.width - box_width) / 2) as u16;
+        let top = ((layout.height - box_height) / 2) as u16;
+        let scroll = self.scroll.min(self.lines.len() - rows);
+
+        border(line, '┌', self.title, '┐', box_width);
+        queue!(out, cursor::MoveTo(left, top))?;
+        print_styled(out, line, style)?;
+        for (row, text) in self.lines[scroll..scroll + rows].iter().enumerate() {
+            line.clear();
+            line.push_str("│ ");
+            line.push_str(text.as_ref());
+            fit_to_width(line, inner + 2);
+            line.push_str(" │");
+            queue!(out, cursor::MoveTo(left, top + 1 + row as u16))?;
+            print_styled(out, line, style)?;
+        }
+        border(line, '└', self.footer, '┘', box_width);
+        queue!(out, cursor::MoveTo(left, top + 1 + rows as u16))?;
+        print_styled(out, line, style)
+    }
+}
+
+/// 上下边框，label 不为空时嵌在边框线中
+fn border(line: &mut String, left: char, label: &str, right: char, box_width: usize) {
+    line.clear();
+    line.push(left);
+    line.push('─');
+    if !label.is_empty() {
+        line.push(' ');
+        line.push_str(label);
+        line.push(' ');
+    }
+    truncate_to_width(line, box_width - 1);
+    let used = str_width(line);
+    line.extend(std::iter::repeat_n('─', box_width - 1 - used));
+    line.push(right);
+}
+
+/// 当前是否有对话框盖在编辑区上
+pub fn is_open(editor: &Editor) -> bool {
+    editor.show_help_page
+        || editor.setup.is_some()
+        || editor.prompt.as_ref().is_some_and(Prompt::is_modal)
+}
@@ -0,0 +1,261 @@
+// 大纲面板：列出文档的标题或代码的符号，输入文字模糊过滤，Enter 跳转，左右键收起、展开下级
+
+use crate::buffer::Position;
+use crate::editor::{Editor, Panel, PanelKind};
+use crate::outline::{self, OutlineItem};
+use crate::render::{SpanStyle, StyledLine};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// 打开大纲面板并把焦点交给它；已打开且有焦点时关闭
+pub fn toggle(editor: &mut Editor) {
+    if let Some(panel) = editor
+        .panel
+        .as_mut()
+        .filter(|p| p.kind == PanelKind::Outline)
+    {
+        if panel.focused {
+            editor.panel = None;
+        } else {
+            panel.focused = true;
+        }
+        return;
+    }
+    if !outline::has_outline(editor.buffer.filetype) {
+        editor.notify_error("当前文件类型没有大纲");
+        return;
+    }
+    let mut panel = Panel::new(PanelKind::Outline, "大纲");
+    panel.focused = true;
+    editor.panel = Some(panel);
+    update_panel(editor);
+    // 默认选中光标所在的一节的标题（或所在的函数等）
+    let cursor_y = editor.buffer.cursor_y;
+    let rows = super::panel::visible_rows(editor);
+    if let Some(panel) = editor.panel.as_mut() {
+        let current = panel
+            .lines
+            .iter()
+            .rposition(|l| l.source_line.is_some_and(|y| y <= cursor_y));
+        panel.selected = Some(current.unwrap_or(0));
+        super::panel::keep_selected_visible(panel, rows);
+    }
+}
+
+/// 第 i 项下面是否有下级的项
+fn has_children(items: &[OutlineItem], i: usize) -> bool {
+    items
+        .get(i + 1)
+        .is_some_and(|next| next.level > items[i].level)
+}
+
+/// 大纲面板特有的按键：输入文字过滤、Backspace 删字、Esc 清除过滤、左右键收起展开；
+/// 返回是否处理了按键，其余的按键按普通面板处理
+pub fn handle_key(editor: &mut Editor, key_event: KeyEvent) -> bool {
+    if key_event
+        .modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    {
+        return false;
+    }
+    let Some(panel) = editor.panel.as_mut() else {
+        return false;
+    };
+    match key_event.code {
+        KeyCode::Left | KeyCode::Right => {
+            fold(editor, key_event.code == KeyCode::Left);
+            return true;
+        }
+        KeyCode::Char(ch) => {
+            panel.filter.push(ch);
+            // 过滤条件变了，选中第一个匹配项
+            panel.lines.clear();
+            panel.selected = Some(0);
+        }
+        KeyCode::Backspace => {
+            panel.filter.pop();
+        }
+        KeyCode::Esc if !panel.filter.is_empty() => panel.filter.clear(),
+        _ => return false,
+    }
+    panel.revision = None;
+    let rows = super::panel::visible_rows(editor);
+    update_panel(editor);
+    if let Some(panel) = editor.panel.as_mut() {
+        super::panel::keep_selected_visible(panel, rows);
+    }
+    true
+}
+
+/// 添加标题文字，模糊匹配到的字符高亮
+fn push_title(styled: &mut StyledLine, title: &str, matched: &[usize], style: SpanStyle) {
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, ch) in title.chars().enumerate() {
+        let is_matched = matched.contains(&i);
+        if is_matched != run_matched && !run.is_empty() {
+            styled.push(&run, if run_matched { SpanStyle::Match } else { style });
+            run.clear();
+        }
+        run_matched = is_matched;
+        run.push(ch);
+    }
+    styled.push(&run, if run_matched { SpanStyle::Match } else { style });
+}
+
+/// 缓冲区变化、过滤或收起展开后重新生成大纲
+///
+/// 不过滤时收起的项下面的各级不显示；过滤时显示所有匹配的项，不管是否收起。
+pub fn update_panel(editor: &mut Editor) {
+    let Some(panel) = editor.panel.as_mut() else {
+        return;
+    };
+    if panel.revision == Some(editor.buffer.revision) {
+        return;
+    }
+    let filetype = editor.buffer.filetype;
+    let items = outline::outline(filetype, &editor.buffer.lines);
+    panel
+        .folded
+        .retain(|&y| items.iter().any(|item| item.line == y));
+    let selected_line = panel
+        .selected
+        .and_then(|i| panel.lines.get(i))
+        .and_then(|l| l.source_line);
+    panel.lines.clear();
+    let filtering = !panel.filter.is_empty();
+    let mut folded_level: Option<usize> = None;
+    for (i, item) in items.iter().enumerate() {
+        let matched = if filtering {
+            match outline::fuzzy_match(&panel.filter, &item.title) {
+                Some(matched) => matched,
+                None => continue,
+            }
+        } else {
+            if folded_level.is_some_and(|level| item.level > level) {
+                continue;
+            }
+            Vec::new()
+        };
+        let folded = !filtering && panel.folded.contains(&item.line);
+        folded_level = folded.then_some(item.level);
+        let marker = if folded {
+            "▸ "
+        } else if !filtering && has_children(&items, i) {
+            "▾ "
+        } else {
+            "  "
+        };
+        let mut styled = StyledLine::new(Some(item.line));
+        styled.push(&"  ".repeat(item.level - 1), SpanStyle::Normal);
+        styled.push(marker, SpanStyle::Dim);
+        if let Some(kind) = item.kind {
+            styled.push(&format!("{} ", kind), SpanStyle::Dim);
+        }
+        let style = if item.kind.is_none() && item.level == 1 {
+            SpanStyle::Heading
+        } else {
+            SpanStyle::Normal
+        };
+        push_title(&mut styled, &item.title, &matched, style);
+        panel.lines.push(styled);
+    }
+    let document = outline::is_document(filetype);
+    let (name, noun) = if document {
+        ("大纲", "个标题")
+    } else {
+        ("符号", "个符号")
+    };
+    if panel.lines.is_empty() {
+        let empty = if filtering {
+            "（没有匹配的项）"
+        } else if document {
+            "（没有标题）"
+        } else {
+            "（没有找到符号）"
+        };
+        let mut styled = StyledLine::new(None);
+        styled.push(empty, SpanStyle::Dim);
+        panel.lines.push(styled);
+    }
+    panel.title = if filtering {
+        format!(
+            "{}：“{}” 匹配 {}/{} {}（Backspace 删字，Esc 清除过滤）",
+            name,
+            panel.filter,
+            panel
+                .lines
+                .iter()
+                .filter(|l| l.source_line.is_some())
+                .count(),
+            items.len(),
+            noun
+        )
+    } else {
+        format!(
+            "{}：{} {}（输入文字过滤，Enter 跳转，←→ 收起/展开，Esc 关闭）",
+            name,
+            items.len(),
+            noun
+        )
+    };
+    panel.revision = Some(editor.buffer.revision);
+    // 尽量保持选中同一个标题
+    let same =
+        selected_line.and_then(|y| panel.lines.iter().position(|l| l.source_line == Some(y)));
+    panel.selected = same.or_else(|| {
+        let last = panel.lines.len() - 1;
+        panel.selected.map(|selected| selected.min(last))
+    });
+}
+
+/// 收起或展开选中的项；收起没有下级（或已收起）的项时选中它的上级
+pub fn fold(editor: &mut Editor, collapse: bool) {
+    let items = outline::outline(editor.buffer.filetype, &editor.buffer.lines);
+    let rows = super::panel::visible_rows(editor);
+    let Some(panel) = editor.panel.as_mut() else {
+        return;
+    };
+    let Some(y) = panel
+        .selected
+        .and_then(|i| panel.lines.get(i))
+        .and_then(|l| l.source_line)
+    else {
+        return;
+    };
+    let Some(index) = items.iter().position(|item| item.line == y) else {
+        return;
+    };
+    let folded = panel.folded.contains(&y);
+    if !collapse {
+        panel.folded.retain(|&line| line != y);
+    } else if has_children(&items, index) && !folded {
+        panel.folded.push(y);
+    } else {
+        let level = items[index].level;
+        let parent = items[..index].iter().rev().find(|item| item.level < level);
+        if let Some(parent) = parent {
+            panel.selected = panel
+                .lines
+                .iter()
+                .position(|l| l.source_line == Some(parent.line));
+        }
+    }
+    panel.revision = None;
+    update_panel(editor);
+    if let Some(panel) = editor.panel.as_mut() {
+        super::panel::keep_selected_visible(panel, rows);
+    }
+}
+
+/// 跳到第 y 行的标题或符号，它显示在编辑区顶部
+pub fn jump_to(editor: &mut Editor, y: usize) {
+    let Some(line) = editor.buffer.lines.get(y) else {
+        return;
+    };
+    // 光标放在缩进之后
+    let x = line.chars().take_while(|c| c.is_whitespace()).count();
+    editor.buffer.mark = None;
+    editor.buffer.set_cursor_position(Position::new(x, y));
+    editor.buffer.offset_y = y;
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+}
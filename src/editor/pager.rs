@@ -0,0 +1,99 @@
+use crate::action::Action;
+use crate::buffer::{Position, TextBuffer};
+use crate::editor::Editor;
+use crate::{EditorError, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::io::{self, IsTerminal, Read};
+
+/// 查看模式下状态栏的提示
+const PAGER_HINT: &str = "查看模式：空格/b 翻页，/ ? 搜索，n/N 下一个/上一个，q 退出，v 开始编辑";
+
+/// 查看模式没有给出文件、标准输入又不是终端时，读入标准输入作为唯一的缓冲区
+pub fn start(editor: &mut Editor) -> Result<()> {
+    if !editor.pager {
+        return Ok(());
+    }
+    if editor.args.files.is_empty() && !io::stdin().is_terminal() {
+        let mut bytes = Vec::new();
+        io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|source| EditorError::Io { path: None, source })?;
+        let mut buffer = TextBuffer::from_bytes(None, &bytes);
+        buffer.settings = editor
+            .defaults
+            .for_filetype(buffer.filetype, &editor.filetype_options);
+        editor.buffer = buffer;
+    }
+    editor.buffer.apply_ansi_colors();
+    for buffer in &mut editor.other_buffers {
+        buffer.apply_ansi_colors();
+    }
+    if editor.status_message.is_empty() {
+        editor.status_message = PAGER_HINT.to_string();
+    }
+    Ok(())
+}
+
+/// 上下滚动 delta 行，光标停在屏幕第一行；到底时最后一行停在屏幕底部
+fn scroll_by(editor: &mut Editor, delta: isize) {
+    let page = editor.layout().page_rows();
+    let buffer = &mut editor.buffer;
+    let max_offset = buffer.lines.len().saturating_sub(page);
+    let offset = buffer.offset_y.saturating_add_signed(delta).min(max_offset);
+    buffer.offset_y = offset;
+    buffer.mark = None;
+    buffer.set_cursor_position(Position::new(0, offset));
+}
+
+/// 查看模式下的按键，返回 true 表示已经处理，其他按键照常按键位表处理
+pub fn handle_key(editor: &mut Editor, key_event: KeyEvent) -> Result<bool> {
+    if !matches!(
+        key_event.modifiers,
+        KeyModifiers::NONE | KeyModifiers::SHIFT
+    ) {
+        return Ok(false);
+    }
+    let page = editor.layout().page_rows() as isize;
+    match key_event.code {
+        KeyCode::Char(' ' | 'f') | KeyCode::PageDown => scroll_by(editor, page),
+        KeyCode::Char('b') | KeyCode::PageUp => scroll_by(editor, -page),
+        KeyCode::Char('d') => scroll_by(editor, page / 2),
+        KeyCode::Char('u') => scroll_by(editor, -page / 2),
+        KeyCode::Char('j' | 'e') | KeyCode::Enter | KeyCode::Down => scroll_by(editor, 1),
+        KeyCode::Char('k' | 'y') | KeyCode::Up => scroll_by(editor, -1),
+        KeyCode::Char('g' | '<') | KeyCode::Home => scroll_by(editor, isize::MIN),
+        KeyCode::Char('G' | '>') | KeyCode::End => scroll_by(editor, isize::MAX),
+        KeyCode::Char('/') => editor.execute(Action::Search)?,
+        KeyCode::Char('?') => editor.execute(Action::SearchBackward)?,
+        KeyCode::Char('n') => editor.execute(Action::SearchNext)?,
+        KeyCode::Char('N') => editor.execute(Action::SearchPrev)?,
+        KeyCode::Char('q' | 'Q') => editor.should_quit = true,
+        KeyCode::Char('c') => toggle_ansi(editor),
+        KeyCode::Char('v') => start_editing(editor),
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// 切换按颜色显示还是按原样显示输入中的 ANSI 控制序列
+pub fn toggle_ansi(editor: &mut Editor) {
+    editor.status_message = match editor.buffer.toggle_ansi_literal() {
+        Some(true) => "按原样显示控制序列".to_string(),
+        Some(false) => "按颜色显示".to_string(),
+        None => "当前缓冲区没有 ANSI 颜色".to_string(),
+    };
+}
+
+/// 离开查看模式，之后打开的缓冲区也可以编辑；读自标准输入的内容可以用 ^O 另存
+fn start_editing(editor: &mut Editor) {
+    editor.pager = false;
+    editor.defaults.read_only = false;
+    editor.buffer.settings.read_only = false;
+    // 编辑后颜色位置不再对应，保留当前显示的文本，去掉颜色
+    editor.buffer.ansi = None;
+    for buffer in &mut editor.other_buffers {
+        buffer.settings.read_only = false;
+        buffer.ansi = None;
+    }
+    editor.status_message = "已进入编辑模式".to_string();
+}
@@ -0,0 +1,285 @@
+use crate::editor::Editor;
+use crate::markdown::render_preview;
+use crate::render::{truncate_to_width, SpanStyle, StyledLine};
+use crate::unicode::str_width;
+use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::style::{Attribute, Color, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::{cursor, queue, style};
+use std::io::{self, Write};
+
+/// 面板显示的内容种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelKind {
+    /// Markdown 预览
+    MarkdownPreview,
+    /// 选择快照时显示与当前内容的差异
+    SnapshotDiff,
+    /// 搜索结果：每个含匹配的行一项
+    SearchResults,
+    /// 当前文件的编辑日志
+    Journal,
+    /// 运行命令的输出，source_line 为位置列表中的序号
+    RunOutput,
+    /// 内置终端的屏幕
+    Terminal,
+    /// 打开的缓冲区中的 TODO 等标记，source_line 为位置列表中的序号
+    Tasks,
+    /// 各缓冲区的内存占用
+    Memory,
+    /// 文档的标题或代码的符号大纲
+    Outline,
+}
+
+/// 编辑区旁边的分屏面板，显示只读的带样式内容
+#[derive(Debug, Clone)]
+pub struct Panel {
+    pub kind: PanelKind,
+    pub title: String,
+    pub lines: Vec<StyledLine>,
+    /// 面板内容的滚动偏移
+    pub scroll: usize,
+    /// 生成内容时缓冲区的版本号，不一致时需要刷新
+    pub revision: Option<u64>,
+    /// 选中的行（列表类面板），反色显示
+    pub selected: Option<usize>,
+    /// 面板获得焦点时方向键在面板中移动选中行
+    pub focused: bool,
+    /// 停靠在编辑区下方，而不是右侧
+    pub bottom: bool,
+    /// 大纲中收起的标题所在的行
+    pub folded: Vec<usize>,
+    /// 大纲中输入的过滤文字，只显示模糊匹配的项
+    pub filter: String,
+}
+
+impl Panel {
+    pub fn new(kind: PanelKind, title: &str) -> Self {
+        Self {
+            kind,
+            title: title.to_string(),
+            lines: Vec::new(),
+            scroll: 0,
+            revision: None,
+            selected: None,
+            focused: false,
+            bottom: false,
+            folded: Vec::new(),
+            filter: String::new(),
+        }
+    }
+}
+
+/// 编辑区文本可用的宽度（有右侧面板时占左半边）
+pub fn text_area_width(editor: &Editor) -> usize {
+    let width = editor.terminal_size.0 as usize;
+    if editor.panel.as_ref().is_some_and(|p| !p.bottom) {
+        width - width / 2
+    } else {
+        width
+    }
+}
+
+/// 每帧绘制前更新面板内容，只在缓冲区变化后重新生成
+pub fn update_panel(editor: &mut Editor) {
+    let Some(panel) = editor.panel.as_mut() else {
+        return;
+    };
+    match panel.kind {
+        PanelKind::MarkdownPreview => {
+            if panel.revision != Some(editor.buffer.revision) {
+                panel.lines = render_preview(&editor.buffer.lines);
+                panel.revision = Some(editor.buffer.revision);
+            }
+            // 预览跟随编辑区滚动
+            panel.scroll = panel
+                .lines
+                .iter()
+                .position(|l| l.source_line.is_some_and(|y| y >= editor.buffer.offset_y))
+                .unwrap_or(panel.lines.len());
+        }
+        PanelKind::SnapshotDiff => super::snapshots::update_diff_panel(editor),
+        PanelKind::SearchResults => super::search::update_results_panel(editor),
+        PanelKind::Journal => super::journal::update_viewer(editor),
+        // 运行输出和任务列表在打开时一次生成
+        PanelKind::RunOutput | PanelKind::Tasks => {}
+        PanelKind::Terminal => super::terminal::update_panel(editor),
+        PanelKind::Memory => super::memory::update_report(editor),
+        PanelKind::Outline => super::outline::update_panel(editor),
+    }
+}
+
+/// 面板可见的内容行数（去掉标题行）
+pub fn visible_rows(editor: &Editor) -> usize {
+    let layout = editor.layout();
+    if editor.panel.as_ref().is_some_and(|p| p.bottom) {
+        layout.panel_rows.saturating_sub(1)
+    } else {
+        layout.text_rows.saturating_sub(1)
+    }
+}
+
+/// 调整滚动位置，让选中行保持可见
+pub fn keep_selected_visible(panel: &mut Panel, rows: usize) {
+    let Some(selected) = panel.selected else {
+        return;
+    };
+    if selected < panel.scroll {
+        panel.scroll = selected;
+    } else if rows > 0 && selected >= panel.scroll + rows {
+        panel.scroll = selected + 1 - rows;
+    }
+}
+
+/// 面板有焦点时处理按键：方向键移动选中行，Enter 跳转，Esc 关闭面板；大纲另有过滤和收起、展开
+pub fn handle_panel_key(editor: &mut Editor, key_event: KeyEvent) {
+    let rows = visible_rows(editor);
+    if editor
+        .panel
+        .as_ref()
+        .is_some_and(|p| p.kind == PanelKind::Outline)
+        && super::outline::handle_key(editor, key_event)
+    {
+        return;
+    }
+    let Some(panel) = editor.panel.as_mut() else {
+        return;
+    };
+    let last = panel.lines.len().saturating_sub(1);
+    let selected = panel.selected.unwrap_or(0);
+    panel.selected = Some(match key_event.code {
+        KeyCode::Up => selected.saturating_sub(1),
+        KeyCode::Down => (selected + 1).min(last),
+        KeyCode::PageUp => selected.saturating_sub(rows),
+        KeyCode::PageDown => (selected + rows).min(last),
+        KeyCode::Home => 0,
+        KeyCode::End => last,
+        KeyCode::Enter => {
+            panel.focused = false;
+            let kind = panel.kind;
+            let line = panel.lines.get(selected).and_then(|l| l.source_line);
+            match (kind, line) {
+                (PanelKind::SearchResults, Some(y)) => super::search::jump_to_line_match(editor, y),
+                (PanelKind::Outline, Some(y)) => super::outline::jump_to(editor, y),
+                (PanelKind::RunOutput | PanelKind::Tasks, Some(index)) => {
+                    super::locations::jump_to(editor, index)
+                }
+                _ => {}
+            }
+            return;
+        }
+        KeyCode::Esc => {
+            editor.panel = None;
+            return;
+        }
+        _ => selected,
+    });
+    keep_selected_visible(panel, rows);
+}
+
+/// 开关 Markdown 预览面板
+pub fn toggle_markdown_preview(editor: &mut Editor) {
+    if editor
+        .panel
+        .as_ref()
+        .is_some_and(|p| p.kind == PanelKind::MarkdownPreview)
+    {
+        editor.panel = None;
+        editor.status_message = "已关闭预览".to_string();
+    } else {
+        editor.panel = Some(Panel::new(PanelKind::MarkdownPreview, "Markdown 预览"));
+        editor.status_message = "已打开预览".to_string();
+    }
+}
+
+fn queue_span_style<W: Write>(out: &mut W, style: SpanStyle) -> io::Result<()> {
+    match style {
+        SpanStyle::Normal => Ok(()),
+        SpanStyle::Heading => queue!(
+            out,
+            SetForegroundColor(Color::Cyan),
+            SetAttribute(Attribute::Bold)
+        ),
+        SpanStyle::Bold => queue!(out, SetAttribute(Attribute::Bold)),
+        SpanStyle::Italic => queue!(out, SetAttribute(Attribute::Italic)),
+        SpanStyle::Code => queue!(out, SetForegroundColor(Color::Green)),
+        SpanStyle::Quote => queue!(out, SetForegroundColor(Color::Magenta)),
+        SpanStyle::Dim => queue!(out, SetForegroundColor(Color::DarkGrey)),
+        SpanStyle::Added => queue!(out, SetForegroundColor(Color::Green)),
+        SpanStyle::Removed => queue!(out, SetForegroundColor(Color::Red)),
+        SpanStyle::AddedChange => queue!(
+            out,
+            SetForegroundColor(Color::Green),
+            SetAttribute(Attribute::Reverse)
+        ),
+        SpanStyle::RemovedChange => queue!(
+            out,
+            SetForegroundColor(Color::Red),
+            SetAttribute(Attribute::Reverse)
+        ),
+        SpanStyle::Match => queue!(
+            out,
+            SetForegroundColor(Color::Yellow),
+            SetAttribute(Attribute::Bold)
+        ),
+        SpanStyle::Cursor => queue!(out, SetAttribute(Attribute::Reverse)),
+    }
+}
+
+/// 绘制面板的第 row 行（第 0 行为标题），column 为面板起始列，为 0 时是底部面板，不画分隔线
+pub fn draw_panel_row<W: Write>(
+    out: &mut W,
+    panel: &Panel,
+    row: usize,
+    column: usize,
+    width: usize,
+    scratch: &mut String,
+) -> io::Result<()> {
+    queue!(out, cursor::MoveToColumn(column as u16))?;
+    let mut inner = width.saturating_sub(column);
+    if column > 0 {
+        queue!(
+            out,
+            SetForegroundColor(Color::DarkGrey),
+            style::Print('│'),
+            ResetColor
+        )?;
+        inner = inner.saturating_sub(1);
+    }
+    if row == 0 {
+        scratch.clear();
+        scratch.push_str(&panel.title);
+        truncate_to_width(scratch, inner);
+        return queue!(
+            out,
+            SetAttribute(Attribute::Reverse),
+            style::Print(&*scratch),
+            SetAttribute(Attribute::Reset)
+        );
+    }
+    let index = panel.scroll + row - 1;
+    let Some(line) = panel.lines.get(index) else {
+        return Ok(());
+    };
+    let selected = panel.selected == Some(index);
+    let mut remaining = inner;
+    for span in &line.spans {
+        if remaining == 0 {
+            break;
+        }
+        scratch.clear();
+        scratch.push_str(&span.text);
+        truncate_to_width(scratch, remaining);
+        remaining -= str_width(scratch);
+        queue_span_style(out, span.style)?;
+        if selected {
+            queue!(out, SetAttribute(Attribute::Reverse))?;
+        }
+        queue!(
+            out,
+            style::Print(&*scratch),
+            SetAttribute(Attribute::Reset),
+            ResetColor
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use crate::editor::{Editor, Prompt, PromptOption, PromptPurpose};
+use crate::print::{self, PrintFormat};
+use crate::EditorError;
+use std::fs;
+use std::path::Path;
+
+/// 页眉中的标题：文件名，没有文件名时为“新缓冲区”
+fn title(editor: &Editor) -> String {
+    editor
+        .buffer
+        .filename
+        .as_ref()
+        .map_or_else(|| "新缓冲区".to_string(), |p| p.display().to_string())
+}
+
+/// 打开打印提示：发送到打印机，或导出为分页文本或 PostScript
+pub fn open_print_prompt(editor: &mut Editor) {
+    let options = [
+        ('p', "打印（lpr）"),
+        ('t', "导出分页文本"),
+        ('s', "导出 PostScript"),
+    ]
+    .into_iter()
+    .map(|(key, label)| PromptOption {
+        key,
+        label: label.to_string(),
+    })
+    .collect();
+    editor.prompt = Some(Prompt::choice(PromptPurpose::Print, "打印:", options));
+}
+
+/// 打印提示的选择：0 为打印机，1、2 为导出格式
+pub fn finish_print_choice(editor: &mut Editor, index: usize) {
+    let format = match index {
+        0 => {
+            print_buffer(editor);
+            return;
+        }
+        1 => PrintFormat::Text,
+        _ => PrintFormat::PostScript,
+    };
+    let default_path = format!(
+        "{}.{}",
+        editor
+            .buffer
+            .filename
+            .as_ref()
+            .map_or_else(|| "print".to_string(), |p| p.display().to_string()),
+        format.extension()
+    );
+    editor.prompt = Some(Prompt::text(
+        PromptPurpose::PrintExport(format),
+        "导出到文件（按 ESC 取消）:",
+        &default_path,
+    ));
+}
+
+fn print_buffer(editor: &mut Editor) {
+    let pages = print::paginate(&title(editor), &editor.buffer.lines, editor.print_options);
+    match print::send_to_printer(&print::to_text(&pages)) {
+        Ok(program) => {
+            editor.status_message = format!("已交给 {} 打印，共 {} 页", program, pages.len());
+        }
+        Err(e) => editor.notify_error(format!("打印失败: {}", e)),
+    }
+}
+
+/// 按格式导出到文件
+pub fn export(editor: &mut Editor, format: PrintFormat, path: &Path) {
+    let title = title(editor);
+    let pages = print::paginate(&title, &editor.buffer.lines, editor.print_options);
+    let text = match format {
+        PrintFormat::Text => print::to_text(&pages),
+        PrintFormat::PostScript => print::to_postscript(&title, &pages),
+    };
+    let Some(note) = super::trash::before_overwrite(editor, path) else {
+        return;
+    };
+    match fs::write(path, text) {
+        Ok(()) => {
+            editor.status_message =
+                format!("已导出到 {}，共 {} 页{}", path.display(), pages.len(), note);
+        }
+        Err(e) => editor.notify_error(EditorError::io(path, e).to_string()),
+    }
+}
@@ -0,0 +1,114 @@
+use crate::buffer::TextBuffer;
+use crate::editor::{Editor, Prompt, PromptOption, PromptOutcome, PromptPurpose};
+use crate::paths;
+use crate::project::{self, ProjectConfig, TrustStore};
+use crate::settings::BufferSettings;
+
+/// 缓冲区所在的、已经生效的项目配置，嵌套时取最近的一个
+pub fn project_for<'a>(
+    projects: &'a [ProjectConfig],
+    buffer: &TextBuffer,
+) -> Option<&'a ProjectConfig> {
+    let file = buffer.filename.as_ref()?;
+    projects
+        .iter()
+        .filter(|p| p.contains(file))
+        .max_by_key(|p| p.root.components().count())
+}
+
+/// 新打开的缓冲区的设置：默认设置和文件类型设置，叠加所在项目的配置，最后是检测到的缩进方式
+pub fn settings_for(editor: &Editor, buffer: &TextBuffer) -> BufferSettings {
+    let mut settings = editor
+        .defaults
+        .for_filetype(buffer.filetype, &editor.filetype_options);
+    if let Some(project) = project_for(&editor.projects, buffer) {
+        project.apply(&mut settings, buffer.filetype);
+    }
+    apply_detected_indent(editor.detect_indent, buffer, &mut settings);
+    settings
+}
+
+/// 开启 detect_indent 时用缓冲区中检测到的缩进方式覆盖设置
+fn apply_detected_indent(enabled: bool, buffer: &TextBuffer, settings: &mut BufferSettings) {
+    if let Some(style) = buffer.indent_style.filter(|_| enabled) {
+        style.apply(settings);
+    }
+}
+
+/// 当前缓冲区所在目录有还没决定是否使用的项目配置时，询问是否信任；
+/// 已经信任过（内容没有变化）的配置直接生效
+pub fn check(editor: &mut Editor) {
+    if paths::safe_mode() {
+        return;
+    }
+    let Some(path) = editor.buffer.filename.as_deref().and_then(project::find) else {
+        return;
+    };
+    let decided =
+        editor.projects.iter().any(|p| p.path == path) || editor.ignored_projects.contains(&path);
+    // 其他提示打开时先不问，切换缓冲区时再检查
+    if decided || editor.prompt.is_some() {
+        return;
+    }
+    let config = match ProjectConfig::load(&path) {
+        Ok(config) => config,
+        Err(e) => {
+            editor.ignored_projects.push(path);
+            editor.notify_error(format!("项目配置有误，未使用: {}", e));
+            return;
+        }
+    };
+    if TrustStore::load().is_trusted(&config) {
+        activate(editor, config);
+        return;
+    }
+    let options = [('y', "信任并记住"), ('o', "只在这次使用"), ('n', "不使用")]
+        .into_iter()
+        .map(|(key, label)| PromptOption {
+            key,
+            label: label.to_string(),
+        })
+        .collect();
+    let label = format!(
+        "发现项目配置 {}，其中的设置会覆盖你的配置，是否信任？",
+        path.display()
+    );
+    editor.pending_project = Some(config);
+    editor.prompt = Some(Prompt::choice(PromptPurpose::TrustProject, &label, options));
+}
+
+/// 处理信任提示的回答
+pub fn finish_trust(editor: &mut Editor, outcome: PromptOutcome) {
+    let Some(config) = editor.pending_project.take() else {
+        return;
+    };
+    match outcome {
+        PromptOutcome::Chosen(0) => {
+            if let Err(e) = TrustStore::load().trust(&config) {
+                editor.notify_error(e.to_string());
+            }
+            activate(editor, config);
+        }
+        PromptOutcome::Chosen(1) => activate(editor, config),
+        _ => {
+            editor.status_message = format!("未使用项目配置 {}", config.path.display());
+            editor.ignored_projects.push(config.path);
+        }
+    }
+}
+
+/// 让项目配置生效，并叠加到已经打开的、在项目目录下的缓冲区上
+fn activate(editor: &mut Editor, config: ProjectConfig) {
+    let detect_indent = editor.detect_indent;
+    let buffers = std::iter::once(&mut editor.buffer).chain(editor.other_buffers.iter_mut());
+    for buffer in buffers {
+        if buffer.filename.as_ref().is_some_and(|f| config.contains(f)) {
+            let mut settings = buffer.settings.clone();
+            config.apply(&mut settings, buffer.filetype);
+            apply_detected_indent(detect_indent, buffer, &mut settings);
+            buffer.settings = settings;
+        }
+    }
+    editor.status_message = format!("已使用项目配置 {}", config.path.display());
+    editor.projects.push(config);
+}
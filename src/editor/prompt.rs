@@ -1,61 +1,671 @@
-use crate::editor::Editor;
+use super::modal::Modal;
+use crate::buffer::Position;
+use crate::config::CONFIRM_NAMES;
+use crate::digraph;
+use crate::editor::{Editor, SetupStep, VersionSource};
+use crate::journal::JournalEvent;
+use crate::layout::Layout;
+use crate::print::PrintFormat;
+use crate::render::wrap_to_width;
+use crate::theme::Style as ThemeStyle;
+use crate::unicode::str_width;
 use crate::Result;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt::Write as _;
+use std::io::{self, Write};
 
-pub fn handle_exit_confirm(editor: &mut Editor, key_event: KeyEvent) -> Result<()> {
-    match key_event.code {
-        KeyCode::Char('y') | KeyCode::Char('Y') => {
-            let init_filename = editor
-                .buffer
-                .filename
-                .as_ref()
-                .and_then(|p| p.to_str())
-                .unwrap_or("");
-            editor.file_save_prompt = Some("请输入要保存的文件名（按 ESC 取消）:".to_string());
-            editor.file_save_input = init_filename.to_string();
-            editor.exit_confirm_prompt = false;
-            editor.status_message.clear();
+/// 提示结束后要执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptPurpose {
+    /// 另存为（^O）
+    SaveAs,
+    /// 退出前保存，保存成功后退出
+    SaveAndQuit,
+    /// 退出前询问是否保存
+    ExitConfirm,
+    /// 新保存的脚本询问是否设置可执行权限，then_quit 表示回答后退出
+    MakeExecutable { then_quit: bool },
+    /// 从剪切历史中选择要粘贴的一条
+    PasteHistory,
+    /// 在新缓冲区中打开文件
+    OpenFile,
+    /// 修改当前缓冲区的设置
+    SetOption,
+    /// 导出生效配置
+    DumpConfig,
+    /// 打印或选择导出格式
+    Print,
+    /// 导出打印格式的文件名
+    PrintExport(PrintFormat),
+    /// 导出 HTML 的文件名
+    ExportHtml,
+    /// 搜索
+    Search,
+    /// 输入二合字母代码
+    Digraph,
+    /// 跳到指定的行和列
+    GotoLine,
+    /// 替换：要查找的内容
+    ReplaceFind,
+    /// 替换：替换成的内容
+    ReplaceWith,
+    /// 替换：选择范围
+    ReplaceScope,
+    /// 替换：逐处确认
+    ReplaceConfirm,
+    /// 选择要恢复的快照或历史版本
+    RestoreVersion(VersionSource),
+    /// 上次异常退出留下快照时，选择恢复、查看差异或丢弃
+    Recover,
+    /// 是否信任发现的项目配置
+    TrustProject,
+    /// 首次运行设置的某一步
+    FirstRun(SetupStep),
+    /// 给录制的宏起名保存
+    SaveMacro,
+    /// 选择要运行的命名宏
+    RunMacro,
+}
+
+impl PromptPurpose {
+    /// 是/否提示在配置 [confirm] 中的名字，可以设置默认回答
+    pub fn confirm_name(self) -> Option<&'static str> {
+        match self {
+            PromptPurpose::ExitConfirm => Some("save_on_exit"),
+            PromptPurpose::MakeExecutable { .. } => Some("make_executable"),
+            _ => None,
         }
-        KeyCode::Char('n') | KeyCode::Char('N') => {
-            editor.should_quit = true;
-            editor.exit_confirm_prompt = false;
-            editor.status_message.clear();
+    }
+}
+
+/// 选择列表中的一个选项
+#[derive(Debug, Clone)]
+pub struct PromptOption {
+    /// 直接选中该项的按键
+    pub key: char,
+    pub label: String,
+}
+
+/// 通用提示状态，新功能只需新增 `PromptPurpose` 而不必给 `Editor` 加字段
+#[derive(Debug, Clone)]
+pub enum Prompt {
+    /// 单行文本输入
+    Text {
+        purpose: PromptPurpose,
+        label: String,
+        input: String,
+    },
+    /// 是/否/取消，default 为直接按回车时的回答
+    Confirm {
+        purpose: PromptPurpose,
+        label: String,
+        default: Option<bool>,
+    },
+    /// 从若干选项中选择一个
+    Choice {
+        purpose: PromptPurpose,
+        label: String,
+        options: Vec<PromptOption>,
+        selected: usize,
+    },
+}
+
+/// 提示对一次按键的处理结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptOutcome {
+    /// 仍在等待输入
+    Pending,
+    /// 用户取消（Esc 或 ^C）
+    Cancelled,
+    /// 文本提示提交的内容
+    Text(String),
+    /// 是/否提示的回答
+    Answer(bool),
+    /// 选择提示选中的下标
+    Chosen(usize),
+}
+
+impl Prompt {
+    pub fn text(purpose: PromptPurpose, label: &str, initial: &str) -> Self {
+        Prompt::Text {
+            purpose,
+            label: label.to_string(),
+            input: initial.to_string(),
         }
-        KeyCode::Char('c') if key_event.modifiers == KeyModifiers::CONTROL => {
-            editor.exit_confirm_prompt = false;
-            editor.status_message.clear();
+    }
+
+    pub fn confirm(purpose: PromptPurpose, label: &str, default: Option<bool>) -> Self {
+        Prompt::Confirm {
+            purpose,
+            label: label.to_string(),
+            default,
+        }
+    }
+
+    pub fn choice(purpose: PromptPurpose, label: &str, options: Vec<PromptOption>) -> Self {
+        Prompt::Choice {
+            purpose,
+            label: label.to_string(),
+            options,
+            selected: 0,
+        }
+    }
+
+    pub fn purpose(&self) -> PromptPurpose {
+        match self {
+            Prompt::Text { purpose, .. }
+            | Prompt::Confirm { purpose, .. }
+            | Prompt::Choice { purpose, .. } => *purpose,
+        }
+    }
+
+    /// 处理一次按键，返回提示是否结束以及结果
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> PromptOutcome {
+        let is_cancel = key_event.code == KeyCode::Esc
+            || (key_event.code == KeyCode::Char('c')
+                && key_event.modifiers == KeyModifiers::CONTROL);
+        if is_cancel {
+            return PromptOutcome::Cancelled;
+        }
+        match self {
+            Prompt::Text { input, .. } => match key_event.code {
+                KeyCode::Enter => return PromptOutcome::Text(input.clone()),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(ch) => input.push(ch),
+                _ => {}
+            },
+            Prompt::Confirm { default, .. } => match key_event.code {
+                KeyCode::Char('y' | 'Y' | '是') => return PromptOutcome::Answer(true),
+                KeyCode::Char('n' | 'N' | '否') => return PromptOutcome::Answer(false),
+                KeyCode::Enter => {
+                    if let Some(answer) = *default {
+                        return PromptOutcome::Answer(answer);
+                    }
+                }
+                _ => {}
+            },
+            Prompt::Choice {
+                options, selected, ..
+            } => match key_event.code {
+                KeyCode::Left | KeyCode::Up => {
+                    *selected = selected.saturating_sub(1);
+                }
+                KeyCode::Right | KeyCode::Down if *selected + 1 < options.len() => {
+                    *selected += 1;
+                }
+                KeyCode::Enter if !options.is_empty() => {
+                    return PromptOutcome::Chosen(*selected);
+                }
+                KeyCode::Char(ch) => {
+                    let ch = ch.to_ascii_lowercase();
+                    if let Some(i) = options
+                        .iter()
+                        .position(|o| o.key.to_ascii_lowercase() == ch)
+                    {
+                        return PromptOutcome::Chosen(i);
+                    }
+                }
+                _ => {}
+            },
+        }
+        PromptOutcome::Pending
+    }
+
+    /// 是否显示成屏幕中央的对话框而不是状态栏中的一行
+    pub fn is_modal(&self) -> bool {
+        matches!(self.purpose(), PromptPurpose::ExitConfirm)
+    }
+
+    /// 以对话框的形式绘制提示
+    pub fn draw_modal<W: Write>(
+        &self,
+        out: &mut W,
+        style: &ThemeStyle,
+        layout: &Layout,
+        line: &mut String,
+    ) -> io::Result<()> {
+        let (label, lines, footer) = match self {
+            Prompt::Text { label, input, .. } => {
+                (label, vec![input.clone()], "Enter=确定 ^C=取消".to_string())
+            }
+            Prompt::Confirm { label, default, .. } => (label, Vec::new(), confirm_hint(*default)),
+            Prompt::Choice {
+                label,
+                options,
+                selected,
+                ..
+            } => {
+                let lines = options
+                    .iter()
+                    .enumerate()
+                    .map(|(i, option)| {
+                        let marker = if i == *selected { '>' } else { ' ' };
+                        format!("{} {}  {}", marker, option.key, option.label)
+                    })
+                    .collect();
+                (label, lines, "↑↓=选择 Enter=确定 ^C=取消".to_string())
+            }
+        };
+        let mut content = vec![label.clone()];
+        if !lines.is_empty() {
+            content.push(String::new());
+            content.extend(lines);
+        }
+        Modal {
+            title: "",
+            lines: &content,
+            footer: &footer,
+            scroll: 0,
+        }
+        .draw(out, style, layout, line)
+    }
+
+    /// 把提示内容写入状态栏缓冲区
+    pub fn render_into(&self, buf: &mut String) {
+        match self {
+            Prompt::Text { label, input, .. } => {
+                let _ = write!(buf, "{} {}", label, input);
+            }
+            Prompt::Confirm { label, default, .. } => {
+                let _ = write!(buf, "{} {}", label, confirm_hint(*default));
+            }
+            Prompt::Choice { label, .. } => {
+                buf.push_str(label);
+                self.render_options(buf);
+            }
+        }
+    }
+
+    /// 选择提示的各个选项，选中的一项加方括号
+    fn render_options(&self, buf: &mut String) {
+        if let Prompt::Choice {
+            options, selected, ..
+        } = self
+        {
+            for (i, option) in options.iter().enumerate() {
+                if i == *selected {
+                    let _ = write!(buf, " [{}={}]", option.key, option.label);
+                } else {
+                    let _ = write!(buf, "  {}={} ", option.key, option.label);
+                }
+            }
         }
-        _ => {}
+    }
+
+    /// 把提示写成状态区的各行：一行放得下时只占一行，否则说明和输入内容（或选项、按键说明）分行显示
+    pub fn render_rows(&self, width: usize, rows: &mut Vec<String>) {
+        let mut line = String::new();
+        self.render_into(&mut line);
+        if str_width(&line) <= width {
+            rows.push(line);
+            return;
+        }
+        let (label, rest) = match self {
+            Prompt::Text { label, input, .. } => (label, input.clone()),
+            Prompt::Confirm { label, default, .. } => (label, confirm_hint(*default)),
+            Prompt::Choice { label, .. } => {
+                let mut options = String::new();
+                self.render_options(&mut options);
+                (label, options.trim().to_string())
+            }
+        };
+        wrap_to_width(label, width, rows);
+        wrap_to_width(&rest, width, rows);
+    }
+}
+
+/// 是/否提示的按键说明，有默认回答时注明回车的作用
+fn confirm_hint(default: Option<bool>) -> String {
+    let mut hint = "Y=是 N=否 ^C=取消".to_string();
+    match default {
+        Some(true) => hint.push_str(" Enter=是"),
+        Some(false) => hint.push_str(" Enter=否"),
+        None => {}
+    }
+    hint
+}
+
+/// 打开是/否提示，回答在事件循环中按 purpose 处理；配置的 [confirm] 决定回车的默认回答
+pub fn ask(editor: &mut Editor, purpose: PromptPurpose, label: &str) {
+    let default = purpose
+        .confirm_name()
+        .and_then(|name| CONFIRM_NAMES.iter().position(|n| *n == name))
+        .and_then(|i| editor.confirm_defaults[i]);
+    editor.prompt = Some(Prompt::confirm(purpose, label, default));
+}
+
+/// 把按键交给当前提示处理，提示结束时执行对应操作
+pub fn handle_prompt_key(editor: &mut Editor, key_event: KeyEvent) -> Result<()> {
+    let searching = editor
+        .prompt
+        .as_ref()
+        .is_some_and(|p| p.purpose() == PromptPurpose::Search);
+    if searching && key_event.code == KeyCode::Char('b') && key_event.modifiers == KeyModifiers::ALT
+    {
+        super::search::toggle_direction(editor);
+        return Ok(());
+    }
+    let replacing = editor
+        .prompt
+        .as_ref()
+        .is_some_and(|p| p.purpose() == PromptPurpose::ReplaceFind);
+    if replacing && key_event.code == KeyCode::Char('r') && key_event.modifiers == KeyModifiers::ALT
+    {
+        super::search::toggle_replace_regex(editor);
+        return Ok(());
+    }
+    let mut outcome = match editor.prompt.as_mut() {
+        Some(prompt) => prompt.handle_key(key_event),
+        None => return Ok(()),
+    };
+    // 二合字母输入满两个字符后自动提交
+    if let Some(Prompt::Text {
+        purpose: PromptPurpose::Digraph,
+        input,
+        ..
+    }) = &editor.prompt
+    {
+        if outcome == PromptOutcome::Pending && input.chars().count() == 2 {
+            outcome = PromptOutcome::Text(input.clone());
+        }
+    }
+    if outcome == PromptOutcome::Pending {
+        if searching {
+            super::search::update_incremental(editor);
+        }
+        return Ok(());
+    }
+    if let Some(prompt) = editor.prompt.take() {
+        finish_prompt(editor, prompt.purpose(), outcome)?;
     }
     Ok(())
 }
 
-pub fn handle_file_save(editor: &mut Editor, key_event: KeyEvent) -> Result<()> {
-    match key_event.code {
-        KeyCode::Enter => {
-            let filename = editor.file_save_input.trim();
+/// 打开另存为提示，默认填入当前文件名
+pub fn open_save_prompt(editor: &mut Editor, purpose: PromptPurpose) {
+    // 预览只读入了文件的一部分，保存会截断文件
+    if editor.buffer.preview.is_some() {
+        editor.notify_error("大文件预览不能保存，先按 Alt+Shift+E 完整读入");
+        return;
+    }
+    let init_filename = editor
+        .buffer
+        .filename
+        .as_ref()
+        .and_then(|p| p.to_str())
+        .unwrap_or("");
+    editor.prompt = Some(Prompt::text(
+        purpose,
+        "请输入要保存的文件名（按 ESC 取消）:",
+        init_filename,
+    ));
+}
+
+fn finish_prompt(
+    editor: &mut Editor,
+    purpose: PromptPurpose,
+    outcome: PromptOutcome,
+) -> Result<()> {
+    match (purpose, outcome) {
+        (PromptPurpose::ExitConfirm, PromptOutcome::Answer(true)) => {
+            editor.status_message.clear();
+            open_save_prompt(editor, PromptPurpose::SaveAndQuit);
+        }
+        (PromptPurpose::ExitConfirm, PromptOutcome::Answer(false)) => {
+            editor.status_message.clear();
+            super::buffers::close_buffer(editor);
+        }
+        (PromptPurpose::ExitConfirm, _) => {
+            editor.status_message.clear();
+        }
+        (PromptPurpose::SaveAs | PromptPurpose::SaveAndQuit, PromptOutcome::Text(input)) => {
+            let filename = input.trim();
             if !filename.is_empty() {
-                editor.buffer.filename = Some(std::path::PathBuf::from(filename));
-                let modified_count = editor.buffer.save()?;
-                editor.status_message = format!("已保存，已修改 {} 行", modified_count);
+                save_as(editor, filename, purpose == PromptPurpose::SaveAndQuit);
             } else {
-                editor.status_message = "文件名不能为空".to_string();
+                editor.notify_error("文件名不能为空");
             }
-            editor.file_save_prompt = None;
-            editor.file_save_input.clear();
         }
-        KeyCode::Esc => {
-            editor.file_save_prompt = None;
-            editor.file_save_input.clear();
+        (PromptPurpose::SaveAs | PromptPurpose::SaveAndQuit, _) => {
             editor.status_message = "已取消保存".to_string();
         }
-        KeyCode::Backspace => {
-            editor.file_save_input.pop();
+        (PromptPurpose::MakeExecutable { then_quit }, outcome) => {
+            if outcome == PromptOutcome::Answer(true) {
+                match editor.buffer.make_executable() {
+                    Ok(()) => editor.status_message = "已设置可执行权限".to_string(),
+                    Err(e) => editor.notify_error(e.to_string()),
+                }
+            }
+            if then_quit {
+                super::buffers::close_buffer(editor);
+            }
+        }
+        (PromptPurpose::PasteHistory, PromptOutcome::Chosen(index)) => {
+            super::clipboard::paste(editor, index);
+        }
+        (PromptPurpose::PasteHistory, _) => {}
+        (PromptPurpose::OpenFile, PromptOutcome::Text(input)) => {
+            let filename = input.trim();
+            if !filename.is_empty() {
+                super::buffers::open_file(editor, std::path::Path::new(filename));
+            }
+        }
+        (PromptPurpose::OpenFile, _) => {}
+        (PromptPurpose::SetOption, PromptOutcome::Text(input)) => set_options(editor, &input),
+        (PromptPurpose::SetOption, _) => {}
+        (PromptPurpose::DumpConfig, PromptOutcome::Text(input)) => {
+            let path = input.trim();
+            if !path.is_empty() {
+                super::configure::dump_config(editor, std::path::Path::new(path));
+            }
+        }
+        (PromptPurpose::DumpConfig, _) => {}
+        (PromptPurpose::Print, PromptOutcome::Chosen(index)) => {
+            super::print::finish_print_choice(editor, index);
+        }
+        (PromptPurpose::Print, _) => {}
+        (PromptPurpose::PrintExport(format), PromptOutcome::Text(input)) => {
+            let path = input.trim();
+            if !path.is_empty() {
+                super::print::export(editor, format, std::path::Path::new(path));
+            }
+        }
+        (PromptPurpose::PrintExport(_), _) => {}
+        (PromptPurpose::ExportHtml, PromptOutcome::Text(input)) => {
+            let path = input.trim();
+            if !path.is_empty() {
+                super::export::export_html(editor, std::path::Path::new(path));
+            }
+        }
+        (PromptPurpose::ExportHtml, _) => {}
+        (PromptPurpose::Search, PromptOutcome::Text(query)) => {
+            super::search::finish_search(editor, Some(&query));
+        }
+        (PromptPurpose::Search, _) => super::search::finish_search(editor, None),
+        (PromptPurpose::Digraph, PromptOutcome::Text(code)) => match digraph::lookup(&code) {
+            Some(ch) => {
+                if editor.type_char(ch) {
+                    editor.status_message = format!("{} → {}", code, ch);
+                }
+            }
+            None => editor.notify_error(format!("未知的二合字母 “{}”", code)),
+        },
+        (PromptPurpose::Digraph, _) => {}
+        (PromptPurpose::GotoLine, PromptOutcome::Text(input)) => goto_line(editor, &input),
+        (PromptPurpose::GotoLine, _) => {}
+        (PromptPurpose::ReplaceFind, PromptOutcome::Text(query)) => {
+            super::search::open_replacement_prompt(editor, query);
         }
-        KeyCode::Char(ch) => {
-            editor.file_save_input.push(ch);
+        (PromptPurpose::ReplaceFind, _) => {}
+        (PromptPurpose::ReplaceWith, PromptOutcome::Text(replacement)) => {
+            super::search::open_scope_prompt(editor, replacement);
         }
-        _ => {}
+        (PromptPurpose::ReplaceWith, _) => {}
+        (PromptPurpose::ReplaceScope, PromptOutcome::Chosen(0)) => {
+            super::search::start_interactive(editor);
+        }
+        (PromptPurpose::ReplaceScope, PromptOutcome::Chosen(_)) => {
+            super::search::replace(editor, true);
+        }
+        (PromptPurpose::ReplaceScope, _) => {}
+        (PromptPurpose::ReplaceConfirm, PromptOutcome::Chosen(index)) => {
+            super::search::answer_replacement(editor, Some(index));
+        }
+        (PromptPurpose::ReplaceConfirm, _) => super::search::answer_replacement(editor, None),
+        (PromptPurpose::RestoreVersion(source), PromptOutcome::Chosen(index)) => {
+            super::snapshots::finish_restore(editor, source, Some(index));
+        }
+        (PromptPurpose::RestoreVersion(source), _) => {
+            super::snapshots::finish_restore(editor, source, None)
+        }
+        (PromptPurpose::Recover, outcome) => super::recovery::finish_recover(editor, outcome),
+        (PromptPurpose::TrustProject, outcome) => super::project::finish_trust(editor, outcome),
+        (PromptPurpose::FirstRun(step), outcome) => {
+            super::setup::finish_step(editor, step, outcome)
+        }
+        (PromptPurpose::SaveMacro, PromptOutcome::Text(name)) => {
+            super::macros::save(editor, &name);
+        }
+        (PromptPurpose::SaveMacro, _) => {}
+        (PromptPurpose::RunMacro, PromptOutcome::Chosen(index)) => {
+            super::macros::finish_list(editor, index)?;
+        }
+        (PromptPurpose::RunMacro, _) => {}
+    }
+    // 启动时被其他提示挡住的项目配置询问，在提示结束后补上
+    if editor.prompt.is_none() {
+        super::project::check(editor);
     }
     Ok(())
 }
+
+/// 以指定文件名保存；新建的脚本文件保存后询问是否设为可执行
+fn save_as(editor: &mut Editor, filename: &str, quit_after: bool) {
+    let path = std::path::PathBuf::from(filename);
+    let is_new_file = !path.exists();
+    let old_size = std::fs::metadata(&path).ok().map(|m| m.len());
+    super::snapshots::save_history(editor, &path);
+    // 另存为覆盖别的文件时原文件移进回收站，保存到自己的文件不算覆盖
+    let same_file =
+        editor.buffer.filename.as_ref().is_some_and(|old| {
+            std::fs::canonicalize(old).ok() == std::fs::canonicalize(&path).ok()
+        });
+    let note = if same_file {
+        ""
+    } else {
+        match super::trash::before_overwrite(editor, &path) {
+            Some(note) => note,
+            None => return,
+        }
+    };
+    editor.buffer.filename = Some(path.clone());
+    editor.buffer.detect_filetype();
+    match editor.buffer.save() {
+        Ok(modified_count) => {
+            editor.status_message = format!("已保存，已修改 {} 行{}", modified_count, note);
+            let new_size = std::fs::metadata(&path).map_or(0, |m| m.len());
+            let detail = super::journal::save_detail(old_size, new_size, modified_count);
+            super::journal::record(editor, JournalEvent::Save, &detail);
+            // 另存为新文件名后给新文件加锁
+            super::recovery::check_current_buffer(editor);
+            if is_new_file && cfg!(unix) && editor.buffer.has_shebang() {
+                ask(
+                    editor,
+                    PromptPurpose::MakeExecutable {
+                        then_quit: quit_after,
+                    },
+                    "文件以 #! 开头，是否设为可执行？",
+                );
+            } else if quit_after {
+                super::buffers::close_buffer(editor);
+            }
+        }
+        // 保存失败时不退出，把错误显示在状态栏
+        Err(e) => editor.notify_error(e.to_string()),
+    }
+}
+
+/// 解析 `行[,列]`：数字为从 1 开始的绝对位置，`+N`、`-N` 为相对当前位置的移动；
+/// 返回从 0 开始的行和列，格式不对时返回 None
+fn parse_goto(input: &str, current: Position) -> Option<Position> {
+    let component = |text: &str, current: usize| -> Option<usize> {
+        let text = text.trim();
+        if let Some(n) = text.strip_prefix('+') {
+            return Some(current.saturating_add(n.trim().parse().ok()?));
+        }
+        if let Some(n) = text.strip_prefix('-') {
+            return Some(current.saturating_sub(n.trim().parse().ok()?));
+        }
+        Some(text.parse::<usize>().ok()?.saturating_sub(1))
+    };
+    let (line, column) = match input.split_once([',', ':']) {
+        Some((line, column)) => (line, Some(column)),
+        None => (input, None),
+    };
+    // 只写列（如 `,10`）时留在当前行
+    let y = if line.trim().is_empty() && column.is_some() {
+        current.y
+    } else {
+        component(line, current.y)?
+    };
+    let x = match column {
+        Some(column) => component(column, current.x)?,
+        None => 0,
+    };
+    Some(Position::new(x, y))
+}
+
+/// 跳到输入的行和列，目标不在屏幕上时让它显示在屏幕中间
+fn goto_line(editor: &mut Editor, input: &str) {
+    if input.trim().is_empty() {
+        return;
+    }
+    let Some(target) = parse_goto(input, editor.buffer.cursor_position()) else {
+        editor.notify_error(format!(
+            "无法识别 “{}”，格式为 行[,列] 或 +N/-N",
+            input.trim()
+        ));
+        return;
+    };
+    let page_rows = editor.layout().page_rows();
+    let buffer = &mut editor.buffer;
+    buffer.mark = None;
+    buffer.set_cursor_position(target);
+    let y = buffer.cursor_y;
+    if y < buffer.offset_y || y >= buffer.offset_y + page_rows {
+        buffer.offset_y = y.saturating_sub(page_rows / 2);
+    }
+    buffer.scroll_to_cursor(page_rows);
+    editor.status_message = format!(
+        "第 {}/{} 行，第 {} 列",
+        y + 1,
+        editor.buffer.lines.len(),
+        editor.buffer.cursor_x + 1
+    );
+}
+
+/// 执行 :set 输入的一条或多条以空格分隔的设置，留空时显示全部设置
+fn set_options(editor: &mut Editor, input: &str) {
+    let mut settings = editor.buffer.settings.clone();
+    let mut replies = Vec::new();
+    for expr in input.split_whitespace() {
+        match settings.apply(expr) {
+            Ok(reply) => replies.extend(reply),
+            Err(e) => {
+                // 有一条出错就全部不生效
+                editor.notify_error(e.to_string());
+                return;
+            }
+        }
+    }
+    editor.buffer.settings = settings;
+    editor.status_message = if input.trim().is_empty() {
+        editor.buffer.settings.describe()
+    } else if replies.is_empty() {
+        "设置已更新（仅当前缓冲区）".to_string()
+    } else {
+        replies.join(" ")
+    };
+}
@@ -0,0 +1,160 @@
+use crate::editor::{Editor, Prompt, PromptOption, PromptOutcome, PromptPurpose, VersionSource};
+use crate::journal::JournalEvent;
+use crate::paths;
+use crate::snapshot::{Owner, SnapshotStore};
+use std::path::Path;
+
+fn store() -> Option<SnapshotStore> {
+    paths::snapshots_dir().map(SnapshotStore::new)
+}
+
+/// 打开文件后调用：加锁，并检查上次会话是否异常退出、留下了快照
+///
+/// 有残留时询问恢复、查看差异还是丢弃；已有别的提示时只加锁不询问。
+/// 启动时当前缓冲区未命名的，检查异常退出的会话中未命名缓冲区留下的快照。
+pub fn check_current_buffer(editor: &mut Editor) {
+    let Some(store) = store() else {
+        return;
+    };
+    let Some(path) = editor.buffer.filename.clone() else {
+        check_unnamed(editor, &store);
+        return;
+    };
+    let stale = store.has_stale_lock(&path);
+    if let Err(e) = store.lock(&path) {
+        editor.notify_error(e.to_string());
+        return;
+    }
+    if !editor.locked_files.contains(&path) {
+        editor.locked_files.push(path.clone());
+    }
+    if !stale || editor.prompt.is_some() {
+        return;
+    }
+    let snapshots = store.list(Owner::File(&path)).unwrap_or_default();
+    let Some(newest) = snapshots.first() else {
+        editor.status_message = "上次编辑该文件时没有正常退出，未留下快照".to_string();
+        return;
+    };
+    let label = format!(
+        "上次没有正常退出，留下 {} 个快照（最新为{}）:",
+        snapshots.len(),
+        newest.label()
+    );
+    ask_recover(editor, &label);
+}
+
+/// 异常退出的会话中未命名缓冲区留下的快照交给当前的未命名缓冲区，询问是否恢复
+///
+/// 一次只接手最新的一个，其余的留到下次启动时再询问。
+fn check_unnamed(editor: &mut Editor, store: &SnapshotStore) {
+    if editor.prompt.is_some() {
+        return;
+    }
+    let Some(dir) = store.stale_unnamed().into_iter().next() else {
+        return;
+    };
+    let owner = Owner::of(&editor.buffer);
+    if let Err(e) = store.adopt(&dir, owner) {
+        editor.notify_error(e.to_string());
+        return;
+    }
+    let snapshots = store.list(owner).unwrap_or_default();
+    let Some(newest) = snapshots.first() else {
+        return;
+    };
+    let label = format!(
+        "上次有未命名的缓冲区没有正常退出，留下 {} 个快照（最新为{}）:",
+        snapshots.len(),
+        newest.label()
+    );
+    ask_recover(editor, &label);
+}
+
+/// 询问恢复最新快照、查看差异、丢弃还是忽略
+fn ask_recover(editor: &mut Editor, label: &str) {
+    let options = [
+        ('r', "恢复最新快照"),
+        ('d', "查看差异"),
+        ('x', "丢弃快照"),
+        ('i', "忽略"),
+    ]
+    .into_iter()
+    .map(|(key, label)| PromptOption {
+        key,
+        label: label.to_string(),
+    })
+    .collect();
+    editor.prompt = Some(Prompt::choice(PromptPurpose::Recover, label, options));
+}
+
+/// 在后台打开的缓冲区（命令行上的第二个及之后的文件）只加锁，不询问恢复
+pub fn lock_background(editor: &mut Editor, path: &Path) {
+    let Some(store) = store() else {
+        return;
+    };
+    if let Err(e) = store.lock(path) {
+        editor.notify_error(e.to_string());
+        return;
+    }
+    if !editor.locked_files.contains(&path.to_path_buf()) {
+        editor.locked_files.push(path.to_path_buf());
+    }
+}
+
+/// 处理恢复提示的回答
+pub fn finish_recover(editor: &mut Editor, outcome: PromptOutcome) {
+    match outcome {
+        PromptOutcome::Chosen(0) => {
+            super::snapshots::finish_restore(editor, VersionSource::Snapshots, Some(0))
+        }
+        PromptOutcome::Chosen(1) => {
+            super::snapshots::open_restore_picker(editor, VersionSource::Snapshots)
+        }
+        PromptOutcome::Chosen(2) => {
+            let Some(store) = store() else {
+                return;
+            };
+            match store.discard(Owner::of(&editor.buffer)) {
+                Ok(count) => {
+                    editor.status_message = format!("已丢弃 {} 个快照", count);
+                    let detail = format!("{} 个", count);
+                    super::journal::record(editor, JournalEvent::DiscardSnapshots, &detail);
+                }
+                Err(e) => editor.notify_error(e.to_string()),
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 关闭文件时释放锁；同一文件还在别的缓冲区中打开时保留
+pub fn release(editor: &mut Editor, path: &Path) {
+    let still_open = std::iter::once(&editor.buffer)
+        .chain(&editor.other_buffers)
+        .any(|b| b.filename.as_deref() == Some(path));
+    if still_open {
+        return;
+    }
+    if let Some(store) = store() {
+        store.unlock(path);
+    }
+    editor.locked_files.retain(|p| p != path);
+}
+
+/// 关闭未命名缓冲区时删除它的快照
+pub fn release_unnamed(id: u64) {
+    if let Some(store) = store() {
+        store.remove_unnamed(id);
+    }
+}
+
+/// 退出时释放所有锁，删除未命名缓冲区的快照
+pub fn release_all(editor: &mut Editor) {
+    if let Some(store) = store() {
+        for path in editor.locked_files.drain(..) {
+            store.unlock(&path);
+        }
+        store.remove_own_unnamed();
+    }
+}
@@ -0,0 +1,217 @@
+use crate::buffer::{Annotation, AnnotationPlacement, Sign, TextBuffer};
+use crate::editor::{Editor, Panel, PanelKind};
+use crate::location::{find_file_position, Location, LocationList};
+use crate::render::{SpanStyle, StyledLine};
+use crate::Result;
+use crossterm::style::Color;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output, Stdio};
+
+/// 把文件名放进命令时加上引号
+fn quote_path(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if cfg!(windows) {
+        format!("\"{}\"", path)
+    } else {
+        format!("'{}'", path.replace('\'', "'\\''"))
+    }
+}
+
+/// 当前缓冲区的运行命令，%f 换成文件名；出错时返回要显示的说明
+fn command_for(editor: &Editor) -> std::result::Result<String, String> {
+    let buffer = &editor.buffer;
+    let template = if buffer.settings.run_command.is_empty() {
+        buffer.filetype.default_run_command().ok_or_else(|| {
+            format!(
+                "没有为 {} 文件设置运行命令（:set runcommand=…）",
+                buffer.filetype.key()
+            )
+        })?
+    } else {
+        buffer.settings.run_command.as_str()
+    };
+    if !template.contains("%f") {
+        return Ok(template.to_string());
+    }
+    match &buffer.filename {
+        Some(path) => Ok(template.replace("%f", &quote_path(path))),
+        None => Err("缓冲区还没有文件名，无法替换命令中的 %f".to_string()),
+    }
+}
+
+/// 通过系统 shell 执行命令，标准错误并入标准输出，保持两者的先后顺序
+fn shell_command(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(format!("({}) 2>&1", command));
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(format!("exec 2>&1\n{}", command));
+        shell
+    };
+    shell
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    shell
+}
+
+fn describe_status(status: ExitStatus) -> String {
+    match status.code() {
+        Some(0) => "成功".to_string(),
+        Some(code) => format!("退出码 {}", code),
+        None => "被信号终止".to_string(),
+    }
+}
+
+/// 运行当前缓冲区的运行命令（F5），输出显示在底部面板
+///
+/// 输出中的 `文件:行:列` 生成位置列表，在面板中按 Enter 或用 F8 跳转。命令运行期间编辑器等待它结束。
+pub fn run(editor: &mut Editor) -> Result<()> {
+    let command = match command_for(editor) {
+        Ok(command) => command,
+        Err(message) => {
+            editor.notify_error(message);
+            return Ok(());
+        }
+    };
+    editor.status_message = format!("正在运行 {} …", command);
+    editor.refresh_screen()?;
+    match shell_command(&command).output() {
+        Ok(output) => show_output(editor, &command, output),
+        Err(e) => editor.notify_error(format!("无法运行 {}: {}", command, e)),
+    }
+    Ok(())
+}
+
+/// 把输出放进底部面板，能找到对应文件的 `文件:行:列` 记入位置列表
+fn show_output(editor: &mut Editor, command: &str, output: Output) {
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut panel = Panel::new(PanelKind::RunOutput, "");
+    panel.bottom = true;
+    let mut items = Vec::new();
+    // 位置所在的行缩进时（如 cargo 的 `--> src/main.rs:3:5`），说明用上面最近的不缩进的行
+    let mut heading = "";
+    for line in text.lines() {
+        if !line.starts_with(char::is_whitespace) && !line.is_empty() {
+            heading = line;
+        }
+        let found = find_file_position(line).filter(|(path, _)| Path::new(path).is_file());
+        let mut styled = StyledLine::new(found.map(|_| items.len()));
+        match found {
+            Some((path, position)) => {
+                let message = if line.starts_with(char::is_whitespace) {
+                    heading
+                } else {
+                    line
+                };
+                items.push(Location::new(
+                    Some(PathBuf::from(path)),
+                    position,
+                    message.trim(),
+                ));
+                styled.push(line, SpanStyle::Match);
+            }
+            None => styled.push(line, SpanStyle::Normal),
+        }
+        panel.lines.push(styled);
+    }
+    if panel.lines.is_empty() {
+        let mut styled = StyledLine::new(None);
+        styled.push("（没有输出）", SpanStyle::Dim);
+        panel.lines.push(styled);
+    }
+    let status = describe_status(output.status);
+    panel.title = format!(
+        "{}：{}，{} 处位置（Enter 跳转，Esc 关闭，Shift+F5 切换焦点）",
+        command,
+        status,
+        items.len()
+    );
+    panel.selected = panel.lines.iter().position(|l| l.source_line.is_some());
+    panel.focused = panel.selected.is_some();
+    editor.status_message = format!("{}：{}", command, status);
+    editor.run_locations = items.clone();
+    annotate_all(editor);
+    if !items.is_empty() {
+        super::locations::set_list(
+            editor,
+            LocationList::new(format!("运行 {}", command), items),
+        );
+    }
+    editor.panel = Some(panel);
+    let rows = super::panel::visible_rows(editor);
+    if let Some(panel) = editor.panel.as_mut() {
+        super::panel::keep_selected_visible(panel, rows);
+    }
+}
+
+/// 行尾显示运行结果的虚拟文本和符号栏中符号的产生者
+const RUN_ANNOTATIONS: &str = "run";
+
+/// 运行结果的符号优先级，低于书签
+const RUN_SIGN_PRIORITY: u8 = 10;
+
+/// 用上一次运行的结果替换缓冲区中的运行说明：位置在这个文件中的，在所在行的行尾显示说明，
+/// 符号栏中标上符号
+pub fn annotate(locations: &[Location], buffer: &mut TextBuffer) {
+    buffer.clear_annotations(RUN_ANNOTATIONS);
+    buffer.clear_signs(RUN_ANNOTATIONS);
+    let Some(file) = buffer
+        .filename
+        .as_ref()
+        .and_then(|p| fs::canonicalize(p).ok())
+    else {
+        return;
+    };
+    for item in locations {
+        let same = item
+            .path
+            .as_ref()
+            .and_then(|p| fs::canonicalize(p).ok())
+            .is_some_and(|p| p == file);
+        if same {
+            buffer.add_annotation(Annotation::new(
+                item.position.y,
+                &item.message,
+                AnnotationPlacement::EndOfLine,
+                RUN_ANNOTATIONS,
+            ));
+            buffer.place_sign(Sign::new(
+                item.position.y,
+                '●',
+                Color::Red,
+                RUN_SIGN_PRIORITY,
+                RUN_ANNOTATIONS,
+            ));
+        }
+    }
+}
+
+/// 对所有打开的缓冲区更新运行说明
+fn annotate_all(editor: &mut Editor) {
+    let locations = &editor.run_locations;
+    for buffer in std::iter::once(&mut editor.buffer).chain(&mut editor.other_buffers) {
+        annotate(locations, buffer);
+    }
+}
+
+/// 把焦点交给运行输出面板；已有焦点时关闭面板
+pub fn toggle_output_panel(editor: &mut Editor) {
+    let Some(panel) = editor
+        .panel
+        .as_mut()
+        .filter(|p| p.kind == PanelKind::RunOutput)
+    else {
+        editor.notify_error("没有运行输出（按 F5 运行）");
+        return;
+    };
+    if panel.focused {
+        editor.panel = None;
+    } else {
+        panel.focused = true;
+        panel.selected.get_or_insert(0);
+    }
+}
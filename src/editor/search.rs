@@ -0,0 +1,666 @@
+use crate::buffer::{find_in_line, Matcher, Position, SearchMatch, TextBuffer};
+use crate::editor::{Editor, Panel, PanelKind, Prompt, PromptOption, PromptPurpose};
+use crate::journal::JournalEvent;
+use crate::location::{Location, LocationList};
+use crate::render::{SpanStyle, StyledLine};
+
+/// 搜索提示打开期间的状态：输入时光标实时跳到匹配，取消时回到原处
+#[derive(Debug, Clone)]
+pub struct IncrementalSearch {
+    /// 打开提示时的光标和滚动位置
+    origin: Position,
+    offset: (usize, usize),
+    /// 打开提示前的高亮内容，取消时恢复
+    highlight: Option<String>,
+    /// 是否从光标处往文件开头方向查找
+    pub backwards: bool,
+}
+
+fn search_label(backwards: bool) -> &'static str {
+    if backwards {
+        "反向搜索（Alt+B 切换方向，ESC 取消）:"
+    } else {
+        "搜索（Alt+B 切换方向，ESC 取消）:"
+    }
+}
+
+/// 打开搜索提示，默认填入上次的搜索内容
+pub fn open_search_prompt(editor: &mut Editor, backwards: bool) {
+    let buffer = &editor.buffer;
+    editor.incremental_search = Some(IncrementalSearch {
+        origin: buffer.cursor_position(),
+        offset: (buffer.offset_x, buffer.offset_y),
+        highlight: buffer.highlight.as_ref().map(|h| h.query.clone()),
+        backwards,
+    });
+    let initial = editor.last_search.clone().unwrap_or_default();
+    editor.prompt = Some(Prompt::text(
+        PromptPurpose::Search,
+        search_label(backwards),
+        &initial,
+    ));
+}
+
+/// 搜索提示中的内容变化后，从打开提示时的位置找第一处匹配并高亮
+pub fn update_incremental(editor: &mut Editor) {
+    let Some(state) = editor.incremental_search.clone() else {
+        return;
+    };
+    let Some(Prompt::Text { input, .. }) = &editor.prompt else {
+        return;
+    };
+    let query = input.clone();
+    restore_origin(editor, &state);
+    if query.is_empty() {
+        editor.buffer.clear_highlight();
+        editor.status_message.clear();
+        return;
+    }
+    editor.buffer.set_highlight(&query);
+    let matches = editor.buffer.find_all(&query);
+    // 正好从原处开始的匹配也算，这样输入时光标不会跳过眼前的匹配
+    let index = if state.backwards {
+        matches
+            .iter()
+            .rposition(|m| m.start_position() < state.origin)
+            .or(matches.len().checked_sub(1))
+    } else {
+        matches
+            .iter()
+            .position(|m| m.start_position() >= state.origin)
+            .or((!matches.is_empty()).then_some(0))
+    };
+    match index {
+        Some(index) => {
+            editor
+                .buffer
+                .set_cursor_position(matches[index].start_position());
+            editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+            editor.status_message = format!("第 {}/{} 处匹配", index + 1, matches.len());
+        }
+        None => editor.status_message = format!("找不到 “{}”", query),
+    }
+}
+
+/// 在搜索提示中切换查找方向
+pub fn toggle_direction(editor: &mut Editor) {
+    let Some(state) = editor.incremental_search.as_mut() else {
+        return;
+    };
+    state.backwards = !state.backwards;
+    let backwards = state.backwards;
+    if let Some(Prompt::Text { label, .. }) = editor.prompt.as_mut() {
+        *label = search_label(backwards).to_string();
+    }
+    update_incremental(editor);
+}
+
+/// 搜索提示结束：提交时从原处正式查找一次，取消时回到原处并恢复原来的高亮
+pub fn finish_search(editor: &mut Editor, query: Option<&str>) {
+    let Some(state) = editor.incremental_search.take() else {
+        if let Some(query) = query {
+            search(editor, query, false);
+        }
+        return;
+    };
+    restore_origin(editor, &state);
+    match query.filter(|q| !q.is_empty()) {
+        Some(query) => search(editor, query, state.backwards),
+        None => {
+            match &state.highlight {
+                Some(query) => editor.buffer.set_highlight(query),
+                None => {
+                    editor.buffer.clear_highlight();
+                }
+            }
+            editor.status_message.clear();
+        }
+    }
+}
+
+fn restore_origin(editor: &mut Editor, state: &IncrementalSearch) {
+    editor.buffer.set_cursor_position(state.origin);
+    (editor.buffer.offset_x, editor.buffer.offset_y) = state.offset;
+}
+
+/// 搜索 query 并把光标移到光标之后（或之前）的下一处匹配
+pub fn search(editor: &mut Editor, query: &str, backwards: bool) {
+    if query.is_empty() {
+        return;
+    }
+    let from = editor.buffer.cursor_position();
+    let found = editor.buffer.find_next(query, from, backwards);
+    let Some((found, index, total)) = show_match(editor, query, found) else {
+        return;
+    };
+    let wrapped = if backwards {
+        found.start_position() >= from
+    } else {
+        found.start_position() <= from
+    };
+    editor.status_message = format!(
+        "第 {}/{} 处匹配{}",
+        index + 1,
+        total,
+        if wrapped {
+            "（已从另一端继续）"
+        } else {
+            ""
+        }
+    );
+}
+
+/// 打开文件时定位到第一处匹配（向后查找时为最后一处），用于命令行的 `+/模式` 和 `+?模式`
+pub fn search_from_start(editor: &mut Editor, query: &str, backwards: bool) {
+    if query.is_empty() {
+        return;
+    }
+    let matches = editor.buffer.find_all(query);
+    let found = if backwards {
+        matches.last()
+    } else {
+        matches.first()
+    };
+    if let Some((_, index, total)) = show_match(editor, query, found.copied()) {
+        editor.status_message = format!("第 {}/{} 处匹配", index + 1, total);
+    }
+}
+
+/// 记下搜索内容并高亮，光标移到找到的匹配上并生成结果列表，返回匹配、序号和总数；
+/// 没有匹配时提示错误并返回 None
+fn show_match(
+    editor: &mut Editor,
+    query: &str,
+    found: Option<SearchMatch>,
+) -> Option<(SearchMatch, usize, usize)> {
+    editor.last_search = Some(query.to_string());
+    // 所有匹配保持高亮，直到清除或开始新的搜索
+    editor.buffer.set_highlight(query);
+    // 结果列表按新的搜索内容重新生成
+    if let Some(panel) = editor.panel.as_mut() {
+        panel.revision = None;
+    }
+    let Some(found) = found else {
+        editor.notify_error(format!("找不到 “{}”", query));
+        return None;
+    };
+    editor.buffer.mark = None;
+    editor.buffer.set_cursor_position(found.start_position());
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+    let matches = editor.buffer.find_all(query);
+    let index = matches.iter().position(|m| *m == found).unwrap_or(0);
+    let items = matches
+        .iter()
+        .map(|m| {
+            let message = format!("第 {} 行第 {} 列", m.y + 1, m.start + 1);
+            Location::new(None, m.start_position(), message)
+        })
+        .collect();
+    let mut list = LocationList::new(format!("搜索 “{}”", query), items);
+    list.current = Some(index);
+    super::locations::set_list(editor, list);
+    Some((found, index, matches.len()))
+}
+
+/// 用上次的搜索内容继续查找
+pub fn search_again(editor: &mut Editor, backwards: bool) {
+    match editor.last_search.clone() {
+        Some(query) => search(editor, &query, backwards),
+        None => editor.notify_error("还没有搜索过"),
+    }
+}
+
+/// 逐处确认的替换进行中的状态
+#[derive(Debug, Clone)]
+pub struct ReplaceSession {
+    matcher: Matcher,
+    replacement: String,
+    /// 从这里继续找下一处
+    next: Position,
+    /// 开始时的光标位置，从开头绕回来后找到这里为止
+    origin: Position,
+    wrapped: bool,
+    /// 是否允许正好在 next 处的空匹配；替换或跳过一处之后不允许，避免原地打转
+    allow_empty_at_next: bool,
+    /// 正在询问的一处
+    current: Option<SearchMatch>,
+    /// 找到过的处数和替换了的处数
+    seen: usize,
+    count: usize,
+}
+
+impl ReplaceSession {
+    /// 从 next 开始找下一处匹配，到末尾后从开头继续，回到起点为止
+    fn find_next(&mut self, buffer: &TextBuffer) -> Option<SearchMatch> {
+        loop {
+            for y in self.next.y..buffer.lines.len() {
+                let next = self.next;
+                let allow_empty = self.allow_empty_at_next;
+                let found = self
+                    .matcher
+                    .find_in_line(&buffer.lines[y])
+                    .into_iter()
+                    .find(|&(start, end)| {
+                        y > next.y
+                            || start > next.x
+                            || (start == next.x && (end > start || allow_empty))
+                    })
+                    .map(|(start, end)| SearchMatch { y, start, end });
+                if let Some(found) = found {
+                    if self.wrapped && found.start_position() >= self.origin {
+                        return None;
+                    }
+                    return Some(found);
+                }
+                if self.wrapped && y >= self.origin.y {
+                    return None;
+                }
+            }
+            if self.wrapped {
+                return None;
+            }
+            self.wrapped = true;
+            self.next = Position::new(0, 0);
+            self.allow_empty_at_next = true;
+        }
+    }
+
+    /// 替换当前的一处，之后从替换内容的末尾继续
+    fn replace_current(&mut self, buffer: &mut TextBuffer) {
+        let Some(found) = self.current.take() else {
+            return;
+        };
+        let text =
+            self.matcher
+                .replacement_for(&buffer.lines[found.y], found.start, &self.replacement);
+        let end = buffer.replace_match(found, &text);
+        // 起点所在行中起点之前的替换会改变起点的列
+        if found.y == self.origin.y && found.end <= self.origin.x {
+            self.origin.x = self.origin.x + end.x - found.start - (found.end - found.start);
+        }
+        self.next = end;
+        self.allow_empty_at_next = false;
+        self.count += 1;
+    }
+
+    /// 跳过当前的一处
+    fn skip_current(&mut self) {
+        if let Some(found) = self.current.take() {
+            self.next = Position::new(found.end, found.y);
+            self.allow_empty_at_next = false;
+        }
+    }
+}
+
+fn replace_label(regex: bool) -> &'static str {
+    if regex {
+        "正则替换（Alt+R 改为普通文字，ESC 取消）:"
+    } else {
+        "替换（Alt+R 改为正则表达式，ESC 取消）:"
+    }
+}
+
+/// 打开替换提示，先输入要查找的内容
+pub fn open_replace_prompt(editor: &mut Editor) {
+    let initial = editor.last_search.clone().unwrap_or_default();
+    editor.prompt = Some(Prompt::text(
+        PromptPurpose::ReplaceFind,
+        replace_label(editor.replace_regex),
+        &initial,
+    ));
+}
+
+/// 在替换提示中切换普通文字和正则表达式
+pub fn toggle_replace_regex(editor: &mut Editor) {
+    editor.replace_regex = !editor.replace_regex;
+    if let Some(Prompt::Text { label, .. }) = editor.prompt.as_mut() {
+        *label = replace_label(editor.replace_regex).to_string();
+    }
+}
+
+/// 记下要查找的内容，接着输入替换成的内容
+pub fn open_replacement_prompt(editor: &mut Editor, query: String) {
+    if query.is_empty() {
+        return;
+    }
+    if let Err(e) = Matcher::new(&query, editor.replace_regex) {
+        editor.notify_error(format!("正则表达式有误：{}", e));
+        return;
+    }
+    editor.last_search = Some(query);
+    if let Some(panel) = editor.panel.as_mut() {
+        panel.revision = None;
+    }
+    let initial = editor.last_replacement.clone().unwrap_or_default();
+    let label = if editor.replace_regex {
+        "替换为（$1 等引用分组，按 ESC 取消）:"
+    } else {
+        "替换为（按 ESC 取消）:"
+    };
+    editor.prompt = Some(Prompt::text(PromptPurpose::ReplaceWith, label, &initial));
+}
+
+/// 记下替换成的内容，有多个缓冲区时询问范围；只替换当前缓冲区时逐处确认
+pub fn open_scope_prompt(editor: &mut Editor, replacement: String) {
+    editor.last_replacement = Some(replacement);
+    if editor.other_buffers.is_empty() {
+        start_interactive(editor);
+        return;
+    }
+    let options = [
+        ('c', "当前缓冲区（逐处确认）"),
+        ('a', "所有缓冲区（全部替换）"),
+    ]
+    .into_iter()
+    .map(|(key, label)| PromptOption {
+        key,
+        label: label.to_string(),
+    })
+    .collect();
+    editor.prompt = Some(Prompt::choice(
+        PromptPurpose::ReplaceScope,
+        "替换范围:",
+        options,
+    ));
+}
+
+fn buffer_name(buffer: &TextBuffer) -> &str {
+    buffer
+        .filename
+        .as_ref()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or("[无文件名]")
+}
+
+/// 把上次搜索的内容全部替换，all 为真时作用于所有打开的缓冲区
+///
+/// 每个缓冲区的替换是一次整体修改；只读的缓冲区跳过。状态栏列出各缓冲区替换的处数。
+pub fn replace(editor: &mut Editor, all: bool) {
+    let (Some(query), Some(replacement)) =
+        (editor.last_search.clone(), editor.last_replacement.clone())
+    else {
+        return;
+    };
+    let matcher = match Matcher::new(&query, editor.replace_regex) {
+        Ok(matcher) => matcher,
+        Err(e) => {
+            editor.notify_error(format!("正则表达式有误：{}", e));
+            return;
+        }
+    };
+    let buffers: Vec<&mut TextBuffer> = if all {
+        std::iter::once(&mut editor.buffer)
+            .chain(editor.other_buffers.iter_mut())
+            .collect()
+    } else {
+        vec![&mut editor.buffer]
+    };
+    let mut total = 0;
+    let mut counts = Vec::new();
+    let mut replaced_files = Vec::new();
+    let mut skipped = 0;
+    for buffer in buffers {
+        if buffer.is_read_only() {
+            if buffer
+                .lines
+                .iter()
+                .any(|line| !matcher.find_in_line(line).is_empty())
+            {
+                skipped += 1;
+            }
+            continue;
+        }
+        let count = buffer.replace_all(&matcher, &replacement);
+        if count > 0 {
+            total += count;
+            counts.push(format!("{} {} 处", buffer_name(buffer), count));
+            if let Some(path) = &buffer.filename {
+                replaced_files.push((path.clone(), count));
+            }
+        }
+    }
+    let skipped = if skipped > 0 {
+        format!("，跳过 {} 个只读缓冲区", skipped)
+    } else {
+        String::new()
+    };
+    if total == 0 {
+        editor.notify_error(format!("找不到 “{}”{}", query, skipped));
+        return;
+    }
+    for (path, count) in replaced_files {
+        let detail = format!("“{}” → “{}”，{} 处（未保存）", query, replacement, count);
+        super::journal::record_for(editor, &path, JournalEvent::Replace, &detail);
+    }
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+    editor.status_message = if all {
+        format!("共替换 {} 处：{}{}", total, counts.join("，"), skipped)
+    } else {
+        format!("替换了 {} 处{}", total, skipped)
+    };
+}
+
+/// 在当前缓冲区中从光标处开始逐处确认替换
+pub fn start_interactive(editor: &mut Editor) {
+    let (Some(query), Some(replacement)) =
+        (editor.last_search.clone(), editor.last_replacement.clone())
+    else {
+        return;
+    };
+    if editor.buffer.is_read_only() {
+        editor.notify_error("缓冲区只读");
+        return;
+    }
+    let matcher = match Matcher::new(&query, editor.replace_regex) {
+        Ok(matcher) => matcher,
+        Err(e) => {
+            editor.notify_error(format!("正则表达式有误：{}", e));
+            return;
+        }
+    };
+    let origin = editor.buffer.cursor_position();
+    editor.replace_session = Some(ReplaceSession {
+        matcher,
+        replacement,
+        next: origin,
+        origin,
+        wrapped: false,
+        allow_empty_at_next: true,
+        current: None,
+        seen: 0,
+        count: 0,
+    });
+    ask_next_replacement(editor);
+}
+
+/// 找到下一处并询问是否替换，没有了就结束
+fn ask_next_replacement(editor: &mut Editor) {
+    let Some(session) = editor.replace_session.as_mut() else {
+        return;
+    };
+    let Some(found) = session.find_next(&editor.buffer) else {
+        finish_interactive(editor);
+        return;
+    };
+    session.current = Some(found);
+    session.seen += 1;
+    // 用选区标出这一处
+    editor
+        .buffer
+        .select_range(found.start_position(), Position::new(found.end, found.y));
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+    let options = [('y', "替换"), ('n', "跳过"), ('a', "全部替换")]
+        .into_iter()
+        .map(|(key, label)| PromptOption {
+            key,
+            label: label.to_string(),
+        })
+        .collect();
+    editor.prompt = Some(Prompt::choice(
+        PromptPurpose::ReplaceConfirm,
+        "替换这一处？（ESC 结束）",
+        options,
+    ));
+}
+
+/// 逐处确认时的回答：0 替换、1 跳过、2 替换这一处及其后全部，None 表示结束
+pub fn answer_replacement(editor: &mut Editor, answer: Option<usize>) {
+    let Some(session) = editor.replace_session.as_mut() else {
+        return;
+    };
+    match answer {
+        Some(0) => session.replace_current(&mut editor.buffer),
+        Some(1) => session.skip_current(),
+        Some(_) => {
+            // 剩下的全部替换算作一步撤销
+            let lines = 0..editor.buffer.lines.len();
+            editor.buffer.record_compound_edit(lines, |buffer| {
+                session.replace_current(buffer);
+                while let Some(found) = session.find_next(buffer) {
+                    session.current = Some(found);
+                    session.replace_current(buffer);
+                }
+            });
+            finish_interactive(editor);
+            return;
+        }
+        None => {
+            finish_interactive(editor);
+            return;
+        }
+    }
+    ask_next_replacement(editor);
+}
+
+/// 结束逐处确认的替换，报告替换的处数
+fn finish_interactive(editor: &mut Editor) {
+    let Some(session) = editor.replace_session.take() else {
+        return;
+    };
+    editor.buffer.mark = None;
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+    let query = editor.last_search.clone().unwrap_or_default();
+    if session.count == 0 {
+        if session.seen == 0 {
+            editor.notify_error(format!("找不到 “{}”", query));
+        } else {
+            editor.status_message = "没有替换".to_string();
+        }
+        return;
+    }
+    if let Some(path) = editor.buffer.filename.clone() {
+        let detail = format!(
+            "“{}” → “{}”，{} 处（未保存）",
+            query, session.replacement, session.count
+        );
+        super::journal::record_for(editor, &path, JournalEvent::Replace, &detail);
+    }
+    editor.status_message = format!("替换了 {} 处", session.count);
+}
+
+/// 打开搜索结果面板并把焦点交给它；已打开且有焦点时关闭
+pub fn toggle_results_panel(editor: &mut Editor) {
+    if let Some(panel) = editor
+        .panel
+        .as_mut()
+        .filter(|p| p.kind == PanelKind::SearchResults)
+    {
+        if panel.focused {
+            editor.panel = None;
+        } else {
+            panel.focused = true;
+        }
+        return;
+    }
+    if editor.last_search.is_none() {
+        editor.notify_error("还没有搜索过");
+        return;
+    }
+    let mut panel = Panel::new(PanelKind::SearchResults, "搜索结果");
+    panel.focused = true;
+    editor.panel = Some(panel);
+    update_results_panel(editor);
+    // 默认选中光标所在或之后的第一行结果
+    let cursor_y = editor.buffer.cursor_y;
+    let rows = super::panel::visible_rows(editor);
+    if let Some(panel) = editor.panel.as_mut() {
+        let first = panel
+            .lines
+            .iter()
+            .position(|l| l.source_line.is_some_and(|y| y >= cursor_y));
+        panel.selected = first.or(panel.lines.len().checked_sub(1));
+        super::panel::keep_selected_visible(panel, rows);
+    }
+}
+
+/// 缓冲区变化后重新生成结果列表，每行显示行号和带高亮的行内容
+pub fn update_results_panel(editor: &mut Editor) {
+    let Some(query) = editor.last_search.clone() else {
+        return;
+    };
+    let Some(panel) = editor.panel.as_mut() else {
+        return;
+    };
+    if panel.revision == Some(editor.buffer.revision) {
+        return;
+    }
+    panel.lines.clear();
+    let mut count = 0;
+    for (y, line) in editor.buffer.lines.iter().enumerate() {
+        let ranges = find_in_line(line, &query);
+        if ranges.is_empty() {
+            continue;
+        }
+        count += ranges.len();
+        let mut styled = StyledLine::new(Some(y));
+        styled.push(&format!("{:>4}: ", y + 1), SpanStyle::Dim);
+        let chars: Vec<char> = line.chars().collect();
+        let mut last = 0;
+        for (start, end) in ranges {
+            let before: String = chars[last..start].iter().collect();
+            let matched: String = chars[start..end].iter().collect();
+            // 行首缩进对列表没有意义，去掉以节省面板宽度
+            let before = if last == 0 {
+                before.trim_start()
+            } else {
+                &before
+            };
+            styled.push(before, SpanStyle::Normal);
+            styled.push(&matched, SpanStyle::Match);
+            last = end;
+        }
+        let rest: String = chars[last..].iter().collect();
+        styled.push(&rest, SpanStyle::Normal);
+        panel.lines.push(styled);
+    }
+    panel.title = format!(
+        "“{}”：{} 处匹配，{} 行（↑↓ 选择，Enter 跳转，Esc 关闭）",
+        query,
+        count,
+        panel.lines.len()
+    );
+    panel.revision = Some(editor.buffer.revision);
+    if let Some(selected) = panel.selected {
+        panel.selected = panel
+            .lines
+            .len()
+            .checked_sub(1)
+            .map(|last| selected.min(last));
+    }
+}
+
+/// 跳到第 y 行的第一处匹配
+pub fn jump_to_line_match(editor: &mut Editor, y: usize) {
+    let Some(line) = editor.buffer.lines.get(y) else {
+        return;
+    };
+    let x = editor
+        .last_search
+        .as_deref()
+        .and_then(|query| find_in_line(line, query).first().map(|&(start, _)| start))
+        .unwrap_or(0);
+    editor.buffer.mark = None;
+    editor.buffer.set_cursor_position(Position::new(x, y));
+    editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+}
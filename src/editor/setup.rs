@@ -0,0 +1,180 @@
+use super::modal::Modal;
+use crate::config::{default_config_text, KeyFlavor};
+use crate::editor::{Editor, Prompt, PromptOption, PromptOutcome, PromptPurpose};
+use crate::layout::Layout;
+use crate::theme::{Style as ThemeStyle, Theme};
+use crate::{paths, EditorError};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// 首次运行设置进行到哪一步
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupStep {
+    /// 询问是否生成配置
+    Offer,
+    /// 选择主题
+    Theme,
+    /// 选择键位风格
+    Flavor,
+}
+
+/// 首次运行设置的状态，存在期间编辑区上方显示说明浮层
+#[derive(Debug, Clone)]
+pub struct FirstRunSetup {
+    /// 要写入的配置文件
+    pub config_path: PathBuf,
+    /// 可选的主题
+    pub themes: Vec<String>,
+    /// 已选的主题
+    pub theme: Option<String>,
+}
+
+const FLAVORS: [KeyFlavor; 2] = [KeyFlavor::Nano, KeyFlavor::Vi];
+
+/// 没有配置文件且以前没有完成或跳过设置时，开始首次运行设置
+pub fn start_if_needed(editor: &mut Editor) {
+    let (Some(config_path), Some(marker)) = (editor.config_path.clone(), paths::first_run_marker())
+    else {
+        return;
+    };
+    if config_path.exists() || marker.exists() {
+        return;
+    }
+    editor.setup = Some(FirstRunSetup {
+        config_path,
+        themes: Theme::available(paths::themes_dir().as_deref()),
+        theme: None,
+    });
+    let options = vec![
+        PromptOption {
+            key: 'y',
+            label: "生成配置".to_string(),
+        },
+        PromptOption {
+            key: 'n',
+            label: "不再询问".to_string(),
+        },
+    ];
+    editor.prompt = Some(Prompt::choice(
+        PromptPurpose::FirstRun(SetupStep::Offer),
+        "首次运行：生成默认配置？（Esc 下次再说）",
+        options,
+    ));
+}
+
+/// 处理设置中某一步的回答
+pub fn finish_step(editor: &mut Editor, step: SetupStep, outcome: PromptOutcome) {
+    let Some(setup) = editor.setup.as_mut() else {
+        return;
+    };
+    match (step, outcome) {
+        (SetupStep::Offer, PromptOutcome::Chosen(0)) => {
+            let options = setup
+                .themes
+                .iter()
+                .zip(('1'..='9').chain('a'..='z'))
+                .map(|(name, key)| PromptOption {
+                    key,
+                    label: name.clone(),
+                })
+                .collect();
+            editor.prompt = Some(Prompt::choice(
+                PromptPurpose::FirstRun(SetupStep::Theme),
+                "选择主题:",
+                options,
+            ));
+        }
+        (SetupStep::Offer, PromptOutcome::Chosen(_)) => {
+            editor.setup = None;
+            match mark_done() {
+                Ok(()) => {
+                    editor.status_message =
+                        "以后不再询问，可以按 Alt+E 导出配置作为起点".to_string()
+                }
+                Err(e) => editor.notify_error(e.to_string()),
+            }
+        }
+        (SetupStep::Theme, PromptOutcome::Chosen(index)) => {
+            setup.theme = setup.themes.get(index).cloned();
+            let options = vec![
+                PromptOption {
+                    key: 'n',
+                    label: "nano".to_string(),
+                },
+                PromptOption {
+                    key: 'v',
+                    label: "vi（Alt+h/j/k/l 移动）".to_string(),
+                },
+            ];
+            editor.prompt = Some(Prompt::choice(
+                PromptPurpose::FirstRun(SetupStep::Flavor),
+                "选择键位风格:",
+                options,
+            ));
+        }
+        (SetupStep::Flavor, PromptOutcome::Chosen(index)) => {
+            let flavor = FLAVORS.get(index).copied().unwrap_or(KeyFlavor::Nano);
+            write_config(editor, flavor);
+        }
+        // 取消时不写标记，下次启动再询问
+        _ => {
+            editor.setup = None;
+            editor.status_message = "已取消，下次启动时再询问".to_string();
+        }
+    }
+}
+
+/// 写入生成的配置并立即加载
+fn write_config(editor: &mut Editor, flavor: KeyFlavor) {
+    let Some(setup) = editor.setup.take() else {
+        return;
+    };
+    let theme = setup.theme.as_deref().unwrap_or("default");
+    let path = &setup.config_path;
+    let result = paths::ensure_parent(path)
+        .and_then(|()| fs::write(path, default_config_text(theme, flavor)))
+        .map_err(|e| EditorError::io(path, e))
+        .and_then(|()| mark_done());
+    if let Err(e) = result {
+        editor.notify_error(e.to_string());
+        return;
+    }
+    super::configure::reload_config(editor);
+    editor.status_message = format!("已生成配置文件 {}", path.display());
+}
+
+/// 记下设置已经完成，以后启动不再询问
+fn mark_done() -> crate::Result<()> {
+    let Some(marker) = paths::first_run_marker() else {
+        return Ok(());
+    };
+    paths::ensure_parent(&marker)
+        .and_then(|()| fs::write(&marker, ""))
+        .map_err(|e| EditorError::io(&marker, e))
+}
+
+/// 在屏幕中央绘制设置说明，具体的选择在底部状态栏中进行
+pub fn draw_overlay<W: Write>(
+    out: &mut W,
+    setup: &FirstRunSetup,
+    style: &ThemeStyle,
+    layout: &Layout,
+    line: &mut String,
+) -> io::Result<()> {
+    let theme = setup.theme.as_deref().unwrap_or("（未选择）");
+    let lines = [
+        "没有找到配置文件，可以生成一份带注释的默认配置：".to_string(),
+        format!("  {}", setup.config_path.display()),
+        String::new(),
+        "依次在底部选择主题和键位风格（nano 或 vi）。".to_string(),
+        format!("主题：{}", theme),
+    ];
+    Modal {
+        title: "欢迎使用 RSNano",
+        lines: &lines,
+        footer: "选择「不再询问」后以后启动不再显示",
+        scroll: 0,
+    }
+    .draw(out, style, layout, line)
+}
@@ -0,0 +1,43 @@
+use crate::editor::{ui, Editor};
+use crate::{EditorError, Result};
+use crossterm::{execute, terminal};
+use std::ffi::OsString;
+use std::io::stdout;
+use std::process::Command;
+
+/// 用户的 shell：$SHELL（Windows 上为 %COMSPEC%），没有设置时用系统默认的 shell
+pub fn user_shell() -> OsString {
+    let (var, fallback) = if cfg!(windows) {
+        ("COMSPEC", "cmd.exe")
+    } else {
+        ("SHELL", "/bin/sh")
+    };
+    std::env::var_os(var)
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| fallback.into())
+}
+
+/// 暂时恢复终端并启动 shell，shell 退出后回到编辑器并完整重绘
+pub fn shell_out(editor: &mut Editor) -> Result<()> {
+    let shell = user_shell();
+    ui::restore_terminal()?;
+    println!("rsnano 已暂停，退出 shell（exit 或 ^D）后返回编辑器");
+    let status = Command::new(&shell).status();
+    ui::setup_terminal()?;
+    // shell 里的输出留在主屏幕上，备用屏幕可能残留之前的内容，整屏清除后重绘
+    execute!(stdout(), terminal::Clear(terminal::ClearType::All)).map_err(EditorError::terminal)?;
+    editor.terminal_size = ui::terminal_size()?;
+    match status {
+        Ok(status) if status.success() => {
+            editor.status_message = "已从 shell 返回".to_string();
+        }
+        Ok(status) => {
+            editor.status_message = match status.code() {
+                Some(code) => format!("shell 退出码 {}", code),
+                None => "shell 被信号终止".to_string(),
+            };
+        }
+        Err(e) => editor.notify_error(format!("无法启动 {}: {}", shell.to_string_lossy(), e)),
+    }
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use crate::editor::{Editor, Panel, PanelKind, Prompt, PromptOption, PromptPurpose};
+use crate::journal::JournalEvent;
+use crate::paths;
+use crate::rawbytes;
+use crate::snapshot::{diff_preview, diff_summary, Owner, Snapshot, SnapshotStore};
+use crate::Result;
+use std::fs;
+use std::path::Path;
+
+/// 可以恢复的旧版本的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionSource {
+    /// 空闲时的自动快照
+    Snapshots,
+    /// 本地历史：每次保存前文件的内容
+    History,
+}
+
+impl VersionSource {
+    fn store(self, editor: &Editor) -> Option<SnapshotStore> {
+        match self {
+            VersionSource::Snapshots => paths::snapshots_dir().map(SnapshotStore::new),
+            VersionSource::History => paths::history_dir()
+                .map(|dir| SnapshotStore::new(dir).with_limit(editor.local_history)),
+        }
+    }
+
+    /// 提示和面板中对一条版本的称呼
+    fn noun(self) -> &'static str {
+        match self {
+            VersionSource::Snapshots => "快照",
+            VersionSource::History => "历史版本",
+        }
+    }
+
+    fn journal_event(self) -> JournalEvent {
+        match self {
+            VersionSource::Snapshots => JournalEvent::RestoreSnapshot,
+            VersionSource::History => JournalEvent::RestoreHistory,
+        }
+    }
+}
+
+/// 当前缓冲区的旧版本，从新到旧
+fn current_versions(editor: &Editor, source: VersionSource) -> Result<Vec<Snapshot>> {
+    match source.store(editor) {
+        Some(store) => store.list(Owner::of(&editor.buffer)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 空闲超过 snapshot_idle 后，给自上次快照以来又修改过的缓冲区各拍一张快照
+pub fn autosnapshot(editor: &mut Editor) {
+    if editor.snapshot_idle.is_zero()
+        || editor.idle_snapshot_done
+        || editor.last_input.elapsed() < editor.snapshot_idle
+    {
+        return;
+    }
+    // 每段空闲只拍一次，有新的输入后才会再拍
+    editor.idle_snapshot_done = true;
+    let Some(store) = VersionSource::Snapshots.store(editor) else {
+        return;
+    };
+    let buffers = std::iter::once(&mut editor.buffer).chain(editor.other_buffers.iter_mut());
+    let mut error = None;
+    for buffer in buffers {
+        if !buffer.modified || buffer.snapshot_revision == Some(buffer.revision) {
+            continue;
+        }
+        match store.save(Owner::of(buffer), &buffer.lines) {
+            Ok(_) => buffer.snapshot_revision = Some(buffer.revision),
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+    if let Some(e) = error {
+        editor.notify_error(e.to_string());
+    }
+}
+
+/// 打开快照或历史版本选择：状态栏列出时间和差异摘要，右侧面板显示选中版本的差异
+pub fn open_restore_picker(editor: &mut Editor, source: VersionSource) {
+    let snapshots = match current_versions(editor, source) {
+        Ok(snapshots) if snapshots.is_empty() => {
+            editor.notify_error(format!("当前缓冲区没有{}", source.noun()));
+            return;
+        }
+        Ok(snapshots) => snapshots,
+        Err(e) => {
+            editor.notify_error(e.to_string());
+            return;
+        }
+    };
+    let keys = ('1'..='9').chain('a'..='z');
+    let options = snapshots
+        .iter()
+        .zip(keys)
+        .map(|(snapshot, key)| {
+            let summary = match snapshot.read() {
+                Ok(lines) => diff_summary(&editor.buffer.lines, &lines),
+                Err(_) => "无法读取".to_string(),
+            };
+            PromptOption {
+                key,
+                label: format!("{} {}", snapshot.label(), summary),
+            }
+        })
+        .collect();
+    let title = format!("{}差异", source.noun());
+    let label = format!("从{}恢复（←→ 选择，Enter 恢复）:", source.noun());
+    editor.panel = Some(Panel::new(PanelKind::SnapshotDiff, &title));
+    editor.prompt = Some(Prompt::choice(
+        PromptPurpose::RestoreVersion(source),
+        &label,
+        options,
+    ));
+}
+
+/// 每帧更新差异面板，只在选中的快照变化时重新读取
+pub fn update_diff_panel(editor: &mut Editor) {
+    let Some(Prompt::Choice {
+        selected,
+        purpose: PromptPurpose::RestoreVersion(source),
+        ..
+    }) = &editor.prompt
+    else {
+        return;
+    };
+    let (selected, source) = (*selected, *source);
+    if editor
+        .panel
+        .as_ref()
+        .is_none_or(|p| p.revision == Some(selected as u64))
+    {
+        return;
+    }
+    let snapshot = current_versions(editor, source)
+        .ok()
+        .and_then(|snapshots| snapshots.into_iter().nth(selected));
+    let (title, lines) = match snapshot.as_ref().map(|s| (s, s.read())) {
+        Some((snapshot, Ok(lines))) => (
+            format!("{}差异：{}", source.noun(), snapshot.label()),
+            diff_preview(&editor.buffer.lines, &lines),
+        ),
+        Some((_, Err(e))) => (e.to_string(), Vec::new()),
+        None => (format!("{}已不存在", source.noun()), Vec::new()),
+    };
+    if let Some(panel) = editor.panel.as_mut() {
+        panel.title = title;
+        panel.lines = lines;
+        panel.scroll = 0;
+        panel.revision = Some(selected as u64);
+    }
+}
+
+/// 关闭差异面板，选中了某个版本时用它替换当前缓冲区的内容
+pub fn finish_restore(editor: &mut Editor, source: VersionSource, chosen: Option<usize>) {
+    if editor
+        .panel
+        .as_ref()
+        .is_some_and(|p| p.kind == PanelKind::SnapshotDiff)
+    {
+        editor.panel = None;
+    }
+    let Some(index) = chosen else {
+        return;
+    };
+    let snapshot = match current_versions(editor, source) {
+        Ok(snapshots) => snapshots.into_iter().nth(index),
+        Err(e) => {
+            editor.notify_error(e.to_string());
+            return;
+        }
+    };
+    let Some(snapshot) = snapshot else {
+        editor.notify_error(format!("{}已不存在", source.noun()));
+        return;
+    };
+    match snapshot.read() {
+        Ok(lines) => {
+            editor.buffer.replace_contents(lines);
+            editor.buffer.scroll_to_cursor(editor.layout().page_rows());
+            editor.status_message = format!(
+                "已恢复到 {} 的{}，保存后写入文件",
+                snapshot.label(),
+                source.noun()
+            );
+            let detail = format!("恢复到{}的{}", snapshot.label(), source.noun());
+            super::journal::record(editor, source.journal_event(), &detail);
+        }
+        Err(e) => editor.notify_error(e.to_string()),
+    }
+}
+
+/// 开启本地历史时，保存前把文件在磁盘上原来的内容存为一个历史版本
+///
+/// 与最新的历史版本相同时不再重复保存。
+pub fn save_history(editor: &mut Editor, path: &Path) {
+    if editor.local_history == 0 {
+        return;
+    }
+    let Some(store) = VersionSource::History.store(editor) else {
+        return;
+    };
+    let Ok(bytes) = fs::read(path) else {
+        // 新文件或无法读取的文件没有可保存的旧版本
+        return;
+    };
+    let (text, _) = rawbytes::decode(&bytes);
+    let lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+    let latest = store
+        .list(Owner::File(path))
+        .ok()
+        .and_then(|versions| versions.into_iter().next())
+        .and_then(|version| version.read().ok());
+    if latest.as_ref() == Some(&lines) {
+        return;
+    }
+    if let Err(e) = store.save(Owner::File(path), &lines) {
+        editor.notify_error(e.to_string());
+    }
+}
@@ -1,111 +1,167 @@
+use super::ui::print_styled;
 use crate::editor::Editor;
-use crate::Result;
-use crossterm::style::{Color, ResetColor, SetForegroundColor};
+use crate::layout::Layout;
+use crate::outline;
+use crate::render::{fit_to_width, pad_to_width, wrap_to_width};
+use crate::unicode::str_width;
 use crossterm::terminal::ClearType;
-use crossterm::{cursor, execute, style, terminal};
-use std::io::stdout;
+use crossterm::{cursor, queue, terminal};
+use std::fmt::Write as _;
+use std::io::{self, Write};
 
-pub fn draw_status_bar(editor: &Editor) -> Result<()> {
-    let (width, height) = editor.terminal_size;
-    // 状态栏在倒数第二行
-    execute!(stdout(), cursor::MoveTo(0, height - 2))?;
-    execute!(stdout(), terminal::Clear(ClearType::CurrentLine))?;
+/// 状态区最多扩展到的行数
+const MAX_STATUS_ROWS: usize = 3;
 
-    // 状态栏
-    if let Some(prompt) = &editor.file_save_prompt {
-        let input = &editor.file_save_input;
-        let msg = format!("{} {}", prompt, input);
-        let msg_len = msg.len();
-        execute!(
-            stdout(),
-            SetForegroundColor(Color::Black),
-            style::SetBackgroundColor(Color::White),
-            style::Print(&msg),
-        )?;
-        let remaining = (width as usize).saturating_sub(msg_len);
-        if remaining > 0 {
-            execute!(stdout(), style::Print(" ".repeat(remaining)))?;
+/// 普通状态栏：文件名、行数和各种模式标记
+fn format_status(editor: &Editor, status: &mut String) {
+    let filename = editor
+        .buffer
+        .filename
+        .as_ref()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or("[无文件名]");
+    let _ = write!(status, " {} - {} 行", filename, editor.buffer.lines.len());
+    if !editor.other_buffers.is_empty() {
+        let _ = write!(
+            status,
+            " [{}/{}]",
+            editor.buffer_index + 1,
+            editor.other_buffers.len() + 1
+        );
+    }
+    if editor.buffer.modified && !editor.buffer.modified_lines_set.is_empty() {
+        let _ = write!(
+            status,
+            " [已修改 {} 行]",
+            editor.buffer.modified_lines_set.len()
+        );
+    }
+    if let Some(stats) = editor.buffer.stats {
+        if editor.warn_line_length > 0 && stats.longest_len > editor.warn_line_length {
+            let _ = write!(status, " [长行 {} 字符]", stats.longest_len);
         }
-        execute!(stdout(), ResetColor)?;
-    } else if editor.exit_confirm_prompt {
-        let msg = "文件已修改，是否保存？Y=保存 N=不保存 ^C=取消";
-        let msg_len = msg.len();
-        execute!(
-            stdout(),
-            SetForegroundColor(Color::Black),
-            style::SetBackgroundColor(Color::White),
-            style::Print(msg),
-        )?;
-        let remaining = (width as usize).saturating_sub(msg_len);
-        if remaining > 0 {
-            execute!(stdout(), style::Print(" ".repeat(remaining)))?;
+        if editor.warn_size > 0 && stats.bytes > editor.warn_size {
+            let _ = write!(status, " [大小 {} KB]", stats.bytes >> 10);
         }
-        execute!(stdout(), ResetColor)?;
-    } else {
-        // 普通状态栏
-        let filename = editor
-            .buffer
-            .filename
-            .as_ref()
-            .and_then(|p| p.file_name())
-            .and_then(|n| n.to_str())
-            .unwrap_or("[无文件名]");
-        let modified_indicator =
-            if editor.buffer.modified && !editor.buffer.modified_lines_set.is_empty() {
-                format!(" [已修改 {} 行]", editor.buffer.modified_lines_set.len())
-            } else {
-                "".to_string()
-            };
-        let secondary_cursor_indicator = if editor.buffer.cursor_x2.is_some() {
-            " [多光标]"
-        } else {
-            ""
-        };
-        let mut status = format!(
-            " {} - {} 行{}{}",
-            filename,
-            editor.buffer.lines.len(),
-            modified_indicator,
-            secondary_cursor_indicator
-        );
+        if let Some(words) = stats.words {
+            let minutes = outline::reading_minutes(words);
+            let _ = write!(status, " [{} 字 约 {} 分钟]", words, minutes);
+        }
+    }
+    if let Some(style) = editor.buffer.indent_style.filter(|_| editor.detect_indent) {
+        let _ = write!(status, " [缩进 {}]", style.label());
+    }
+    if let Some(preview) = &editor.buffer.preview {
+        let _ = write!(status, " [预览 {}%]", preview.percent());
+    } else if editor.buffer.settings.read_only {
+        status.push_str(" [只读]");
+    }
+    if editor.buffer.is_line_protected(editor.buffer.cursor_y) {
+        status.push_str(" [保护行]");
+    }
+    if editor.buffer.has_multiple_cursors() {
+        let _ = write!(status, " [{} 光标]", editor.buffer.cursor_count());
+    }
+    if let Some((lines, cols)) = editor.buffer.block_range() {
+        let _ = write!(status, " [块 {}×{}]", lines.len(), cols.len());
+    }
+    if let Some(steps) = &editor.recording {
+        let _ = write!(status, " [录制宏 {}]", steps.len());
+    }
+    if editor.buffer.mark.is_some() {
+        status.push_str(" [标记]");
+    }
+    if editor.buffer.settings.virtual_space {
+        status.push_str(" [自由光标]");
+    }
+    if editor.buffer.settings.prose {
+        status.push_str(" [散文]");
+    } else if editor.buffer.settings.soft_wrap {
+        status.push_str(" [软换行]");
+    }
+    if let Some(style) = editor.box_drawing {
+        let _ = write!(status, " [画框 {}]", style.label());
+    }
+}
 
-        if !editor.status_message.is_empty() {
-            let left_len = status.len();
-            let right_msg = format!("  {}", editor.status_message);
-            let space = (width as usize).saturating_sub(left_len + right_msg.len());
-            if space > 0 {
-                status.push_str(&" ".repeat(space));
-            }
-            status.push_str(&right_msg);
-        } else {
-            let status_len = status.len();
-            let remaining = (width as usize).saturating_sub(status_len);
-            if remaining > 0 {
-                status.push_str(&" ".repeat(remaining));
-            }
+/// 按宽度生成状态区的各行
+///
+/// 有提示时显示提示，一行放不下时说明和输入内容（或选项）分行显示；
+/// 否则显示普通状态栏，消息放得下时靠右显示在同一行，放不下时折到下面的行。
+pub fn status_lines(editor: &Editor, width: usize, lines: &mut Vec<String>) {
+    lines.clear();
+    if let Some(prompt) = editor.prompt.as_ref().filter(|p| !p.is_modal()) {
+        prompt.render_rows(width, lines);
+        if lines.len() > MAX_STATUS_ROWS {
+            // 保留第一行的说明和最后的正在输入的内容
+            lines.drain(1..lines.len() + 1 - MAX_STATUS_ROWS);
         }
-        execute!(
-            stdout(),
-            SetForegroundColor(Color::Black),
-            style::SetBackgroundColor(Color::White),
-            style::Print(status),
-            ResetColor
-        )?;
-    }
-    // 最下方帮助栏始终不被覆盖
-    execute!(stdout(), cursor::MoveTo(0, height - 1))?;
-    execute!(stdout(), terminal::Clear(ClearType::CurrentLine))?;
-    let help = "^X 退出  ^O 保存  ^G 帮助  ^C 多光标  Alt+方向键 移动多光标";
-    execute!(
-        stdout(),
-        SetForegroundColor(Color::Black),
-        style::SetBackgroundColor(Color::White),
-        style::Print(help),
-    )?;
-    let remaining = (width as usize).saturating_sub(help.len());
-    if remaining > 0 {
-        execute!(stdout(), style::Print(" ".repeat(remaining)))?;
-    }
-    execute!(stdout(), ResetColor)?;
+        return;
+    }
+    let mut status = String::new();
+    format_status(editor, &mut status);
+    let message = &editor.status_message;
+    let right_len = 2 + str_width(message);
+    if message.is_empty() {
+        lines.push(status);
+    } else if str_width(&status) + right_len <= width {
+        // 状态消息靠右显示
+        pad_to_width(&mut status, width - right_len);
+        status.push_str("  ");
+        status.push_str(message);
+        lines.push(status);
+    } else {
+        lines.push(status);
+        wrap_to_width(message, width, lines);
+        lines.truncate(MAX_STATUS_ROWS);
+    }
+}
+
+/// 状态区需要的行数
+pub fn status_rows(editor: &Editor, width: usize) -> usize {
+    let mut lines = Vec::new();
+    status_lines(editor, width, &mut lines);
+    lines.len()
+}
+
+pub fn draw_status_bar<W: Write>(
+    out: &mut W,
+    editor: &mut Editor,
+    layout: &Layout,
+) -> io::Result<()> {
+    // 状态区在编辑区下方，终端只有一行时也保留第一行
+    let Some(status_row) = layout.status_bar else {
+        return Ok(());
+    };
+    let mut lines = std::mem::take(&mut editor.frame.status_lines);
+    status_lines(editor, layout.width, &mut lines);
+    for (i, line) in lines.iter_mut().take(layout.status_rows).enumerate() {
+        queue!(out, cursor::MoveTo(0, status_row + i as u16))?;
+        queue!(out, terminal::Clear(ClearType::CurrentLine))?;
+        fit_to_width(line, layout.width);
+        print_styled(out, line, &editor.theme.status_bar)?;
+    }
+    editor.frame.status_lines = lines;
+    Ok(())
+}
+
+/// 最下方的帮助栏，按配置的操作列表和当前键位生成
+pub fn draw_help_line<W: Write>(
+    out: &mut W,
+    editor: &mut Editor,
+    layout: &Layout,
+) -> io::Result<()> {
+    // 终端太矮或配置关闭时不显示
+    let Some(help_row) = layout.help_bar else {
+        return Ok(());
+    };
+    queue!(out, cursor::MoveTo(0, help_row))?;
+    queue!(out, terminal::Clear(ClearType::CurrentLine))?;
+    let mut help = std::mem::take(&mut editor.frame.help_bar);
+    super::help_line::render_into(editor, layout.width, &mut help);
+    fit_to_width(&mut help, layout.width);
+    print_styled(out, &help, &editor.theme.help_bar)?;
+    editor.frame.help_bar = help;
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,85 @@
+use crate::buffer::{Position, TextBuffer};
+use crate::editor::{Editor, Panel, PanelKind};
+use crate::location::{Location, LocationList};
+use crate::render::{SpanStyle, StyledLine};
+use crate::unicode::is_word_char;
+
+/// 行中第一个作为完整的词出现的标记，返回它的字节范围
+fn find_marker(line: &str, markers: &[String]) -> Option<(usize, usize)> {
+    markers
+        .iter()
+        .filter_map(|marker| {
+            line.match_indices(marker.as_str())
+                .map(|(start, m)| (start, start + m.len()))
+                .find(|&(start, end)| {
+                    !line[..start].chars().next_back().is_some_and(is_word_char)
+                        && !line[end..].chars().next().is_some_and(is_word_char)
+                })
+        })
+        .min()
+}
+
+/// 在一个缓冲区中找标记，每处生成一项位置和一行面板内容
+fn scan_buffer(
+    buffer: &TextBuffer,
+    markers: &[String],
+    items: &mut Vec<Location>,
+    lines: &mut Vec<StyledLine>,
+) {
+    let name = buffer
+        .filename
+        .as_ref()
+        .map_or_else(|| "新缓冲区".to_string(), |p| p.display().to_string());
+    for (y, line) in buffer.lines.iter().enumerate() {
+        let Some((start, end)) = find_marker(line, markers) else {
+            continue;
+        };
+        let text = line[start..].trim_end();
+        let mut styled = StyledLine::new(Some(items.len()));
+        styled.push(&format!("{}:{}: ", name, y + 1), SpanStyle::Dim);
+        styled.push(&line[start..end], SpanStyle::Match);
+        styled.push(&text[end - start..], SpanStyle::Normal);
+        lines.push(styled);
+        let x = line[..start].chars().count();
+        items.push(Location::new(
+            buffer.filename.clone(),
+            Position::new(x, y),
+            text,
+        ));
+    }
+}
+
+/// 在打开的缓冲区中查找 TODO、FIXME 等标记并显示在底部面板，已打开且有焦点时关闭
+///
+/// 标记词由配置 todo_markers 决定。没有文件名的缓冲区只扫描当前这一个，其他的无法跳转。
+pub fn toggle_task_list(editor: &mut Editor) {
+    if let Some(panel) = editor.panel.as_mut().filter(|p| p.kind == PanelKind::Tasks) {
+        if panel.focused {
+            editor.panel = None;
+            return;
+        }
+    }
+    let markers = &editor.todo_markers;
+    let mut items = Vec::new();
+    let mut lines = Vec::new();
+    scan_buffer(&editor.buffer, markers, &mut items, &mut lines);
+    for buffer in editor.other_buffers.iter().filter(|b| b.filename.is_some()) {
+        scan_buffer(buffer, markers, &mut items, &mut lines);
+    }
+    if items.is_empty() {
+        let message = format!("打开的缓冲区中没有 {}", markers.join("、"));
+        editor.notify_error(message);
+        return;
+    }
+    let mut panel = Panel::new(
+        PanelKind::Tasks,
+        &format!("任务列表：{} 处（Enter 跳转，Esc 关闭）", items.len()),
+    );
+    panel.lines = lines;
+    panel.bottom = true;
+    panel.focused = true;
+    panel.selected = Some(0);
+    editor.status_message = format!("找到 {} 处任务标记", items.len());
+    super::locations::set_list(editor, LocationList::new("任务", items));
+    editor.panel = Some(panel);
+}
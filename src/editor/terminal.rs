@@ -0,0 +1,195 @@
+use crate::editor::{Editor, Panel, PanelKind};
+use crate::pty::Pty;
+use crate::render::{SpanStyle, StyledLine};
+use crate::vt::Screen;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// 内置终端：伪终端中的 shell 和它的屏幕，隐藏面板后仍在后台运行
+pub struct EmbeddedTerminal {
+    pty: Pty,
+    screen: Screen,
+    /// 本帧读到的输出，复用缓冲区
+    output: Vec<u8>,
+}
+
+/// 终端面板的大小（列数, 行数），去掉标题行
+fn panel_size(editor: &Editor) -> (usize, usize) {
+    let layout = editor.layout();
+    (layout.width, layout.panel_rows.saturating_sub(1))
+}
+
+fn is_terminal_panel(panel: &Panel) -> bool {
+    panel.kind == PanelKind::Terminal
+}
+
+/// 显示终端面板并把焦点交给它，第一次打开时启动 shell；面板有焦点时隐藏
+pub fn toggle(editor: &mut Editor) {
+    if let Some(panel) = editor.panel.as_mut().filter(|p| is_terminal_panel(p)) {
+        if panel.focused {
+            editor.panel = None;
+            editor.status_message = "已隐藏终端，shell 仍在运行（F6 显示）".to_string();
+        } else {
+            panel.focused = true;
+        }
+        return;
+    }
+    let shell = super::shell::user_shell();
+    let mut panel = Panel::new(
+        PanelKind::Terminal,
+        &format!(
+            "终端 {}（F6 隐藏，其他按键都发给 shell）",
+            shell.to_string_lossy()
+        ),
+    );
+    panel.bottom = true;
+    panel.focused = true;
+    editor.panel = Some(panel);
+    let (width, height) = panel_size(editor);
+    if height == 0 {
+        editor.panel = None;
+        editor.notify_error("终端窗口太小，无法显示内置终端");
+        return;
+    }
+    if editor.terminal.is_none() {
+        match Pty::spawn(&shell, (width as u16, height as u16)) {
+            Ok(pty) => {
+                editor.terminal = Some(EmbeddedTerminal {
+                    pty,
+                    screen: Screen::new(width, height),
+                    output: Vec::new(),
+                });
+            }
+            Err(e) => {
+                editor.panel = None;
+                editor.notify_error(format!("无法启动终端: {}", e));
+                return;
+            }
+        }
+    }
+    editor.status_message = "已打开终端".to_string();
+}
+
+/// 每帧读入 shell 的输出，面板隐藏时也照常读取，输出不会越积越多；
+/// shell 退出后关闭终端和它的面板
+pub fn poll(editor: &mut Editor) {
+    let Some(terminal) = editor.terminal.as_mut() else {
+        return;
+    };
+    terminal.output.clear();
+    terminal.pty.read_available(&mut terminal.output);
+    terminal.screen.feed(&terminal.output);
+    if terminal.output.is_empty() && terminal.pty.has_exited() {
+        editor.terminal = None;
+        if editor.panel.as_ref().is_some_and(is_terminal_panel) {
+            editor.panel = None;
+        }
+        editor.status_message = "终端中的 shell 已退出".to_string();
+    }
+}
+
+/// 每帧绘制前跟随面板大小，并重新生成面板内容
+pub fn update_panel(editor: &mut Editor) {
+    let (width, height) = panel_size(editor);
+    let Some(terminal) = editor.terminal.as_mut() else {
+        editor.panel = None;
+        return;
+    };
+    if height > 0 && terminal.screen.size() != (width, height) {
+        terminal.screen.resize(width, height);
+        let _ = terminal.pty.resize((width as u16, height as u16));
+    }
+    let Some(panel) = editor.panel.as_mut() else {
+        return;
+    };
+    let screen = &terminal.screen;
+    let (cursor_x, cursor_y) = screen.cursor();
+    let mut text = String::new();
+    panel.lines.clear();
+    for y in 0..screen.size().1 {
+        let mut line = StyledLine::new(None);
+        if panel.focused && y == cursor_y {
+            // 光标所在格反色显示
+            screen.row_slice(y, 0, cursor_x, &mut text);
+            line.push(&text, SpanStyle::Normal);
+            screen.row_slice(y, cursor_x, cursor_x + 1, &mut text);
+            if text.is_empty() {
+                text.push(' ');
+            }
+            line.push(&text, SpanStyle::Cursor);
+            screen.row_slice(y, cursor_x + 1, screen.size().0, &mut text);
+            line.push(text.trim_end(), SpanStyle::Normal);
+        } else {
+            screen.row_text(y, &mut text);
+            line.push(&text, SpanStyle::Normal);
+        }
+        panel.lines.push(line);
+    }
+    panel.scroll = 0;
+}
+
+/// 把按键换成终端收到的字节，不能发送的按键返回 None
+fn key_bytes(key_event: KeyEvent) -> Option<Vec<u8>> {
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    let mut bytes = match key_event.code {
+        KeyCode::Char(ch) if ctrl => match ch.to_ascii_lowercase() {
+            ch @ 'a'..='z' => vec![ch as u8 - b'a' + 1],
+            '@' | ' ' | '2' => vec![0],
+            '[' | '3' => vec![0x1b],
+            '\\' | '4' => vec![0x1c],
+            ']' | '5' => vec![0x1d],
+            '^' | '6' => vec![0x1e],
+            '_' | '-' | '7' => vec![0x1f],
+            _ => return None,
+        },
+        KeyCode::Char(ch) => ch.to_string().into_bytes(),
+        KeyCode::Enter => b"\r".to_vec(),
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab => b"\t".to_vec(),
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => b"\x1b[A".to_vec(),
+        KeyCode::Down => b"\x1b[B".to_vec(),
+        KeyCode::Right => b"\x1b[C".to_vec(),
+        KeyCode::Left => b"\x1b[D".to_vec(),
+        KeyCode::Home => b"\x1b[H".to_vec(),
+        KeyCode::End => b"\x1b[F".to_vec(),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        KeyCode::Insert => b"\x1b[2~".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        _ => return None,
+    };
+    if key_event.modifiers.contains(KeyModifiers::ALT) {
+        bytes.insert(0, 0x1b);
+    }
+    Some(bytes)
+}
+
+/// 面板有焦点时把按键发给 shell
+pub fn send_key(editor: &mut Editor, key_event: KeyEvent) {
+    if let Some(bytes) = key_bytes(key_event) {
+        send(editor, &bytes);
+    }
+}
+
+/// 把粘贴或输入法提交的文字发给 shell
+pub fn send_text(editor: &mut Editor, text: &str) {
+    send(editor, text.as_bytes());
+}
+
+fn send(editor: &mut Editor, bytes: &[u8]) {
+    let Some(terminal) = editor.terminal.as_mut() else {
+        return;
+    };
+    if let Err(e) = terminal.pty.write(bytes) {
+        editor.notify_error(format!("无法写入终端: {}", e));
+    }
+}
+
+/// 焦点是否在终端面板上
+pub fn is_focused(editor: &Editor) -> bool {
+    editor
+        .panel
+        .as_ref()
+        .is_some_and(|p| p.focused && is_terminal_panel(p))
+}
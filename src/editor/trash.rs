@@ -0,0 +1,33 @@
+use crate::editor::Editor;
+use crate::trash;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 原文件移进回收站后附在状态消息后面的说明
+const TRASHED_NOTE: &str = "，原文件已移进回收站";
+
+/// 系统不支持回收站、直接覆盖时附在状态消息后面的说明
+const UNSUPPORTED_NOTE: &str = "，此系统不支持回收站，原文件已直接覆盖";
+
+/// 写入 path 之前调用：已有文件时先移进回收站（配置 trash = false 时不移）
+///
+/// 返回附在成功消息后面的说明；移动失败时显示错误并返回 None，调用方不应再写入。
+pub fn before_overwrite(editor: &mut Editor, path: &Path) -> Option<&'static str> {
+    if !editor.trash || !fs::metadata(path).is_ok_and(|m| m.is_file()) {
+        return Some("");
+    }
+    match trash::move_to_trash(path) {
+        Ok(_) => Some(TRASHED_NOTE),
+        // 系统没有回收站时照常覆盖，只在消息中说明
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => Some(UNSUPPORTED_NOTE),
+        Err(e) => {
+            editor.notify_error(format!(
+                "无法把 {} 移进回收站，没有写入（配置 trash = false 可关闭）: {}",
+                path.display(),
+                e
+            ));
+            None
+        }
+    }
+}
@@ -1,26 +1,391 @@
+use crate::buffer::{AnnotationPlacement, Position, TextBuffer};
+use crate::charinfo;
+use crate::config::BellStyle;
 use crate::editor::Editor;
-use crate::Result;
-use crossterm::style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crate::rawbytes;
+use crate::render::{clip_end, fit_to_width, truncate_to_width, wrap_end, wrap_position};
+use crate::theme::{Style as ThemeStyle, Theme};
+use crate::unicode::{cell_width, display_column};
+use crate::{EditorError, Result};
+use crossterm::event::{self, Event};
+use crossterm::style::{
+    Attribute, Color, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
 use crossterm::terminal::ClearType;
-use crossterm::{cursor, execute, style, terminal};
-use std::io::stdout;
+use crossterm::{cursor, execute, queue, style, terminal};
+use std::fmt::Write as _;
+use std::io::{self, stdout, Write};
+use std::ops::Range;
+use std::time::{Duration, Instant};
 
 pub fn setup_terminal() -> Result<()> {
-    terminal::enable_raw_mode()?;
-    execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+    terminal::enable_raw_mode().map_err(EditorError::terminal)?;
+    // 括号粘贴：终端把粘贴的内容包起来整段送来，而不是一串按键
+    execute!(
+        stdout(),
+        terminal::EnterAlternateScreen,
+        event::EnableBracketedPaste,
+        cursor::Hide
+    )
+    .map_err(EditorError::terminal)?;
     Ok(())
 }
 
 pub fn restore_terminal() -> Result<()> {
-    execute!(stdout(), terminal::LeaveAlternateScreen, cursor::Show)?;
-    terminal::disable_raw_mode()?;
+    // 关闭可能还在进行的闪屏
+    execute!(
+        stdout(),
+        style::Print("\x1b[?5l"),
+        event::DisableBracketedPaste,
+        terminal::LeaveAlternateScreen,
+        cursor::Show
+    )
+    .map_err(EditorError::terminal)?;
+    terminal::disable_raw_mode().map_err(EditorError::terminal)?;
     Ok(())
 }
 
-pub fn refresh_screen(editor: &mut Editor) -> Result<()> {
-    // 顶部信息栏
-    execute!(stdout(), cursor::MoveTo(0, 0))?;
-    execute!(stdout(), terminal::Clear(ClearType::CurrentLine))?;
+/// 当前终端尺寸（列, 行）
+pub fn terminal_size() -> Result<(u16, u16)> {
+    terminal::size().map_err(EditorError::terminal)
+}
+
+/// 等待终端事件，超时返回 false
+pub fn poll_event(timeout: Duration) -> Result<bool> {
+    event::poll(timeout).map_err(EditorError::terminal)
+}
+
+/// 读取一个终端事件
+pub fn read_event() -> Result<Event> {
+    event::read().map_err(EditorError::terminal)
+}
+
+/// 编辑区单元格的显示样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CellStyle {
+    Normal,
+    Selected,
+    Cursor,
+    Match,
+    Overflow,
+    /// 可疑的 Unicode 字符
+    Suspicious,
+    /// 查看模式中输入自带的 ANSI 颜色
+    Ansi(ThemeStyle),
+}
+
+/// 切换到主题中某个元素的样式
+pub fn set_style<W: Write>(out: &mut W, style: &ThemeStyle) -> io::Result<()> {
+    if let Some(fg) = style.fg {
+        queue!(out, SetForegroundColor(fg))?;
+    }
+    if let Some(bg) = style.bg {
+        queue!(out, SetBackgroundColor(bg))?;
+    }
+    if style.bold {
+        queue!(out, SetAttribute(Attribute::Bold))?;
+    }
+    if style.reverse {
+        queue!(out, SetAttribute(Attribute::Reverse))?;
+    }
+    Ok(())
+}
+
+/// 恢复默认样式
+pub fn reset_style<W: Write>(out: &mut W) -> io::Result<()> {
+    queue!(out, SetAttribute(Attribute::Reset), ResetColor)
+}
+
+/// 用主题中某个元素的样式输出文本
+pub fn print_styled<W: Write>(out: &mut W, text: &str, style: &ThemeStyle) -> io::Result<()> {
+    set_style(out, style)?;
+    queue!(out, style::Print(text))?;
+    reset_style(out)
+}
+
+/// 按样式输出一段文本
+fn print_segment<W: Write>(
+    out: &mut W,
+    text: &str,
+    style: CellStyle,
+    theme: &Theme,
+) -> io::Result<()> {
+    if text.is_empty() {
+        return Ok(());
+    }
+    match style {
+        CellStyle::Normal => queue!(out, style::Print(text)),
+        CellStyle::Selected => print_styled(out, text, &theme.selection),
+        CellStyle::Cursor => print_styled(out, text, &theme.cursor),
+        CellStyle::Match => print_styled(out, text, &theme.search_match),
+        CellStyle::Overflow => print_styled(out, text, &theme.overflow),
+        CellStyle::Suspicious => print_styled(out, text, &theme.suspicious),
+        CellStyle::Ansi(style) => print_styled(out, text, &style),
+    }
+}
+
+/// 在行尾之后绘制光标，gap 为光标前需要跳过的列数
+fn draw_eol_cursor<W: Write>(out: &mut W, gap: usize, theme: &Theme) -> io::Result<()> {
+    if gap > 0 {
+        queue!(out, cursor::MoveRight(gap as u16))?;
+    }
+    print_styled(out, "▏", &theme.cursor)
+}
+
+pub fn refresh_screen(editor: &mut Editor) -> io::Result<()> {
+    // 整帧先排队再统一刷新，避免每次输出都触发一次系统调用
+    let mut out = stdout().lock();
+    draw_frame(&mut out, editor)
+}
+
+/// 把一整帧画到 out，基准测试中 out 是内存里的假终端
+pub fn draw_frame<W: Write>(mut out: &mut W, editor: &mut Editor) -> io::Result<()> {
+    let width = editor.terminal_size.0;
+    let layout = editor.layout();
+    if layout.is_empty() {
+        return Ok(());
+    }
+
+    // 顶部信息栏，终端太矮时不显示
+    if let Some(row) = layout.title_bar {
+        draw_title_bar(&mut out, editor, row)?;
+    }
+
+    // 编辑器区域，有面板时只占左侧
+    let text_width = super::panel::text_area_width(editor);
+    let editor_height = layout.text_rows;
+    let gutter_width = editor.buffer.gutter_width();
+    let display_width = text_width.saturating_sub(gutter_width);
+    let soft_wrap = editor.buffer.settings.soft_wrap;
+    let tab_width = editor.buffer.settings.tab_width;
+    // 栏的显示和终端尺寸都可能改变编辑区的高度，每帧按当前布局保证光标可见
+    if soft_wrap {
+        scroll_wrapped(
+            &mut editor.buffer,
+            display_width,
+            tab_width,
+            layout.page_rows(),
+        );
+    } else {
+        editor.buffer.scroll_to_cursor(layout.page_rows());
+        scroll_past_annotations(&mut editor.buffer, layout.page_rows());
+        editor.buffer.scroll_to_cursor_column(display_width);
+    }
+    // 软换行时一个文件行可能占多个屏幕行，row_start 是下一屏幕行开头的字符索引
+    let offset_x = editor.buffer.offset_x;
+    let mut file_row = editor.buffer.offset_y;
+    let mut row_start = 0;
+    // 当前文件行上方已经画了几条虚拟文本
+    let mut above_drawn = 0;
+    // 有对话框时编辑区变暗，也不显示光标、选区和高亮
+    let dim = super::modal::is_open(editor);
+    queue!(out, cursor::MoveTo(0, layout.text_top))?;
+    for screen_row in 0..editor_height {
+        queue!(out, terminal::Clear(ClearType::CurrentLine))?;
+        if file_row < editor.buffer.lines.len()
+            && row_start == 0
+            && above_drawn < editor.buffer.annotation_rows_above(file_row)
+        {
+            draw_gutter(
+                &mut out,
+                &editor.buffer,
+                &editor.theme,
+                None,
+                &mut editor.frame.line,
+            )?;
+            draw_above_annotation(
+                &mut out,
+                &editor.buffer,
+                file_row,
+                above_drawn,
+                display_width,
+                &mut editor.frame.line,
+            )?;
+            above_drawn += 1;
+        } else if file_row < editor.buffer.lines.len() {
+            let line = &editor.buffer.lines[file_row];
+            let char_count = line.chars().count();
+            let row = if soft_wrap {
+                let start = row_start.min(char_count);
+                ClippedRow {
+                    range: start..wrap_end(line, start, display_width, tab_width),
+                    ..ClippedRow::default()
+                }
+            } else {
+                clip_row(line, offset_x, display_width, tab_width)
+            };
+            let end = row.range.end;
+            // 折行后的续行不显示符号和行号
+            draw_gutter(
+                &mut out,
+                &editor.buffer,
+                &editor.theme,
+                (row_start == 0).then_some(file_row),
+                &mut editor.frame.line,
+            )?;
+            if row.left_marker {
+                queue!(out, style::Print('<'))?;
+            }
+            if row.pad > 0 {
+                queue!(out, cursor::MoveRight(row.pad as u16))?;
+            }
+            let lead = row.left_marker as usize + row.pad;
+            let width = display_width.saturating_sub(lead + row.right_marker as usize);
+            let used = lead
+                + if dim {
+                    draw_dimmed_row(
+                        &mut out,
+                        line,
+                        row.range.clone(),
+                        row_first_column(&editor.buffer, line, row.range.start),
+                        tab_width,
+                        &mut editor.frame.line,
+                    )?
+                } else {
+                    draw_text_row(
+                        &mut out,
+                        &editor.buffer,
+                        &editor.theme,
+                        file_row,
+                        row.range,
+                        width,
+                        &mut editor.frame.line,
+                    )?
+                };
+            // 右边放不下的部分截掉，最后一列画 >，行尾的虚拟文本也不再显示
+            let used = if row.right_marker {
+                let gap = (display_width - 1).saturating_sub(used);
+                if gap > 0 {
+                    queue!(out, cursor::MoveRight(gap as u16))?;
+                }
+                queue!(out, style::Print('>'))?;
+                display_width
+            } else {
+                used
+            };
+            if soft_wrap && end < char_count {
+                row_start = end;
+            } else {
+                draw_eol_annotations(
+                    &mut out,
+                    &editor.buffer,
+                    file_row,
+                    used,
+                    display_width,
+                    &mut editor.frame.line,
+                )?;
+                file_row += 1;
+                row_start = 0;
+                above_drawn = 0;
+            }
+        } else {
+            if editor.buffer.cursor_y == file_row && !dim {
+                // 自由光标模式下光标可能在文件末尾之后
+                let gap = editor
+                    .buffer
+                    .cursor_x
+                    .saturating_sub(offset_x)
+                    .min(text_width.saturating_sub(gutter_width + 1));
+                queue!(out, cursor::MoveToColumn(gutter_width as u16))?;
+                draw_eol_cursor(&mut out, gap, &editor.theme)?;
+            }
+            file_row += 1;
+        }
+        if let Some(panel) = editor.panel.as_ref().filter(|p| !p.bottom) {
+            super::panel::draw_panel_row(
+                &mut out,
+                panel,
+                screen_row,
+                text_width,
+                width as usize,
+                &mut editor.frame.line,
+            )?;
+        }
+        queue!(out, cursor::MoveToNextLine(1))?;
+    }
+    if let Some(panel) = editor.panel.as_ref().filter(|p| p.bottom) {
+        for row in 0..layout.panel_rows {
+            queue!(
+                out,
+                cursor::MoveTo(0, layout.panel_top + row as u16),
+                terminal::Clear(ClearType::CurrentLine)
+            )?;
+            super::panel::draw_panel_row(
+                &mut out,
+                panel,
+                row,
+                0,
+                width as usize,
+                &mut editor.frame.line,
+            )?;
+        }
+    }
+    super::status::draw_status_bar(&mut out, editor, &layout)?;
+    super::status::draw_help_line(&mut out, editor, &layout)?;
+    // 对话框最后绘制，盖在编辑区和状态栏之上
+    if let Some(setup) = &editor.setup {
+        super::setup::draw_overlay(
+            &mut out,
+            setup,
+            &editor.theme.help_bar,
+            &layout,
+            &mut editor.frame.line,
+        )?;
+    }
+    if let Some(prompt) = editor.prompt.as_ref().filter(|p| p.is_modal()) {
+        prompt.draw_modal(
+            &mut out,
+            &editor.theme.help_bar,
+            &layout,
+            &mut editor.frame.line,
+        )?;
+    }
+    if editor.show_help_page {
+        super::help::draw(&mut out, editor, &layout)?;
+    }
+    ring_bell(&mut out, editor)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// 文本左侧的符号栏和行号；y 为 None 时（虚拟文本行、折行后的续行）只留出空白
+fn draw_gutter<W: Write>(
+    out: &mut W,
+    buffer: &TextBuffer,
+    theme: &Theme,
+    y: Option<usize>,
+    scratch: &mut String,
+) -> io::Result<()> {
+    if buffer.sign_column_width() > 0 {
+        match y.and_then(|y| buffer.sign_at(y)) {
+            Some(sign) => queue!(
+                out,
+                SetForegroundColor(sign.color),
+                style::Print(sign.symbol),
+                ResetColor,
+                style::Print(' ')
+            )?,
+            None => queue!(out, style::Print("  "))?,
+        }
+    }
+    if buffer.settings.line_numbers {
+        scratch.clear();
+        match y {
+            Some(y) => {
+                let _ = write!(scratch, "{:3} ", y + 1);
+            }
+            None => scratch.push_str("    "),
+        }
+        print_styled(out, scratch, &theme.line_number)?;
+    }
+    Ok(())
+}
+
+/// 顶部信息栏：程序名、版本和文件名
+fn draw_title_bar<W: Write>(out: &mut W, editor: &mut Editor, row: u16) -> io::Result<()> {
+    let width = editor.terminal_size.0;
+    queue!(out, cursor::MoveTo(0, row))?;
+    queue!(out, terminal::Clear(ClearType::CurrentLine))?;
     let filename = editor
         .buffer
         .filename
@@ -28,67 +393,364 @@ pub fn refresh_screen(editor: &mut Editor) -> Result<()> {
         .and_then(|p| p.file_name())
         .and_then(|n| n.to_str())
         .unwrap_or("新缓冲区");
-    let info_bar = format!(
+    let info_bar = &mut editor.frame.info_bar;
+    info_bar.clear();
+    let _ = write!(
+        info_bar,
         "{} v{}    文件: {}",
         editor.app_info.name, editor.app_info.version, filename
     );
-    execute!(
-        stdout(),
-        SetForegroundColor(Color::White),
-        style::SetBackgroundColor(Color::Blue),
-        style::Print(&info_bar),
-        ResetColor
+    fit_to_width(info_bar, width as usize);
+    print_styled(out, info_bar, &editor.theme.title_bar)
+}
+
+/// 对话框打开时用暗淡的样式绘制文件行，不区分光标、选区和高亮，返回占用的列数
+fn draw_dimmed_row<W: Write>(
+    out: &mut W,
+    line: &str,
+    range: Range<usize>,
+    first_col: usize,
+    tab_width: usize,
+    segment: &mut String,
+) -> io::Result<usize> {
+    segment.clear();
+    let mut col = first_col;
+    for ch in line.chars().skip(range.start).take(range.end - range.start) {
+        let w = cell_width(ch, col, tab_width);
+        push_cell(segment, ch, w);
+        col += w;
+    }
+    queue!(
+        out,
+        SetAttribute(Attribute::Dim),
+        style::Print(&segment),
+        SetAttribute(Attribute::Reset)
     )?;
+    Ok(col - first_col)
+}
 
-    // 编辑器区域
-    let (width, height) = editor.terminal_size;
-    let editor_height = height - 3;
-    execute!(stdout(), cursor::MoveTo(0, 1))?;
-    for screen_row in 0..editor_height {
-        let file_row = screen_row as usize + editor.buffer.offset_y;
-        execute!(stdout(), terminal::Clear(ClearType::CurrentLine))?;
-        if file_row < editor.buffer.lines.len() {
-            let line = &editor.buffer.lines[file_row];
-            let line_number_width = if editor.show_line_numbers { 4 } else { 0 };
-            if editor.show_line_numbers {
-                execute!(
-                    stdout(),
-                    SetForegroundColor(Color::Yellow),
-                    style::Print(format!("{:3} ", file_row + 1)),
-                    ResetColor
-                )?;
+/// 用暗淡的颜色输出虚拟文本，控制字符换成空格，超出 width 的部分截掉
+fn draw_annotation<W: Write>(
+    out: &mut W,
+    text: &str,
+    width: usize,
+    segment: &mut String,
+) -> io::Result<()> {
+    segment.clear();
+    segment.extend(text.chars().map(|c| if c.is_control() { ' ' } else { c }));
+    truncate_to_width(segment, width);
+    queue!(
+        out,
+        SetForegroundColor(Color::DarkGrey),
+        style::Print(&segment),
+        ResetColor
+    )
+}
+
+/// 文件第 y 行行尾之后的虚拟文本，与文本和彼此之间隔开两列；used 是这一屏幕行已占用的列数
+fn draw_eol_annotations<W: Write>(
+    out: &mut W,
+    buffer: &TextBuffer,
+    y: usize,
+    used: usize,
+    display_width: usize,
+    segment: &mut String,
+) -> io::Result<()> {
+    let mut text = String::new();
+    for annotation in buffer.annotations_on(y, AnnotationPlacement::EndOfLine) {
+        text.push_str("  ");
+        text.push_str(&annotation.text);
+    }
+    if text.is_empty() {
+        return Ok(());
+    }
+    draw_annotation(out, &text, display_width.saturating_sub(used), segment)
+}
+
+/// 文件第 y 行上方的第 index 条虚拟文本，与该行的缩进对齐
+fn draw_above_annotation<W: Write>(
+    out: &mut W,
+    buffer: &TextBuffer,
+    y: usize,
+    index: usize,
+    display_width: usize,
+    segment: &mut String,
+) -> io::Result<()> {
+    let Some(annotation) = buffer
+        .annotations_on(y, AnnotationPlacement::Above)
+        .nth(index)
+    else {
+        return Ok(());
+    };
+    let line = &buffer.lines[y];
+    let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+    let indent = display_column(line, indent, buffer.settings.tab_width).min(display_width / 2);
+    let mut text = " ".repeat(indent);
+    text.push_str(&annotation.text);
+    draw_annotation(out, &text, display_width, segment)
+}
+
+/// 闪屏持续的时间
+const FLASH_DURATION: Duration = Duration::from_millis(120);
+
+/// 输出待处理的响铃；闪屏用终端的反显模式（DECSCNM），到时后在下一帧恢复
+fn ring_bell<W: Write>(out: &mut W, editor: &mut Editor) -> io::Result<()> {
+    if std::mem::take(&mut editor.bell_pending) {
+        match editor.bell {
+            BellStyle::Audible => out.write_all(b"\x07")?,
+            BellStyle::Visual => {
+                out.write_all(b"\x1b[?5h")?;
+                editor.flash_until = Some(Instant::now() + FLASH_DURATION);
             }
-            let display_width = width as usize - line_number_width;
-            let start = editor.buffer.offset_x.min(line.chars().count());
-            let end = (start + display_width).min(line.chars().count());
-            for (i, ch) in line.chars().enumerate().skip(start).take(end - start) {
-                if i == editor.buffer.cursor_x && file_row == editor.buffer.cursor_y {
-                    execute!(
-                        stdout(),
-                        SetBackgroundColor(Color::Yellow),
-                        SetForegroundColor(Color::Black),
-                        style::Print(ch),
-                        ResetColor
-                    )?;
-                } else {
-                    execute!(stdout(), style::Print(ch))?;
-                }
+            BellStyle::None => {}
+        }
+    }
+    if editor
+        .flash_until
+        .is_some_and(|until| Instant::now() >= until)
+    {
+        out.write_all(b"\x1b[?5l")?;
+        editor.flash_until = None;
+    }
+    Ok(())
+}
+
+/// 屏幕行第一个字符（行中第 start 个）所在的显示列，用来对齐制表位：
+/// 制表位按文件行开头计算，软换行时每个屏幕行从头算起
+fn row_first_column(buffer: &TextBuffer, line: &str, start: usize) -> usize {
+    if buffer.settings.soft_wrap {
+        0
+    } else {
+        // 整行都在左边滚出屏幕时，行尾之后的虚拟空间从屏幕上的第一列开始
+        display_column(line, start, buffer.settings.tab_width)
+            .max(first_shown_column(line, buffer.offset_x))
+    }
+}
+
+/// 不换行时横向滚动到 offset_x 后第一个显示文字的列，左边画了 `<` 时往后一列
+fn first_shown_column(line: &str, offset_x: usize) -> usize {
+    offset_x + (offset_x > 0 && !line.is_empty()) as usize
+}
+
+/// 一个屏幕行显示的字符范围，以及不换行时两边的滚动标记
+#[derive(Debug, Clone, Default)]
+struct ClippedRow {
+    range: Range<usize>,
+    /// 左边有滚出屏幕的文字，第一列画 `<`
+    left_marker: bool,
+    /// 第一个显示的字符前面补的空格数（跨过左边界的宽字符或制表符不显示）
+    pad: usize,
+    /// 右边还有放不下的文字，最后一列画 `>`
+    right_marker: bool,
+}
+
+/// 不换行时一行中从显示列 offset_x 开始、放得进 width 列的部分，与 nano 一样两边留出标记的位置
+fn clip_row(line: &str, offset_x: usize, width: usize, tab_width: usize) -> ClippedRow {
+    let first_shown = first_shown_column(line, offset_x);
+    let left_marker = first_shown > offset_x;
+    let mut col = 0;
+    let mut start = 0;
+    for ch in line.chars() {
+        if col >= first_shown {
+            break;
+        }
+        col += cell_width(ch, col, tab_width);
+        start += 1;
+    }
+    let pad = col.saturating_sub(first_shown);
+    let width = width.saturating_sub(left_marker as usize + pad);
+    let mut end = clip_end(line, start, col, width, tab_width);
+    let right_marker = end < line.chars().count();
+    if right_marker {
+        end = clip_end(line, start, col, width.saturating_sub(1), tab_width);
+    }
+    ClippedRow {
+        range: start..end,
+        left_marker,
+        pad,
+        right_marker,
+    }
+}
+
+/// 把一个字符放进待输出的片段，制表符展开成 width 个空格
+fn push_cell(segment: &mut String, ch: char, width: usize) {
+    if ch == '\t' {
+        segment.extend(std::iter::repeat_n(' ', width));
+    } else {
+        segment.push(rawbytes::display_char(ch));
+    }
+}
+
+/// 绘制文件第 file_row 行中 range 范围内的字符，连同光标、选区、搜索高亮和超出行长上限的部分
+///
+/// 超出 display_width 的字符不绘制，返回占用的列数（包括行尾之后的光标）。
+fn draw_text_row<W: Write>(
+    out: &mut W,
+    buffer: &TextBuffer,
+    theme: &Theme,
+    file_row: usize,
+    range: Range<usize>,
+    display_width: usize,
+    segment: &mut String,
+) -> io::Result<usize> {
+    let line = &buffer.lines[file_row];
+    let char_count = line.chars().count();
+    let cursor_here = file_row == buffer.cursor_y;
+    let cursor_x = buffer.cursor_x;
+    let selection = buffer.selection_range();
+    let block = buffer
+        .block_range()
+        .filter(|(lines, _)| lines.contains(&file_row))
+        .map(|(_, cols)| cols);
+    let matches = buffer
+        .highlight
+        .as_ref()
+        .map_or(&[][..], |h| h.matches_on(file_row));
+    let colors = buffer
+        .ansi
+        .as_ref()
+        .map_or(&[][..], |a| a.spans_on(file_row));
+    let limit = match buffer.settings.max_line_length {
+        0 => usize::MAX,
+        limit => limit,
+    };
+
+    // 相同样式的连续字符合并成一段输出，而不是逐字符输出
+    segment.clear();
+    let mut segment_style = CellStyle::Normal;
+    let tab_width = buffer.settings.tab_width;
+    let first_col = row_first_column(buffer, line, range.start);
+    let check = buffer.settings.unicode_check;
+    // 开启 unicodecheck 时不输出不占宽度的可疑字符，改由下一个显示的字符标出
+    let mut hidden = None;
+    let mut used = 0;
+    let mut truncated = false;
+    for (i, ch) in line
+        .chars()
+        .enumerate()
+        .skip(range.start)
+        .take(range.end - range.start)
+    {
+        let w = cell_width(ch, first_col + used, tab_width);
+        if used + w > display_width {
+            truncated = true;
+            break;
+        }
+        let flagged = check && charinfo::suspicious(ch).is_some();
+        let mut style = if (cursor_here && i == cursor_x) || buffer.has_cursor_at(i, file_row) {
+            CellStyle::Cursor
+        } else if selection.is_some_and(|(from, to)| {
+            let pos = Position::new(i, file_row);
+            from <= pos && pos < to
+        }) || block
+            .as_ref()
+            .is_some_and(|cols| cols.contains(&(first_col + used)))
+        {
+            CellStyle::Selected
+        } else if matches.iter().any(|&(start, end)| start <= i && i < end) {
+            CellStyle::Match
+        } else if flagged {
+            CellStyle::Suspicious
+        } else if i >= limit {
+            CellStyle::Overflow
+        } else if let Some(&(_, _, color)) = colors
+            .iter()
+            .find(|&&(start, end, _)| start <= i && i < end)
+        {
+            CellStyle::Ansi(color)
+        } else {
+            CellStyle::Normal
+        };
+        if flagged && w == 0 {
+            if hidden != Some(CellStyle::Cursor) {
+                hidden = Some(match style {
+                    CellStyle::Cursor => CellStyle::Cursor,
+                    _ => CellStyle::Suspicious,
+                });
             }
-            if editor.buffer.cursor_y == file_row
-                && editor.buffer.cursor_x == line.chars().count()
-                && end == line.chars().count()
-            {
-                execute!(
-                    stdout(),
-                    SetBackgroundColor(Color::Yellow),
-                    SetForegroundColor(Color::Black),
-                    style::Print("▏"),
-                    ResetColor
-                )?;
+            continue;
+        }
+        if let Some(hidden) = hidden.take() {
+            if style != CellStyle::Cursor {
+                style = hidden;
             }
         }
-        execute!(stdout(), cursor::MoveToNextLine(1))?;
+        if style != segment_style {
+            print_segment(out, segment, segment_style, theme)?;
+            segment.clear();
+            segment_style = style;
+        }
+        push_cell(segment, ch, w);
+        used += w;
+    }
+    print_segment(out, segment, segment_style, theme)?;
+    if let Some(hidden) = hidden.filter(|_| !truncated && used < display_width) {
+        // 行尾的不可见字符画成一个占位符
+        print_segment(out, "◌", hidden, theme)?;
+        used += 1;
+    }
+    // 横向滚动后行尾在屏幕左边时，行尾到屏幕第一列之间的列数
+    let skipped = first_col.saturating_sub(display_column(line, char_count, tab_width));
+    if cursor_here
+        && cursor_x >= char_count
+        && range.end == char_count
+        && !truncated
+        && used < display_width
+    {
+        // 自由光标模式下光标可能在行尾之后，跳过空白列到光标所在列
+        let gap = (cursor_x - char_count).saturating_sub(skipped);
+        let gap = gap.min(display_width - used - 1);
+        draw_eol_cursor(out, gap, theme)?;
+        used += gap + 1;
+    } else if buffer.has_cursor_at(char_count, file_row)
+        && skipped == 0
+        && range.end == char_count
+        && !truncated
+        && used < display_width
+    {
+        // 多光标中停在行尾的光标
+        draw_eol_cursor(out, 0, theme)?;
+        used += 1;
+    }
+    Ok(if truncated { display_width } else { used })
+}
+
+/// 不换行时，行上方的虚拟文本会把下面的行往下推，继续向下滚动直到光标所在的行可见
+fn scroll_past_annotations(buffer: &mut TextBuffer, height: usize) {
+    while buffer.offset_y < buffer.cursor_y {
+        let rows: usize = (buffer.offset_y..=buffer.cursor_y)
+            .map(|y| 1 + buffer.annotation_rows_above(y))
+            .sum();
+        if rows <= height {
+            break;
+        }
+        buffer.offset_y += 1;
+    }
+}
+
+/// 软换行时调整纵向滚动，保证光标所在的屏幕行可见
+fn scroll_wrapped(buffer: &mut TextBuffer, width: usize, tab_width: usize, height: usize) {
+    buffer.offset_x = 0;
+    if buffer.cursor_y < buffer.offset_y {
+        buffer.offset_y = buffer.cursor_y;
+    }
+    let cursor_row = |buffer: &TextBuffer| match buffer.lines.get(buffer.cursor_y) {
+        Some(line) => wrap_position(line, width, buffer.cursor_x, tab_width).0,
+        None => 0,
+    };
+    while buffer.offset_y < buffer.cursor_y {
+        let rows_above: usize = buffer.lines
+            [buffer.offset_y..buffer.cursor_y.min(buffer.lines.len())]
+            .iter()
+            .map(|line| wrap_position(line, width, 0, tab_width).1)
+            .sum::<usize>()
+            + (buffer.offset_y..=buffer.cursor_y)
+                .map(|y| buffer.annotation_rows_above(y))
+                .sum::<usize>()
+            + buffer.cursor_y.saturating_sub(buffer.lines.len());
+        if rows_above + cursor_row(buffer) < height {
+            break;
+        }
+        buffer.offset_y += 1;
     }
-    super::status::draw_status_bar(editor)?;
-    Ok(())
 }
@@ -0,0 +1,25 @@
+use crate::editor::Editor;
+use std::time::Instant;
+
+/// 修改前调用：距上一次修改超过 undo_group_ms 时另起一步撤销，
+/// 这样连续输入的一阵文字算一步，停顿之后的输入是新的一步
+pub fn before_edit(editor: &mut Editor) {
+    if !editor.undo_group.is_zero() && editor.last_edit.elapsed() > editor.undo_group {
+        editor.buffer.history.break_group();
+    }
+}
+
+/// 修改后调用：记下修改时间，撤销历史超过配置的步数或内存上限时丢弃最早的几步
+pub fn after_edit(editor: &mut Editor) {
+    editor.last_edit = Instant::now();
+    editor
+        .buffer
+        .history
+        .enforce_limits(editor.undo_limit, editor.undo_memory);
+}
+
+/// 在撤销历史中插入断点，下一次修改不与之前的输入合并
+pub fn insert_boundary(editor: &mut Editor) {
+    editor.buffer.history.break_group();
+    editor.status_message = "已插入撤销断点，之后的修改另起一步".to_string();
+}
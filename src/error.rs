@@ -0,0 +1,84 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 编辑器错误类型，可按失败种类匹配，并可直接显示在状态栏
+#[derive(Debug)]
+pub enum EditorError {
+    /// 文件读写失败，尽量附带出错的路径
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    /// 终端初始化、绘制或读取事件失败
+    Terminal(io::Error),
+    /// 配置有误
+    Config(String),
+    /// 文件内容编码无法处理
+    Encoding {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// 命令行参数有误（包括 --help / --version 的输出）
+    Args(clap::Error),
+}
+
+impl EditorError {
+    /// 带路径的文件读写错误
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        EditorError::Io {
+            path: Some(path.to_path_buf()),
+            source,
+        }
+    }
+
+    /// 终端操作错误
+    pub fn terminal(source: io::Error) -> Self {
+        EditorError::Terminal(source)
+    }
+}
+
+impl fmt::Display for EditorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditorError::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "无法读写文件 {}: {}", path.display(), source),
+            EditorError::Io { path: None, source } => write!(f, "读写错误: {}", source),
+            EditorError::Terminal(source) => write!(f, "终端错误: {}", source),
+            EditorError::Config(message) => write!(f, "配置错误: {}", message),
+            EditorError::Encoding {
+                path: Some(path),
+                message,
+            } => write!(f, "文件 {} 编码错误: {}", path.display(), message),
+            EditorError::Encoding {
+                path: None,
+                message,
+            } => write!(f, "编码错误: {}", message),
+            EditorError::Args(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for EditorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EditorError::Io { source, .. } | EditorError::Terminal(source) => Some(source),
+            EditorError::Args(e) => Some(e),
+            EditorError::Config(_) | EditorError::Encoding { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for EditorError {
+    fn from(source: io::Error) -> Self {
+        EditorError::Io { path: None, source }
+    }
+}
+
+impl From<clap::Error> for EditorError {
+    fn from(e: clap::Error) -> Self {
+        EditorError::Args(e)
+    }
+}
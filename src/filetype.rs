@@ -0,0 +1,140 @@
+// 文件类型识别
+
+use std::path::Path;
+
+/// 缓冲区的文件类型，决定各种按文件类型生效的功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileType {
+    #[default]
+    Plain,
+    /// .txt 等纯文字文档
+    Text,
+    Markdown,
+    /// reStructuredText
+    Rst,
+    Rust,
+    Python,
+    Shell,
+    C,
+    Toml,
+}
+
+impl FileType {
+    /// 全部文件类型，按配置中 `[filetype.<名字>]` 表的顺序
+    pub const ALL: [FileType; 9] = [
+        FileType::Plain,
+        FileType::Text,
+        FileType::Markdown,
+        FileType::Rst,
+        FileType::Rust,
+        FileType::Python,
+        FileType::Shell,
+        FileType::C,
+        FileType::Toml,
+    ];
+
+    /// 配置文件中使用的名字
+    pub fn key(self) -> &'static str {
+        match self {
+            FileType::Plain => "plain",
+            FileType::Text => "text",
+            FileType::Markdown => "markdown",
+            FileType::Rst => "rst",
+            FileType::Rust => "rust",
+            FileType::Python => "python",
+            FileType::Shell => "shell",
+            FileType::C => "c",
+            FileType::Toml => "toml",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.key() == key)
+    }
+
+    /// 没有设置 runcommand 时 F5 运行的命令，%f 换成文件名
+    pub fn default_run_command(self) -> Option<&'static str> {
+        match self {
+            FileType::Rust => Some("cargo build"),
+            FileType::C => Some("make"),
+            FileType::Python => Some("python3 %f"),
+            FileType::Shell => Some("sh %f"),
+            _ => None,
+        }
+    }
+
+    /// 根据文件扩展名识别类型
+    pub fn from_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("md" | "markdown" | "mkd") => FileType::Markdown,
+            Some("rst" | "rest") => FileType::Rst,
+            Some("txt" | "text") => FileType::Text,
+            Some("rs") => FileType::Rust,
+            Some("py") => FileType::Python,
+            Some("sh" | "bash" | "zsh") => FileType::Shell,
+            Some("c" | "h" | "cpp" | "hpp" | "cc") => FileType::C,
+            Some("toml") => FileType::Toml,
+            _ => FileType::Plain,
+        }
+    }
+
+    /// 根据首行的 `#!` 解释器识别类型，例如 `#!/usr/bin/env python3`
+    pub fn from_shebang(first_line: &str) -> Option<Self> {
+        let command = first_line.strip_prefix("#!")?.trim();
+        let mut parts = command.split_whitespace();
+        let mut program = parts.next()?.rsplit('/').next()?;
+        if program == "env" {
+            // 跳过 env 的选项，例如 `env -S`
+            program = parts.find(|p| !p.starts_with('-'))?;
+        }
+        let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        match name {
+            "python" | "pypy" => Some(FileType::Python),
+            "sh" | "bash" | "zsh" | "dash" | "ksh" | "fish" => Some(FileType::Shell),
+            "rust-script" => Some(FileType::Rust),
+            _ => None,
+        }
+    }
+
+    /// 先按扩展名识别，识别不出时再看首行的 shebang
+    pub fn detect(path: Option<&Path>, first_line: &str) -> Self {
+        match path.map(Self::from_path) {
+            Some(filetype) if filetype != FileType::Plain => filetype,
+            _ => Self::from_shebang(first_line).unwrap_or(FileType::Plain),
+        }
+    }
+
+    /// 行注释的前缀
+    pub fn comment_prefix(self) -> Option<&'static str> {
+        match self {
+            FileType::Rust | FileType::C => Some("//"),
+            FileType::Python | FileType::Shell | FileType::Toml => Some("#"),
+            FileType::Rst => Some(".."),
+            FileType::Plain | FileType::Text | FileType::Markdown => None,
+        }
+    }
+
+    /// 是否是以文字为主的文件，只有这类文件可以开启散文模式
+    pub fn is_prose(self) -> bool {
+        matches!(self, FileType::Text | FileType::Markdown | FileType::Rst)
+    }
+
+    /// 状态栏显示的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            FileType::Plain => "文本",
+            FileType::Text => "纯文本",
+            FileType::Markdown => "Markdown",
+            FileType::Rst => "reStructuredText",
+            FileType::Rust => "Rust",
+            FileType::Python => "Python",
+            FileType::Shell => "Shell",
+            FileType::C => "C",
+            FileType::Toml => "TOML",
+        }
+    }
+}
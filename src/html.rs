@@ -0,0 +1,185 @@
+// 导出 HTML：把缓冲区写成独立的网页，用当前主题的颜色标出编辑器里能看到的高亮
+//
+// 编辑器没有语法高亮，导出的高亮与屏幕上一致：搜索匹配、选区、超出行长上限的部分
+// 和行号（开启时）。页面不引用外部文件，可以直接发给别人或贴进评审意见。
+
+use crate::buffer::{Position, TextBuffer};
+use crate::theme::{Style, Theme};
+use crossterm::style::Color;
+use std::fmt::Write as _;
+
+/// 终端 16 色在网页中的近似值，按 xterm 的默认配色
+fn named_color(color: Color) -> Option<(u8, u8, u8)> {
+    Some(match color {
+        Color::Black => (0, 0, 0),
+        Color::DarkRed => (205, 0, 0),
+        Color::DarkGreen => (0, 205, 0),
+        Color::DarkYellow => (205, 205, 0),
+        Color::DarkBlue => (0, 0, 238),
+        Color::DarkMagenta => (205, 0, 205),
+        Color::DarkCyan => (0, 205, 205),
+        Color::Grey => (229, 229, 229),
+        Color::DarkGrey => (127, 127, 127),
+        Color::Red => (255, 0, 0),
+        Color::Green => (0, 255, 0),
+        Color::Yellow => (255, 255, 0),
+        Color::Blue => (92, 92, 255),
+        Color::Magenta => (255, 0, 255),
+        Color::Cyan => (0, 255, 255),
+        Color::White => (255, 255, 255),
+        Color::Rgb { r, g, b } => (r, g, b),
+        Color::AnsiValue(n) => return ansi_color(n),
+        Color::Reset => return None,
+    })
+}
+
+/// 256 色中的一种：前 16 色同上，然后是 6×6×6 色立方和 24 级灰度
+fn ansi_color(n: u8) -> Option<(u8, u8, u8)> {
+    const BASIC: [Color; 16] = [
+        Color::Black,
+        Color::DarkRed,
+        Color::DarkGreen,
+        Color::DarkYellow,
+        Color::DarkBlue,
+        Color::DarkMagenta,
+        Color::DarkCyan,
+        Color::Grey,
+        Color::DarkGrey,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+    ];
+    match n {
+        0..=15 => named_color(BASIC[n as usize]),
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let n = n - 16;
+            Some((level(n / 36), level(n / 6 % 6), level(n % 6)))
+        }
+        _ => {
+            let gray = 8 + (n - 232) * 10;
+            Some((gray, gray, gray))
+        }
+    }
+}
+
+/// 一个主题元素的 CSS 规则
+fn write_rule(out: &mut String, class: &str, style: Style) {
+    let (mut fg, mut bg) = (style.fg, style.bg);
+    if style.reverse {
+        // 反色时没有指定的一方用页面的默认颜色
+        (fg, bg) = (bg.or(Some(Color::Black)), fg.or(Some(Color::Grey)));
+    }
+    let _ = write!(out, ".{} {{", class);
+    if let Some((r, g, b)) = fg.and_then(named_color) {
+        let _ = write!(out, " color: #{:02x}{:02x}{:02x};", r, g, b);
+    }
+    if let Some((r, g, b)) = bg.and_then(named_color) {
+        let _ = write!(out, " background: #{:02x}{:02x}{:02x};", r, g, b);
+    }
+    if style.bold {
+        out.push_str(" font-weight: bold;");
+    }
+    out.push_str(" }\n");
+}
+
+fn push_escaped(out: &mut String, ch: char) {
+    match ch {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        ch => out.push(ch),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Normal,
+    Selected,
+    Match,
+    Overflow,
+}
+
+impl Mark {
+    fn class(self) -> Option<&'static str> {
+        match self {
+            Mark::Normal => None,
+            Mark::Selected => Some("sel"),
+            Mark::Match => Some("match"),
+            Mark::Overflow => Some("over"),
+        }
+    }
+}
+
+/// 把缓冲区转换成完整的 HTML 页面，title 为页面标题
+pub fn buffer_to_html(buffer: &TextBuffer, theme: &Theme, title: &str) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>");
+    title.chars().for_each(|ch| push_escaped(&mut out, ch));
+    out.push_str("</title>\n<style>\n");
+    out.push_str(
+        "pre { font-family: monospace; background: #000000; color: #e5e5e5; padding: 1em; }\n",
+    );
+    write_rule(&mut out, "ln", theme.line_number);
+    write_rule(&mut out, "sel", theme.selection);
+    write_rule(&mut out, "match", theme.search_match);
+    write_rule(&mut out, "over", theme.overflow);
+    out.push_str("</style>\n</head>\n<body>\n<pre>");
+
+    let selection = buffer.selection_range();
+    let limit = match buffer.settings.max_line_length {
+        0 => usize::MAX,
+        limit => limit,
+    };
+    let number_width = buffer.lines.len().to_string().len();
+    for (y, line) in buffer.lines.iter().enumerate() {
+        if buffer.settings.line_numbers {
+            let _ = write!(
+                out,
+                "<span class=\"ln\">{:>w$} </span>",
+                y + 1,
+                w = number_width
+            );
+        }
+        let matches = buffer
+            .highlight
+            .as_ref()
+            .map_or(&[][..], |h| h.matches_on(y));
+        let mut current = Mark::Normal;
+        for (x, ch) in line.chars().enumerate() {
+            let mark = if selection.is_some_and(|(from, to)| {
+                let pos = Position::new(x, y);
+                from <= pos && pos < to
+            }) {
+                Mark::Selected
+            } else if matches.iter().any(|&(start, end)| start <= x && x < end) {
+                Mark::Match
+            } else if x >= limit {
+                Mark::Overflow
+            } else {
+                Mark::Normal
+            };
+            if mark != current {
+                if current != Mark::Normal {
+                    out.push_str("</span>");
+                }
+                if let Some(class) = mark.class() {
+                    let _ = write!(out, "<span class=\"{}\">", class);
+                }
+                current = mark;
+            }
+            push_escaped(&mut out, ch);
+        }
+        if current != Mark::Normal {
+            out.push_str("</span>");
+        }
+        out.push('\n');
+    }
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
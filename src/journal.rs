@@ -0,0 +1,140 @@
+// 编辑日志：开启 journal 后，每次保存和几种重要操作都在状态目录下追加一行记录
+//
+// 每个文件一个日志，文件名由文件的绝对路径转义而来（见 paths::file_key），
+// 每行为 `<秒级时间戳>\t<事件>\t<说明>`。没有版本控制时可以用来回顾文件的修改经过。
+
+use crate::snapshot::time_ago;
+use crate::{paths, EditorError, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 日志记录的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalEvent {
+    /// 保存
+    Save,
+    /// 从快照恢复
+    RestoreSnapshot,
+    /// 从本地历史恢复
+    RestoreHistory,
+    /// 丢弃快照
+    DiscardSnapshots,
+    /// 全部替换
+    Replace,
+}
+
+const EVENT_NAMES: [(JournalEvent, &str, &str); 5] = [
+    (JournalEvent::Save, "save", "保存"),
+    (JournalEvent::RestoreSnapshot, "restore", "恢复快照"),
+    (
+        JournalEvent::RestoreHistory,
+        "restore_history",
+        "恢复历史版本",
+    ),
+    (JournalEvent::DiscardSnapshots, "discard", "丢弃快照"),
+    (JournalEvent::Replace, "replace", "替换"),
+];
+
+impl JournalEvent {
+    /// 日志文件中的名字
+    pub fn name(self) -> &'static str {
+        EVENT_NAMES
+            .iter()
+            .find(|(event, _, _)| *event == self)
+            .map_or("", |(_, name, _)| name)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        EVENT_NAMES
+            .iter()
+            .find(|(_, n, _)| *n == name)
+            .map(|(event, _, _)| *event)
+    }
+
+    /// 在查看器中显示的名字
+    pub fn label(self) -> &'static str {
+        EVENT_NAMES
+            .iter()
+            .find(|(event, _, _)| *event == self)
+            .map_or("", |(_, _, label)| label)
+    }
+}
+
+/// 日志中的一条记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub time: SystemTime,
+    pub event: JournalEvent,
+    /// 说明，例如 `+12 字节，修改 3 行`
+    pub detail: String,
+}
+
+impl JournalEntry {
+    /// 解析日志中的一行，格式不对时返回 None
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(3, '\t');
+        let secs: u64 = fields.next()?.parse().ok()?;
+        let event = JournalEvent::from_name(fields.next()?)?;
+        Some(Self {
+            time: UNIX_EPOCH + Duration::from_secs(secs),
+            event,
+            detail: fields.next().unwrap_or("").to_string(),
+        })
+    }
+
+    /// 查看器中的一行，例如 `3 分钟前  保存  +12 字节，修改 3 行`
+    pub fn label(&self) -> String {
+        format!(
+            "{}  {}  {}",
+            time_ago(self.time),
+            self.event.label(),
+            self.detail
+        )
+    }
+}
+
+/// 日志目录
+#[derive(Debug, Clone)]
+pub struct Journal {
+    root: PathBuf,
+}
+
+impl Journal {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path_for(&self, file: &Path) -> PathBuf {
+        self.root.join(format!("{}.log", paths::file_key(file)))
+    }
+
+    /// 追加一条记录，说明中的换行和制表符换成空格
+    pub fn append(&self, file: &Path, event: JournalEvent, detail: &str) -> Result<()> {
+        let path = self.path_for(file);
+        paths::ensure_parent(&path).map_err(|e| EditorError::io(&path, e))?;
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let detail = detail.replace(['\t', '\n'], " ");
+        let line = format!("{}\t{}\t{}\n", secs, event.name(), detail);
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut f| f.write_all(line.as_bytes()))
+            .map_err(|e| EditorError::io(&path, e))
+    }
+
+    /// 某个文件的全部记录，从旧到新；没有日志时为空
+    pub fn read(&self, file: &Path) -> Result<Vec<JournalEntry>> {
+        let path = self.path_for(file);
+        match fs::read_to_string(&path) {
+            Ok(text) => Ok(text.lines().filter_map(JournalEntry::parse).collect()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(EditorError::io(&path, e)),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+// 按键记录与回放：`--record 文件` 把输入写成文本，`--replay 文件` 按原来的节奏重新输入
+//
+// 每行一个事件：`<距上一个事件的毫秒数>\t<种类>\t<内容>`，种类为 key（按键，写法同配置
+// 文件中的键位，例如 ^O、M-S-Up）或 paste（粘贴的文字，\n、\t、\\ 转义）。
+// 以 # 开头的行是注释，可以手工编辑记录来删减或调整步骤。
+
+use crate::keymap::KeyBinding;
+use crate::{EditorError, Result};
+use crossterm::event::{Event, KeyEvent, KeyEventKind};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            ch => out.push(ch),
+        }
+    }
+    out
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// 事件的记录写法（不含时间），不需要记录的事件（按键松开、鼠标、尺寸变化）返回 None
+pub fn format_event(event: &Event) -> Option<String> {
+    match event {
+        Event::Key(key) if key.kind != KeyEventKind::Release => {
+            let binding = KeyBinding::new(key.code, key.modifiers);
+            Some(format!("key\t{}", binding.describe()))
+        }
+        Event::Paste(text) => Some(format!("paste\t{}", escape(text))),
+        _ => None,
+    }
+}
+
+/// 解析记录中的一行，空行和注释返回 None
+pub fn parse_line(line: &str) -> std::result::Result<Option<(Duration, Event)>, String> {
+    if line.trim().is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let mut fields = line.splitn(3, '\t');
+    let (Some(delay), Some(kind), Some(content)) = (fields.next(), fields.next(), fields.next())
+    else {
+        return Err("应为 “毫秒<Tab>种类<Tab>内容”".to_string());
+    };
+    let delay = delay
+        .trim()
+        .parse()
+        .map(Duration::from_millis)
+        .map_err(|_| format!("无效的毫秒数 {}", delay))?;
+    let event = match kind {
+        "key" => {
+            let binding =
+                KeyBinding::parse(content).ok_or_else(|| format!("无法识别的按键 {}", content))?;
+            Event::Key(KeyEvent::new(binding.code, binding.modifiers))
+        }
+        "paste" => Event::Paste(unescape(content)),
+        other => return Err(format!("未知的事件种类 {}", other)),
+    };
+    Ok(Some((delay, event)))
+}
+
+/// 把事件追加到记录文件，每个事件立即写入，编辑器崩溃时记录也是完整的
+pub struct Recorder {
+    file: File,
+    last: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> Result<Self> {
+        let mut file = File::create(path).map_err(|e| EditorError::io(path, e))?;
+        file.write_all(
+            "# rsnano 按键记录：毫秒、种类、内容以 Tab 分隔，用 --replay 回放\n".as_bytes(),
+        )
+        .map_err(|e| EditorError::io(path, e))?;
+        Ok(Self {
+            file,
+            last: Instant::now(),
+        })
+    }
+
+    /// 记录一个事件，写入失败时返回 false（之后不再记录）
+    pub fn record(&mut self, event: &Event) -> bool {
+        let Some(text) = format_event(event) else {
+            return true;
+        };
+        let now = Instant::now();
+        let delay = now.duration_since(self.last).as_millis();
+        self.last = now;
+        writeln!(self.file, "{}\t{}", delay, text).is_ok()
+    }
+}
+
+/// 读入的记录，按时间依次交出事件
+pub struct Replay {
+    events: VecDeque<(Duration, Event)>,
+    /// 上一个事件交出的时间，下一个事件在此之后再等待它的间隔
+    last: Instant,
+    /// 已经交出的事件数
+    pub played: usize,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| EditorError::io(path, e))?;
+        let mut events = VecDeque::new();
+        for (i, line) in text.lines().enumerate() {
+            let parsed = parse_line(line).map_err(|message| {
+                EditorError::Config(format!("{} 第 {} 行: {}", path.display(), i + 1, message))
+            })?;
+            events.extend(parsed);
+        }
+        Ok(Self {
+            events,
+            last: Instant::now(),
+            played: 0,
+        })
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// 距离下一个事件还要等多久，已经到时间时为 0
+    pub fn wait_time(&self) -> Option<Duration> {
+        let (delay, _) = self.events.front()?;
+        Some((self.last + *delay).saturating_duration_since(Instant::now()))
+    }
+
+    /// 到时间的下一个事件
+    pub fn next_due(&mut self) -> Option<Event> {
+        if self.wait_time()? > Duration::ZERO {
+            return None;
+        }
+        let (_, event) = self.events.pop_front()?;
+        self.last = Instant::now();
+        self.played += 1;
+        Some(event)
+    }
+}
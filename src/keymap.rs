@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::action::Action;
+use crate::direction::Direction;
+
+/// 按键组合，作为键位表的键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+/// 有名字的按键，配置文件中按名字书写（不区分大小写）
+const NAMED_KEYS: [(&str, KeyCode); 15] = [
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("BackTab", KeyCode::BackTab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Esc", KeyCode::Esc),
+];
+
+impl KeyBinding {
+    /// Ctrl、Alt 加 Shift 和字母统一记成大写字母、不带 SHIFT：
+    /// 终端对 Alt+Shift+y 有的报告 `Y` 加 ALT，有的报告 `Y` 或 `y` 加 ALT|SHIFT
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        match code {
+            KeyCode::Char(ch)
+                if ch.is_ascii_alphabetic()
+                    && modifiers.contains(KeyModifiers::SHIFT)
+                    && modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                Self {
+                    code: KeyCode::Char(ch.to_ascii_uppercase()),
+                    modifiers: modifiers - KeyModifiers::SHIFT,
+                }
+            }
+            _ => Self { code, modifiers },
+        }
+    }
+
+    /// 解析配置文件中的按键写法
+    ///
+    /// 支持 nano 风格的 `^K`、`M-k`、`S-`，以及 `Ctrl+K`、`Alt+Shift+Up` 这样的写法，
+    /// 按键名可以是单个字符、`Space`、`F1`～`F12` 或 `NAMED_KEYS` 中的名字。
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut rest = spec.trim();
+        let mut modifiers = KeyModifiers::NONE;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let (modifier, len) = if rest.len() > 1 && rest.starts_with('^') {
+                (KeyModifiers::CONTROL, 1)
+            } else if rest.len() > 2 && (lower.starts_with("c-")) {
+                (KeyModifiers::CONTROL, 2)
+            } else if rest.len() > 2 && lower.starts_with("m-") {
+                (KeyModifiers::ALT, 2)
+            } else if rest.len() > 2 && lower.starts_with("s-") {
+                (KeyModifiers::SHIFT, 2)
+            } else if rest.len() > 5 && lower.starts_with("ctrl+") {
+                (KeyModifiers::CONTROL, 5)
+            } else if rest.len() > 4 && lower.starts_with("alt+") {
+                (KeyModifiers::ALT, 4)
+            } else if rest.len() > 5 && lower.starts_with("meta+") {
+                (KeyModifiers::ALT, 5)
+            } else if rest.len() > 6 && lower.starts_with("shift+") {
+                (KeyModifiers::SHIFT, 6)
+            } else {
+                break;
+            };
+            modifiers |= modifier;
+            rest = &rest[len..];
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(ch), None) => {
+                // ^K、M-K 与 ^k、M-k 相同，终端报告的是小写字母
+                let ch = if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+                    && !modifiers.contains(KeyModifiers::SHIFT)
+                {
+                    ch.to_ascii_lowercase()
+                } else {
+                    ch
+                };
+                KeyCode::Char(ch)
+            }
+            _ if rest.eq_ignore_ascii_case("space") => KeyCode::Char(' '),
+            _ => match rest.strip_prefix(['F', 'f']).and_then(|n| n.parse().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => NAMED_KEYS
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(rest))
+                    .map(|(_, code)| *code)?,
+            },
+        };
+        Some(Self::new(code, modifiers))
+    }
+
+    /// nano 风格的按键写法，例如 `^K`、`M-k`、`M-S-y`、`M-S-Up`，可以被 `parse` 读回
+    ///
+    /// `parse` 不区分 `M-K` 与 `M-k`，所以 Ctrl、Alt 加大写字母写成 `S-` 加小写字母。
+    pub fn describe(&self) -> String {
+        let shifted_letter = matches!(self.code, KeyCode::Char(ch) if ch.is_ascii_uppercase())
+            && self
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let modifiers = if shifted_letter {
+            self.modifiers | KeyModifiers::SHIFT
+        } else {
+            self.modifiers
+        };
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(ch) if shifted_letter => ch.to_ascii_lowercase().to_string(),
+            KeyCode::Char(ch) => ch.to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            code => NAMED_KEYS
+                .iter()
+                .find(|(_, c)| *c == code)
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_else(|| format!("{:?}", code)),
+        };
+        if modifiers == KeyModifiers::CONTROL && matches!(self.code, KeyCode::Char(_)) {
+            return format!("^{}", key.to_ascii_uppercase());
+        }
+        let mut out = String::new();
+        for (modifier, prefix) in [
+            (KeyModifiers::CONTROL, "C-"),
+            (KeyModifiers::ALT, "M-"),
+            (KeyModifiers::SHIFT, "S-"),
+        ] {
+            if modifiers.contains(modifier) {
+                out.push_str(prefix);
+            }
+        }
+        out.push_str(&key);
+        out
+    }
+}
+
+/// 按键到操作的映射表
+pub struct Keymap {
+    bindings: HashMap<KeyBinding, Action>,
+    /// 绑定到命名宏的按键，与 bindings 中的按键不重复
+    macros: HashMap<KeyBinding, String>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyCode::*;
+        let ctrl = KeyModifiers::CONTROL;
+        let alt = KeyModifiers::ALT;
+        let none = KeyModifiers::NONE;
+
+        let mut keymap = Self {
+            bindings: HashMap::new(),
+            macros: HashMap::new(),
+        };
+        keymap.bind(Char('x'), ctrl, Action::Quit);
+        keymap.bind(Char('o'), ctrl, Action::Save);
+        keymap.bind(Char('g'), ctrl, Action::ShowHelp);
+        keymap.bind(Char('r'), ctrl, Action::OpenFile);
+        for mods in [alt, alt | KeyModifiers::SHIFT] {
+            keymap.bind(Char('<'), mods, Action::PrevBuffer);
+            keymap.bind(Char('>'), mods, Action::NextBuffer);
+        }
+        keymap.bind(Char('c'), alt, Action::ToggleSecondaryCursor);
+        keymap.bind(Char('c'), ctrl, Action::ClearCursors);
+        for (code, direction) in [
+            (Up, Direction::Up),
+            (Down, Direction::Down),
+            (Left, Direction::Left),
+            (Right, Direction::Right),
+        ] {
+            keymap.bind(code, none, Action::MoveCursor(direction));
+            keymap.bind(code, alt, Action::MoveSecondaryCursor(direction));
+            keymap.bind(code, KeyModifiers::SHIFT, Action::SelectMove(direction));
+        }
+        keymap.bind(PageUp, none, Action::PageUp);
+        keymap.bind(PageDown, none, Action::PageDown);
+        keymap.bind(Home, none, Action::LineStart);
+        keymap.bind(End, none, Action::LineEnd);
+        keymap.bind(Char('a'), ctrl, Action::LineStart);
+        keymap.bind(Char('e'), ctrl, Action::LineEnd);
+        keymap.bind(Left, ctrl, Action::WordLeft);
+        keymap.bind(Right, ctrl, Action::WordRight);
+        // 多数终端把 Ctrl+Backspace 报告为 Ctrl+H；Alt+Backspace 也按单词删除
+        keymap.bind(Backspace, ctrl, Action::DeleteWordBackward);
+        keymap.bind(Char('h'), ctrl, Action::DeleteWordBackward);
+        keymap.bind(Backspace, alt, Action::DeleteWordBackward);
+        keymap.bind(Delete, ctrl, Action::DeleteWordForward);
+        keymap.bind(Enter, none, Action::InsertNewline);
+        keymap.bind(Backspace, none, Action::DeleteBackward);
+        keymap.bind(Delete, none, Action::DeleteForward);
+        keymap.bind(Char('k'), ctrl, Action::CutLine);
+        keymap.bind(Char('d'), ctrl, Action::DuplicateLine);
+        keymap.bind(Delete, alt, Action::DeleteLine);
+        keymap.bind(Up, alt | KeyModifiers::SHIFT, Action::MoveLineUp);
+        keymap.bind(Down, alt | KeyModifiers::SHIFT, Action::MoveLineDown);
+        keymap.bind(Char('u'), ctrl, Action::Paste);
+        keymap.bind(Char('6'), alt, Action::Copy);
+        keymap.bind(Char('k'), alt, Action::ToggleCutFromCursor);
+        keymap.bind(Char('y'), alt, Action::YankPop);
+        keymap.bind(Char('Y'), alt, Action::PasteFromHistory);
+        keymap.bind(Char('V'), alt, Action::PasteSystem);
+        keymap.bind(Char('U'), alt, Action::UndoBoundary);
+        keymap.bind(Char('F'), alt, Action::ToggleProtectLines);
+        keymap.bind(Char('z'), ctrl, Action::Undo);
+        keymap.bind(Char('u'), alt, Action::Undo);
+        keymap.bind(Char('y'), ctrl, Action::Redo);
+        keymap.bind(Char('t'), ctrl, Action::TransposeChars);
+        keymap.bind(Char('t'), alt, Action::TransposeWords);
+        keymap.bind(Char(']'), alt, Action::JumpPastCloser);
+        keymap.bind(Char('o'), alt, Action::SetOption);
+        keymap.bind(Char('r'), alt, Action::ReloadConfig);
+        keymap.bind(Char('e'), alt, Action::DumpConfig);
+        keymap.bind(Char('z'), alt, Action::CycleTheme);
+        keymap.bind(Char('w'), ctrl, Action::Search);
+        keymap.bind(Char('q'), ctrl, Action::SearchBackward);
+        keymap.bind(Char('w'), alt, Action::SearchNext);
+        keymap.bind(Char('q'), alt, Action::SearchPrev);
+        keymap.bind(Char('W'), alt, Action::SearchResults);
+        // 终端把 Ctrl+\ 报告为 Ctrl+4
+        keymap.bind(Char('\\'), ctrl, Action::Replace);
+        keymap.bind(Char('4'), ctrl, Action::Replace);
+        keymap.bind(Char('Q'), alt, Action::ClearHighlight);
+        keymap.bind(Char('D'), alt, Action::InsertDigraph);
+        keymap.bind(Char('E'), alt, Action::EnableEditing);
+        keymap.bind(Char('G'), alt, Action::JumpLongestLine);
+        keymap.bind(Char('R'), alt, Action::RestoreSnapshot);
+        keymap.bind(Char('H'), alt, Action::RestoreHistory);
+        keymap.bind(F(8), none, Action::NextLocation);
+        keymap.bind(F(8), KeyModifiers::SHIFT, Action::PrevLocation);
+        keymap.bind(F(8), alt, Action::FirstLocation);
+        keymap.bind(F(8), alt | KeyModifiers::SHIFT, Action::LastLocation);
+        keymap.bind(F(7), none, Action::ListChanges);
+        keymap.bind(F(5), none, Action::RunCommand);
+        keymap.bind(F(5), KeyModifiers::SHIFT, Action::RunOutput);
+        keymap.bind(F(6), none, Action::ToggleTerminal);
+        keymap.bind(F(9), none, Action::TaskList);
+        keymap.bind(Char('J'), alt, Action::ShowJournal);
+        keymap.bind(Char('M'), alt, Action::MemoryReport);
+        keymap.bind(Char('Z'), alt, Action::CompactMemory);
+        keymap.bind(Char('I'), alt, Action::InspectChar);
+        keymap.bind(Char('N'), alt, Action::NextSuspiciousChar);
+        keymap.bind(Char('B'), alt, Action::ToggleBookmark);
+        keymap.bind(Char('A'), alt, Action::ToggleBlockSelection);
+        keymap.bind(Char('b'), ctrl, Action::NextBookmark);
+        // 冒号和分号：有的终端给冒号一并报告 SHIFT 修饰
+        for mods in [alt, alt | KeyModifiers::SHIFT] {
+            keymap.bind(Char(':'), mods, Action::RecordMacro);
+        }
+        keymap.bind(Char(';'), alt, Action::RunMacro);
+        keymap.bind(F(4), none, Action::SaveMacro);
+        keymap.bind(F(4), KeyModifiers::SHIFT, Action::ListMacros);
+        keymap.bind(Char('a'), alt, Action::ToggleMark);
+        // 终端把 Ctrl+^ 报告为 Ctrl+6
+        keymap.bind(Char('6'), ctrl, Action::ToggleMark);
+        // 终端把 Ctrl+_ 报告为 Ctrl+7，也有的报告为 Ctrl+_
+        keymap.bind(Char('_'), ctrl, Action::GotoLine);
+        keymap.bind(Char('7'), ctrl, Action::GotoLine);
+        keymap.bind(Char('g'), alt, Action::GotoLine);
+        keymap.bind(Char('v'), alt, Action::ToggleVirtualSpace);
+        keymap.bind(Char('b'), alt, Action::CycleBoxDrawing);
+        keymap.bind(Char('f'), alt, Action::FormatTable);
+        keymap.bind(Char('p'), alt, Action::ToggleMarkdownPreview);
+        keymap.bind(F(2), none, Action::ToggleOutline);
+        keymap.bind(Char('m'), alt, Action::ToggleProse);
+        keymap.bind(Char('s'), alt, Action::ToggleSoftWrap);
+        keymap.bind(Char('x'), alt, Action::HelpLineNextPage);
+        for mods in [alt, alt | KeyModifiers::SHIFT] {
+            keymap.bind(Char('!'), mods, Action::ShellOut);
+        }
+        keymap.bind(Char('T'), alt, Action::ToggleTitleBar);
+        keymap.bind(Char('S'), alt, Action::ToggleStatusBar);
+        keymap.bind(Char('X'), alt, Action::ToggleHelpLine);
+        keymap.bind(Char('P'), alt, Action::Print);
+        keymap.bind(Char('L'), alt, Action::ExportHtml);
+        keymap.bind(Char('C'), alt, Action::ToggleAnsi);
+        keymap.bind(Char('K'), alt, Action::CopyReference);
+        // 括号需要按 Shift，有的终端会一并报告 SHIFT 修饰
+        for mods in [alt, alt | KeyModifiers::SHIFT] {
+            keymap.bind(Char('('), mods, Action::PrevSentence);
+            keymap.bind(Char(')'), mods, Action::NextSentence);
+        }
+        keymap.bind(Char('.'), alt, Action::SelectSentence);
+        keymap.bind(Char('d'), alt, Action::SelectWord);
+        keymap.bind(Char('l'), alt, Action::SelectLine);
+        keymap.bind(Char('='), alt, Action::ExpandSelection);
+        keymap.bind(Tab, none, Action::InsertTab);
+        // Shift+Tab 在不同终端里可能带或不带 SHIFT 修饰
+        keymap.bind(BackTab, none, Action::TablePrevCell);
+        keymap.bind(BackTab, KeyModifiers::SHIFT, Action::TablePrevCell);
+        keymap
+    }
+}
+
+impl Keymap {
+    /// 绑定按键，覆盖已有绑定
+    pub fn bind(&mut self, code: KeyCode, modifiers: KeyModifiers, action: Action) {
+        let binding = KeyBinding::new(code, modifiers);
+        self.macros.remove(&binding);
+        self.bindings.insert(binding, action);
+    }
+
+    /// 把按键绑定到命名宏，覆盖已有绑定
+    pub fn bind_macro(&mut self, binding: KeyBinding, name: &str) {
+        self.bindings.remove(&binding);
+        self.macros.insert(binding, name.to_string());
+    }
+
+    /// 取消按键的绑定
+    pub fn unbind(&mut self, binding: &KeyBinding) {
+        self.bindings.remove(binding);
+        self.macros.remove(binding);
+    }
+
+    /// 所有绑定，按操作名和按键排序，便于显示和导出
+    pub fn sorted_bindings(&self) -> Vec<(KeyBinding, Action)> {
+        let mut bindings: Vec<_> = self.bindings.iter().map(|(k, a)| (*k, *a)).collect();
+        bindings.sort_by_cached_key(|(key, action)| (action.name().unwrap_or(""), key.describe()));
+        bindings
+    }
+
+    /// 默认绑定了操作、现在既没有绑定操作也没有绑定宏的按键，按按键排序
+    pub fn removed_defaults(&self) -> Vec<KeyBinding> {
+        let mut removed: Vec<_> = Keymap::default()
+            .bindings
+            .into_keys()
+            .filter(|key| !self.bindings.contains_key(key) && !self.macros.contains_key(key))
+            .collect();
+        removed.sort_by_cached_key(KeyBinding::describe);
+        removed
+    }
+
+    /// 绑定到宏的按键，按按键排序
+    pub fn sorted_macro_bindings(&self) -> Vec<(KeyBinding, &str)> {
+        let mut bindings: Vec<_> = self.macros.iter().map(|(k, n)| (*k, n.as_str())).collect();
+        bindings.sort_by_cached_key(|(key, _)| key.describe());
+        bindings
+    }
+
+    /// 操作最简短的按键，没有绑定时返回 None
+    ///
+    /// 优先 ^X 这样的 Ctrl 组合；^4、^6 这类只是终端对 ^\、^^ 的另一种报告方式，排在最后。
+    pub fn key_for(&self, action: Action) -> Option<KeyBinding> {
+        let rank = |key: &KeyBinding| match key.code {
+            KeyCode::Char(ch) if key.modifiers == KeyModifiers::CONTROL => {
+                if ch.is_ascii_digit() {
+                    2
+                } else {
+                    0
+                }
+            }
+            _ => 1,
+        };
+        self.bindings
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(key, _)| (rank(key), key.describe(), *key))
+            .min_by(|(rank_a, a, _), (rank_b, b, _)| {
+                (rank_a, a.len(), a).cmp(&(rank_b, b.len(), b))
+            })
+            .map(|(_, _, key)| key)
+    }
+
+    /// 查找按键绑定的操作
+    pub fn lookup(&self, key_event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .get(&KeyBinding::new(key_event.code, key_event.modifiers))
+            .copied()
+    }
+
+    /// 查找按键绑定的宏名
+    pub fn macro_for(&self, key_event: &KeyEvent) -> Option<&str> {
+        self.macros
+            .get(&KeyBinding::new(key_event.code, key_event.modifiers))
+            .map(String::as_str)
+    }
+}
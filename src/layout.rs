@@ -0,0 +1,115 @@
+// 屏幕布局：每帧按终端尺寸算一次各区域的位置和大小
+//
+// 正常情况下从上到下是标题栏、编辑区、状态栏和帮助栏，三个栏都可以在配置中关掉，
+// 空出的行留给编辑区。终端很小时依次隐藏标题栏和帮助栏，编辑区至少保留一行，
+// 最后只剩状态栏（提示和消息仍然可见）；宽或高为 0 时什么也不画。
+// 消息或提示太长时状态区临时扩展到多行，多出的行从编辑区中扣除。
+// 停靠在底部的面板占编辑区下方的三分之一。
+
+/// 标题栏、状态栏和帮助栏是否显示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bars {
+    pub title: bool,
+    pub status: bool,
+    pub help: bool,
+}
+
+impl Default for Bars {
+    fn default() -> Self {
+        Self {
+            title: true,
+            status: true,
+            help: true,
+        }
+    }
+}
+
+/// 各区域所在的行，None 表示放不下而隐藏
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub width: usize,
+    pub height: usize,
+    pub title_bar: Option<u16>,
+    /// 编辑区第一行
+    pub text_top: u16,
+    /// 编辑区行数，可能为 0
+    pub text_rows: usize,
+    /// 状态区第一行
+    pub status_bar: Option<u16>,
+    /// 状态区行数，状态栏隐藏时为 0
+    pub status_rows: usize,
+    pub help_bar: Option<u16>,
+    /// 底部面板第一行（含标题行）
+    pub panel_top: u16,
+    /// 底部面板行数，没有底部面板时为 0
+    pub panel_rows: usize,
+}
+
+impl Layout {
+    /// status_rows 为状态区需要的行数，放不下时减少
+    pub fn new(terminal_size: (u16, u16), bars: Bars, status_rows: usize) -> Self {
+        let (width, height) = terminal_size;
+        let mut layout = Self {
+            width: width as usize,
+            height: height as usize,
+            title_bar: None,
+            text_top: 0,
+            text_rows: 0,
+            status_bar: None,
+            status_rows: 0,
+            help_bar: None,
+            panel_top: 0,
+            panel_rows: 0,
+        };
+        if width == 0 || height == 0 {
+            return layout;
+        }
+        // 从最重要的状态栏开始，按 编辑区第一行 → 状态区多出的行 → 帮助栏 → 标题栏
+        // 的顺序分配剩下的行
+        let status = bars.status;
+        let mut rest = height - status as u16;
+        let text_first = rest >= 1;
+        rest = rest.saturating_sub(1);
+        let status_extra = if status {
+            (status_rows.saturating_sub(1) as u16).min(rest)
+        } else {
+            0
+        };
+        rest -= status_extra;
+        let help = bars.help && rest >= 1;
+        rest -= help as u16;
+        let title = bars.title && rest >= 1;
+        rest -= title as u16;
+        let text_rows = text_first as u16 + rest;
+
+        layout.title_bar = title.then_some(0);
+        layout.text_top = title as u16;
+        layout.text_rows = text_rows as usize;
+        layout.status_bar = status.then_some(layout.text_top + text_rows);
+        layout.status_rows = if status { 1 + status_extra as usize } else { 0 };
+        layout.help_bar = help.then_some(height - 1);
+        layout
+    }
+
+    /// 从编辑区下方分出底部面板，编辑区至少保留两行，太矮时不显示面板
+    pub fn with_bottom_panel(mut self) -> Self {
+        if self.text_rows < 4 {
+            return self;
+        }
+        let rows = (self.text_rows / 3).max(2);
+        self.text_rows -= rows;
+        self.panel_rows = rows;
+        self.panel_top = self.text_top + self.text_rows as u16;
+        self
+    }
+
+    /// 翻页和滚动使用的编辑区高度，至少为 1
+    pub fn page_rows(&self) -> usize {
+        self.text_rows.max(1)
+    }
+
+    /// 终端是否小到什么都画不了
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
@@ -1,11 +1,48 @@
 // RSNano编辑器库入口点
 
 // 导出各个模块
+pub mod action;
+pub mod ansi;
+pub mod boxdraw;
 pub mod buffer;
+pub mod charinfo;
+pub mod config;
+pub mod cutbuffer;
+pub mod digraph;
 pub mod editor;
 pub mod direction;
+pub mod error;
+pub mod filetype;
+pub mod html;
+pub mod journal;
+pub mod keylog;
 pub mod version;
 pub mod args;
+pub mod keymap;
+pub mod layout;
+pub mod location;
+pub mod macros;
+pub mod markdown;
+pub mod outline;
+pub mod paths;
+pub mod print;
+pub mod project;
+pub mod prose;
+pub mod pty;
+pub mod rawbytes;
+pub mod regex;
+pub mod render;
+pub mod settings;
+pub mod sha256;
+pub mod snapshot;
+pub mod system_clipboard;
+pub mod theme;
+pub mod toml;
+pub mod trash;
+pub mod unicode;
+pub mod vt;
+
+pub use error::EditorError;
 
 // 定义Result类型别名
-pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, EditorError>;
\ No newline at end of file
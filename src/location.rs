@@ -0,0 +1,129 @@
+// 位置列表：搜索结果、修改过的行、检查结果等一串可以依次跳转的位置
+//
+// 产生位置的功能只负责生成列表，跳转、编号和状态栏显示由编辑器统一处理。
+
+use crate::buffer::Position;
+use std::path::PathBuf;
+
+/// 列表中的一项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// 所在文件，None 表示生成列表时的当前缓冲区
+    pub path: Option<PathBuf>,
+    pub position: Position,
+    /// 在状态栏显示的说明
+    pub message: String,
+}
+
+impl Location {
+    pub fn new(path: Option<PathBuf>, position: Position, message: impl Into<String>) -> Self {
+        Self {
+            path,
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+/// 跳转方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Next,
+    Prev,
+    First,
+    Last,
+}
+
+/// 一组位置和当前所在的项
+#[derive(Debug, Clone)]
+pub struct LocationList {
+    /// 列表来源，例如 `搜索 “foo”`
+    pub title: String,
+    pub items: Vec<Location>,
+    pub current: Option<usize>,
+}
+
+impl LocationList {
+    pub fn new(title: impl Into<String>, items: Vec<Location>) -> Self {
+        Self {
+            title: title.into(),
+            items,
+            current: None,
+        }
+    }
+
+    /// 移动到下一项、上一项、第一项或最后一项，到达末尾后从另一端继续
+    ///
+    /// 还没有当前项时，下一项是第一项，上一项是最后一项。
+    pub fn step(&mut self, step: Step) -> Option<&Location> {
+        let last = self.items.len().checked_sub(1)?;
+        let index = match (step, self.current) {
+            (Step::First, _) | (Step::Next, None) => 0,
+            (Step::Last, _) | (Step::Prev, None) => last,
+            (Step::Next, Some(i)) if i >= last => 0,
+            (Step::Next, Some(i)) => i + 1,
+            (Step::Prev, Some(0)) => last,
+            (Step::Prev, Some(i)) => (i - 1).min(last),
+        };
+        self.current = Some(index);
+        self.items.get(index)
+    }
+
+    /// 当前项的状态栏说明，例如 `搜索 “foo” 2/5：第 3 行`
+    pub fn describe(&self) -> String {
+        match self.current.and_then(|i| Some((i, self.items.get(i)?))) {
+            Some((i, item)) => format!(
+                "{} {}/{}：{}",
+                self.title,
+                i + 1,
+                self.items.len(),
+                item.message
+            ),
+            None => format!("{}：{} 项", self.title, self.items.len()),
+        }
+    }
+}
+
+/// 在编译器等工具输出的一行中找第一个 `文件:行[:列]`，例如 `--> src/main.rs:3:5`、
+/// `main.c:10:2: error: …`；输出中的行列从 1 开始，返回的位置从 0 开始
+pub fn find_file_position(line: &str) -> Option<(&str, Position)> {
+    line.split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '"' | '\'' | ','))
+        .find_map(parse_file_position)
+}
+
+fn parse_file_position(token: &str) -> Option<(&str, Position)> {
+    let token = token.trim_end_matches(':');
+    let (head, last) = token.rsplit_once(':')?;
+    let last: usize = last.parse().ok()?;
+    let (path, line, column) = match head.rsplit_once(':') {
+        Some((path, line)) if line.parse::<usize>().is_ok() => (path, line.parse().ok()?, last),
+        _ => (head, last, 1),
+    };
+    // 排除 12:30:45 这样的时间
+    if path.is_empty() || line == 0 || path.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((path, Position::new(column.saturating_sub(1), line - 1)))
+}
+
+/// 指向文件中某处的引用，可以贴到聊天、问题单和代码评审里：光标处为 `路径:行[:列]`
+/// （在行首时省略列），选区为 GitHub 风格的 `路径#L10-L20`；行列从 1 开始
+pub fn format_reference(path: &str, from: Position, to: Option<Position>) -> String {
+    match to {
+        Some(to) => {
+            // 选区结束在某行行首时不算这一行
+            let last = if to.x == 0 && to.y > from.y {
+                to.y - 1
+            } else {
+                to.y
+            };
+            if last == from.y {
+                format!("{}#L{}", path, from.y + 1)
+            } else {
+                format!("{}#L{}-L{}", path, from.y + 1, last + 1)
+            }
+        }
+        None if from.x == 0 => format!("{}:{}", path, from.y + 1),
+        None => format!("{}:{}:{}", path, from.y + 1, from.x + 1),
+    }
+}
@@ -0,0 +1,145 @@
+// 键盘宏：录下的一串按键，可以命名保存，也可以在配置中定义并绑定到按键
+//
+// 宏的每一步写法同配置文件中的键位（^Left、M-b、Enter 等），不是按键写法的步骤作为
+// 文字输入，例如 ["Home", "> ", "Down"]。命名的宏保存在状态目录的 macros.toml，
+// 格式与配置文件的 [macros] 表相同；[keys] 中 "F3" = "macro:名字" 把宏绑定到按键。
+
+use crate::keymap::KeyBinding;
+use crate::toml::{self, write_quoted, Value};
+use crate::{paths, EditorError, Result};
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 宏中的一步
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroStep {
+    /// 一个按键，按当时的键位执行
+    Key(KeyBinding),
+    /// 一段文字，逐字输入
+    Text(String),
+}
+
+impl MacroStep {
+    /// 能识别为按键的写成按键，其余作为文字；前后有空白的总是文字
+    pub fn parse(step: &str) -> Self {
+        match KeyBinding::parse(step).filter(|_| step.trim() == step) {
+            Some(binding) => MacroStep::Key(binding),
+            None => MacroStep::Text(step.to_string()),
+        }
+    }
+
+    /// 保存时的写法，可以被 `parse` 读回
+    pub fn describe(&self) -> String {
+        match self {
+            MacroStep::Key(binding) => binding.describe(),
+            MacroStep::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// 一个命名的宏
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Macro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+/// 录制时追加一个按键；连续的普通字符合并成一段文字，保存后更容易阅读和修改
+pub fn push_key(steps: &mut Vec<MacroStep>, binding: KeyBinding) {
+    let plain = matches!(binding.modifiers, KeyModifiers::NONE | KeyModifiers::SHIFT);
+    match (binding.code, steps.last_mut()) {
+        (KeyCode::Char(ch), Some(MacroStep::Text(text))) if plain => text.push(ch),
+        (KeyCode::Char(ch), _) if plain => steps.push(MacroStep::Text(ch.to_string())),
+        _ => steps.push(MacroStep::Key(binding)),
+    }
+}
+
+/// 追加一段输入的文字
+pub fn push_text(steps: &mut Vec<MacroStep>, text: &str) {
+    match steps.last_mut() {
+        Some(MacroStep::Text(last)) => last.push_str(text),
+        _ => steps.push(MacroStep::Text(text.to_string())),
+    }
+}
+
+/// 一步在文件中的写法；会被误读成按键的文字（如 "Up"）拆成单个字符
+fn step_specs(step: &MacroStep) -> Vec<String> {
+    match step {
+        MacroStep::Text(text)
+            if text.chars().count() > 1 && matches!(MacroStep::parse(text), MacroStep::Key(_)) =>
+        {
+            text.chars().map(String::from).collect()
+        }
+        step => vec![step.describe()],
+    }
+}
+
+/// 从 `名字 = [步骤, ...]` 形式的表项读出宏
+pub fn parse_entries(entries: &[(String, Value, usize)]) -> Result<Vec<Macro>> {
+    let mut macros = Vec::new();
+    for (name, value, line) in entries {
+        let invalid = || {
+            EditorError::Config(format!(
+                "第 {} 行: 宏 {} 必须是非空字符串的数组",
+                line, name
+            ))
+        };
+        let Value::Array(items) = value else {
+            return Err(invalid());
+        };
+        let steps = items
+            .iter()
+            .map(|item| match item.as_str() {
+                Some(s) if !s.is_empty() => Ok(MacroStep::parse(s)),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        macros.push(Macro {
+            name: name.clone(),
+            steps,
+        });
+    }
+    Ok(macros)
+}
+
+/// 输出一个宏的表项
+pub fn write_entry(out: &mut String, item: &Macro) {
+    let _ = write_quoted(out, &item.name);
+    out.push_str(" = [");
+    let specs: Vec<String> = item.steps.iter().flat_map(step_specs).collect();
+    for (i, spec) in specs.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        let _ = write_quoted(out, spec);
+    }
+    out.push_str("]\n");
+}
+
+/// 读取保存的宏，文件不存在时返回空列表
+pub fn load(path: &Path) -> Result<Vec<Macro>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(EditorError::io(path, e)),
+    };
+    let document = toml::parse(&text).map_err(|e| match e {
+        EditorError::Config(message) => {
+            EditorError::Config(format!("{}: {}", path.display(), message))
+        }
+        other => other,
+    })?;
+    parse_entries(&document.root().entries)
+}
+
+/// 保存宏，目录不存在时自动创建
+pub fn save(path: &Path, macros: &[Macro]) -> Result<()> {
+    let mut out = String::from("# rsnano 保存的键盘宏，格式同配置文件的 [macros] 表\n");
+    for item in macros {
+        write_entry(&mut out, item);
+    }
+    paths::ensure_parent(path).map_err(|e| EditorError::io(path, e))?;
+    fs::write(path, out).map_err(|e| EditorError::io(path, e))
+}
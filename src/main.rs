@@ -3,11 +3,30 @@
 use rsnano::args::Args;
 use rsnano::editor::Editor;
 use rsnano::version::read_app_info; // 新增
-use rsnano::Result;
+use rsnano::{EditorError, Result};
 
-fn main() -> Result<()> {
+fn main() {
+    if let Err(e) = run() {
+        match e {
+            // clap 自己负责输出帮助/版本信息和参数错误
+            EditorError::Args(e) => e.exit(),
+            e => {
+                eprintln!("rsnano: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn run() -> Result<()> {
     let args = Args::from_cli()?;
+    if args.safe {
+        rsnano::paths::enable_safe_mode();
+    }
+    if let Some(path) = &args.dump_config {
+        return rsnano::config::dump_effective_config(&args, path);
+    }
     let app_info = read_app_info();
     let mut editor = Editor::new(args, app_info)?; // 修改签名
     editor.run()
-}
\ No newline at end of file
+}
@@ -0,0 +1,253 @@
+// Markdown 辅助功能：管道表格格式化与预览渲染
+
+use crate::render::{SpanStyle, StyledLine};
+use crate::unicode::str_width;
+
+/// 列的对齐方式，来自分隔行的 `:---`、`:---:`、`---:` 标记
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// 是否是表格行（去掉缩进后以 `|` 开头）
+pub fn is_table_row(line: &str) -> bool {
+    line.trim_start().starts_with('|')
+}
+
+/// 行内未转义的 `|` 所在的字符索引
+pub fn pipe_positions(line: &str) -> Vec<usize> {
+    let mut positions = Vec::new();
+    let mut escaped = false;
+    for (i, ch) in line.chars().enumerate() {
+        if ch == '|' && !escaped {
+            positions.push(i);
+        }
+        escaped = ch == '\\' && !escaped;
+    }
+    positions
+}
+
+/// 拆出一行中的各个单元格（已去除首尾空白）
+fn split_row(line: &str) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    let pipes = pipe_positions(line);
+    let mut cells: Vec<String> = pipes
+        .windows(2)
+        .map(|w| {
+            chars[w[0] + 1..w[1]]
+                .iter()
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+        .collect();
+    // 最后一个 `|` 之后还有内容时也算一个单元格
+    if let Some(&last) = pipes.last() {
+        let rest: String = chars[last + 1..].iter().collect();
+        if !rest.trim().is_empty() {
+            cells.push(rest.trim().to_string());
+        }
+    }
+    cells
+}
+
+/// 分隔行单元格（如 `:---:`）对应的对齐方式
+fn separator_align(cell: &str) -> Option<Align> {
+    let inner = cell.trim_start_matches(':').trim_end_matches(':');
+    if inner.is_empty() || !inner.chars().all(|c| c == '-') {
+        return None;
+    }
+    Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+        (true, true) => Align::Center,
+        (true, false) => Align::Left,
+        (false, true) => Align::Right,
+        (false, false) => Align::None,
+    })
+}
+
+fn push_aligned(out: &mut String, cell: &str, width: usize, align: Align) {
+    let pad = width.saturating_sub(str_width(cell));
+    let (left, right) = match align {
+        Align::Right => (pad, 0),
+        Align::Center => (pad / 2, pad - pad / 2),
+        Align::None | Align::Left => (0, pad),
+    };
+    out.extend(std::iter::repeat_n(' ', left));
+    out.push_str(cell);
+    out.extend(std::iter::repeat_n(' ', right));
+}
+
+/// 格式化一张管道表格：补齐单元格、按 `|` 对齐，并保留分隔行的对齐标记
+pub fn format_table(rows: &[String]) -> Vec<String> {
+    let indent: String = rows
+        .first()
+        .map(|r| r.chars().take_while(|c| c.is_whitespace()).collect())
+        .unwrap_or_default();
+    let parsed: Vec<Vec<String>> = rows.iter().map(|r| split_row(r)).collect();
+    let columns = parsed.iter().map(Vec::len).max().unwrap_or(0);
+
+    // 找出分隔行（所有单元格都是 --- 形式）
+    let separator = parsed
+        .iter()
+        .position(|cells| !cells.is_empty() && cells.iter().all(|c| separator_align(c).is_some()));
+    let aligns: Vec<Align> = (0..columns)
+        .map(|col| {
+            separator
+                .and_then(|s| parsed[s].get(col))
+                .and_then(|c| separator_align(c))
+                .unwrap_or(Align::None)
+        })
+        .collect();
+
+    let mut widths = vec![3; columns];
+    for (row, cells) in parsed.iter().enumerate() {
+        if Some(row) == separator {
+            continue;
+        }
+        for (col, cell) in cells.iter().enumerate() {
+            widths[col] = widths[col].max(str_width(cell));
+        }
+    }
+
+    parsed
+        .iter()
+        .enumerate()
+        .map(|(row, cells)| {
+            let mut out = indent.clone();
+            out.push('|');
+            for col in 0..columns {
+                out.push(' ');
+                if Some(row) == separator {
+                    let width = widths[col];
+                    let (left, right) = match aligns[col] {
+                        Align::Left => (":", ""),
+                        Align::Center => (":", ":"),
+                        Align::Right => ("", ":"),
+                        Align::None => ("", ""),
+                    };
+                    out.push_str(left);
+                    out.extend(std::iter::repeat_n('-', width - left.len() - right.len()));
+                    out.push_str(right);
+                } else {
+                    let cell = cells.get(col).map(String::as_str).unwrap_or("");
+                    push_aligned(&mut out, cell, widths[col], aligns[col]);
+                }
+                out.push_str(" |");
+            }
+            out
+        })
+        .collect()
+}
+
+/// 是否是表格的分隔行
+pub fn is_separator_row(line: &str) -> bool {
+    let cells = split_row(line);
+    !cells.is_empty() && cells.iter().all(|c| separator_align(c).is_some())
+}
+
+/// 解析行内的 `**粗体**`、`*斜体*`、`` `代码` `` 标记
+fn push_inline(out: &mut StyledLine, text: &str, base: SpanStyle) {
+    let mut bold = false;
+    let mut italic = false;
+    let mut code = false;
+    let mut chars = text.chars().peekable();
+    let mut run = String::new();
+    let style = |bold: bool, italic: bool, code: bool| {
+        if code {
+            SpanStyle::Code
+        } else if bold {
+            SpanStyle::Bold
+        } else if italic {
+            SpanStyle::Italic
+        } else {
+            base
+        }
+    };
+    let mut prev = ' ';
+    while let Some(ch) = chars.next() {
+        // snake_case 里的下划线不算强调标记
+        let is_marker = ch == '*' || (ch == '_' && !prev.is_alphanumeric());
+        prev = ch;
+        let toggle = match ch {
+            '`' => Some(0),
+            _ if is_marker && !code && chars.peek() == Some(&ch) => {
+                chars.next();
+                Some(1)
+            }
+            _ if is_marker && !code => Some(2),
+            _ => None,
+        };
+        match toggle {
+            Some(kind) => {
+                out.push(&run, style(bold, italic, code));
+                run.clear();
+                match kind {
+                    0 => code = !code,
+                    1 => bold = !bold,
+                    _ => italic = !italic,
+                }
+            }
+            None => run.push(ch),
+        }
+    }
+    out.push(&run, style(bold, italic, code));
+}
+
+/// 把 Markdown 源文本渲染成带样式的预览行
+pub fn render_preview(lines: &[String]) -> Vec<StyledLine> {
+    let mut out = Vec::new();
+    let mut in_code_block = false;
+    for (y, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        let mut styled = StyledLine::new(Some(y));
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            styled.push("  ┈┈┈┈", SpanStyle::Dim);
+            out.push(styled);
+            continue;
+        }
+        if in_code_block {
+            styled.push("  ", SpanStyle::Normal);
+            styled.push(line, SpanStyle::Code);
+            out.push(styled);
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            let title = trimmed[level..].trim();
+            push_inline(&mut styled, title, SpanStyle::Heading);
+            out.push(styled);
+            if level <= 2 {
+                let mut underline = StyledLine::new(Some(y));
+                let rule = if level == 1 { "═" } else { "─" };
+                underline.push(&rule.repeat(str_width(title).max(3)), SpanStyle::Heading);
+                out.push(underline);
+            }
+            continue;
+        }
+
+        if matches!(trimmed, "---" | "***" | "___") {
+            styled.push(&"─".repeat(20), SpanStyle::Dim);
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            styled.push("│ ", SpanStyle::Quote);
+            push_inline(&mut styled, quote.trim_start(), SpanStyle::Quote);
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .or_else(|| trimmed.strip_prefix("+ "))
+        {
+            let indent = line.len() - trimmed.len();
+            styled.push(&" ".repeat(indent), SpanStyle::Normal);
+            styled.push("• ", SpanStyle::Bold);
+            push_inline(&mut styled, item, SpanStyle::Normal);
+        } else {
+            push_inline(&mut styled, line, SpanStyle::Normal);
+        }
+        out.push(styled);
+    }
+    out
+}
@@ -0,0 +1,339 @@
+// 文件结构：按文件类型找出文档的标题或代码中的函数、类型等符号（大纲），以及字数和阅读时间
+//
+// 每种文件类型一个提供者，只做按行扫描的轻量识别，不完整解析语法：文档按标题的写法，
+// 代码按每种类型的一组正则表达式规则，符号的层级由缩进决定（如 impl 中的方法）。
+
+use crate::filetype::FileType;
+use crate::regex::Regex;
+
+/// 大纲中的一项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineItem {
+    /// 所在的行
+    pub line: usize,
+    /// 层级，从 1 开始
+    pub level: usize,
+    /// 代码符号的种类（fn、class 等），文档标题没有
+    pub kind: Option<&'static str>,
+    pub title: String,
+}
+
+/// 代码符号的识别规则
+struct SymbolRule {
+    kind: &'static str,
+    /// 行中必须含有的文字，不含时不必再用正则表达式匹配
+    hint: &'static str,
+    /// 第 1 组是符号的名字
+    pattern: &'static str,
+}
+
+const fn rule(kind: &'static str, hint: &'static str, pattern: &'static str) -> SymbolRule {
+    SymbolRule {
+        kind,
+        hint,
+        pattern,
+    }
+}
+
+const RUST_RULES: &[SymbolRule] = &[
+    rule(
+        "fn",
+        "fn",
+        r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:const|async|unsafe|extern(?:\s+"[^"]*")?)\s+)*fn\s+(\w+)"#,
+    ),
+    rule(
+        "struct",
+        "struct",
+        r"^\s*(?:pub(?:\([^)]*\))?\s+)?struct\s+(\w+)",
+    ),
+    rule("enum", "enum", r"^\s*(?:pub(?:\([^)]*\))?\s+)?enum\s+(\w+)"),
+    rule(
+        "union",
+        "union",
+        r"^\s*(?:pub(?:\([^)]*\))?\s+)?union\s+(\w+)",
+    ),
+    rule(
+        "trait",
+        "trait",
+        r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:unsafe\s+)?trait\s+(\w+)",
+    ),
+    rule("type", "type", r"^\s*(?:pub(?:\([^)]*\))?\s+)?type\s+(\w+)"),
+    // 只列出内联的模块，`mod x;` 声明不算
+    rule(
+        "mod",
+        "mod",
+        r"^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)\s*\{",
+    ),
+    rule(
+        "impl",
+        "impl",
+        r"^\s*(?:unsafe\s+)?impl\b(?:<[^>]*>)?\s*([^{]*[^{\s])",
+    ),
+    rule("macro", "macro_rules", r"^\s*macro_rules!\s*(\w+)"),
+];
+
+const PYTHON_RULES: &[SymbolRule] = &[
+    rule("class", "class", r"^\s*class\s+(\w+)"),
+    rule("def", "def", r"^\s*(?:async\s+)?def\s+(\w+)"),
+];
+
+const SHELL_RULES: &[SymbolRule] = &[
+    rule("function", "function", r"^\s*function\s+([\w.:-]+)"),
+    rule("function", "()", r"^\s*([\w.:-]+)\s*\(\)"),
+];
+
+const C_RULES: &[SymbolRule] = &[
+    rule("define", "define", r"^\s*#\s*define\s+(\w+)"),
+    rule(
+        "struct",
+        "struct",
+        r"^\s*(?:typedef\s+)?struct\s+(\w+)\s*\{?\s*$",
+    ),
+    rule("enum", "enum", r"^\s*(?:typedef\s+)?enum\s+(\w+)\s*\{?\s*$"),
+    rule(
+        "union",
+        "union",
+        r"^\s*(?:typedef\s+)?union\s+(\w+)\s*\{?\s*$",
+    ),
+    // 函数定义从第 0 列开始，不以分号结尾（排除声明和调用）
+    rule(
+        "function",
+        "(",
+        r"^[A-Za-z_][\w \t\*&:<>,]*?\b(~?\w+)\s*\([^;]*$",
+    ),
+];
+
+const TOML_RULES: &[SymbolRule] = &[rule("table", "[", r"^\s*\[+\s*([^\]]+?)\s*\]")];
+
+/// 像函数定义但其实是语句的名字
+const C_KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "return", "else", "do", "sizeof",
+];
+
+/// 每分钟阅读的词数，中日韩文字每个字算一个词
+const WORDS_PER_MINUTE: usize = 250;
+
+/// 是否是有标题结构的文档，这类文件的大纲是标题，并统计字数
+pub fn is_document(filetype: FileType) -> bool {
+    matches!(filetype, FileType::Markdown | FileType::Rst)
+}
+
+/// 该文件类型是否有大纲提供者
+pub fn has_outline(filetype: FileType) -> bool {
+    is_document(filetype) || !symbol_rules(filetype).is_empty()
+}
+
+fn symbol_rules(filetype: FileType) -> &'static [SymbolRule] {
+    match filetype {
+        FileType::Rust => RUST_RULES,
+        FileType::Python => PYTHON_RULES,
+        FileType::Shell => SHELL_RULES,
+        FileType::C => C_RULES,
+        FileType::Toml => TOML_RULES,
+        _ => &[],
+    }
+}
+
+/// 找出文档中的标题或代码中的符号，文件类型没有大纲提供者时返回空列表
+pub fn outline(filetype: FileType, lines: &[String]) -> Vec<OutlineItem> {
+    match filetype {
+        FileType::Markdown => markdown_outline(lines),
+        FileType::Rst => rst_outline(lines),
+        _ => symbol_outline(symbol_rules(filetype), lines),
+    }
+}
+
+/// 按规则逐行找符号，每行用第一条匹配的规则；层级是外层（缩进更少的）符号数加一
+fn symbol_outline(rules: &[SymbolRule], lines: &[String]) -> Vec<OutlineItem> {
+    let compiled: Vec<(&SymbolRule, Regex)> = rules
+        .iter()
+        .filter_map(|rule| Regex::new(rule.pattern).ok().map(|regex| (rule, regex)))
+        .collect();
+    let mut items = Vec::new();
+    // 外层符号的缩进
+    let mut enclosing: Vec<usize> = Vec::new();
+    for (y, line) in lines.iter().enumerate() {
+        let Some((kind, name)) = compiled
+            .iter()
+            .filter(|(rule, _)| line.contains(rule.hint))
+            .find_map(|(rule, regex)| {
+                let hay: Vec<char> = line.chars().collect();
+                let (from, to) = regex.captures_at(&hay, 0)?[1]?;
+                let name: String = hay[from..to].iter().collect();
+                Some((rule.kind, name))
+            })
+        else {
+            continue;
+        };
+        if C_KEYWORDS.contains(&name.as_str()) {
+            continue;
+        }
+        let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+        while enclosing.last().is_some_and(|&outer| outer >= indent) {
+            enclosing.pop();
+        }
+        enclosing.push(indent);
+        items.push(OutlineItem {
+            line: y,
+            level: enclosing.len(),
+            kind: Some(kind),
+            title: name,
+        });
+    }
+    items
+}
+
+/// 模糊匹配：pattern 的字符按顺序出现在 text 中（不区分大小写），返回匹配到的字符下标
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let mut positions = Vec::new();
+    let mut wanted = pattern.chars().flat_map(char::to_lowercase).peekable();
+    for (i, ch) in text.chars().enumerate() {
+        let Some(&next) = wanted.peek() else {
+            break;
+        };
+        if ch.to_lowercase().eq([next]) {
+            positions.push(i);
+            wanted.next();
+        }
+    }
+    wanted.peek().is_none().then_some(positions)
+}
+
+/// Markdown 标题：`#` 开头的 ATX 标题和下一行是 `===`、`---` 的 Setext 标题，代码块中的不算
+fn markdown_outline(lines: &[String]) -> Vec<OutlineItem> {
+    let mut items = Vec::new();
+    let mut fence: Option<&str> = None;
+    for (y, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            continue;
+        }
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            continue;
+        }
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) {
+            let rest = &trimmed[level..];
+            if rest.is_empty() || rest.starts_with([' ', '\t']) {
+                let title = rest.trim().trim_end_matches('#').trim_end();
+                items.push(OutlineItem {
+                    line: y,
+                    level,
+                    kind: None,
+                    title: title.to_string(),
+                });
+            }
+            continue;
+        }
+        let underline = lines.get(y + 1).map(|l| l.trim()).unwrap_or("");
+        if !trimmed.is_empty() && !underline.is_empty() {
+            let level = if underline.chars().all(|c| c == '=') {
+                1
+            } else if underline.chars().all(|c| c == '-') && !is_list_item(trimmed) {
+                2
+            } else {
+                continue;
+            };
+            items.push(OutlineItem {
+                line: y,
+                level,
+                kind: None,
+                title: trimmed.trim_end().to_string(),
+            });
+        }
+    }
+    items
+}
+
+/// 列表项下面的 `---` 是分隔线而不是 Setext 标题
+fn is_list_item(line: &str) -> bool {
+    line.starts_with(['-', '*', '+']) || line.starts_with('>')
+}
+
+/// reStructuredText 的装饰线：一串相同的标点
+fn rst_adornment(line: &str) -> Option<char> {
+    let line = line.trim_end();
+    let first = line.chars().next()?;
+    let valid = first.is_ascii_punctuation()
+        && line.chars().count() >= 2
+        && line.chars().all(|c| c == first);
+    valid.then_some(first)
+}
+
+/// reStructuredText 标题：下面（或上下）有装饰线的行，层级按各种装饰第一次出现的顺序
+fn rst_outline(lines: &[String]) -> Vec<OutlineItem> {
+    let mut styles: Vec<(char, bool)> = Vec::new();
+    let mut items = Vec::new();
+    let mut y = 0;
+    while y + 1 < lines.len() {
+        let title = lines[y].trim();
+        let overline = y > 0 && rst_adornment(&lines[y - 1]).is_some();
+        match rst_adornment(&lines[y + 1]) {
+            Some(ch) if !title.is_empty() && rst_adornment(&lines[y]).is_none() => {
+                let style = (ch, overline && rst_adornment(&lines[y - 1]) == Some(ch));
+                let level = match styles.iter().position(|s| *s == style) {
+                    Some(i) => i + 1,
+                    None => {
+                        styles.push(style);
+                        styles.len()
+                    }
+                };
+                items.push(OutlineItem {
+                    line: y,
+                    level,
+                    kind: None,
+                    title: title.to_string(),
+                });
+                y += 2;
+            }
+            _ => y += 1,
+        }
+    }
+    items
+}
+
+/// 字数：连续的字母数字算一个词，中日韩文字每个字算一个词
+pub fn word_count(lines: &[String]) -> usize {
+    let mut count = 0;
+    for line in lines {
+        let mut in_word = false;
+        for ch in line.chars() {
+            if is_cjk(ch) {
+                count += 1;
+                in_word = false;
+            } else if ch.is_alphanumeric() {
+                if !in_word {
+                    count += 1;
+                }
+                in_word = true;
+            } else if ch != '\'' && ch != '’' {
+                // 撇号不拆开 don't 这样的词
+                in_word = false;
+            }
+        }
+    }
+    count
+}
+
+/// 估计的阅读时间（分钟），有内容时至少一分钟
+pub fn reading_minutes(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE)
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch,
+        '\u{3040}'..='\u{30ff}'
+            | '\u{3400}'..='\u{4dbf}'
+            | '\u{4e00}'..='\u{9fff}'
+            | '\u{ac00}'..='\u{d7af}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{20000}'..='\u{2ebef}')
+}
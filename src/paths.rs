@@ -0,0 +1,149 @@
+// 磁盘上的文件位置
+//
+// 配置（config.toml、themes/）放在 $XDG_CONFIG_HOME/rsnano，
+// 运行中产生的状态（剪切历史、会话、光标位置、日志等）放在 $XDG_STATE_HOME/rsnano。
+// 设置了 RSNANO_CONFIG_DIR 时，配置直接放在该目录，状态放在其中的 state 子目录，
+// 方便做便携安装或在测试时与用户的真实配置隔离。
+// 安全模式（--safe）下两个目录都视为不存在，只使用内置默认设置。
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{env, fs, io};
+
+/// 覆盖配置目录的环境变量
+pub const CONFIG_DIR_ENV: &str = "RSNANO_CONFIG_DIR";
+
+static SAFE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// 进入安全模式，之后不再读写配置目录和状态目录
+pub fn enable_safe_mode() {
+    SAFE_MODE.store(true, Ordering::Relaxed);
+}
+
+/// 是否处于安全模式
+pub fn safe_mode() -> bool {
+    SAFE_MODE.load(Ordering::Relaxed)
+}
+
+/// 非空的环境变量
+fn env_dir(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// 按 XDG 规范取基础目录，未设置时用 $HOME 下的默认位置
+fn xdg_dir(name: &str, fallback: &str) -> Option<PathBuf> {
+    env_dir(name).or_else(|| env_dir("HOME").map(|home| home.join(fallback)))
+}
+
+/// 配置目录：$RSNANO_CONFIG_DIR，或 $XDG_CONFIG_HOME/rsnano，或 ~/.config/rsnano
+pub fn config_dir() -> Option<PathBuf> {
+    if safe_mode() {
+        return None;
+    }
+    env_dir(CONFIG_DIR_ENV)
+        .or_else(|| xdg_dir("XDG_CONFIG_HOME", ".config").map(|d| d.join("rsnano")))
+}
+
+/// 状态目录：$RSNANO_CONFIG_DIR/state，或 $XDG_STATE_HOME/rsnano，或 ~/.local/state/rsnano
+pub fn state_dir() -> Option<PathBuf> {
+    if safe_mode() {
+        return None;
+    }
+    match env_dir(CONFIG_DIR_ENV) {
+        Some(dir) => Some(dir.join("state")),
+        None => xdg_dir("XDG_STATE_HOME", ".local/state").map(|d| d.join("rsnano")),
+    }
+}
+
+/// 系统回收站：macOS 为 ~/.Trash，其他系统按 freedesktop.org 规范为 $XDG_DATA_HOME/Trash
+pub fn trash_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join(".Trash"))
+    } else {
+        xdg_dir("XDG_DATA_HOME", ".local/share").map(|d| d.join("Trash"))
+    }
+}
+
+/// 配置文件
+pub fn config_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// 主题目录，每个主题一个 `<名字>.toml`
+pub fn themes_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("themes"))
+}
+
+/// 剪切历史的持久化文件
+pub fn cutbuffer_file() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("cutbuffer"))
+}
+
+/// 命名保存的键盘宏
+pub fn macros_file() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("macros.toml"))
+}
+
+/// 文件历史版本目录
+pub fn history_dir() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("history"))
+}
+
+/// 会话目录
+pub fn sessions_dir() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("sessions"))
+}
+
+/// 自动快照目录
+pub fn snapshots_dir() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("snapshots"))
+}
+
+/// 记录各文件上次光标位置的文件
+pub fn positions_file() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("positions"))
+}
+
+/// 已信任的项目配置列表
+pub fn trusted_projects_file() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("trusted-projects"))
+}
+
+/// 首次运行设置完成（或被跳过）的标记文件
+pub fn first_run_marker() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("first-run-done"))
+}
+
+/// 编辑日志目录，每个文件一个日志
+pub fn journal_dir() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("journal"))
+}
+
+/// 日志目录
+pub fn log_dir() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("log"))
+}
+
+/// 在状态目录中代表某个文件的名字：绝对路径中的 `%` 和路径分隔符转义
+pub fn file_key(file: &Path) -> String {
+    let absolute = fs::canonicalize(file).unwrap_or_else(|_| {
+        env::current_dir()
+            .map(|dir| dir.join(file))
+            .unwrap_or_else(|_| file.to_path_buf())
+    });
+    absolute
+        .to_string_lossy()
+        .replace('%', "%25")
+        .replace('/', "%2F")
+        .replace('\\', "%5C")
+}
+
+/// 写文件前创建它所在的目录
+pub fn ensure_parent(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir),
+        _ => Ok(()),
+    }
+}
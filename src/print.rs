@@ -0,0 +1,160 @@
+// 打印：把缓冲区排成带页眉的分页文本或 PostScript，交给 lpr/lp 或写到文件
+//
+// 分页文本用换页符（\f）分隔各页，lpr 和大多数打印机都认识。PostScript 使用打印机内置的
+// Courier 字体，只能显示 ASCII 字符，其他字符印成 ?。
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// 每页正文的行数
+pub const PAGE_LINES: usize = 60;
+
+/// 打印选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrintOptions {
+    /// 每行前面加行号
+    pub line_numbers: bool,
+    /// 每页顶部印文件名和页码
+    pub header: bool,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            line_numbers: false,
+            header: true,
+        }
+    }
+}
+
+/// 导出的文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrintFormat {
+    /// 用换页符分页的纯文本
+    Text,
+    PostScript,
+}
+
+impl PrintFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            PrintFormat::Text => "txt",
+            PrintFormat::PostScript => "ps",
+        }
+    }
+}
+
+/// 把文本分成页，每页是要印出的各行（含页眉）
+pub fn paginate(title: &str, lines: &[String], options: PrintOptions) -> Vec<Vec<String>> {
+    let page_count = lines.len().div_ceil(PAGE_LINES).max(1);
+    let number_width = lines.len().max(1).to_string().len();
+    let mut pages = Vec::with_capacity(page_count);
+    for page in 0..page_count {
+        let mut out = Vec::with_capacity(PAGE_LINES + 2);
+        if options.header {
+            // 页码不用中文，PostScript 的 Courier 字体也能印出
+            out.push(format!("{}    {}/{}", title, page + 1, page_count));
+            out.push(String::new());
+        }
+        let start = page * PAGE_LINES;
+        let end = (start + PAGE_LINES).min(lines.len());
+        for (i, line) in lines[start..end].iter().enumerate() {
+            if options.line_numbers {
+                out.push(format!("{:>w$}  {}", start + i + 1, line, w = number_width));
+            } else {
+                out.push(line.clone());
+            }
+        }
+        pages.push(out);
+    }
+    pages
+}
+
+/// 分页的纯文本，页与页之间是换页符
+pub fn to_text(pages: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    for (i, page) in pages.iter().enumerate() {
+        if i > 0 {
+            out.push('\x0c');
+        }
+        for line in page {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// PostScript 字符串中的一行，转义括号和反斜杠，非 ASCII 字符换成 ?
+fn ps_string(line: &str, out: &mut String) {
+    out.push('(');
+    for ch in line.chars() {
+        match ch {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            // 制表符按 8 列展开成空格
+            '\t' => out.push_str("        "),
+            ' '..='~' => out.push(ch),
+            _ => out.push('?'),
+        }
+    }
+    out.push(')');
+}
+
+/// A4 和 Letter 纸都放得下的 PostScript，10 磅 Courier，行距 12 磅
+pub fn to_postscript(title: &str, pages: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    out.push_str("%!PS-Adobe-3.0\n");
+    out.push_str("%%Title: ");
+    ps_string(title, &mut out);
+    let _ = writeln!(out, "\n%%Creator: rsnano\n%%Pages: {}", pages.len());
+    out.push_str("%%EndComments\n/Courier findfont 10 scalefont setfont\n");
+    for (i, page) in pages.iter().enumerate() {
+        let _ = writeln!(out, "%%Page: {} {}", i + 1, i + 1);
+        for (row, line) in page.iter().enumerate() {
+            let _ = write!(out, "40 {} moveto ", 780 - 12 * row);
+            ps_string(line, &mut out);
+            out.push_str(" show\n");
+        }
+        out.push_str("showpage\n");
+    }
+    out.push_str("%%EOF\n");
+    out
+}
+
+/// 把文本交给系统的打印命令，先试 lpr 再试 lp，返回使用的命令名
+pub fn send_to_printer(text: &str) -> io::Result<&'static str> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "找不到 lpr 或 lp");
+    for program in ["lpr", "lp"] {
+        let mut child = match Command::new(program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                last_error = e;
+                continue;
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if output.status.success() {
+            return Ok(program);
+        }
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(if message.is_empty() {
+            format!("{} 执行失败", program)
+        } else {
+            message
+        }));
+    }
+    Err(last_error)
+}
@@ -0,0 +1,174 @@
+// 项目配置：从打开的文件所在目录向上查找 .rsnano.toml，其中的设置覆盖用户配置
+//
+// 项目配置只能包含 [options] 和 [filetype.<名字>] 两种表，写法与用户配置相同，
+// 只对项目目录（.rsnano.toml 所在目录）下的缓冲区生效。配置可以设置 runcommand 等
+// 会执行命令的选项，所以第一次遇到或内容变化后要由用户确认信任，信任过的配置按路径和
+// 内容的 SHA-256 摘要记在状态目录的 trusted-projects 中。
+
+use crate::config::apply_option;
+use crate::filetype::FileType;
+use crate::settings::{BufferSettings, FiletypeOptions};
+use crate::{paths, sha256, toml, EditorError, Result};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 项目配置文件名
+pub const PROJECT_FILE: &str = ".rsnano.toml";
+
+/// 文件的绝对路径；文件还不存在时按所在目录计算
+fn absolute(file: &Path) -> Option<PathBuf> {
+    fs::canonicalize(file).ok().or_else(|| {
+        let parent = match file.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p,
+            _ => Path::new("."),
+        };
+        Some(fs::canonicalize(parent).ok()?.join(file.file_name()?))
+    })
+}
+
+/// 从 file 所在目录向上查找最近的项目配置文件
+pub fn find(file: &Path) -> Option<PathBuf> {
+    let file = absolute(file)?;
+    file.ancestors()
+        .skip(1)
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|candidate| candidate.is_file())
+}
+
+/// 读入的一个项目配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectConfig {
+    /// .rsnano.toml 的绝对路径
+    pub path: PathBuf,
+    /// 项目目录
+    pub root: PathBuf,
+    /// [options] 中的设置，每项是一条 `:set` 表达式
+    pub options: Vec<String>,
+    /// [filetype.<名字>] 中的设置
+    pub filetypes: FiletypeOptions,
+    /// 内容的 SHA-256 摘要（十六进制），配置改动后需要重新确认信任；
+    /// 必须抗碰撞，否则可以构造出与信任过的配置摘要相同的恶意配置
+    digest: String,
+}
+
+impl ProjectConfig {
+    /// 读入并检查项目配置，path 为 .rsnano.toml 的绝对路径
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| EditorError::io(path, e))?;
+        Self::parse(path, &text).map_err(|e| match e {
+            EditorError::Config(message) => {
+                EditorError::Config(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })
+    }
+
+    fn parse(path: &Path, text: &str) -> Result<Self> {
+        let document = toml::parse(text)?;
+        if let Some((key, _, line)) = document.root().entries.first() {
+            return Err(EditorError::Config(format!(
+                "第 {} 行: 项目配置不支持 {}，只能使用 [options] 和 [filetype.<名字>]",
+                line, key
+            )));
+        }
+        let mut config = Self {
+            path: path.to_path_buf(),
+            root: path.parent().unwrap_or(Path::new("/")).to_path_buf(),
+            options: Vec::new(),
+            filetypes: Vec::new(),
+            digest: sha256::hex_digest(text.as_bytes()),
+        };
+        for table in &document.tables[1..] {
+            // 先在一份默认设置上检查，生效时再叠加到缓冲区的设置上
+            let mut check = BufferSettings::default();
+            let mut exprs = Vec::new();
+            for (key, value, line) in &table.entries {
+                exprs.push(apply_option(&mut check, key, value, *line)?);
+            }
+            match table.name.as_str() {
+                "options" => config.options.extend(exprs),
+                name if name.starts_with("filetype.") => {
+                    let filetype = FileType::from_key(&name["filetype.".len()..])
+                        .ok_or_else(|| EditorError::Config(format!("未知的文件类型 [{}]", name)))?;
+                    config.filetypes.push((filetype, exprs));
+                }
+                other => {
+                    return Err(EditorError::Config(format!(
+                        "项目配置不支持 [{}]，只能使用 [options] 和 [filetype.<名字>]",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// 文件是否在这个项目目录下
+    pub fn contains(&self, file: &Path) -> bool {
+        absolute(file).is_some_and(|file| file.starts_with(&self.root))
+    }
+
+    /// 把项目的设置叠加到某种文件类型的缓冲区设置上
+    pub fn apply(&self, settings: &mut BufferSettings, filetype: FileType) {
+        let filetype_exprs = self
+            .filetypes
+            .iter()
+            .filter(|(t, _)| *t == filetype)
+            .flat_map(|(_, exprs)| exprs);
+        for expr in self.options.iter().chain(filetype_exprs) {
+            // 表达式在读配置时已经检查过
+            let _ = settings.apply(expr);
+        }
+    }
+}
+
+/// 已信任的项目配置：每行是内容摘要和配置文件路径
+#[derive(Debug, Clone, Default)]
+pub struct TrustStore {
+    file: Option<PathBuf>,
+    entries: Vec<(String, PathBuf)>,
+}
+
+impl TrustStore {
+    /// 读入状态目录中的列表，文件不存在时为空；不是 SHA-256 摘要的旧记录忽略，需要重新确认
+    pub fn load() -> Self {
+        let file = paths::trusted_projects_file();
+        let entries = file
+            .as_ref()
+            .and_then(|f| fs::read_to_string(f).ok())
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let (hash, path) = line.split_once(' ')?;
+                let valid = hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit());
+                valid.then(|| (hash.to_ascii_lowercase(), PathBuf::from(path)))
+            })
+            .collect();
+        Self { file, entries }
+    }
+
+    /// 这份配置（路径和内容都相同）是否信任过
+    pub fn is_trusted(&self, config: &ProjectConfig) -> bool {
+        self.entries
+            .iter()
+            .any(|(hash, path)| *hash == config.digest && *path == config.path)
+    }
+
+    /// 信任这份配置并写回列表，同一路径原来的记录被替换
+    pub fn trust(&mut self, config: &ProjectConfig) -> Result<()> {
+        self.entries.retain(|(_, path)| *path != config.path);
+        self.entries
+            .push((config.digest.clone(), config.path.clone()));
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let mut text = String::new();
+        for (hash, path) in &self.entries {
+            let _ = writeln!(text, "{} {}", hash, path.display());
+        }
+        paths::ensure_parent(file)
+            .and_then(|()| fs::write(file, text))
+            .map_err(|e| EditorError::io(file, e))
+    }
+}
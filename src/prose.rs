@@ -0,0 +1,92 @@
+// 散文相关：排版替换（只在散文模式下生效）与分句
+
+/// 输入一个字符时实际要做的修改
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Typography {
+    /// 插入字符（可能已替换为弯引号）
+    Insert(char),
+    /// 把光标前的字符替换掉，用于 `--` → `—`
+    ReplacePrevious(char),
+}
+
+/// 根据光标前的字符决定输入 ch 时的排版替换
+pub fn typographic_replacement(prev: Option<char>, ch: char) -> Typography {
+    // 行首、空白和左括号之后的引号是左引号，其余是右引号或撇号
+    let opening = prev.is_none_or(|p| p.is_whitespace() || "([{<“‘—".contains(p));
+    match ch {
+        '-' if prev == Some('-') => Typography::ReplacePrevious('—'),
+        '"' if opening => Typography::Insert('“'),
+        '"' => Typography::Insert('”'),
+        '\'' if opening => Typography::Insert('‘'),
+        '\'' => Typography::Insert('’'),
+        _ => Typography::Insert(ch),
+    }
+}
+
+/// 以句点结尾、但通常不是句子结束的缩写（不含最后的句点，小写）
+const ABBREVIATIONS: [&str; 12] = [
+    "e.g", "i.e", "etc", "vs", "cf", "mr", "mrs", "ms", "dr", "st", "no", "approx",
+];
+
+/// 刚输入的字母是否在句首，before 是它之前的文本
+///
+/// 句首指字母前是空白、空白前是句末标点（之后可以跟右引号、右括号），
+/// 省略号和 `e.g.` 这类常见缩写之后不算。
+pub fn starts_sentence(before: &str) -> bool {
+    let trimmed = before.trim_end();
+    if trimmed.len() == before.len() {
+        return false;
+    }
+    let body = trimmed.trim_end_matches(is_closing_punctuation);
+    let Some(last) = body.chars().next_back() else {
+        return false;
+    };
+    if !is_sentence_terminal(last) || last == '…' {
+        return false;
+    }
+    if last == '.' {
+        let word = body[..body.len() - 1]
+            .rsplit(char::is_whitespace)
+            .next()
+            .unwrap_or("");
+        if word.ends_with('.') || ABBREVIATIONS.contains(&word.to_lowercase().as_str()) {
+            return false;
+        }
+    }
+    true
+}
+
+/// 句末标点（包括中日文的全角句号、问号、叹号）
+pub fn is_sentence_terminal(ch: char) -> bool {
+    matches!(
+        ch,
+        '.' | '!'
+            | '?'
+            | '…'
+            | '‼'
+            | '⁇'
+            | '⁈'
+            | '⁉'
+            | '。'
+            | '！'
+            | '？'
+            | '｡'
+            | '؟'
+            | '۔'
+            | '।'
+            | '॥'
+    )
+}
+
+/// 全角句末标点之后不需要空格就开始下一句
+pub fn is_wide_sentence_terminal(ch: char) -> bool {
+    matches!(ch, '。' | '！' | '？' | '｡')
+}
+
+/// 可以跟在句末标点之后、仍属于本句的右引号和右括号
+pub fn is_closing_punctuation(ch: char) -> bool {
+    matches!(
+        ch,
+        '"' | '\'' | ')' | ']' | '}' | '”' | '’' | '»' | '›' | '）' | '」' | '』' | '》' | '】'
+    )
+}
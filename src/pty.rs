@@ -0,0 +1,176 @@
+// 伪终端：内置终端面板中的 shell 运行在伪终端里，输出由后台线程读出
+//
+// 只支持类 Unix 系统，其他系统上 spawn 返回 Unsupported 错误。
+
+use std::ffi::OsStr;
+use std::io;
+
+#[cfg(unix)]
+pub use unix::Pty;
+
+#[cfg(not(unix))]
+pub use unsupported::Pty;
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use std::ffi::CStr;
+    use std::fs::{File, OpenOptions};
+    use std::io::{Read, Write};
+    use std::os::fd::{AsRawFd, FromRawFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::process::CommandExt;
+    use std::path::Path;
+    use std::process::{Child, Command};
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+
+    /// 在伪终端中运行的子进程
+    pub struct Pty {
+        master: File,
+        child: Child,
+        output: Receiver<Vec<u8>>,
+    }
+
+    fn check(result: libc::c_int) -> io::Result<()> {
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn set_size(master: &File, (width, height): (u16, u16)) -> io::Result<()> {
+        let size = libc::winsize {
+            ws_row: height,
+            ws_col: width,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        // SAFETY: master 是打开的伪终端主设备，size 在调用期间有效
+        check(unsafe { libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &size) })
+    }
+
+    impl Pty {
+        /// 打开伪终端并在其中启动 program，size 为（列数, 行数）
+        pub fn spawn(program: &OsStr, size: (u16, u16)) -> io::Result<Self> {
+            // SAFETY: 只调用 POSIX 伪终端函数，返回的描述符立即交给 File 管理，
+            // ptsname_r 把从设备路径写进 name，长度由 name.len() 限定
+            let (master, slave_path) = unsafe {
+                let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+                check(fd)?;
+                let master = File::from_raw_fd(fd);
+                // posix_openpt 不接受 O_CLOEXEC，打开后立即设置，
+                // 以免主设备泄漏到 shell 或之后启动的其他子进程中
+                check(libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC))?;
+                check(libc::grantpt(fd))?;
+                check(libc::unlockpt(fd))?;
+                let mut name = [0 as libc::c_char; 128];
+                let result = libc::ptsname_r(fd, name.as_mut_ptr(), name.len());
+                // 有的实现直接返回错误码，有的返回 -1 并设置 errno
+                if result > 0 {
+                    return Err(io::Error::from_raw_os_error(result));
+                }
+                check(result)?;
+                let path = OsStr::from_bytes(CStr::from_ptr(name.as_ptr()).to_bytes()).to_owned();
+                (master, path)
+            };
+            set_size(&master, size)?;
+            let slave = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .custom_flags(libc::O_NOCTTY)
+                .open(Path::new(&slave_path))?;
+            let mut command = Command::new(program);
+            command
+                .stdin(slave.try_clone()?)
+                .stdout(slave.try_clone()?)
+                .stderr(slave)
+                .env("TERM", "vt100");
+            // SAFETY: fork 之后只调用异步信号安全的 setsid 和 ioctl
+            unsafe {
+                command.pre_exec(|| {
+                    // 新会话，伪终端成为 shell 的控制终端，^C 等信号才能发给前台进程
+                    check(libc::setsid())?;
+                    check(libc::ioctl(0, libc::TIOCSCTTY as _, 0))
+                });
+            }
+            let child = command.spawn()?;
+            // command 中还留着从设备的描述符，关掉后子进程退出时读取才会结束
+            drop(command);
+            let mut reader = master.try_clone()?;
+            let (sender, output) = mpsc::channel();
+            thread::spawn(move || {
+                let mut chunk = [0; 4096];
+                while let Ok(n @ 1..) = reader.read(&mut chunk) {
+                    if sender.send(chunk[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            });
+            Ok(Self {
+                master,
+                child,
+                output,
+            })
+        }
+
+        /// 发送按键或粘贴的文字
+        pub fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+            self.master.write_all(bytes)
+        }
+
+        /// 取出后台线程已经读到的全部输出
+        pub fn read_available(&self, out: &mut Vec<u8>) {
+            while let Ok(chunk) = self.output.try_recv() {
+                out.extend_from_slice(&chunk);
+            }
+        }
+
+        pub fn resize(&self, size: (u16, u16)) -> io::Result<()> {
+            set_size(&self.master, size)
+        }
+
+        pub fn has_exited(&mut self) -> bool {
+            !matches!(self.child.try_wait(), Ok(None))
+        }
+    }
+
+    impl Drop for Pty {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod unsupported {
+    use super::*;
+
+    pub struct Pty;
+
+    impl Pty {
+        pub fn spawn(_program: &OsStr, _size: (u16, u16)) -> io::Result<Self> {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "内置终端只支持类 Unix 系统",
+            ))
+        }
+
+        pub fn write(&mut self, _bytes: &[u8]) -> io::Result<()> {
+            Ok(())
+        }
+
+        pub fn read_available(&self, _out: &mut Vec<u8>) {}
+
+        pub fn resize(&self, _size: (u16, u16)) -> io::Result<()> {
+            Ok(())
+        }
+
+        pub fn has_exited(&mut self) -> bool {
+            true
+        }
+    }
+}
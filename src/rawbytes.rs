@@ -0,0 +1,109 @@
+// 无效 UTF-8 字节的无损保存
+//
+// 大部分是文本、偶尔夹着无效字节的文件按字节读入：有效的部分照常解码，每个无效字节
+// 换成补充私用区 B 末尾的一个字符（U+10FF00 + 字节值），保存时再换回原来的字节。
+// 文件里本来就有的 U+10FF00..U+10FFFF 字符按它的四个字节逐个转义，保证往返不变。
+// 这些字符在屏幕上显示为 �，空字符显示为 ␀。
+
+use std::str;
+
+/// 转义字符的起点
+const RAW_BYTE_BASE: u32 = 0x10FF00;
+
+/// 转义单个字节
+fn escape(byte: u8) -> char {
+    char::from_u32(RAW_BYTE_BASE + byte as u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+/// 转义字符代表的原始字节，普通字符返回 None
+pub fn byte_of(ch: char) -> Option<u8> {
+    (ch as u32)
+        .checked_sub(RAW_BYTE_BASE)
+        .and_then(|b| u8::try_from(b).ok())
+}
+
+fn push_valid(text: &mut String, valid: &str) {
+    for ch in valid.chars() {
+        if byte_of(ch).is_some() {
+            let mut buf = [0; 4];
+            text.extend(ch.encode_utf8(&mut buf).bytes().map(escape));
+        } else {
+            text.push(ch);
+        }
+    }
+}
+
+/// 按字节解码，返回文本和其中无效字节的个数
+pub fn decode(bytes: &[u8]) -> (String, usize) {
+    let mut text = String::with_capacity(bytes.len());
+    let mut invalid = 0;
+    let mut rest = bytes;
+    loop {
+        match str::from_utf8(rest) {
+            Ok(valid) => {
+                push_valid(&mut text, valid);
+                return (text, invalid);
+            }
+            Err(e) => {
+                let (valid, after) = rest.split_at(e.valid_up_to());
+                push_valid(&mut text, str::from_utf8(valid).unwrap_or_default());
+                let bad = e.error_len().unwrap_or(after.len());
+                text.extend(after[..bad].iter().map(|&b| escape(b)));
+                invalid += bad;
+                rest = &after[bad..];
+            }
+        }
+    }
+}
+
+/// 把文本编码成字节，转义字符换回原来的字节
+pub fn encode(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for ch in text.chars() {
+        match byte_of(ch) {
+            Some(b) => bytes.push(b),
+            None => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    bytes
+}
+
+/// 末尾不完整的多字节字符的长度，分块读入时这部分留到下一块
+pub fn incomplete_tail(bytes: &[u8]) -> usize {
+    let start = bytes.len().saturating_sub(3);
+    let Some(lead) = (start..bytes.len())
+        .rev()
+        .find(|&i| bytes[i] & 0xC0 != 0x80)
+    else {
+        return 0;
+    };
+    let needed = match bytes[lead] {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => 1,
+    };
+    let have = bytes.len() - lead;
+    if have < needed {
+        have
+    } else {
+        0
+    }
+}
+
+/// 屏幕上显示的字符：空字符显示为 ␀，ESC 显示为 ␛，无效字节显示为 �
+pub fn display_char(ch: char) -> char {
+    if ch == '\0' {
+        '␀'
+    } else if ch == '\x1b' {
+        // 原样输出会被终端当作控制序列，弄乱屏幕
+        '␛'
+    } else if byte_of(ch).is_some() {
+        char::REPLACEMENT_CHARACTER
+    } else {
+        ch
+    }
+}
@@ -0,0 +1,579 @@
+// 替换（^\）使用的正则表达式
+//
+// 支持字面字符、`.`、字符类 `[a-z]` `[^...]`、`\d \w \s` 及大写的反义、`\b \B`、`^ $`、
+// 分组 `( )` 和非捕获分组 `(?: )`、选择 `|`、量词 `* + ? {n} {n,} {n,m}` 及其非贪婪形式。
+// 只在一行之内匹配，用回溯实现；模式中没有大写字母时不区分大小写，与普通搜索一致。
+// 替换内容中 `$0` 到 `$9` 引用分组，`$$` 表示 `$` 本身。
+
+use crate::unicode::is_word_char;
+
+/// 一次匹配最多尝试的步数，超过时当作不匹配，防止病态的模式卡住编辑器
+const MAX_STEPS: usize = 1_000_000;
+
+/// 回溯的最大嵌套层数，超过时当作不匹配，防止栈溢出
+const MAX_DEPTH: usize = 5_000;
+
+/// 分组的最大嵌套层数，超过时报错，防止解析时栈溢出
+const MAX_NESTING: usize = 200;
+
+/// 各分组匹配到的范围（字符索引），第 0 组是整个匹配
+pub type Captures = Vec<Option<(usize, usize)>>;
+
+/// 编译好的正则表达式
+#[derive(Debug, Clone)]
+pub struct Regex {
+    node: Node,
+    groups: usize,
+    ignore_case: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    Any,
+    /// 字符类，第二项为真时取反
+    Class(Vec<ClassItem>, bool),
+    Start,
+    End,
+    /// \b（真）或 \B（假）
+    WordBoundary(bool),
+    /// 分组，捕获分组带序号
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+/// 字符类中的一项；\d 等带的布尔值为假时表示大写的反义形式
+#[derive(Debug, Clone, Copy)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl ClassItem {
+    fn matches(self, c: char) -> bool {
+        match self {
+            ClassItem::Range(lo, hi) => (lo..=hi).contains(&c),
+            ClassItem::Digit(want) => c.is_ascii_digit() == want,
+            ClassItem::Word(want) => is_word_char(c) == want,
+            ClassItem::Space(want) => c.is_whitespace() == want,
+        }
+    }
+}
+
+/// `\d` 等可以出现在字符类里的转义
+fn class_escape(c: char) -> Option<ClassItem> {
+    match c {
+        'd' => Some(ClassItem::Digit(true)),
+        'D' => Some(ClassItem::Digit(false)),
+        'w' => Some(ClassItem::Word(true)),
+        'W' => Some(ClassItem::Word(false)),
+        's' => Some(ClassItem::Space(true)),
+        'S' => Some(ClassItem::Space(false)),
+        _ => None,
+    }
+}
+
+/// 转义的普通字符：`\t` 是制表符，其他字符代表自身
+fn escaped_char(c: char) -> char {
+    match c {
+        't' => '\t',
+        c => c,
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    groups: usize,
+    /// 当前所在分组的嵌套层数
+    nesting: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_alt(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.parse_concat()?];
+        while self.eat('|') {
+            branches.push(self.parse_concat()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.remove(0)
+        } else {
+            Node::Alt(branches)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, String> {
+        let mut items = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            items.push(self.parse_quantifier(atom)?);
+        }
+        Ok(Node::Concat(items))
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, String> {
+        let Some(c) = self.next() else {
+            return Err("模式意外结束".to_string());
+        };
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '(' => {
+                if self.nesting >= MAX_NESTING {
+                    return Err(format!("分组嵌套超过 {} 层", MAX_NESTING));
+                }
+                let index = if self.chars[self.pos..].starts_with(&['?', ':']) {
+                    self.pos += 2;
+                    None
+                } else {
+                    self.groups += 1;
+                    Some(self.groups)
+                };
+                self.nesting += 1;
+                let inner = self.parse_alt()?;
+                self.nesting -= 1;
+                if !self.eat(')') {
+                    return Err("缺少 )".to_string());
+                }
+                Node::Group(Box::new(inner), index)
+            }
+            '[' => self.parse_class()?,
+            '\\' => {
+                let Some(c) = self.next() else {
+                    return Err("模式末尾多了一个 \\".to_string());
+                };
+                match c {
+                    'b' => Node::WordBoundary(true),
+                    'B' => Node::WordBoundary(false),
+                    c => match class_escape(c) {
+                        Some(item) => Node::Class(vec![item], false),
+                        None => Node::Char(escaped_char(c)),
+                    },
+                }
+            }
+            '*' | '+' | '?' => return Err(format!("{} 前面没有可以重复的内容", c)),
+            c => Node::Char(c),
+        })
+    }
+
+    fn parse_class(&mut self) -> Result<Node, String> {
+        let missing = || "字符类缺少 ]".to_string();
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.next().ok_or_else(missing)?;
+            // 紧跟在 [ 或 [^ 之后的 ] 是普通字符
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            let lo = if c == '\\' {
+                let e = self.next().ok_or_else(missing)?;
+                if let Some(item) = class_escape(e) {
+                    items.push(item);
+                    continue;
+                }
+                escaped_char(e)
+            } else {
+                c
+            };
+            let is_range =
+                self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']');
+            if !is_range {
+                items.push(ClassItem::Range(lo, lo));
+                continue;
+            }
+            self.pos += 1;
+            let mut hi = self.next().ok_or_else(missing)?;
+            if hi == '\\' {
+                hi = escaped_char(self.next().ok_or_else(missing)?);
+            }
+            if hi < lo {
+                return Err(format!("字符范围 {}-{} 顺序颠倒", lo, hi));
+            }
+            items.push(ClassItem::Range(lo, hi));
+        }
+        Ok(Node::Class(items, negated))
+    }
+
+    fn parse_quantifier(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some('{') => match self.braces() {
+                Some((min, max, len)) => {
+                    if let Some(max) = max.filter(|&max| max < min) {
+                        return Err(format!("{{{},{}}} 中的次数顺序颠倒", min, max));
+                    }
+                    self.pos += len;
+                    (min, max)
+                }
+                // 不是合法的 {n,m} 时 { 当普通字符
+                None => return Ok(atom),
+            },
+            Some(c @ ('*' | '+' | '?')) => {
+                self.pos += 1;
+                match c {
+                    '*' => (0, None),
+                    '+' => (1, None),
+                    _ => (0, Some(1)),
+                }
+            }
+            _ => return Ok(atom),
+        };
+        if matches!(atom, Node::Start | Node::End | Node::WordBoundary(_)) {
+            return Err("锚点不能重复".to_string());
+        }
+        let greedy = !self.eat('?');
+        // x{2}{3} 这样连用的量词含义不明，* + 已在 parse_atom 中报错
+        if self.peek() == Some('{') && self.braces().is_some() {
+            return Err("量词不能连用，请用 (?: ) 分组后再重复".to_string());
+        }
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    /// 解析当前位置的 `{n}`、`{n,}` 或 `{n,m}`，返回次数和所占的字符数，不移动位置
+    fn braces(&self) -> Option<(usize, Option<usize>, usize)> {
+        let rest = &self.chars[self.pos + 1..];
+        let end = rest.iter().position(|&c| c == '}')?;
+        let body: String = rest[..end].iter().collect();
+        let (min, max) = match body.split_once(',') {
+            Some((min, "")) => (min.parse().ok()?, None),
+            Some((min, max)) => (min.parse().ok()?, Some(max.parse().ok()?)),
+            None => {
+                let n = body.parse().ok()?;
+                (n, Some(n))
+            }
+        };
+        Some((min, max, end + 2))
+    }
+}
+
+impl Node {
+    /// 是否只匹配一个字符，这样的节点重复时可以不用回溯逐个尝试
+    fn is_single_char(&self) -> bool {
+        matches!(self, Node::Char(_) | Node::Any | Node::Class(..))
+    }
+
+    /// 单个字符节点是否匹配 c，其他节点返回 None
+    fn single_char(&self, c: char, ignore_case: bool) -> Option<bool> {
+        let test = |c: char| match self {
+            Node::Char(expected) => Some(c == *expected),
+            Node::Any => Some(true),
+            Node::Class(items, negated) => Some(items.iter().any(|i| i.matches(c)) != *negated),
+            _ => None,
+        };
+        let direct = test(c)?;
+        if direct || !ignore_case {
+            return Some(direct);
+        }
+        let other_case = c.to_lowercase().chain(c.to_uppercase());
+        Some(
+            other_case
+                .filter(|&o| o != c)
+                .any(|o| test(o) == Some(true)),
+        )
+    }
+}
+
+/// 模式中（转义之外）有没有大写字母
+fn has_uppercase(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+/// 一次匹配过程中的状态
+struct Search<'a> {
+    regex: &'a Regex,
+    hay: &'a [char],
+    steps: usize,
+    depth: usize,
+}
+
+type Continuation<'k, 'a> = &'k mut dyn FnMut(&mut Search<'a>, usize, &mut Captures) -> bool;
+
+impl<'a> Search<'a> {
+    fn run(
+        &mut self,
+        node: &'a Node,
+        pos: usize,
+        caps: &mut Captures,
+        k: Continuation<'_, 'a>,
+    ) -> bool {
+        self.steps += 1;
+        if self.steps > MAX_STEPS || self.depth > MAX_DEPTH {
+            return false;
+        }
+        self.depth += 1;
+        let matched = self.step(node, pos, caps, k);
+        self.depth -= 1;
+        matched
+    }
+
+    fn step(
+        &mut self,
+        node: &'a Node,
+        pos: usize,
+        caps: &mut Captures,
+        k: Continuation<'_, 'a>,
+    ) -> bool {
+        let hay = self.hay;
+        let ignore_case = self.regex.ignore_case;
+        match node {
+            Node::Char(_) | Node::Any | Node::Class(..) => {
+                pos < hay.len()
+                    && node.single_char(hay[pos], ignore_case) == Some(true)
+                    && k(self, pos + 1, caps)
+            }
+            Node::Start => pos == 0 && k(self, pos, caps),
+            Node::End => pos == hay.len() && k(self, pos, caps),
+            Node::WordBoundary(want) => {
+                let before = pos > 0 && is_word_char(hay[pos - 1]);
+                let after = pos < hay.len() && is_word_char(hay[pos]);
+                (before != after) == *want && k(self, pos, caps)
+            }
+            Node::Group(inner, None) => self.run(inner, pos, caps, k),
+            Node::Group(inner, Some(index)) => {
+                let index = *index;
+                self.run(inner, pos, caps, &mut |search, end, caps| {
+                    let old = caps[index];
+                    caps[index] = Some((pos, end));
+                    if k(search, end, caps) {
+                        return true;
+                    }
+                    caps[index] = old;
+                    false
+                })
+            }
+            Node::Concat(items) => self.concat(items, pos, caps, k),
+            Node::Alt(branches) => {
+                for branch in branches {
+                    if self.run(branch, pos, caps, k) {
+                        return true;
+                    }
+                }
+                false
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => {
+                if node.is_single_char() {
+                    self.repeat_single(node, *min, *max, *greedy, pos, caps, k)
+                } else {
+                    self.repeat(node, (*min, *max, *greedy), 0, pos, caps, k)
+                }
+            }
+        }
+    }
+
+    fn concat(
+        &mut self,
+        items: &'a [Node],
+        pos: usize,
+        caps: &mut Captures,
+        k: Continuation<'_, 'a>,
+    ) -> bool {
+        match items.split_first() {
+            None => k(self, pos, caps),
+            Some((first, rest)) => self.run(first, pos, caps, &mut |search, next, caps| {
+                search.concat(rest, next, caps, k)
+            }),
+        }
+    }
+
+    /// 单个字符的重复：先数出最多能匹配几个，再从多到少（非贪婪时从少到多）尝试后续
+    #[allow(clippy::too_many_arguments)]
+    fn repeat_single(
+        &mut self,
+        node: &'a Node,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+        pos: usize,
+        caps: &mut Captures,
+        k: Continuation<'_, 'a>,
+    ) -> bool {
+        let ignore_case = self.regex.ignore_case;
+        let mut count = 0;
+        while max.is_none_or(|max| count < max)
+            && pos + count < self.hay.len()
+            && node.single_char(self.hay[pos + count], ignore_case) == Some(true)
+        {
+            count += 1;
+        }
+        if count < min {
+            return false;
+        }
+        if greedy {
+            (min..=count).rev().any(|n| k(self, pos + n, caps))
+        } else {
+            (min..=count).any(|n| k(self, pos + n, caps))
+        }
+    }
+
+    /// 一般的重复，count 为已经匹配的次数；一次没有前进时停止，避免空循环
+    fn repeat(
+        &mut self,
+        node: &'a Node,
+        bounds: (usize, Option<usize>, bool),
+        count: usize,
+        pos: usize,
+        caps: &mut Captures,
+        k: Continuation<'_, 'a>,
+    ) -> bool {
+        let (min, max, greedy) = bounds;
+        let can_stop = count >= min;
+        let can_continue = max.is_none_or(|max| count < max);
+        if !greedy && can_stop && k(self, pos, caps) {
+            return true;
+        }
+        if can_continue
+            && self.run(node, pos, caps, &mut |search, next, caps| {
+                (next != pos || !can_stop) && search.repeat(node, bounds, count + 1, next, caps, k)
+            })
+        {
+            return true;
+        }
+        greedy && can_stop && k(self, pos, caps)
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+            groups: 0,
+            nesting: 0,
+        };
+        let node = parser.parse_alt()?;
+        if parser.pos < parser.chars.len() {
+            return Err("多余的 )".to_string());
+        }
+        Ok(Self {
+            node,
+            groups: parser.groups,
+            ignore_case: !has_uppercase(pattern),
+        })
+    }
+
+    /// 从 start 开始的第一处匹配
+    pub fn captures_at(&self, hay: &[char], start: usize) -> Option<Captures> {
+        let mut search = Search {
+            regex: self,
+            hay,
+            steps: 0,
+            depth: 0,
+        };
+        for begin in start..=hay.len() {
+            let mut caps: Captures = vec![None; self.groups + 1];
+            let mut end = None;
+            let matched = search.run(&self.node, begin, &mut caps, &mut |_, pos, _| {
+                end = Some(pos);
+                true
+            });
+            if let (true, Some(end)) = (matched, end) {
+                caps[0] = Some((begin, end));
+                return Some(caps);
+            }
+            if search.steps > MAX_STEPS {
+                return None;
+            }
+        }
+        None
+    }
+
+    /// 一行中所有不重叠的匹配，返回字符索引范围；紧接在上一处匹配之后的空匹配不算
+    pub fn find_in_line(&self, line: &str) -> Vec<(usize, usize)> {
+        let hay: Vec<char> = line.chars().collect();
+        let mut found = Vec::new();
+        let mut start = 0;
+        let mut last_end = None;
+        while start <= hay.len() {
+            let Some((from, to)) = self.captures_at(&hay, start).and_then(|caps| caps[0]) else {
+                break;
+            };
+            if from == to && last_end == Some(from) {
+                start = from + 1;
+                continue;
+            }
+            found.push((from, to));
+            last_end = Some(to);
+            start = if to > from { to } else { to + 1 };
+        }
+        found
+    }
+
+    /// 把替换模板中的 `$0`…`$9` 换成分组的内容，`$$` 换成 `$`
+    pub fn expand(&self, template: &str, hay: &[char], caps: &Captures) -> String {
+        let mut out = String::with_capacity(template.len());
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                out.push(c);
+                continue;
+            }
+            match chars.peek().copied() {
+                Some('$') => {
+                    chars.next();
+                    out.push('$');
+                }
+                Some(d) if d.is_ascii_digit() => {
+                    chars.next();
+                    let index = d as usize - '0' as usize;
+                    if let Some(Some((from, to))) = caps.get(index) {
+                        out.extend(&hay[*from..*to]);
+                    }
+                }
+                _ => out.push('$'),
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,205 @@
+// 渲染辅助：每帧复用的字符串缓冲区与按显示宽度填充
+
+use crate::unicode::{cell_width, char_width, str_width};
+
+/// 缓冲区以外的文本（状态栏、提示等）中制表符的宽度
+const TAB_WIDTH: usize = 8;
+
+/// 每帧复用的字符串缓冲区，避免渲染路径上反复分配 String
+#[derive(Default)]
+pub struct FrameBuffers {
+    /// 顶部信息栏
+    pub info_bar: String,
+    /// 状态栏
+    pub status_bar: String,
+    /// 状态区各行，消息或提示太长时有多行
+    pub status_lines: Vec<String>,
+    /// 底部帮助栏
+    pub help_bar: String,
+    /// 编辑区当前正在绘制的行
+    pub line: String,
+}
+
+impl FrameBuffers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// 用空格把 buf 填充到指定显示宽度（中文等全角字符按两列计算）
+pub fn pad_to_width(buf: &mut String, width: usize) {
+    let used = str_width(buf);
+    if used < width {
+        buf.extend(std::iter::repeat_n(' ', width - used));
+    }
+}
+
+/// 把 buf 截断到不超过指定显示宽度，不会截断在字符中间
+pub fn truncate_to_width(buf: &mut String, width: usize) {
+    let mut used = 0;
+    let mut cut = None;
+    for (pos, ch) in buf.char_indices() {
+        used += char_width(ch);
+        if used > width {
+            cut = Some(pos);
+            break;
+        }
+    }
+    if let Some(pos) = cut {
+        buf.truncate(pos);
+    }
+}
+
+/// 截断并填充，使 buf 恰好占满指定显示宽度
+pub fn fit_to_width(buf: &mut String, width: usize) {
+    let used = str_width(buf);
+    if used > width {
+        truncate_to_width(buf, width);
+        pad_to_width(buf, width);
+    } else {
+        buf.extend(std::iter::repeat_n(' ', width - used));
+    }
+}
+
+/// 按显示宽度把 text 折成多行追加到 lines，断行规则同软换行
+pub fn wrap_to_width(text: &str, width: usize, lines: &mut Vec<String>) {
+    let count = text.chars().count();
+    let mut start = 0;
+    while start < count {
+        let end = wrap_end(text, start, width, TAB_WIDTH);
+        lines.push(text.chars().skip(start).take(end - start).collect());
+        start = end;
+    }
+}
+
+/// 不换行时，从第 start 个字符（位于显示列 first_col）开始放得进 width 列的字符在哪里结束（不含）
+///
+/// 按显示宽度计算：中日韩等宽字符占两列，放不下的宽字符整个不显示；不占宽度的组合字符
+/// 跟着前面的字符。
+pub fn clip_end(
+    line: &str,
+    start: usize,
+    first_col: usize,
+    width: usize,
+    tab_width: usize,
+) -> usize {
+    let mut used = 0;
+    let mut count = start;
+    for (i, ch) in line.chars().enumerate().skip(start) {
+        let w = cell_width(ch, first_col + used, tab_width);
+        if used + w > width {
+            break;
+        }
+        used += w;
+        count = i + 1;
+    }
+    count
+}
+
+/// 软换行时，从第 start 个字符开始的屏幕行在哪个字符处结束（不含）
+///
+/// 按显示宽度计算，优先在空格或制表符之后断开，整段没有空白时才从单词中间断开。
+/// 每个屏幕行的制表位从该行开头算起。
+pub fn wrap_end(line: &str, start: usize, width: usize, tab_width: usize) -> usize {
+    let mut used = 0;
+    let mut last_break = None;
+    let mut count = start;
+    for (i, ch) in line.chars().enumerate().skip(start) {
+        let w = cell_width(ch, used, tab_width);
+        if used + w > width && i > start {
+            return last_break.unwrap_or(i);
+        }
+        used += w;
+        if ch == ' ' || ch == '\t' {
+            last_break = Some(i + 1);
+        }
+        count = i + 1;
+    }
+    count
+}
+
+/// 软换行后第 x 个字符位于第几个屏幕行，以及整行共占几个屏幕行
+pub fn wrap_position(line: &str, width: usize, x: usize, tab_width: usize) -> (usize, usize) {
+    let char_count = line.chars().count();
+    let mut start = 0;
+    let mut row = 0;
+    let mut cursor_row = None;
+    loop {
+        let end = wrap_end(line, start, width, tab_width);
+        if cursor_row.is_none() && (x < end || end >= char_count) {
+            cursor_row = Some(row);
+        }
+        if end >= char_count {
+            return (cursor_row.unwrap_or(row), row + 1);
+        }
+        start = end;
+        row += 1;
+    }
+}
+
+/// 文本片段的显示样式，由界面层映射为具体颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanStyle {
+    Normal,
+    Heading,
+    Bold,
+    Italic,
+    Code,
+    Quote,
+    Dim,
+    /// 差异中新增的行
+    Added,
+    /// 差异中删除的行
+    Removed,
+    /// 差异中新增的行里与原行不同的字符
+    AddedChange,
+    /// 差异中删除的行里与新行不同的字符
+    RemovedChange,
+    /// 搜索匹配
+    Match,
+    /// 内置终端的光标
+    Cursor,
+}
+
+/// 一段带样式的文本
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub text: String,
+    pub style: SpanStyle,
+}
+
+/// 由若干片段组成的一行，用于面板等非缓冲区内容
+#[derive(Debug, Clone, Default)]
+pub struct StyledLine {
+    pub spans: Vec<Span>,
+    /// 对应的缓冲区行号，用于与编辑区同步滚动
+    pub source_line: Option<usize>,
+}
+
+impl StyledLine {
+    pub fn new(source_line: Option<usize>) -> Self {
+        Self {
+            spans: Vec::new(),
+            source_line,
+        }
+    }
+
+    /// 追加一段文本，与上一段样式相同时合并
+    pub fn push(&mut self, text: &str, style: SpanStyle) {
+        if text.is_empty() {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => self.spans.push(Span {
+                text: text.to_string(),
+                style,
+            }),
+        }
+    }
+
+    /// 整行的纯文本
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+}
@@ -0,0 +1,296 @@
+// 缓冲区设置：每个缓冲区一份，可以用 :set 单独修改而不影响全局默认值
+
+use crate::filetype::FileType;
+use crate::{EditorError, Result};
+use std::fmt::Write as _;
+
+/// 每个缓冲区独立的设置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferSettings {
+    /// 制表符宽度
+    pub tab_width: usize,
+    /// 按 Tab 时插入空格（补到下一个制表位）而不是制表符
+    pub expand_tab: bool,
+    /// 软换行：过长的行折到下一屏幕行显示，不修改文本
+    pub soft_wrap: bool,
+    /// 显示行号
+    pub line_numbers: bool,
+    /// 只读：禁止修改文本
+    pub read_only: bool,
+    /// 自由光标模式：光标可以停在行尾之后和文件末尾之后，输入时再用空格补齐
+    pub virtual_space: bool,
+    /// 自动补全括号和引号
+    pub auto_close: bool,
+    /// 散文模式：输入时做排版替换（破折号、弯引号），只用于文字类文件
+    pub prose: bool,
+    /// 散文模式下句末标点之后输入的第一个字母自动大写
+    pub auto_capitalize: bool,
+    /// 散文模式下词与词之间连续输入的空格合并成一个
+    pub collapse_spaces: bool,
+    /// 显示附在行上的虚拟文本（运行结果中的错误等）
+    pub annotations: bool,
+    /// 用醒目的颜色标出双向控制符、零宽字符和形似 ASCII 的字符
+    pub unicode_check: bool,
+    /// 有书签、运行结果或修改过的行时，在行号左侧显示符号栏
+    pub sign_column: bool,
+    /// 行长上限（字符数），0 表示不限制
+    pub max_line_length: usize,
+    /// 超过行长上限时的处理方式
+    pub line_limit: LineLimit,
+    /// 运行命令（F5），%f 换成文件名；留空时按文件类型使用默认命令
+    pub run_command: String,
+}
+
+/// 超过行长上限时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineLimit {
+    /// 只把超出的部分标上颜色
+    #[default]
+    Warn,
+    /// 输入时在上限之前的空白处自动换行
+    Wrap,
+    /// 拒绝在已达到上限的行中输入
+    Block,
+}
+
+impl LineLimit {
+    pub fn name(self) -> &'static str {
+        match self {
+            LineLimit::Warn => "warn",
+            LineLimit::Wrap => "wrap",
+            LineLimit::Block => "block",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "warn" => Some(LineLimit::Warn),
+            "wrap" => Some(LineLimit::Wrap),
+            "block" => Some(LineLimit::Block),
+            _ => None,
+        }
+    }
+}
+
+impl Default for BufferSettings {
+    fn default() -> Self {
+        Self {
+            tab_width: 8,
+            expand_tab: false,
+            soft_wrap: false,
+            line_numbers: false,
+            read_only: false,
+            virtual_space: false,
+            auto_close: false,
+            prose: false,
+            auto_capitalize: false,
+            collapse_spaces: false,
+            annotations: true,
+            unicode_check: false,
+            sign_column: true,
+            max_line_length: 0,
+            line_limit: LineLimit::Warn,
+            run_command: String::new(),
+        }
+    }
+}
+
+/// 选项名，按 `:set` 列出时的顺序
+pub const OPTION_NAMES: [&str; 16] = [
+    "tabsize",
+    "tabstospaces",
+    "softwrap",
+    "linenumbers",
+    "readonly",
+    "virtualspace",
+    "autoclose",
+    "prose",
+    "autocapitalize",
+    "collapsespaces",
+    "annotations",
+    "unicodecheck",
+    "signcolumn",
+    "maxlinelength",
+    "linelimit",
+    "runcommand",
+];
+
+/// 把别名换成标准选项名
+fn canonical_name(name: &str) -> Option<&'static str> {
+    let name = match name {
+        "ts" | "tabwidth" => "tabsize",
+        "et" | "expandtab" => "tabstospaces",
+        "wrap" => "softwrap",
+        "nu" | "number" => "linenumbers",
+        "ro" => "readonly",
+        "ve" => "virtualspace",
+        "tw" | "textwidth" => "maxlinelength",
+        "run" => "runcommand",
+        "scl" => "signcolumn",
+        "acap" => "autocapitalize",
+        other => other,
+    };
+    OPTION_NAMES.iter().copied().find(|n| *n == name)
+}
+
+/// 按文件类型覆盖的设置，每项是一条 `:set` 表达式，来自配置中的 `[filetype.<名字>]` 表
+pub type FiletypeOptions = Vec<(FileType, Vec<String>)>;
+
+fn unknown(name: &str) -> EditorError {
+    EditorError::Config(format!("未知选项 {}", name))
+}
+
+impl BufferSettings {
+    /// 全局默认设置应用到某种文件类型的缓冲区：散文模式只对文字类文件生效，并带上软换行，
+    /// 最后叠加该文件类型自己的设置
+    pub fn for_filetype(&self, filetype: FileType, overrides: &FiletypeOptions) -> Self {
+        let mut settings = self.clone();
+        if settings.prose {
+            if filetype.is_prose() {
+                settings.soft_wrap = true;
+            } else {
+                settings.prose = false;
+            }
+        }
+        for (_, exprs) in overrides.iter().filter(|(t, _)| *t == filetype) {
+            for expr in exprs {
+                // 表达式在读配置时已经检查过
+                let _ = settings.apply(expr);
+            }
+        }
+        settings
+    }
+
+    fn flag(&self, name: &str) -> Option<bool> {
+        match name {
+            "tabstospaces" => Some(self.expand_tab),
+            "softwrap" => Some(self.soft_wrap),
+            "linenumbers" => Some(self.line_numbers),
+            "readonly" => Some(self.read_only),
+            "virtualspace" => Some(self.virtual_space),
+            "autoclose" => Some(self.auto_close),
+            "prose" => Some(self.prose),
+            "autocapitalize" => Some(self.auto_capitalize),
+            "collapsespaces" => Some(self.collapse_spaces),
+            "annotations" => Some(self.annotations),
+            "unicodecheck" => Some(self.unicode_check),
+            "signcolumn" => Some(self.sign_column),
+            _ => None,
+        }
+    }
+
+    fn flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "tabstospaces" => Some(&mut self.expand_tab),
+            "softwrap" => Some(&mut self.soft_wrap),
+            "linenumbers" => Some(&mut self.line_numbers),
+            "readonly" => Some(&mut self.read_only),
+            "virtualspace" => Some(&mut self.virtual_space),
+            "autoclose" => Some(&mut self.auto_close),
+            "prose" => Some(&mut self.prose),
+            "autocapitalize" => Some(&mut self.auto_capitalize),
+            "collapsespaces" => Some(&mut self.collapse_spaces),
+            "annotations" => Some(&mut self.annotations),
+            "unicodecheck" => Some(&mut self.unicode_check),
+            "signcolumn" => Some(&mut self.sign_column),
+            _ => None,
+        }
+    }
+
+    /// 选项当前值的文字形式
+    pub fn get(&self, name: &str) -> Option<String> {
+        let name = canonical_name(name)?;
+        match name {
+            "tabsize" => Some(self.tab_width.to_string()),
+            "maxlinelength" => Some(self.max_line_length.to_string()),
+            "linelimit" => Some(self.line_limit.name().to_string()),
+            "runcommand" => Some(self.run_command.clone()),
+            _ => self.flag(name).map(|on| on.to_string()),
+        }
+    }
+
+    /// 执行一条 `:set` 表达式，返回需要显示给用户的说明（查询时）
+    ///
+    /// 支持 `name=value`、`name`（开启）、`noname`（关闭）、`name!`（切换）
+    /// 和 `name?`（查询），例如 `tabsize=4`、`nowrap`、`ro!`。
+    pub fn apply(&mut self, expr: &str) -> Result<Option<String>> {
+        let expr = expr.trim();
+        if let Some(name) = expr.strip_suffix('?') {
+            let value = self.get(name).ok_or_else(|| unknown(name))?;
+            return Ok(Some(format!("{}={}", name, value)));
+        }
+        if let Some((name, value)) = expr.split_once('=') {
+            let canonical = canonical_name(name.trim()).ok_or_else(|| unknown(name))?;
+            let value = value.trim();
+            if canonical == "tabsize" {
+                self.tab_width = match value.parse() {
+                    Ok(width @ 1..=16) => width,
+                    _ => {
+                        return Err(EditorError::Config(format!(
+                            "tabsize 必须是 1 到 16 之间的整数：{}",
+                            value
+                        )))
+                    }
+                };
+            } else if canonical == "maxlinelength" {
+                self.max_line_length = value.parse().map_err(|_| {
+                    EditorError::Config(format!("maxlinelength 必须是非负整数：{}", value))
+                })?;
+            } else if canonical == "linelimit" {
+                self.line_limit = LineLimit::from_name(value).ok_or_else(|| {
+                    EditorError::Config(format!(
+                        "linelimit 的值必须是 warn、wrap 或 block：{}",
+                        value
+                    ))
+                })?;
+            } else if canonical == "runcommand" {
+                self.run_command = value.to_string();
+            } else {
+                let flag = self.flag_mut(canonical).ok_or_else(|| unknown(name))?;
+                *flag = match value {
+                    "true" | "on" | "yes" | "1" => true,
+                    "false" | "off" | "no" | "0" => false,
+                    _ => {
+                        return Err(EditorError::Config(format!(
+                            "{} 的值必须是 on 或 off：{}",
+                            canonical, value
+                        )))
+                    }
+                };
+            }
+            return Ok(None);
+        }
+        let (name, value) = if let Some(name) = expr.strip_suffix('!') {
+            let canonical = canonical_name(name).ok_or_else(|| unknown(name))?;
+            (name, !self.flag(canonical).ok_or_else(|| unknown(name))?)
+        } else if let Some(name) = expr
+            .strip_prefix("no")
+            .filter(|n| canonical_name(n).is_some())
+        {
+            (name, false)
+        } else {
+            (expr, true)
+        };
+        let flag = canonical_name(name)
+            .and_then(|n| self.flag_mut(n))
+            .ok_or_else(|| unknown(name))?;
+        *flag = value;
+        Ok(None)
+    }
+
+    /// 所有选项的当前值，例如 `tabsize=8 notabstospaces nosoftwrap linenumbers …`
+    pub fn describe(&self) -> String {
+        let mut out = format!("tabsize={}", self.tab_width);
+        for name in &OPTION_NAMES[1..] {
+            match self.flag(name) {
+                Some(on) => {
+                    let _ = write!(out, " {}{}", if on { "" } else { "no" }, name);
+                }
+                None => {
+                    let _ = write!(out, " {}={}", name, self.get(name).unwrap_or_default());
+                }
+            }
+        }
+        out
+    }
+}
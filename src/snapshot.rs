@@ -0,0 +1,411 @@
+// 自动快照：编辑器空闲时把修改过的缓冲区存到状态目录，保留有限条历史
+//
+// 每个文件一个子目录，目录名由文件的绝对路径转义而来（见 paths::file_key），里面是以毫秒时间戳命名的快照，
+// 以及记录原文件路径的 origin 文件。未命名的缓冲区按进程号和缓冲区编号区分，正常退出时删除，
+// 进程已不存在却还留着的目录说明上次会话没有正常退出。
+// 打开文件时还会在同一目录留下 lock 文件（内容为进程号），正常关闭时删除；
+// 下次打开时发现持有进程已不存在的锁，说明上次会话没有正常退出。
+
+use crate::buffer::TextBuffer;
+use crate::render::{SpanStyle, StyledLine};
+use crate::{paths, EditorError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 每个文件最多保留的快照数
+pub const MAX_SNAPSHOTS: usize = 20;
+
+/// 一条快照
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// 快照文件
+    pub path: PathBuf,
+    /// 拍摄时间
+    pub time: SystemTime,
+}
+
+impl Snapshot {
+    /// 读取快照内容，按行拆分
+    pub fn read(&self) -> Result<Vec<String>> {
+        let text = fs::read_to_string(&self.path).map_err(|e| EditorError::io(&self.path, e))?;
+        Ok(text.split('\n').map(str::to_string).collect())
+    }
+
+    /// 距今多久，例如 `3 分钟前`
+    pub fn label(&self) -> String {
+        time_ago(self.time)
+    }
+}
+
+/// 某个时刻距今多久，例如 `3 分钟前`
+pub fn time_ago(time: SystemTime) -> String {
+    let ago = SystemTime::now().duration_since(time).unwrap_or_default();
+    format!("{}前", describe_duration(ago))
+}
+
+fn describe_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    match secs {
+        0..=59 => format!("{} 秒", secs),
+        60..=3599 => format!("{} 分钟", secs / 60),
+        3600..=86_399 => format!("{} 小时", secs / 3600),
+        _ => format!("{} 天", secs / 86_400),
+    }
+}
+
+/// 快照属于哪个缓冲区
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Owner<'a> {
+    /// 有文件名的缓冲区，按文件区分
+    File(&'a Path),
+    /// 未命名的缓冲区，按缓冲区编号区分（见 TextBuffer::id）
+    Unnamed(u64),
+}
+
+impl<'a> Owner<'a> {
+    pub fn of(buffer: &'a TextBuffer) -> Self {
+        match &buffer.filename {
+            Some(path) => Owner::File(path),
+            None => Owner::Unnamed(buffer.id),
+        }
+    }
+}
+
+/// 快照目录：状态目录下每个文件一个子目录
+///
+/// 本地历史（保存前的版本）用同样的格式存在另一个目录，只是保留的数量不同。
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    root: PathBuf,
+    /// 每个文件最多保留的条数
+    limit: usize,
+}
+
+impl SnapshotStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            limit: MAX_SNAPSHOTS,
+        }
+    }
+
+    /// 改变每个文件最多保留的条数
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// 缓冲区对应的子目录名，未命名缓冲区按进程号和缓冲区编号区分
+    fn key(owner: Owner) -> String {
+        match owner {
+            Owner::File(file) => paths::file_key(file),
+            Owner::Unnamed(id) => format!("unnamed-{}-{}", std::process::id(), id),
+        }
+    }
+
+    fn dir_for(&self, owner: Owner) -> PathBuf {
+        self.root.join(Self::key(owner))
+    }
+
+    /// 保存一条快照，超过上限时删除最旧的
+    pub fn save(&self, owner: Owner, lines: &[String]) -> Result<PathBuf> {
+        let dir = self.dir_for(owner);
+        fs::create_dir_all(&dir).map_err(|e| EditorError::io(&dir, e))?;
+        if let Owner::File(file) = owner {
+            let origin = dir.join("origin");
+            if !origin.exists() {
+                fs::write(&origin, file.to_string_lossy().as_bytes())
+                    .map_err(|e| EditorError::io(&origin, e))?;
+            }
+        }
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = dir.join(format!("{}.snap", millis));
+        fs::write(&path, lines.join("\n")).map_err(|e| EditorError::io(&path, e))?;
+        for old in self.list(owner)?.iter().skip(self.limit) {
+            fs::remove_file(&old.path).map_err(|e| EditorError::io(&old.path, e))?;
+        }
+        Ok(path)
+    }
+
+    /// 在文件的快照目录中记下本进程正在编辑它
+    pub fn lock(&self, file: &Path) -> Result<()> {
+        let dir = self.dir_for(Owner::File(file));
+        fs::create_dir_all(&dir).map_err(|e| EditorError::io(&dir, e))?;
+        let lock = dir.join("lock");
+        fs::write(&lock, std::process::id().to_string()).map_err(|e| EditorError::io(&lock, e))
+    }
+
+    /// 删除本进程持有的锁，别的进程的锁保持不变
+    pub fn unlock(&self, file: &Path) {
+        let lock = self.dir_for(Owner::File(file)).join("lock");
+        if lock_owner(&lock) == Some(std::process::id()) {
+            let _ = fs::remove_file(&lock);
+        }
+    }
+
+    /// 文件是否留有上次会话没有删除的锁（持有的进程已经不在了）
+    pub fn has_stale_lock(&self, file: &Path) -> bool {
+        let lock = self.dir_for(Owner::File(file)).join("lock");
+        match lock_owner(&lock) {
+            Some(pid) => pid != std::process::id() && !process_alive(pid),
+            // 锁文件存在但内容损坏，也按残留处理
+            None => lock.exists(),
+        }
+    }
+
+    /// 已退出的进程留下的未命名缓冲区快照目录，最新的在前，说明那次会话没有正常退出
+    ///
+    /// 正常退出时会删除本进程的未命名快照目录（见 remove_own_unnamed），所以留下来的都值得恢复；
+    /// 其中没有快照的空目录顺便删除。
+    pub fn stale_unnamed(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return Vec::new();
+        };
+        let mut stale: Vec<(SystemTime, PathBuf)> = entries
+            .filter_map(|entry| {
+                let dir = entry.ok()?.path();
+                let pid = unnamed_pid(dir.file_name()?.to_str()?)?;
+                if pid == std::process::id() || process_alive(pid) {
+                    return None;
+                }
+                let newest = list_dir(&dir).ok()?.first().map(|s| s.time);
+                if newest.is_none() {
+                    let _ = fs::remove_dir_all(&dir);
+                }
+                Some((newest?, dir))
+            })
+            .collect();
+        stale.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+        stale.into_iter().map(|(_, dir)| dir).collect()
+    }
+
+    /// 把一个残留的快照目录交给 owner，之后按 owner 列出、恢复或丢弃其中的快照
+    pub fn adopt(&self, dir: &Path, owner: Owner) -> Result<()> {
+        let target = self.dir_for(owner);
+        fs::rename(dir, &target).map_err(|e| EditorError::io(dir, e))
+    }
+
+    /// 未命名缓冲区关闭后删除它的快照目录
+    pub fn remove_unnamed(&self, id: u64) {
+        let _ = fs::remove_dir_all(self.dir_for(Owner::Unnamed(id)));
+    }
+
+    /// 正常退出时删除本进程所有未命名缓冲区的快照目录，包括已经另存为文件的
+    pub fn remove_own_unnamed(&self) {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            if name.to_str().and_then(unnamed_pid) == Some(std::process::id()) {
+                let _ = fs::remove_dir_all(entry.path());
+            }
+        }
+    }
+
+    /// 删除文件的所有快照，保留锁
+    pub fn discard(&self, owner: Owner) -> Result<usize> {
+        let snapshots = self.list(owner)?;
+        for snapshot in &snapshots {
+            fs::remove_file(&snapshot.path).map_err(|e| EditorError::io(&snapshot.path, e))?;
+        }
+        Ok(snapshots.len())
+    }
+
+    /// 某个文件的所有快照，从新到旧
+    pub fn list(&self, owner: Owner) -> Result<Vec<Snapshot>> {
+        list_dir(&self.dir_for(owner))
+    }
+}
+
+/// 一个快照目录中的所有快照，从新到旧
+fn list_dir(dir: &Path) -> Result<Vec<Snapshot>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(EditorError::io(dir, e)),
+    };
+    let mut snapshots: Vec<Snapshot> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "snap" {
+                return None;
+            }
+            let millis: u64 = path.file_stem()?.to_str()?.parse().ok()?;
+            Some(Snapshot {
+                path,
+                time: UNIX_EPOCH + Duration::from_millis(millis),
+            })
+        })
+        .collect();
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.time));
+    Ok(snapshots)
+}
+
+/// 未命名缓冲区快照目录名（unnamed-进程号-编号）中的进程号
+fn unnamed_pid(name: &str) -> Option<u32> {
+    name.strip_prefix("unnamed-")?.split('-').next()?.parse().ok()
+}
+
+fn lock_owner(lock: &Path) -> Option<u32> {
+    fs::read_to_string(lock).ok()?.trim().parse().ok()
+}
+
+/// 进程是否还在运行：向它发送信号 0，只检查不打扰；没有权限说明进程存在但属于别的用户
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // 0 和负数表示进程组，不是一个进程
+    let Some(pid) = libc::pid_t::try_from(pid).ok().filter(|&pid| pid > 0) else {
+        return false;
+    };
+    // SAFETY: 信号 0 不会真正发送给进程
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// 无法判断进程是否在运行的系统上一律当作已退出
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    false
+}
+
+/// 两个版本去掉相同的开头和结尾后，不同部分的行范围
+fn changed_region(old: &[String], new: &[String]) -> (usize, usize, usize) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (prefix, old.len() - suffix, new.len() - suffix)
+}
+
+/// 差异摘要，例如 `+3 -1 行`
+pub fn diff_summary(old: &[String], new: &[String]) -> String {
+    let (prefix, old_end, new_end) = changed_region(old, new);
+    if old_end == prefix && new_end == prefix {
+        return "与当前内容相同".to_string();
+    }
+    format!("+{} -{} 行", new_end - prefix, old_end - prefix)
+}
+
+/// 差异预览：从 old（当前内容）到 new（快照）变化的部分，带少量上下文
+pub fn diff_preview(old: &[String], new: &[String]) -> Vec<StyledLine> {
+    const CONTEXT: usize = 2;
+    let (prefix, old_end, new_end) = changed_region(old, new);
+    let mut lines = Vec::new();
+    let mut push = |text: String, style: SpanStyle| {
+        let mut line = StyledLine::new(None);
+        line.push(&text, style);
+        lines.push(line);
+    };
+    if old_end == prefix && new_end == prefix {
+        push("（与当前内容相同）".to_string(), SpanStyle::Dim);
+        return lines;
+    }
+    let start = prefix.saturating_sub(CONTEXT);
+    push(format!("@@ 第 {} 行 @@", prefix + 1), SpanStyle::Heading);
+    for line in &old[start..prefix] {
+        push(format!("  {}", line), SpanStyle::Normal);
+    }
+    let removed = &old[prefix..old_end];
+    let added = &new[prefix..new_end];
+    // 删除和新增的行按顺序两两配对，相似的一对只强调其中不同的字符
+    let changes: Vec<_> = removed
+        .iter()
+        .zip(added)
+        .map(|(a, b)| char_changes(a, b))
+        .collect();
+    for (i, line) in removed.iter().enumerate() {
+        let changed = changes.get(i).and_then(|c| c.as_ref()).map(|(c, _)| &c[..]);
+        lines.push(diff_line('-', line, changed, SpanStyle::Removed));
+    }
+    for (i, line) in added.iter().enumerate() {
+        let changed = changes.get(i).and_then(|c| c.as_ref()).map(|(_, c)| &c[..]);
+        lines.push(diff_line('+', line, changed, SpanStyle::Added));
+    }
+    for line in old[old_end..].iter().take(CONTEXT) {
+        let mut styled = StyledLine::new(None);
+        styled.push(&format!("  {}", line), SpanStyle::Normal);
+        lines.push(styled);
+    }
+    lines
+}
+
+/// 行内比较时两行字符数乘积的上限，超过时只去掉相同的开头和结尾
+const CHAR_DIFF_LIMIT: usize = 250_000;
+
+/// 两行之间逐字符的差异：每个字符是否属于变化的部分；两行差别太大时返回 None，整行按普通差异显示
+fn char_changes(old: &str, new: &str) -> Option<(Vec<bool>, Vec<bool>)> {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+    let mut old_changed = vec![false; old.len()];
+    let mut new_changed = vec![false; new.len()];
+    let mut common = prefix + suffix;
+    if a.len() * b.len() > CHAR_DIFF_LIMIT {
+        old_changed[prefix..old.len() - suffix].fill(true);
+        new_changed[prefix..new.len() - suffix].fill(true);
+    } else {
+        // 最长公共子序列，lcs[i][j] 为 a[i..] 与 b[j..] 的结果
+        let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i][j] = if a[i] == b[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        common += lcs[0][0];
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                i += 1;
+                j += 1;
+            } else if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                old_changed[prefix + i] = true;
+                i += 1;
+            } else {
+                new_changed[prefix + j] = true;
+                j += 1;
+            }
+        }
+    }
+    // 相同的字符不到较长一行的一半时，看作整行改写
+    (common * 2 >= old.len().max(new.len()) && common > 0).then_some((old_changed, new_changed))
+}
+
+/// 差异中的一行：sign 加上行内容，changed 标出的字符用强调样式
+fn diff_line(sign: char, line: &str, changed: Option<&[bool]>, style: SpanStyle) -> StyledLine {
+    let mut styled = StyledLine::new(None);
+    styled.push(&format!("{} ", sign), style);
+    let Some(changed) = changed else {
+        styled.push(line, style);
+        return styled;
+    };
+    let emphasis = match style {
+        SpanStyle::Added => SpanStyle::AddedChange,
+        _ => SpanStyle::RemovedChange,
+    };
+    let mut buf = [0; 4];
+    for (ch, &changed) in line.chars().zip(changed) {
+        let style = if changed { emphasis } else { style };
+        styled.push(ch.encode_utf8(&mut buf), style);
+    }
+    styled
+}
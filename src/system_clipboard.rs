@@ -0,0 +1,192 @@
+// 系统剪贴板
+//
+// 复制时依次尝试系统自带的命令（pbcopy、wl-copy、xclip、xsel、clip.exe），
+// 都不可用或在 SSH 会话中时改用 OSC 52 转义序列，由终端设置本机的剪贴板。
+// 读取只能通过外部命令：多数终端出于安全考虑不响应 OSC 52 的读取请求，
+// 这时用终端自己的粘贴键，内容会作为括号粘贴送进编辑器。
+
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// 剪切和复制如何同步到系统剪贴板
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardMode {
+    /// 优先用系统命令，不可用或在 SSH 会话中时用 OSC 52
+    Auto,
+    /// 只用 OSC 52，由终端设置剪贴板
+    Osc52,
+    /// 不同步，只用编辑器内部的剪切缓冲区
+    None,
+}
+
+impl ClipboardMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            ClipboardMode::Auto => "auto",
+            ClipboardMode::Osc52 => "osc52",
+            ClipboardMode::None => "none",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            ClipboardMode::Auto,
+            ClipboardMode::Osc52,
+            ClipboardMode::None,
+        ]
+        .into_iter()
+        .find(|mode| mode.name() == name)
+    }
+}
+
+/// 一种剪贴板命令：写入时从标准输入读内容，读取时把内容写到标准输出
+struct Tool {
+    copy: &'static [&'static str],
+    paste: &'static [&'static str],
+}
+
+fn env_set(name: &str) -> bool {
+    env::var_os(name).is_some_and(|value| !value.is_empty())
+}
+
+fn in_ssh() -> bool {
+    env_set("SSH_TTY") || env_set("SSH_CONNECTION")
+}
+
+/// 当前环境下可能可用的命令，按优先顺序
+fn tools() -> Vec<Tool> {
+    let mut tools = Vec::new();
+    if cfg!(target_os = "macos") {
+        tools.push(Tool {
+            copy: &["pbcopy"],
+            paste: &["pbpaste"],
+        });
+    }
+    if cfg!(windows) {
+        tools.push(Tool {
+            copy: &["clip.exe"],
+            paste: &["powershell", "-NoProfile", "-Command", "Get-Clipboard"],
+        });
+    }
+    if env_set("WAYLAND_DISPLAY") {
+        tools.push(Tool {
+            copy: &["wl-copy"],
+            paste: &["wl-paste", "--no-newline"],
+        });
+    }
+    if env_set("DISPLAY") {
+        tools.push(Tool {
+            copy: &["xclip", "-selection", "clipboard"],
+            paste: &["xclip", "-selection", "clipboard", "-o"],
+        });
+        tools.push(Tool {
+            copy: &["xsel", "--clipboard", "--input"],
+            paste: &["xsel", "--clipboard", "--output"],
+        });
+    }
+    tools
+}
+
+/// 把文本放进系统剪贴板，返回所用的方式
+pub fn copy(mode: ClipboardMode, text: &str) -> io::Result<&'static str> {
+    match mode {
+        ClipboardMode::None => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "系统剪贴板已关闭",
+        )),
+        ClipboardMode::Osc52 => osc52(text).map(|()| "OSC 52"),
+        ClipboardMode::Auto => {
+            // SSH 会话中要设置的是本机的剪贴板，远端的 X 或 Wayland 没有用
+            if !in_ssh() {
+                for tool in tools() {
+                    if run_copy(tool.copy, text).is_ok() {
+                        return Ok(tool.copy[0]);
+                    }
+                }
+            }
+            osc52(text).map(|()| "OSC 52")
+        }
+    }
+}
+
+/// 读取系统剪贴板的内容，换行统一为 \n
+pub fn paste(mode: ClipboardMode) -> io::Result<String> {
+    if mode == ClipboardMode::None {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "系统剪贴板已关闭",
+        ));
+    }
+    for tool in tools() {
+        let output = Command::new(tool.paste[0])
+            .args(&tool.paste[1..])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                let text = String::from_utf8_lossy(&output.stdout);
+                return Ok(text.replace("\r\n", "\n"));
+            }
+            _ => continue,
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "没有可用的剪贴板命令（pbpaste、wl-paste、xclip 或 xsel）",
+    ))
+}
+
+fn run_copy(command: &[&str], text: &str) -> io::Result<()> {
+    let mut child = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{} 失败：{}", command[0], status)))
+    }
+}
+
+/// 用 OSC 52 让终端设置剪贴板；在 tmux 中包一层直通序列
+fn osc52(text: &str) -> io::Result<()> {
+    let sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+    let sequence = if env_set("TMUX") {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    };
+    let mut out = io::stdout();
+    out.write_all(sequence.as_bytes())?;
+    out.flush()
+}
+
+/// 标准 Base64 编码，带 = 补齐
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,285 @@
+// 界面配色
+
+use crate::toml::{self, Value};
+use crate::{EditorError, Result};
+use crossterm::style::Color;
+use std::fs;
+use std::path::Path;
+
+/// 一个界面元素的样式，颜色为 None 时使用终端默认颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    /// 前景与背景互换（反色）
+    pub reverse: bool,
+}
+
+impl Style {
+    pub const fn new(fg: Option<Color>, bg: Option<Color>) -> Self {
+        Self {
+            fg,
+            bg,
+            bold: false,
+            reverse: false,
+        }
+    }
+}
+
+/// 各界面元素的配色
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    /// 顶部标题栏
+    pub title_bar: Style,
+    /// 状态栏
+    pub status_bar: Style,
+    /// 底部帮助栏
+    pub help_bar: Style,
+    /// 行号
+    pub line_number: Style,
+    /// 光标
+    pub cursor: Style,
+    /// 选区
+    pub selection: Style,
+    /// 搜索高亮
+    pub search_match: Style,
+    /// 超出行长上限的部分
+    pub overflow: Style,
+    /// 可疑的 Unicode 字符：双向控制符、零宽字符和形似 ASCII 的字符
+    pub suspicious: Style,
+}
+
+/// 内置主题的名字和主题文件内容，default 之外的内置主题与用户主题文件格式相同
+const BUILTIN_THEMES: [(&str, &str); 5] = [
+    ("dark", include_str!("themes/dark.toml")),
+    ("light", include_str!("themes/light.toml")),
+    ("high-contrast", include_str!("themes/high-contrast.toml")),
+    ("deuteranopia", include_str!("themes/deuteranopia.toml")),
+    ("mono", include_str!("themes/mono.toml")),
+];
+
+/// 主题文件中可以配置的元素名，每个元素是一个表，例如 `[status_bar]`
+pub const ELEMENT_NAMES: [&str; 9] = [
+    "title_bar",
+    "status_bar",
+    "help_bar",
+    "line_number",
+    "cursor",
+    "selection",
+    "search_match",
+    "overflow",
+    "suspicious",
+];
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            title_bar: Style::new(Some(Color::White), Some(Color::Blue)),
+            status_bar: Style::new(Some(Color::Black), Some(Color::White)),
+            help_bar: Style::new(Some(Color::Black), Some(Color::White)),
+            line_number: Style::new(Some(Color::Yellow), None),
+            cursor: Style::new(Some(Color::Black), Some(Color::Yellow)),
+            selection: Style {
+                reverse: true,
+                ..Style::default()
+            },
+            search_match: Style::new(Some(Color::Black), Some(Color::DarkYellow)),
+            overflow: Style::new(Some(Color::White), Some(Color::DarkRed)),
+            suspicious: Style::new(Some(Color::White), Some(Color::DarkMagenta)),
+        }
+    }
+}
+
+impl Theme {
+    pub fn element(&self, name: &str) -> Option<&Style> {
+        match name {
+            "title_bar" => Some(&self.title_bar),
+            "status_bar" => Some(&self.status_bar),
+            "help_bar" => Some(&self.help_bar),
+            "line_number" => Some(&self.line_number),
+            "cursor" => Some(&self.cursor),
+            "selection" => Some(&self.selection),
+            "search_match" => Some(&self.search_match),
+            "overflow" => Some(&self.overflow),
+            "suspicious" => Some(&self.suspicious),
+            _ => None,
+        }
+    }
+
+    fn element_mut(&mut self, name: &str) -> Option<&mut Style> {
+        match name {
+            "title_bar" => Some(&mut self.title_bar),
+            "status_bar" => Some(&mut self.status_bar),
+            "help_bar" => Some(&mut self.help_bar),
+            "line_number" => Some(&mut self.line_number),
+            "cursor" => Some(&mut self.cursor),
+            "selection" => Some(&mut self.selection),
+            "search_match" => Some(&mut self.search_match),
+            "overflow" => Some(&mut self.overflow),
+            "suspicious" => Some(&mut self.suspicious),
+            _ => None,
+        }
+    }
+
+    /// 解析主题文件，没有写到的元素沿用默认配色
+    ///
+    /// ```toml
+    /// [status_bar]
+    /// fg = "black"
+    /// bg = "#d0d0d0"
+    /// bold = true
+    /// ```
+    pub fn parse(name: &str, text: &str) -> Result<Self> {
+        let document = toml::parse(text)?;
+        let mut theme = Theme {
+            name: name.to_string(),
+            ..Theme::default()
+        };
+        if let Some((key, _, line)) = document.root().entries.first() {
+            return Err(EditorError::Config(format!(
+                "第 {} 行: 主题的颜色要写在元素表中，例如 [status_bar]，而不是 {}",
+                line, key
+            )));
+        }
+        for table in &document.tables[1..] {
+            let style = theme
+                .element_mut(&table.name)
+                .ok_or_else(|| EditorError::Config(format!("未知的界面元素 [{}]", table.name)))?;
+            for (key, value, line) in &table.entries {
+                let bad =
+                    |what: &str| EditorError::Config(format!("第 {} 行: {} {}", line, key, what));
+                match key.as_str() {
+                    "fg" | "bg" => {
+                        let color = match value {
+                            Value::String(s) if s == "default" => None,
+                            Value::String(s) => {
+                                Some(parse_color(s).ok_or_else(|| bad("不是有效的颜色"))?)
+                            }
+                            Value::Integer(n @ 0..=255) => Some(Color::AnsiValue(*n as u8)),
+                            _ => return Err(bad("不是有效的颜色")),
+                        };
+                        if key == "fg" {
+                            style.fg = color;
+                        } else {
+                            style.bg = color;
+                        }
+                    }
+                    "bold" => style.bold = value.as_bool().ok_or_else(|| bad("必须是布尔值"))?,
+                    "reverse" => {
+                        style.reverse = value.as_bool().ok_or_else(|| bad("必须是布尔值"))?
+                    }
+                    _ => return Err(bad("不是可配置的样式属性")),
+                }
+            }
+        }
+        Ok(theme)
+    }
+
+    /// 按名字取内置主题
+    pub fn builtin(name: &str) -> Option<Self> {
+        if name == "default" {
+            return Some(Theme::default());
+        }
+        let (name, text) = BUILTIN_THEMES.iter().find(|(n, _)| *n == name)?;
+        Some(Self::parse(name, text).expect("内置主题可以解析"))
+    }
+
+    /// 可选的主题名：内置主题在前，之后是主题目录中的 `<名字>.toml`，按名字排序
+    pub fn available(themes_dir: Option<&Path>) -> Vec<String> {
+        let mut names: Vec<String> = themes_dir
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "toml" {
+                    return None;
+                }
+                Some(path.file_stem()?.to_str()?.to_string())
+            })
+            .filter(|name| Self::builtin(name).is_none())
+            .collect();
+        names.sort();
+        let builtin = std::iter::once("default").chain(BUILTIN_THEMES.iter().map(|(n, _)| *n));
+        builtin.map(str::to_string).chain(names).collect()
+    }
+
+    /// 按名字加载主题：主题目录中的 `<名字>.toml` 优先，其次是同名的内置主题
+    pub fn load(name: &str, themes_dir: Option<&Path>) -> Result<Self> {
+        let path = themes_dir
+            .map(|dir| dir.join(format!("{}.toml", name)))
+            .filter(|path| path.exists());
+        let Some(path) = path else {
+            return Self::builtin(name)
+                .ok_or_else(|| EditorError::Config(format!("找不到主题 {}", name)));
+        };
+        let text = fs::read_to_string(&path).map_err(|e| EditorError::io(&path, e))?;
+        Self::parse(name, &text).map_err(|e| match e {
+            EditorError::Config(message) => {
+                EditorError::Config(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })
+    }
+}
+
+/// 颜色名称，与 `parse_color` 互逆
+const COLOR_NAMES: [(&str, Color); 17] = [
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("white", Color::White),
+    ("grey", Color::Grey),
+    ("gray", Color::Grey),
+    ("dark_grey", Color::DarkGrey),
+    ("dark_red", Color::DarkRed),
+    ("dark_green", Color::DarkGreen),
+    ("dark_yellow", Color::DarkYellow),
+    ("dark_blue", Color::DarkBlue),
+    ("dark_magenta", Color::DarkMagenta),
+    ("dark_cyan", Color::DarkCyan),
+];
+
+/// 解析颜色：颜色名（如 `blue`、`dark_grey`）或 `#rrggbb`
+pub fn parse_color(s: &str) -> Option<Color> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+    let lower = s.to_ascii_lowercase().replace('-', "_");
+    COLOR_NAMES
+        .iter()
+        .find(|(name, _)| *name == lower)
+        .map(|(_, color)| *color)
+}
+
+/// 颜色在配置文件中的写法
+pub fn color_to_value(color: Option<Color>) -> Value {
+    match color {
+        None => Value::String("default".to_string()),
+        Some(Color::Rgb { r, g, b }) => Value::String(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        Some(Color::AnsiValue(n)) => Value::Integer(n as i64),
+        Some(color) => Value::String(
+            COLOR_NAMES
+                .iter()
+                .find(|(_, c)| *c == color)
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_else(|| "default".to_string()),
+        ),
+    }
+}
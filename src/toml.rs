@@ -0,0 +1,236 @@
+// 配置文件用到的 TOML 子集解析器
+//
+// 支持 `[表]`、`键 = 值`、`#` 注释，值可以是字符串、整数、布尔值以及由它们组成的数组。
+// 键可以是裸键或带引号的键（快捷键常写成 "^K"）。不支持内联表、多行字符串和日期。
+
+use crate::{EditorError, Result};
+use std::fmt;
+
+/// 配置值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(s) => write_quoted(f, s),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+/// 输出带引号并转义的字符串
+pub fn write_quoted(f: &mut impl fmt::Write, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for ch in s.chars() {
+        match ch {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\t' => f.write_str("\\t")?,
+            _ => f.write_char(ch)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// 一个表：按文件中的顺序保存键值对
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    pub name: String,
+    pub entries: Vec<(String, Value, usize)>,
+}
+
+impl Table {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _, _)| k == key)
+            .map(|(_, v, _)| v)
+    }
+}
+
+/// 解析后的文档：第一个表是没有名字的顶层表
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    pub tables: Vec<Table>,
+}
+
+impl Document {
+    /// 按名字查找表，顶层表的名字为空
+    pub fn table(&self, name: &str) -> Option<&Table> {
+        self.tables.iter().find(|t| t.name == name)
+    }
+
+    pub fn root(&self) -> &Table {
+        &self.tables[0]
+    }
+}
+
+/// 解析错误，带行号
+fn error(line: usize, message: &str) -> EditorError {
+    EditorError::Config(format!("第 {} 行: {}", line, message))
+}
+
+/// 解析 TOML 文本
+pub fn parse(text: &str) -> Result<Document> {
+    let mut tables = vec![Table::default()];
+    for (i, raw) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let mut rest = raw.trim();
+        if rest.is_empty() || rest.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = rest.strip_prefix('[') {
+            let (name, after) = header
+                .split_once(']')
+                .ok_or_else(|| error(line_no, "表名缺少 ]"))?;
+            if !is_comment_or_empty(after) {
+                return Err(error(line_no, "表名之后有多余内容"));
+            }
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(error(line_no, "表名为空"));
+            }
+            if tables.iter().any(|t| t.name == name) {
+                return Err(error(line_no, &format!("重复的表 [{}]", name)));
+            }
+            tables.push(Table {
+                name: name.to_string(),
+                entries: Vec::new(),
+            });
+            continue;
+        }
+        let key = parse_key(&mut rest).map_err(|m| error(line_no, m))?;
+        rest = rest
+            .trim_start()
+            .strip_prefix('=')
+            .ok_or_else(|| error(line_no, "键之后缺少 ="))?;
+        let value = parse_value(&mut rest).map_err(|m| error(line_no, m))?;
+        if !is_comment_or_empty(rest) {
+            return Err(error(line_no, "值之后有多余内容"));
+        }
+        let table = tables.last_mut().expect("至少有顶层表");
+        table.entries.push((key, value, line_no));
+    }
+    Ok(Document { tables })
+}
+
+fn is_comment_or_empty(s: &str) -> bool {
+    let s = s.trim();
+    s.is_empty() || s.starts_with('#')
+}
+
+fn parse_key(rest: &mut &str) -> std::result::Result<String, &'static str> {
+    *rest = rest.trim_start();
+    if rest.starts_with('"') {
+        return parse_string(rest);
+    }
+    let end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(rest.len());
+    if end == 0 {
+        return Err("缺少键名");
+    }
+    let key = rest[..end].to_string();
+    *rest = &rest[end..];
+    Ok(key)
+}
+
+fn parse_value(rest: &mut &str) -> std::result::Result<Value, &'static str> {
+    *rest = rest.trim_start();
+    if rest.starts_with('"') {
+        return parse_string(rest).map(Value::String);
+    }
+    if let Some(inner) = rest.strip_prefix('[') {
+        *rest = inner;
+        let mut items = Vec::new();
+        loop {
+            *rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                *rest = after;
+                return Ok(Value::Array(items));
+            }
+            items.push(parse_value(rest)?);
+            *rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                *rest = after;
+            } else if !rest.starts_with(']') {
+                return Err("数组元素之间缺少逗号");
+            }
+        }
+    }
+    let end = rest
+        .find(|c: char| c.is_whitespace() || c == ',' || c == ']' || c == '#')
+        .unwrap_or(rest.len());
+    let word = &rest[..end];
+    let value = match word {
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        _ => Value::Integer(word.replace('_', "").parse().map_err(|_| "无法识别的值")?),
+    };
+    *rest = &rest[end..];
+    Ok(value)
+}
+
+fn parse_string(rest: &mut &str) -> std::result::Result<String, &'static str> {
+    let mut out = String::new();
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '"' => {
+                *rest = &rest[i + 1..];
+                return Ok(out);
+            }
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('"') => out.push('"'),
+                Some('\\') => out.push('\\'),
+                _ => return Err("无法识别的转义字符"),
+            },
+            _ => out.push(ch),
+        }
+    }
+    Err("字符串缺少结尾的引号")
+}
@@ -0,0 +1,144 @@
+// 回收站：覆盖用户的文件之前先把原来的文件移进系统回收站，误操作后还能找回
+//
+// Linux 等系统按 freedesktop.org 回收站规范，在 files/ 中放文件、在 info/ 中写原路径和
+// 删除时间，文件管理器的回收站可以直接还原；macOS 只移到 ~/.Trash。不在同一个文件系统时
+// 复制后再删除原文件。Windows 等其他系统上不支持，默认关闭，开启时照常覆盖。
+
+use crate::paths;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 把 path 移进回收站，返回它在回收站中的位置
+pub fn move_to_trash(path: &Path) -> io::Result<PathBuf> {
+    if !cfg!(unix) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "此系统不支持回收站",
+        ));
+    }
+    let trash = paths::trash_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "找不到回收站目录"))?;
+    let original = fs::canonicalize(path)?;
+    let name = original
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "不是文件"))?
+        .to_string_lossy()
+        .into_owned();
+    if cfg!(target_os = "macos") {
+        fs::create_dir_all(&trash)?;
+        let target = unique_name(&name, |candidate| !trash.join(candidate).exists());
+        let target = trash.join(target);
+        move_file(&original, &target)?;
+        return Ok(target);
+    }
+
+    let files = trash.join("files");
+    let info = trash.join("info");
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+    // 先独占创建 .trashinfo，同名的文件已在回收站中时换一个名字
+    let mut n = 1;
+    let (candidate, info_path, mut file) = loop {
+        let candidate = numbered(&name, n);
+        let info_path = info.join(format!("{}.trashinfo", candidate));
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(file) if !files.join(&candidate).exists() => break (candidate, info_path, file),
+            Ok(_) => {
+                let _ = fs::remove_file(&info_path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+        n += 1;
+    };
+    let contents = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(&original),
+        deletion_date()
+    );
+    let target = files.join(candidate);
+    let result = file
+        .write_all(contents.as_bytes())
+        .and_then(|()| move_file(&original, &target));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&info_path);
+        return Err(e);
+    }
+    Ok(target)
+}
+
+/// 第 n 个候选名字：第一个是原名，之后是 `名字.2`、`名字.3`……
+fn numbered(name: &str, n: usize) -> String {
+    if n == 1 {
+        name.to_string()
+    } else {
+        format!("{}.{}", name, n)
+    }
+}
+
+/// 第一个满足 free 的候选名字
+fn unique_name(name: &str, free: impl Fn(&str) -> bool) -> String {
+    (1..)
+        .map(|n| numbered(name, n))
+        .find(|candidate| free(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// 移动文件，跨文件系统时复制后删除原文件
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from).inspect_err(|_| {
+        let _ = fs::remove_file(to);
+    })
+}
+
+/// .trashinfo 中的路径：除字母、数字、`/` 和 `-_.~` 外按字节做百分号编码
+fn encode_path(path: &Path) -> String {
+    let mut out = String::new();
+    for &byte in path.to_string_lossy().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// 当地时间的删除时间，格式为 `2024-05-01T12:30:00`
+#[cfg(unix)]
+fn deletion_date() -> String {
+    let mut tm = std::mem::MaybeUninit::<libc::tm>::zeroed();
+    // SAFETY: time 允许传空指针；tm 是可写的有效结构，localtime_r 只写它
+    let ok = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        !libc::localtime_r(&now, tm.as_mut_ptr()).is_null()
+    };
+    if !ok {
+        return String::new();
+    }
+    // SAFETY: tm 已清零，全零对 libc::tm 是合法的值
+    let tm = unsafe { tm.assume_init() };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+#[cfg(not(unix))]
+fn deletion_date() -> String {
+    String::new()
+}
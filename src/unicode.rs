@@ -0,0 +1,160 @@
+// Unicode 显示宽度辅助函数
+
+/// 返回字符在终端中占用的列数（控制字符与组合字符为 0，中日韩全角字符为 2，
+/// 空字符显示为 ␀、ESC 显示为 ␛，各占 1 列）
+pub fn char_width(ch: char) -> usize {
+    let c = ch as u32;
+    // ASCII 可打印字符是最常见的情况，直接返回
+    if (0x20..0x7f).contains(&c) || c == 0 || c == 0x1b {
+        return 1;
+    }
+    if c < 0x20 || (0x7f..0xa0).contains(&c) {
+        return 0;
+    }
+    if is_zero_width(c) {
+        return 0;
+    }
+    if is_wide(c) {
+        return 2;
+    }
+    1
+}
+
+/// 字符从第 col 列开始显示时占的列数：制表符补齐到下一个 tab_width 的倍数，其余同 char_width
+pub fn cell_width(ch: char, col: usize, tab_width: usize) -> usize {
+    if ch == '\t' {
+        let tab_width = tab_width.max(1);
+        tab_width - col % tab_width
+    } else {
+        char_width(ch)
+    }
+}
+
+/// 行中前 x 个字符占的列数，制表符按 tab_width 对齐；x 超出行尾的部分按每个字符一列计算
+pub fn display_column(line: &str, x: usize, tab_width: usize) -> usize {
+    let mut col = 0;
+    let mut count = 0;
+    for ch in line.chars().take(x) {
+        col += cell_width(ch, col, tab_width);
+        count += 1;
+    }
+    col + (x - count)
+}
+
+/// 返回字符串在终端中占用的总列数
+pub fn str_width(s: &str) -> usize {
+    if s.is_ascii() {
+        return s
+            .bytes()
+            .filter(|&b| (0x20..0x7f).contains(&b) || b == 0)
+            .count();
+    }
+    s.chars().map(char_width).sum()
+}
+
+/// 单词由字母、数字（包括中文等文字）和下划线组成
+pub fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// 按单词移动时字符的类别，类别变化的地方是单词边界
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    /// 空白
+    Space,
+    /// 字母、数字和下划线
+    Word,
+    /// 中日韩文字，与相邻的西文单词分开
+    Cjk,
+    /// 标点和其他符号
+    Punct,
+}
+
+pub fn char_class(ch: char) -> CharClass {
+    if ch.is_whitespace() {
+        CharClass::Space
+    } else if is_word_char(ch) {
+        if is_wide(ch as u32) {
+            CharClass::Cjk
+        } else {
+            CharClass::Word
+        }
+    } else {
+        CharClass::Punct
+    }
+}
+
+/// 把字符序列拆成字素簇（用户眼中的一个字符），返回各簇开头的字符索引，
+/// 最后再附加一个字符总数，相邻两项就是一个簇的范围
+///
+/// 组合符号、变体选择符和肤色修饰附着在前一个字符上，零宽连接符（ZWJ）
+/// 把前后两个字符连成一个簇，足以覆盖带声调的字母和组合 emoji。
+pub fn grapheme_bounds(chars: &[char]) -> Vec<usize> {
+    let mut bounds = Vec::with_capacity(chars.len() + 1);
+    let mut joined = false;
+    for (i, &ch) in chars.iter().enumerate() {
+        if i == 0 || !(joined || is_grapheme_extend(ch)) {
+            bounds.push(i);
+        }
+        joined = ch == '\u{200D}';
+    }
+    bounds.push(chars.len());
+    bounds
+}
+
+fn is_grapheme_extend(ch: char) -> bool {
+    let c = ch as u32;
+    matches!(
+        c,
+        0x0300..=0x036F
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x200C..=0x200D
+            | 0x20D0..=0x20FF
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF
+            | 0xE0100..=0xE01EF
+    )
+}
+
+fn is_zero_width(c: u32) -> bool {
+    matches!(
+        c,
+        0x0300..=0x036F
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x200B..=0x200F
+            | 0x202A..=0x202E
+            | 0x2060..=0x2064
+            | 0x20D0..=0x20FF
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0xFEFF
+            | 0xE0100..=0xE01EF
+    )
+}
+
+fn is_wide(c: u32) -> bool {
+    matches!(
+        c,
+        0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F300..=0x1F64F
+            | 0x1F900..=0x1F9FF
+            | 0x20000..=0x3FFFD
+    )
+}
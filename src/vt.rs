@@ -0,0 +1,335 @@
+// 内置终端的屏幕模拟：把 shell 的输出画到字符网格上
+//
+// 只处理文字、换行滚动、光标移动和擦除等让 shell 和常见命令正常显示的 VT100 序列，
+// 颜色等显示属性和终端模式切换直接忽略。
+
+use crate::unicode::char_width;
+
+/// 全角字符占两格，第二格放这个占位符，取出文字时跳过
+const WIDE_TAIL: char = '\0';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    /// `ESC [` 之后，收集参数直到结束字符
+    Csi,
+    /// `ESC ]` 之后的操作系统命令（如设置窗口标题），到 BEL 或 `ESC \` 结束
+    Osc,
+    OscEscape,
+    /// `ESC (` 等选择字符集的序列，再跳过一个字符
+    Charset,
+}
+
+/// 终端屏幕：固定大小的字符网格和光标
+#[derive(Debug, Clone)]
+pub struct Screen {
+    width: usize,
+    height: usize,
+    cells: Vec<Vec<char>>,
+    /// 光标列，可以等于 width，表示下一个字符写入前先换行
+    cursor_x: usize,
+    cursor_y: usize,
+    saved_cursor: (usize, usize),
+    state: State,
+    params: String,
+    /// 上次输入末尾不完整的 UTF-8 字节
+    pending: Vec<u8>,
+}
+
+impl Screen {
+    pub fn new(width: usize, height: usize) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+        Self {
+            width,
+            height,
+            cells: vec![vec![' '; width]; height],
+            cursor_x: 0,
+            cursor_y: 0,
+            saved_cursor: (0, 0),
+            state: State::Ground,
+            params: String::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// 光标所在的格（列, 行）
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.cursor_x.min(self.width - 1), self.cursor_y)
+    }
+
+    /// 改变屏幕大小；变矮时去掉顶部的行，保持光标所在行可见
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let width = width.max(1);
+        let height = height.max(1);
+        for row in &mut self.cells {
+            row.resize(width, ' ');
+            if row[width - 1] != WIDE_TAIL && char_width(row[width - 1]) == 2 {
+                row[width - 1] = ' ';
+            }
+        }
+        if height < self.height {
+            let drop = (self.cursor_y + 1).saturating_sub(height);
+            self.cells.drain(..drop);
+            self.cursor_y -= drop;
+            self.saved_cursor.1 = self.saved_cursor.1.saturating_sub(drop);
+        }
+        self.cells.resize(height, vec![' '; width]);
+        self.width = width;
+        self.height = height;
+        self.cursor_x = self.cursor_x.min(width);
+        self.cursor_y = self.cursor_y.min(height - 1);
+        self.saved_cursor = (
+            self.saved_cursor.0.min(width),
+            self.saved_cursor.1.min(height - 1),
+        );
+    }
+
+    /// 回到保存的光标位置（ESC 8、CSI u），限制在屏幕内
+    fn restore_cursor(&mut self) {
+        let (x, y) = self.saved_cursor;
+        self.cursor_x = x.min(self.width);
+        self.cursor_y = y.min(self.height - 1);
+    }
+
+    /// 第 y 行的文字，去掉行尾空白
+    pub fn row_text(&self, y: usize, out: &mut String) {
+        out.clear();
+        out.extend(self.cells[y].iter().filter(|&&c| c != WIDE_TAIL));
+        out.truncate(out.trim_end().len());
+    }
+
+    /// 第 y 行从第 start 格开始、到第 end 格之前的文字
+    pub fn row_slice(&self, y: usize, start: usize, end: usize, out: &mut String) {
+        out.clear();
+        let end = end.min(self.width);
+        if start < end {
+            out.extend(
+                self.cells[y][start..end]
+                    .iter()
+                    .filter(|&&c| c != WIDE_TAIL),
+            );
+        }
+    }
+
+    /// 写入 shell 输出的一段字节
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        let pending = std::mem::take(&mut self.pending);
+        let mut rest = &pending[..];
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(text) => {
+                    text.chars().for_each(|ch| self.process(ch));
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    // 前面 valid_up_to 个字节已确认是合法的 UTF-8
+                    if let Ok(text) = std::str::from_utf8(valid) {
+                        text.chars().for_each(|ch| self.process(ch));
+                    }
+                    match e.error_len() {
+                        Some(len) => {
+                            self.process(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        None => {
+                            // 字符被截断在两次输入之间，留到下次
+                            self.pending = after.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn process(&mut self, ch: char) {
+        match self.state {
+            State::Ground => match ch {
+                '\x1b' => self.state = State::Escape,
+                '\r' => self.cursor_x = 0,
+                '\n' | '\x0b' | '\x0c' => self.line_feed(),
+                '\x08' => self.cursor_x = self.cursor_x.min(self.width - 1).saturating_sub(1),
+                '\t' => self.cursor_x = ((self.cursor_x / 8 + 1) * 8).min(self.width - 1),
+                ch if ch.is_control() => {}
+                ch => self.put(ch),
+            },
+            State::Escape => {
+                self.state = State::Ground;
+                match ch {
+                    '[' => {
+                        self.params.clear();
+                        self.state = State::Csi;
+                    }
+                    ']' => self.state = State::Osc,
+                    '(' | ')' | '*' | '+' => self.state = State::Charset,
+                    '7' => self.saved_cursor = (self.cursor_x, self.cursor_y),
+                    '8' => self.restore_cursor(),
+                    'D' => self.line_feed(),
+                    'E' => {
+                        self.cursor_x = 0;
+                        self.line_feed();
+                    }
+                    'M' => self.reverse_line_feed(),
+                    'c' => *self = Self::new(self.width, self.height),
+                    _ => {}
+                }
+            }
+            State::Csi => match ch {
+                '0'..='9' | ';' | '?' | '>' | '=' | '!' | ' ' | '"' | '$' => self.params.push(ch),
+                '\x40'..='\x7e' => {
+                    self.state = State::Ground;
+                    self.csi(ch);
+                }
+                _ => self.state = State::Ground,
+            },
+            State::Osc => match ch {
+                '\x07' => self.state = State::Ground,
+                '\x1b' => self.state = State::OscEscape,
+                _ => {}
+            },
+            State::OscEscape | State::Charset => self.state = State::Ground,
+        }
+    }
+
+    fn put(&mut self, ch: char) {
+        let width = char_width(ch);
+        if width == 0 {
+            return;
+        }
+        if self.cursor_x + width > self.width {
+            self.cursor_x = 0;
+            self.line_feed();
+        }
+        let row = &mut self.cells[self.cursor_y];
+        row[self.cursor_x] = ch;
+        if width == 2 && self.cursor_x + 1 < self.width {
+            row[self.cursor_x + 1] = WIDE_TAIL;
+        }
+        self.cursor_x += width;
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor_y + 1 < self.height {
+            self.cursor_y += 1;
+        } else {
+            self.cells.remove(0);
+            self.cells.push(vec![' '; self.width]);
+        }
+    }
+
+    fn reverse_line_feed(&mut self) {
+        if self.cursor_y > 0 {
+            self.cursor_y -= 1;
+        } else {
+            self.cells.pop();
+            self.cells.insert(0, vec![' '; self.width]);
+        }
+    }
+
+    fn clear_cells(&mut self, y: usize, start: usize, end: usize) {
+        let end = end.min(self.width);
+        if start < end {
+            self.cells[y][start..end].fill(' ');
+        }
+    }
+
+    fn csi(&mut self, action: char) {
+        // 私有模式（如 `ESC [ ? 25 l` 隐藏光标）和带中间字符的序列都不处理
+        if !self.params.bytes().all(|b| b.is_ascii_digit() || b == b';') {
+            return;
+        }
+        let params: Vec<usize> = self
+            .params
+            .split(';')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        let arg = |i: usize| params.get(i).copied().unwrap_or(0);
+        let count = arg(0).max(1);
+        let (x, y) = (self.cursor_x.min(self.width - 1), self.cursor_y);
+        match action {
+            'A' => self.cursor_y = y.saturating_sub(count),
+            'B' => self.cursor_y = (y + count).min(self.height - 1),
+            'C' => self.cursor_x = (x + count).min(self.width - 1),
+            'D' => self.cursor_x = x.saturating_sub(count),
+            'E' => (self.cursor_x, self.cursor_y) = (0, (y + count).min(self.height - 1)),
+            'F' => (self.cursor_x, self.cursor_y) = (0, y.saturating_sub(count)),
+            'G' | '`' => self.cursor_x = (count - 1).min(self.width - 1),
+            'd' => self.cursor_y = (count - 1).min(self.height - 1),
+            'H' | 'f' => {
+                self.cursor_y = (arg(0).max(1) - 1).min(self.height - 1);
+                self.cursor_x = (arg(1).max(1) - 1).min(self.width - 1);
+            }
+            'J' => {
+                let (from, to) = match arg(0) {
+                    0 => {
+                        self.clear_cells(y, x, self.width);
+                        (y + 1, self.height)
+                    }
+                    1 => {
+                        self.clear_cells(y, 0, x + 1);
+                        (0, y)
+                    }
+                    _ => (0, self.height),
+                };
+                for row in from..to {
+                    self.clear_cells(row, 0, self.width);
+                }
+            }
+            'K' => match arg(0) {
+                0 => self.clear_cells(y, x, self.width),
+                1 => self.clear_cells(y, 0, x + 1),
+                _ => self.clear_cells(y, 0, self.width),
+            },
+            'X' => self.clear_cells(y, x, x + count),
+            'P' => {
+                let row = &mut self.cells[y];
+                let count = count.min(self.width - x);
+                row.drain(x..x + count);
+                row.resize(self.width, ' ');
+            }
+            '@' => {
+                let row = &mut self.cells[y];
+                for _ in 0..count.min(self.width - x) {
+                    row.insert(x, ' ');
+                }
+                row.truncate(self.width);
+            }
+            'L' | 'M' => {
+                let count = count.min(self.height - y);
+                for _ in 0..count {
+                    if action == 'L' {
+                        self.cells.pop();
+                        self.cells.insert(y, vec![' '; self.width]);
+                    } else {
+                        self.cells.remove(y);
+                        self.cells.push(vec![' '; self.width]);
+                    }
+                }
+            }
+            'S' => {
+                for _ in 0..count.min(self.height) {
+                    self.cells.remove(0);
+                    self.cells.push(vec![' '; self.width]);
+                }
+            }
+            'T' => {
+                for _ in 0..count.min(self.height) {
+                    self.cells.pop();
+                    self.cells.insert(0, vec![' '; self.width]);
+                }
+            }
+            's' => self.saved_cursor = (self.cursor_x, self.cursor_y),
+            'u' => self.restore_cursor(),
+            // 'm' 等显示属性忽略
+            _ => {}
+        }
+    }
+}
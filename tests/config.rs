@@ -0,0 +1,41 @@
+use rsnano::config::Config;
+use rsnano::keymap::Keymap;
+
+/// 导出的配置读回后得到同样的键位表
+fn assert_round_trip(config: &Config) {
+    let keymap = config.keymap();
+    let dumped = config.to_toml(&keymap);
+    let reloaded = Config::parse(&dumped)
+        .unwrap_or_else(|e| panic!("导出的配置无法读回：{}\n{}", e, dumped))
+        .keymap();
+    assert_same_keymap(&keymap, &reloaded);
+}
+
+fn assert_same_keymap(expected: &Keymap, actual: &Keymap) {
+    assert_eq!(expected.sorted_bindings(), actual.sorted_bindings());
+    assert_eq!(
+        expected.sorted_macro_bindings(),
+        actual.sorted_macro_bindings()
+    );
+}
+
+#[test]
+fn default_keymap_round_trips() {
+    assert_round_trip(&Config::default());
+}
+
+#[test]
+fn changed_keymap_round_trips() {
+    let config = Config::parse(
+        r#"
+[keys]
+"M-u" = "none"
+"M-S-r" = "none"
+"^K" = "macro:demo"
+"M-S-y" = "undo"
+"C-S-k" = "redo"
+"#,
+    )
+    .unwrap();
+    assert_round_trip(&config);
+}